@@ -0,0 +1,49 @@
+//! Utilities for doubled functions at an **FFI boundary**.
+//!
+//! `extern "C"` shims are regenerated like any other function, so the qualifiers of the
+//! signature (`unsafe`, the ABI) have to be carried over to the generated function.
+//! Attributes like `#[no_mangle]` are kept with the other attributes of the function.
+
+use quote::quote;
+
+/// Gets the qualifiers of a function, which are preserved on the generated function.
+///
+/// # Examples
+///
+/// - `pub extern "C" fn read_sensor(id: u32) -> i32` → `extern "C"`
+/// - `pub unsafe extern "C" fn free_buffer(ptr: *mut u8)` → `unsafe extern "C"`
+/// - `pub fn fetch_user(id: u32) -> String` → nothing
+pub(crate) fn fn_qualifiers(signature: &syn::Signature) -> proc_macro2::TokenStream {
+    let unsafety = &signature.unsafety;
+    let abi = &signature.abi;
+    quote! { #unsafety #abi }
+}
+
+/// Validates that the signature of a function can be preserved on the generated function.
+///
+/// # Arguments
+///
+/// * `signature` - The signature of the function
+/// * `macro_name` - The name of the attribute macro, for the error message
+///
+/// # Returns
+///
+/// - `Ok(())` if the generated function can keep the signature
+/// - `Err(syn::Error)` if the function is `const`, since checking for a double at runtime isn't const,
+///   or variadic, since the parameters can't be passed on to the double
+pub(crate) fn validate_preservable(signature: &syn::Signature, macro_name: &str) -> syn::Result<()> {
+    if let Some(constness) = &signature.constness {
+        return Err(syn::Error::new_spanned(
+            constness,
+            format!("{} can't be used on const functions, since the double is looked up at runtime", macro_name)
+        ));
+    }
+    if let Some(variadic) = &signature.variadic {
+        return Err(syn::Error::new_spanned(
+            variadic,
+            format!("{} can't be used on variadic functions, since the variadic arguments can't be passed to the double", macro_name)
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//! Utilities for doubles returning values drawn from **proptest** strategies (see `fnmock::arbitrary`).
+//!
+//! With the `proptest` feature, mock and stub modules get `setup_arbitrary()` and `setup_strategy(strategy)`,
+//! which set up a generator drawing a new return value for every call.
+
+use quote::quote;
+
+/// Creates the `setup_arbitrary` and `setup_strategy` proxy functions of a double.
+///
+/// The proxies are only emitted if the `proptest` feature is enabled, since they require the
+/// `proptest` dependency of fnmock. Like the `Clone` bound of `setup_returns`, the `Arbitrary` bound
+/// is higher-ranked, so it's only checked when `setup_arbitrary` is used.
+///
+/// # Arguments
+///
+/// * `doubles_vis` - The visibility of the proxy functions
+/// * `return_type` - The nameable return type of the double
+/// * `proxy_generics` - The generics of the proxy functions of generic doubles
+/// * `send` - Whether the generator has to be `Send`, e.g. for stubs and shared mocks
+/// * `setup_generator` - Sets up the double with the generator, which is available as `generator`
+pub(crate) fn create_arbitrary_proxies(
+    doubles_vis: &syn::Visibility,
+    return_type: &proc_macro2::TokenStream,
+    proxy_generics: &syn::Generics,
+    send: bool,
+    setup_generator: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if !cfg!(feature = "proptest") {
+        return quote! {};
+    }
+
+    let (impl_generics, proxy_ty_generics, where_clause) = proxy_generics.split_for_impl();
+    let proxy_turbofish = proxy_ty_generics.as_turbofish();
+    let send_bound = if send { quote! { + Send } } else { quote! {} };
+
+    let mut arbitrary_generics = proxy_generics.clone();
+    let arbitrary_predicates = &mut arbitrary_generics.make_where_clause().predicates;
+    arbitrary_predicates.push(syn::parse_quote! { for<'a> #return_type: fnmock::proptest::arbitrary::Arbitrary });
+    arbitrary_predicates.push(syn::parse_quote! {
+        for<'a> <#return_type as fnmock::proptest::arbitrary::Arbitrary>::Strategy: 'static #send_bound
+    });
+    let arbitrary_where_clause = &arbitrary_generics.where_clause;
+
+    quote! {
+        #[doc = "Sets up the double to return a new arbitrary value for every call (`proptest` feature)."]
+        #[doc = ""]
+        #[doc = "The values are drawn from the `proptest::arbitrary::Arbitrary` implementation of the return type."]
+        #[doc = "Use `setup_strategy()` to constrain the values."]
+        #doubles_vis fn setup_arbitrary #impl_generics () #arbitrary_where_clause {
+            setup_strategy #proxy_turbofish(fnmock::proptest::arbitrary::any::<#return_type>())
+        }
+
+        #[doc = "Sets up the double to return a new value drawn from the strategy for every call (`proptest` feature)."]
+        #[doc = ""]
+        #[doc = "# Parameters"]
+        #[doc = ""]
+        #[doc = "* `strategy` - The proptest strategy, e.g. `0u32..100` or `\"[a-z]{3,8}\"`"]
+        #doubles_vis fn setup_strategy #impl_generics (
+            strategy: impl fnmock::proptest::strategy::Strategy<Value = #return_type> #send_bound + 'static
+        ) #where_clause {
+            let mut generator = fnmock::arbitrary::generator(strategy);
+            #setup_generator
+        }
+    }
+}
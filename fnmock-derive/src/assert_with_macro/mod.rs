@@ -0,0 +1,45 @@
+use quote::quote;
+use syn::__private::TokenStream2;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::Token;
+use crate::param_utils::create_argument_matchers;
+
+/// The input of the assert_with macro: the mock module followed by the expected arguments
+pub(crate) struct AssertWithInput {
+    mock_module: syn::Path,
+    args: Punctuated<syn::Expr, Token![,]>,
+}
+
+impl Parse for AssertWithInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mock_module: syn::Path = input.parse()?;
+        let args = if input.is_empty() {
+            Punctuated::new()
+        } else {
+            input.parse::<Token![,]>()?;
+            Punctuated::parse_terminated(input)?
+        };
+        Ok(Self { mock_module, args })
+    }
+}
+
+/// Processes the input of the assert_with macro and turns it into a partial match of the recorded calls.
+///
+/// This is the main entry point for the assert_with macro. `assert_with!(send_email_mock, "bob", _)`
+/// is turned into `send_email_mock::assert_with_matchers(fnmock::matchers::eq_to("bob"), fnmock::matchers::any())`,
+/// so only the written arguments are checked and `_` accepts every value.
+///
+/// # Arguments
+///
+/// * `input` - The mock module and the expected arguments, one per non-ignored parameter
+///
+/// # Returns
+///
+/// The call of the `assert_with_matchers()` proxy function of the mock module
+pub(crate) fn process_assert_with(input: AssertWithInput) -> TokenStream2 {
+    let mock_module = &input.mock_module;
+    let matchers = create_argument_matchers(&input.args);
+
+    quote! { #mock_module::assert_with_matchers(#(#matchers),*) }
+}
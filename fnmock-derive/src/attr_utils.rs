@@ -0,0 +1,61 @@
+//! Utilities for the **attributes** of doubled functions.
+
+use syn::parse::ParseStream;
+use syn::Token;
+
+/// Gets the `#[cfg(...)]` attributes of a function.
+///
+/// They have to be added to every generated item, so the doubles only exist in builds
+/// where the function exists.
+///
+/// # Examples
+///
+/// - `#[cfg(feature = "db")] #[inline] fn foo()` → `[#[cfg(feature = "db")]]`
+pub(crate) fn get_cfg_attrs(attrs: &[syn::Attribute]) -> Vec<syn::Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .cloned()
+        .collect()
+}
+
+/// Adds the `#[cfg(...)]` attributes of the function to a generated item.
+///
+/// Empty token streams (e.g. of disabled features) stay empty, so the attributes
+/// can't end up on the next item.
+pub(crate) fn add_cfg_attrs(cfg_attrs: &[syn::Attribute], item: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if item.is_empty() {
+        return item;
+    }
+
+    quote::quote! {
+        #(#cfg_attrs)*
+        #item
+    }
+}
+
+/// Gets the name of the generated double module.
+///
+/// # Examples
+///
+/// - `fetch_user` with suffix `_mock` → `fetch_user_mock`
+/// - `fetch_user` with `name = "fetch_user_double"` → `fetch_user_double`
+pub(crate) fn double_mod_name(fn_name: &syn::Ident, name: Option<&syn::Ident>, suffix: &str) -> syn::Ident {
+    match name {
+        Some(name) => name.clone(),
+        None => syn::Ident::new(&format!("{}{}", fn_name, suffix), fn_name.span()),
+    }
+}
+
+/// Parses the value of a `name = "..."` attribute argument into the name of the generated double module.
+///
+/// # Returns
+///
+/// - `Ok(Ident)` - The name of the module, e.g. `fetch_user_double`
+/// - `Err(syn::Error)` - If the value isn't a string literal containing a valid identifier
+pub(crate) fn parse_name_arg(input: ParseStream) -> syn::Result<syn::Ident> {
+    input.parse::<Token![=]>()?;
+    let name: syn::LitStr = input.parse()?;
+    name.parse()
+        .map_err(|_| syn::Error::new_spanned(&name, "name must be a valid identifier, e.g. `fetch_user_double`"))
+}
@@ -0,0 +1,108 @@
+//! Gating of the generated doubles.
+//!
+//! By default, the doubles (mock modules, test versions of the functions, ...) are only generated
+//! for unit tests, since they are gated on `cfg(test)`. Integration tests in `tests/` link against
+//! the library compiled without `cfg(test)`, so they can't reach them.
+//! With the `test-doubles` feature, the doubles are generated unconditionally instead,
+//! e.g. by enabling `fnmock/test-doubles` only in the dev-dependencies.
+//! The generated modules are `pub` then, since integration tests are compiled as separate crates.
+//! The `runtime-doubles` feature does the same for normal builds, e.g. staging binaries replacing
+//! a payment provider with a fake. It is a separate feature, so enabling the doubles outside of tests is explicit.
+//!
+//! A single double can choose its own predicate with the `cfg = "..."` argument of its attribute,
+//! e.g. `cfg = "feature = \"mocks\""`, which replaces the default predicate.
+//! Likewise, the `vis = "..."` argument replaces the default visibility of its generated module,
+//! e.g. `vis = "pub"` to export it to other crates or `vis = "pub(self)"` to keep it in the current module.
+
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::Token;
+
+/// Returns the `cfg` predicate the generated doubles are gated on.
+///
+/// # Returns
+///
+/// - `test` - by default, so the doubles only exist in unit tests
+/// - `all()` - with the `test-doubles` or `runtime-doubles` feature, so the doubles exist in every build
+///   (the predicate is always true, `not(all())` is always false)
+pub(crate) fn doubles_cfg() -> proc_macro2::TokenStream {
+    if cfg!(any(feature = "test-doubles", feature = "runtime-doubles")) {
+        quote! { all() }
+    } else {
+        quote! { test }
+    }
+}
+
+/// Returns the visibility of the generated double modules.
+///
+/// # Returns
+///
+/// - `pub(crate)` - by default, since unit tests are part of the crate
+/// - `pub` - with the `test-doubles` or `runtime-doubles` feature, so integration tests and binaries can reach the doubles
+pub(crate) fn doubles_vis() -> syn::Visibility {
+    if cfg!(any(feature = "test-doubles", feature = "runtime-doubles")) {
+        syn::parse_quote! { pub }
+    } else {
+        syn::parse_quote! { pub(crate) }
+    }
+}
+
+/// Returns the visibility of the items in a generated double module, so they are visible
+/// wherever the module is.
+///
+/// Paths relative to the current module have to start one module further up inside the double module.
+///
+/// # Examples
+///
+/// - `pub` → `pub`
+/// - `pub(crate)` → `pub(crate)`
+/// - no visibility → `pub(super)`
+/// - `pub(self)` → `pub(in super)`
+/// - `pub(super)` → `pub(in super::super)`
+pub(crate) fn inner_vis(module_vis: &syn::Visibility) -> syn::Visibility {
+    match module_vis {
+        syn::Visibility::Public(_) => module_vis.clone(),
+        syn::Visibility::Inherited => syn::parse_quote! { pub(super) },
+        syn::Visibility::Restricted(restricted) => {
+            let mut path = (*restricted.path).clone();
+            match path.segments.first_mut() {
+                Some(first) if first.ident == "crate" => return module_vis.clone(),
+                // `self` of the current module is `super` of the double module
+                Some(first) if first.ident == "self" => first.ident = syn::Ident::new("super", first.ident.span()),
+                _ => path.segments.insert(0, syn::parse_quote! { super }),
+            }
+            syn::parse_quote! { pub(in #path) }
+        }
+    }
+}
+
+/// Parses the value of a `vis = "..."` attribute argument into the visibility of the generated module.
+///
+/// # Returns
+///
+/// - `Ok(Visibility)` - The visibility, e.g. `pub` or `pub(super)`
+/// - `Err(syn::Error)` - If the value isn't a string literal containing a valid visibility
+pub(crate) fn parse_vis_arg(input: ParseStream) -> syn::Result<syn::Visibility> {
+    input.parse::<Token![=]>()?;
+    let vis: syn::LitStr = input.parse()?;
+    vis.parse()
+        .map_err(|_| syn::Error::new_spanned(&vis, "vis must be a valid visibility, e.g. `pub` or `pub(super)`"))
+}
+
+/// Parses the value of a `cfg = "..."` attribute argument into a `cfg` predicate.
+///
+/// The predicate is validated, so an invalid predicate is reported at the argument
+/// instead of at the generated `#[cfg(...)]` attributes.
+///
+/// # Returns
+///
+/// - `Ok(TokenStream)` - The predicate, e.g. `feature = "mocks"` or `any(test, debug_assertions)`
+/// - `Err(syn::Error)` - If the value isn't a string literal containing a valid predicate
+pub(crate) fn parse_cfg_arg(input: ParseStream) -> syn::Result<proc_macro2::TokenStream> {
+    input.parse::<Token![=]>()?;
+    let predicate: syn::LitStr = input.parse()?;
+    let meta: syn::Meta = predicate.parse()
+        .map_err(|_| syn::Error::new_spanned(&predicate, "cfg must be a valid cfg predicate, e.g. `debug_assertions`"))?;
+
+    Ok(quote! { #meta })
+}
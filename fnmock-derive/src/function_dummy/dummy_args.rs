@@ -0,0 +1,42 @@
+use syn::parse::{Parse, ParseStream};
+use syn::Token;
+use crate::cfg_utils::parse_cfg_arg;
+
+/// Structure to parse the dummy_function attribute arguments
+#[derive(Default)]
+pub(crate) struct DummyFunctionArgs {
+    pub(crate) returns: Option<syn::Expr>,
+    pub(crate) cfg: Option<proc_macro2::TokenStream>,
+}
+
+impl Parse for DummyFunctionArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = DummyFunctionArgs::default();
+
+        // Parse "returns = "..."" and "cfg = "..."" syntax
+        while !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key == "returns" {
+                input.parse::<Token![=]>()?;
+                let value: syn::LitStr = input.parse()?;
+                args.returns = Some(value.parse().map_err(|_| {
+                    syn::Error::new_spanned(&value, "returns must be a valid expression, e.g. `Ok(())`")
+                })?);
+            } else if key == "cfg" {
+                args.cfg = Some(parse_cfg_arg(input)?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    &key,
+                    format!("Unknown dummy_function argument '{}'. Expected 'returns' or 'cfg'", key)
+                ));
+            }
+
+            // Allow trailing comma or end of input
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
@@ -0,0 +1,67 @@
+use quote::quote;
+use syn::__private::TokenStream2;
+use crate::abi_utils::validate_preservable;
+use crate::function_dummy::dummy_args::DummyFunctionArgs;
+use crate::attr_utils::{add_cfg_attrs, get_cfg_attrs};
+use crate::manifest_utils::create_manifest_entry;
+use crate::cfg_utils::doubles_cfg;
+
+pub(crate) mod dummy_args;
+
+/// Processes a function and generates its dummy.
+///
+/// This is the main entry point for the dummy_function attribute macro. In test mode, the
+/// function returns `Default::default()` (or the configured `returns` expression) without
+/// executing the original body. Dummies don't need any setup, so no module is generated.
+///
+/// The original body is still compiled in test mode, so items only used by it don't cause
+/// warnings, but it is unreachable.
+///
+/// # Arguments
+///
+/// * `dummy_function` - The function item to create a dummy for
+/// * `args` - The parsed dummy_function attribute arguments
+///
+/// # Returns
+///
+/// - `Ok(TokenStream2)` - The function returning the dummy value in test mode
+/// - `Err(syn::Error)` - If the function cannot be replaced by a dummy
+pub(crate) fn process_dummy_function(dummy_function: syn::ItemFn, args: DummyFunctionArgs) -> syn::Result<TokenStream2> {
+    let fn_attrs = &dummy_function.attrs;
+    let fn_visibility = &dummy_function.vis;
+    let fn_sig = &dummy_function.sig;
+    let original_fn_stmts = &dummy_function.block.stmts;
+    let cfg_attrs = get_cfg_attrs(fn_attrs);
+
+    validate_preservable(fn_sig, "dummy_function")?;
+    if let Some(receiver) = fn_sig.receiver() {
+        return Err(syn::Error::new_spanned(
+            receiver,
+            "dummy_function does not support methods with 'self' parameters"
+        ));
+    }
+
+    let dummy_value = args.returns.map_or_else(
+        || quote! { ::std::default::Default::default() },
+        |returns| quote! { #returns },
+    );
+
+    // The dummy value is returned for the custom cfg predicate or for tests
+    let doubles_cfg = args.cfg.unwrap_or_else(doubles_cfg);
+    let manifest_entry = add_cfg_attrs(&cfg_attrs, create_manifest_entry("Dummy", fn_sig));
+
+    Ok(quote! {
+        #(#fn_attrs)*
+        #[allow(unused_variables)]
+        #[cfg_attr(#doubles_cfg, allow(unreachable_code))]
+        #fn_visibility #fn_sig {
+            // Return the dummy value (only in test mode)
+            #[cfg(#doubles_cfg)]
+            return #dummy_value;
+
+            #(#original_fn_stmts)*
+        }
+
+        #manifest_entry
+    })
+}
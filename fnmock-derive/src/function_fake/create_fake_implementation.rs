@@ -1,7 +1,10 @@
 use quote::quote;
 use syn::token::Async;
-use crate::param_utils::get_param_names;
+use crate::param_utils::{get_param_names, is_nameable};
 use crate::function_fake::proxy_docs::FakeProxyDocs;
+use crate::stream_utils::create_stream_function;
+use crate::generic_utils::{create_alias_generics, create_monomorphization_key, has_defaults, is_generic};
+use crate::cfg_utils::inner_vis;
 
 /// Generates the original function with fake checking logic injected.
 ///
@@ -9,38 +12,135 @@ use crate::function_fake::proxy_docs::FakeProxyDocs;
 /// configured via the fake module. If a fake is set, it calls the fake implementation.
 /// Otherwise, it executes the original function body.
 ///
+/// Async functions additionally check for an async fake implementation and await the returned future.
+/// If the fake is set up with a delay, the function sleeps before returning the result of the fake
+/// (blocking the thread for sync functions, with a runtime independent timer for async functions).
+///
+/// Functions returning `impl Stream` box the streams in test mode (see [`create_stream_function`]).
+///
+/// For generic functions the test version of the function has to carry the additional
+/// storage bounds, so separate versions are generated for test and non-test builds.
+///
 /// # Arguments
 ///
+/// * `fn_attrs` - The attributes of the original function (doc comments, `cfg`, ...)
 /// * `fn_name` - The name of the original function
 /// * `fn_visibility` - The visibility modifier of the function (pub, pub(crate), etc.)
 /// * `fn_asyncness` - Optional async keyword if the function is async
+/// * `fn_qualifiers` - The `unsafe` keyword and the ABI of the function (see `fn_qualifiers`)
+/// * `fn_generics` - The generics of the original function, including the where clause
+/// * `storage_generics` - The generics including the bounds required to store the fake per monomorphization
 /// * `fn_inputs` - The function parameters
 /// * `fn_output` - The return type
 /// * `fn_block` - The original function body to execute when fake is not set
 /// * `fake_mod_name` - The name of the fake module containing the fake infrastructure
+/// * `boxed_stream_type` - The boxed stream type, if the function returns `impl Stream`
+/// * `doubles_cfg` - The `cfg` predicate the fake check is compiled for (see `doubles_cfg`)
 ///
 /// # Returns
 ///
 /// Generated token stream for the function with injected fake checking logic
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create_fake_function(
+    fn_attrs: &[syn::Attribute],
     fn_name: syn::Ident,
     fn_visibility: syn::Visibility,
     fn_asyncness: Option<Async>,
+    fn_qualifiers: &proc_macro2::TokenStream,
+    fn_generics: syn::Generics,
+    storage_generics: &syn::Generics,
     fn_inputs: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
     fn_output: syn::ReturnType,
-    fn_block: Box<syn::Block>,
+    fn_block: syn::Block,
     fake_mod_name: syn::Ident,
+    boxed_stream_type: Option<syn::Type>,
+    doubles_cfg: &proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
     let param_names = get_param_names(&fn_inputs);
     let original_fn_stmts = &fn_block.stmts;
-    
-    quote! {
-        #fn_visibility #fn_asyncness fn #fn_name(#fn_inputs) #fn_output {
-            // Call the fake implementation if set (only in test mode)
-            #[cfg(test)]
-            if #fake_mod_name::is_set() {
-                return #fake_mod_name::get_implementation()(#(#param_names),*);
+    // Lifetimes are inferred, only type and const parameters select the fake of a monomorphization
+    let turbofish = if is_generic(storage_generics) {
+        let (_, storage_ty_generics, _) = storage_generics.split_for_impl();
+        let turbofish = storage_ty_generics.as_turbofish();
+        quote! { #turbofish }
+    } else {
+        quote! {}
+    };
+
+    let async_fake_check = if fn_asyncness.is_some() {
+        quote! {
+            // Await the async fake implementation if set
+            if #fake_mod_name::is_async_set #turbofish() {
+                return #fake_mod_name::call_async #turbofish(#(#param_names),*).await;
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let sleep = if fn_asyncness.is_some() {
+        quote! { fnmock::future::sleep(delay).await; }
+    } else {
+        quote! { ::std::thread::sleep(delay); }
+    };
+    let fake_check = quote! {
+        #async_fake_check
+
+        // Call the fake implementation if set, simulating latency if it is set up with a delay
+        if #fake_mod_name::is_set #turbofish() {
+            let result = #fake_mod_name::call #turbofish(#(#param_names),*);
+            if let Some(delay) = #fake_mod_name::delay #turbofish() {
+                #sleep
+            }
+            return result;
+        }
+    };
+
+    if let Some(boxed_stream_type) = boxed_stream_type {
+        return create_stream_function(
+            fn_attrs, &fn_name, &fn_visibility, fn_asyncness, fn_qualifiers, &fn_inputs, &fn_output, &fn_block,
+            &boxed_stream_type, fake_check, doubles_cfg,
+        );
+    }
+
+    if !is_generic(&fn_generics) {
+        let where_clause = &fn_generics.where_clause;
+
+        return quote! {
+            #(#fn_attrs)*
+            #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #fn_generics (#fn_inputs) #fn_output #where_clause {
+                // Call the fake implementation if set (only in test mode)
+                #[cfg(#doubles_cfg)]
+                {
+                    #fake_check
+                }
+
+                #(#original_fn_stmts)*
             }
+        };
+    }
+
+    let where_clause = &fn_generics.where_clause;
+    let allow_defaults = if has_defaults(&fn_generics) {
+        quote! { #[allow(invalid_type_param_default)] }
+    } else {
+        quote! {}
+    };
+    let storage_where_clause = &storage_generics.where_clause;
+
+    quote! {
+        #(#fn_attrs)*
+        #[cfg(not(#doubles_cfg))]
+        #allow_defaults
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #fn_generics (#fn_inputs) #fn_output #where_clause {
+            #(#original_fn_stmts)*
+        }
+
+        #(#fn_attrs)*
+        #[cfg(#doubles_cfg)]
+        #[allow(clippy::multiple_bound_locations)]
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #storage_generics (#fn_inputs) #fn_output #storage_where_clause {
+            // Call the fake implementation of this monomorphization if set
+            #fake_check
 
             #(#original_fn_stmts)*
         }
@@ -50,57 +150,334 @@ pub(crate) fn create_fake_function(
 /// Generates a fake module containing the fake infrastructure.
 ///
 /// Creates a module with the same name as the fake function that contains:
-/// - Type alias for the function type
-/// - Thread-local storage for the FunctionFake instance
+/// - Public `Params` and `Return` type aliases (omitted if the type can't be named, e.g. for references)
+/// - Thread-local storage for the FunctionFake instance, registered with `fnmock::registry` on first use
+/// - Thread-local storage for the async FunctionFake instance (only for async functions)
 /// - Proxy functions for fake operations
+/// - A call counter with the `times_called` proxy (only with `count_calls`)
+///
+/// Closures set up with `setup` are stored as shared `dyn FnMut` trait objects, borrowed mutably while they run.
+/// Function pointers set up with `setup_fn` are stored in a `Cell`, so calling the fake doesn't check any borrows.
+/// The async storage holds implementations returning a boxed future, which can be awaited
+/// by any executor (tokio, embassy, `block_on`, ...), since it doesn't depend on a runtime.
 ///
 /// # Arguments
 ///
 /// * `fake_fn_name` - The name of the fake module (same as fake function name)
+/// * `function_type` - The function pointer type of the fake implementation
+/// * `closure_trait` - The `FnMut` trait of fake implementations capturing test state
+/// * `async_function_type` - The function pointer type of the async fake implementation
+/// * `async_closure_trait` - The `FnMut` trait of the async fake implementation
 /// * `params_type` - The type representing the function parameters (single type or tuple)
 /// * `return_type` - The return type of the function
-/// * `fn_inputs` - The original function parameters (for documentation)
+/// * `fn_inputs` - The original function parameters (for the `call` proxies and documentation)
+/// * `fn_asyncness` - Optional async keyword if the function is async
+/// * `count_calls` - Whether the calls of the fake implementation are counted
+/// * `fn_generics` - The generics of the original function, passed to the `call` proxies
+/// * `storage_generics` - The generics including the storage bounds (see `create_fake_function`)
+/// * `module_vis` - The visibility of the fake module (see `doubles_vis`)
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create_fake_module(
     fake_fn_name: syn::Ident,
+    function_type: proc_macro2::TokenStream,
+    closure_trait: proc_macro2::TokenStream,
+    async_function_type: proc_macro2::TokenStream,
+    async_closure_trait: proc_macro2::TokenStream,
     params_type: syn::Type,
     return_type: syn::Type,
     fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
     fn_asyncness: Option<syn::token::Async>,
+    count_calls: bool,
+    fn_generics: &syn::Generics,
+    storage_generics: &syn::Generics,
+    module_vis: &syn::Visibility,
 ) -> proc_macro2::TokenStream {
+    let doubles_vis = inner_vis(module_vis);
+    let param_names = get_param_names(fn_inputs);
     // Generate documentation using the proxy_docs module
     let docs = FakeProxyDocs::new(&fake_fn_name, fn_inputs, &return_type, fn_asyncness);
     let setup_docs = docs.setup_docs();
+    let setup_fn_docs = docs.setup_fn_docs();
+    let setup_scoped_docs = docs.setup_scoped_docs();
+    let setup_with_delay_docs = docs.setup_with_delay_docs();
+    let delay_docs = docs.delay_docs();
     let clear_docs = docs.clear_docs();
     let is_set_docs = docs.is_set_docs();
-    let get_implementation_docs = docs.get_implementation_docs();
+    let call_docs = docs.call_docs();
+    let snapshot_docs = docs.snapshot_docs();
+    let restore_docs = docs.restore_docs();
+
+    let params_alias = if is_nameable(&params_type) {
+        let params_type_docs = docs.params_type_docs();
+        let params_alias_generics = create_alias_generics(storage_generics, &params_type);
+        quote! {
+            #params_type_docs
+            #doubles_vis type Params #params_alias_generics = #params_type;
+        }
+    } else {
+        quote! {}
+    };
+    let return_alias = if is_nameable(&return_type) {
+        let return_type_docs = docs.return_type_docs();
+        let return_alias_generics = create_alias_generics(storage_generics, &return_type);
+        quote! {
+            #return_type_docs
+            #doubles_vis type Return #return_alias_generics = #return_type;
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generic functions store one fake per monomorphization and pass their generics to the proxy functions.
+    // The `call` proxies of functions with lifetime parameters take the lifetimes as well.
+    let fake_type = quote! { fnmock::function_fake::FunctionFake<#function_type, dyn #closure_trait> };
+    let async_fake_type = quote! { fnmock::function_fake::FunctionFake<#async_function_type, dyn #async_closure_trait> };
+    // `selected_fake` and `selected_async_fake` access the fake of the current call from the `fake` closure parameter
+    let (storage_type, async_storage_type, create_storage, selected_fake, selected_async_fake, stored_fake, proxy_generics, call_generics) = if is_generic(storage_generics) {
+        let key = create_monomorphization_key(storage_generics);
+        (
+            quote! { ::std::cell::RefCell<fnmock::generic_doubles::GenericDoubles> },
+            quote! { ::std::cell::RefCell<fnmock::generic_doubles::GenericDoubles> },
+            quote! { ::std::cell::RefCell::new(fnmock::generic_doubles::GenericDoubles::new(stringify!(#fake_fn_name))) },
+            quote! { fake.borrow_mut().get_or_create::<#key, #fake_type>(fnmock::function_fake::FunctionFake::new) },
+            quote! { fake.borrow_mut().get_or_create::<#key, #async_fake_type>(fnmock::function_fake::FunctionFake::new) },
+            quote! { &*fake.borrow() },
+            storage_generics.clone(),
+            storage_generics.clone(),
+        )
+    } else {
+        (
+            fake_type.clone(),
+            async_fake_type.clone(),
+            quote! { fnmock::function_fake::FunctionFake::new(stringify!(#fake_fn_name)) },
+            quote! { fake },
+            quote! { fake },
+            quote! { fake },
+            syn::Generics::default(),
+            fn_generics.clone(),
+        )
+    };
+    let (impl_generics, proxy_ty_generics, where_clause) = proxy_generics.split_for_impl();
+    let proxy_turbofish = proxy_ty_generics.as_turbofish();
+    let (call_impl_generics, _, call_where_clause) = call_generics.split_for_impl();
+
+    // The counter doesn't require the parameters to be stored, so it works for all fakes
+    let (call_counter, record_call, clear_calls) = if count_calls {
+        let times_called_docs = docs.times_called_docs();
+
+        (
+            quote! {
+                thread_local! {
+                    static CALLS: ::std::cell::Cell<u32> = const { ::std::cell::Cell::new(0) };
+                }
+
+                #times_called_docs
+                #doubles_vis fn times_called() -> u32 {
+                    CALLS.with(|calls| { calls.get() })
+                }
+            },
+            quote! { CALLS.with(|calls| { calls.set(calls.get() + 1) }); },
+            quote! { CALLS.with(|calls| { calls.set(0) }); },
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {})
+    };
+
+    let async_fake = if fn_asyncness.is_some() {
+        let setup_async_docs = docs.setup_async_docs();
+        let is_async_set_docs = docs.is_async_set_docs();
+        let call_async_docs = docs.call_async_docs();
+
+        quote! {
+            thread_local! {
+                static ASYNC_FAKE: #async_storage_type = #create_storage;
+            }
+
+            #setup_async_docs
+            #doubles_vis fn setup_async #impl_generics (new_f: impl #async_closure_trait + 'static) #where_clause {
+                FAKE.with(|fake| { #selected_fake.clear() });
+                ASYNC_FAKE.with(|fake| {
+                    #selected_async_fake.setup_closure(::std::rc::Rc::new(::std::cell::RefCell::new(new_f)))
+                })
+            }
+
+            #is_async_set_docs
+            #doubles_vis fn is_async_set #impl_generics () -> bool #where_clause {
+                ASYNC_FAKE.with(|fake| { #selected_async_fake.is_set() })
+            }
+
+            #call_async_docs
+            #doubles_vis fn call_async #call_impl_generics (#fn_inputs) -> fnmock::future::LocalBoxFuture<#return_type> #call_where_clause {
+                let implementation = ASYNC_FAKE.with(|fake| { #selected_async_fake.get_implementation() });
+                #record_call
+                match implementation {
+                    fnmock::function_fake::Implementation::Function(implementation) => implementation(#(#param_names),*),
+                    fnmock::function_fake::Implementation::Closure(implementation) => {
+                        (*fnmock::function_fake::borrow_closure(&implementation, stringify!(#fake_fn_name)))(#(#param_names),*)
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Setting up or clearing the sync fake has to reset the async fake as well
+    let (reset_async_fake, clear_async_fake) = match (fn_asyncness.is_some(), is_generic(storage_generics)) {
+        (false, _) => (quote! {}, quote! {}),
+        (true, false) => (
+            quote! { ASYNC_FAKE.with(|fake| { fake.clear() }); },
+            quote! { ASYNC_FAKE.with(|fake| { fake.clear() }); },
+        ),
+        (true, true) => (
+            quote! { ASYNC_FAKE.with(|fake| { #selected_async_fake.clear() }); },
+            quote! { ASYNC_FAKE.with(|fake| { fake.borrow_mut().clear() }); },
+        ),
+    };
+
+    // The snapshot of async functions contains the implementations of both fakes.
+    // Only FAKE registers the module, since setting up the async fake always accesses FAKE first.
+    // Generic functions snapshot the fakes of all monomorphizations.
+    let (snapshot_state, restore_state, clear_fake) = if is_generic(storage_generics) {
+        let (snapshot_state, restore_state) = if fn_asyncness.is_some() {
+            (
+                quote! {
+                    FAKE.with(|fake| {
+                        ASYNC_FAKE.with(|async_fake| (fake.borrow().clone(), async_fake.borrow().clone()))
+                    })
+                },
+                quote! {
+                    let (state, async_state): (fnmock::generic_doubles::GenericDoubles, fnmock::generic_doubles::GenericDoubles) =
+                        snapshot.into_state(stringify!(#fake_fn_name));
+                    FAKE.with(|fake| { *fake.borrow_mut() = state });
+                    ASYNC_FAKE.with(|fake| { *fake.borrow_mut() = async_state });
+                },
+            )
+        } else {
+            (
+                quote! { FAKE.with(|fake| { fake.borrow().clone() }) },
+                quote! {
+                    let state: fnmock::generic_doubles::GenericDoubles = snapshot.into_state(stringify!(#fake_fn_name));
+                    FAKE.with(|fake| { *fake.borrow_mut() = state });
+                },
+            )
+        };
+        (snapshot_state, restore_state, quote! { FAKE.with(|fake| { fake.borrow_mut().clear() }) })
+    } else if fn_asyncness.is_some() {
+        (
+            quote! {
+                FAKE.with(|fake| {
+                    ASYNC_FAKE.with(|async_fake| (fake.clone(), async_fake.clone()))
+                })
+            },
+            quote! {
+                let (state, async_state): (#fake_type, #async_fake_type) = snapshot.into_state(stringify!(#fake_fn_name));
+                FAKE.with(|fake| { fake.restore(state) });
+                ASYNC_FAKE.with(|fake| { fake.restore(async_state) });
+            },
+            quote! { FAKE.with(|fake| { fake.clear() }) },
+        )
+    } else {
+        (
+            quote! { FAKE.with(|fake| { fake.clone() }) },
+            quote! {
+                let state: #fake_type = snapshot.into_state(stringify!(#fake_fn_name));
+                FAKE.with(|fake| { fake.restore(state) });
+            },
+            quote! { FAKE.with(|fake| { fake.clear() }) },
+        )
+    };
     
     quote! {
-        pub(crate) mod #fake_fn_name {
+        #[allow(clippy::multiple_bound_locations)]
+        #module_vis mod #fake_fn_name {
             use super::*;
 
+            #params_alias
+
+            #return_alias
+
             thread_local! {
-                static FAKE: std::cell::RefCell<fnmock::function_fake::FunctionFake<fn(#params_type) -> #return_type>> =
-                    std::cell::RefCell::new(fnmock::function_fake::FunctionFake::new(stringify!(#fake_fn_name)));
+                static FAKE: #storage_type = {
+                    fnmock::registry::register(stringify!(#fake_fn_name), snapshot, restore, clear, control);
+                    #create_storage
+                };
             }
 
+            // Gives the registry access to the stored fake
+            fn control(f: &mut dyn FnMut(&dyn fnmock::registry::MockControl)) {
+                FAKE.with(|fake| f(#stored_fake))
+            }
+
+            #async_fake
+
+            #call_counter
+
             #setup_docs
-            pub(crate) fn setup(new_f: fn(#params_type) -> #return_type) {
-                FAKE.with(|fake| { fake.borrow_mut().setup(new_f) })
+            #doubles_vis fn setup #impl_generics (new_f: impl #closure_trait + 'static) #where_clause {
+                #reset_async_fake
+                FAKE.with(|fake| { #selected_fake.setup_closure(::std::rc::Rc::new(::std::cell::RefCell::new(new_f))) })
+            }
+
+            #setup_fn_docs
+            #doubles_vis fn setup_fn #impl_generics (new_f: #function_type) #where_clause {
+                #reset_async_fake
+                FAKE.with(|fake| { #selected_fake.setup(new_f) })
+            }
+
+            #setup_scoped_docs
+            #doubles_vis fn setup_scoped #impl_generics (new_f: impl #closure_trait + 'static) -> fnmock::guard::MockGuard #where_clause {
+                setup #proxy_turbofish(new_f);
+                fnmock::guard::MockGuard::new(clear)
+            }
+
+            #setup_with_delay_docs
+            #doubles_vis fn setup_with_delay #impl_generics (delay: ::std::time::Duration, new_f: impl #closure_trait + 'static) #where_clause {
+                #reset_async_fake
+                FAKE.with(|fake| {
+                    #selected_fake.setup_with_delay(delay, ::std::rc::Rc::new(::std::cell::RefCell::new(new_f)))
+                })
+            }
+
+            #delay_docs
+            #doubles_vis fn delay #impl_generics () -> Option<::std::time::Duration> #where_clause {
+                FAKE.with(|fake| { #selected_fake.delay() })
             }
 
             #clear_docs
-            pub(crate) fn clear() {
-                FAKE.with(|fake| { fake.borrow_mut().clear() })
+            #doubles_vis fn clear() {
+                #clear_async_fake
+                #clear_calls
+                #clear_fake
             }
 
             #is_set_docs
-            pub(crate) fn is_set() -> bool {
-                FAKE.with(|fake| { fake.borrow().is_set() })
+            #doubles_vis fn is_set #impl_generics () -> bool #where_clause {
+                FAKE.with(|fake| { #selected_fake.is_set() })
+            }
+
+            #call_docs
+            #doubles_vis fn call #call_impl_generics (#fn_inputs) -> #return_type #call_where_clause {
+                // The fake isn't borrowed while the implementation runs, so a function pointer may call the faked function again
+                let implementation = FAKE.with(|fake| { #selected_fake.get_implementation() });
+                #record_call
+                match implementation {
+                    fnmock::function_fake::Implementation::Function(implementation) => implementation(#(#param_names),*),
+                    fnmock::function_fake::Implementation::Closure(implementation) => {
+                        (*fnmock::function_fake::borrow_closure(&implementation, stringify!(#fake_fn_name)))(#(#param_names),*)
+                    }
+                }
+            }
+
+            #snapshot_docs
+            #doubles_vis fn snapshot() -> fnmock::snapshot::StateSnapshot {
+                let state = #snapshot_state;
+                fnmock::snapshot::StateSnapshot::new(stringify!(#fake_fn_name), state)
             }
 
-            #get_implementation_docs
-            pub(crate) fn get_implementation() -> fn(#params_type) -> #return_type {
-                FAKE.with(|fake| { fake.borrow().get_implementation() })
+            #restore_docs
+            #doubles_vis fn restore(snapshot: fnmock::snapshot::StateSnapshot) {
+                #restore_state
             }
         }
     }
@@ -0,0 +1,45 @@
+use syn::parse::{Parse, ParseStream};
+use syn::Token;
+use crate::attr_utils::parse_name_arg;
+use crate::cfg_utils::{parse_cfg_arg, parse_vis_arg};
+
+/// Structure to parse the fake_function attribute arguments
+#[derive(Default)]
+pub(crate) struct FakeFunctionArgs {
+    pub(crate) count_calls: bool,
+    pub(crate) cfg: Option<proc_macro2::TokenStream>,
+    pub(crate) vis: Option<syn::Visibility>,
+    pub(crate) name: Option<syn::Ident>,
+}
+
+impl Parse for FakeFunctionArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = FakeFunctionArgs::default();
+
+        // Parse "count_calls", "cfg = "...", "vis = "..."" and "name = "..."" syntax
+        while !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key == "count_calls" {
+                args.count_calls = true;
+            } else if key == "cfg" {
+                args.cfg = Some(parse_cfg_arg(input)?);
+            } else if key == "vis" {
+                args.vis = Some(parse_vis_arg(input)?);
+            } else if key == "name" {
+                args.name = Some(parse_name_arg(input)?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    &key,
+                    format!("Unknown fake_function argument '{}'. Expected 'count_calls', 'cfg', 'vis' or 'name'", key)
+                ));
+            }
+
+            // Allow trailing comma or end of input
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
@@ -1,11 +1,19 @@
 use quote::quote;
 use syn::__private::TokenStream2;
+use crate::abi_utils::{fn_qualifiers, validate_preservable};
 use crate::function_fake::create_fake_implementation::{create_fake_function, create_fake_module};
-use crate::param_utils::{create_param_type, get_param_names};
+use crate::attr_utils::{add_cfg_attrs, double_mod_name, get_cfg_attrs};
+use crate::generic_utils::{add_bounds, create_storage_bounds, strip_defaults, validate_generics};
+use crate::manifest_utils::create_manifest_entry;
+use crate::param_utils::{bind_param_patterns, create_param_type, get_param_types};
 use crate::return_utils::extract_return_type;
+use crate::stream_utils::get_boxed_stream_type;
+use crate::cfg_utils::{doubles_cfg, doubles_vis};
+use crate::function_fake::fake_args::FakeFunctionArgs;
 
 mod create_fake_implementation;
 mod proxy_docs;
+pub(crate) mod fake_args;
 
 /// Processes a function and generates the complete fake infrastructure.
 ///
@@ -14,53 +22,118 @@ mod proxy_docs;
 /// 1. The original function with fake checking logic injected (in test mode, checks if a fake
 ///    is configured and calls it; otherwise executes the original implementation)
 /// 2. A fake module with control methods (test-only) containing `setup()`, `clear()`, `is_set()`,
-///    and `get_implementation()` functions
+///    `setup_fn()` and `call()` functions (and `setup_async()`, `is_async_set()` and
+///    `call_async()` for async functions, and `times_called()` with `count_calls`)
 ///
 /// # Arguments
 ///
 /// * `fake_function` - The function item to create fakes for
+/// * `args` - The arguments of the fake_function attribute
 ///
 /// # Returns
 ///
 /// - `Ok(TokenStream2)` - The complete generated code including original and fake infrastructure
 /// - `Err(syn::Error)` - If validation fails or the function cannot be faked
-pub(crate) fn process_fake_function(fake_function: syn::ItemFn) -> syn::Result<TokenStream2> {
+pub(crate) fn process_fake_function(mut fake_function: syn::ItemFn, args: FakeFunctionArgs) -> syn::Result<TokenStream2> {
+    // Destructured params are bound to names, so they can be passed on
+    bind_param_patterns(&mut fake_function.sig, &mut fake_function.block);
+
     // Extract function details
     let fn_visibility = fake_function.vis.clone();
     let fn_asyncness = fake_function.sig.asyncness;
+    let fn_qualifiers = fn_qualifiers(&fake_function.sig);
     let fn_name = fake_function.sig.ident.clone();
+    let fn_generics = fake_function.sig.generics.clone();
     let fn_inputs = fake_function.sig.inputs.clone();
     let fn_output = fake_function.sig.output.clone();
-    let fn_block = fake_function.block.clone();
+    let fn_block = (*fake_function.block).clone();
+    let cfg_attrs = get_cfg_attrs(&fake_function.attrs);
+
+    // The `unsafe` keyword, the ABI and attributes like `no_mangle` are kept on the generated function
+    validate_preservable(&fake_function.sig, "fake_function")?;
+    validate_generics(&fn_generics)?;
 
     // Generate fake function name
-    let fake_mod_name = syn::Ident::new(&format!("{}_fake", &fn_name), fn_name.span());
+    let fake_mod_name = double_mod_name(&fn_name, args.name.as_ref(), "_fake");
 
     let params_type = create_param_type(&fn_inputs, &[]);
     let return_type = extract_return_type(&fake_function.sig.output);
 
+    // Fakes of functions returning `impl Stream` return boxed streams
+    let boxed_stream_type = get_boxed_stream_type(&return_type);
+    let return_type = boxed_stream_type.clone().unwrap_or(return_type);
+    if boxed_stream_type.is_some() && !fn_generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &fn_generics,
+            "fake_function does not support generic functions returning impl Stream"
+        ));
+    }
+
+    // The fakes are stored per monomorphization, which requires 'static type parameters
+    let storage_generics = add_bounds(&strip_defaults(&fn_generics), &create_storage_bounds(&fn_generics, &syn::parse_quote! { () }));
+
+    // The fake implementation takes the same arguments as the original function.
+    // Function pointers are stored in a Cell, closures capturing test state are shared `FnMut` trait objects.
+    // Lifetime parameters of the function are higher-ranked, so the fake works for all lifetimes.
+    let param_types = get_param_types(&fn_inputs);
+    let lifetimes = fn_generics.lifetimes().map(|lifetime| &lifetime.lifetime);
+    let higher_ranked = if fn_generics.lifetimes().next().is_some() {
+        quote! { for<#(#lifetimes),*> }
+    } else {
+        quote! {}
+    };
+    let function_type = quote! { #higher_ranked fn(#(#param_types),*) -> #return_type };
+    let closure_trait = quote! { #higher_ranked FnMut(#(#param_types),*) -> #return_type };
+    let async_function_type = quote! {
+        #higher_ranked fn(#(#param_types),*) -> fnmock::future::LocalBoxFuture<#return_type>
+    };
+    let async_closure_trait = quote! {
+        #higher_ranked FnMut(#(#param_types),*) -> fnmock::future::LocalBoxFuture<#return_type>
+    };
+
+    // The fake check and module are compiled for the custom cfg predicate or for tests
+    let doubles_cfg = args.cfg.unwrap_or_else(doubles_cfg);
+    let manifest_entry = add_cfg_attrs(&cfg_attrs, create_manifest_entry("Fake", &fake_function.sig));
+
     let fake_function = create_fake_function(
+        &fake_function.attrs,
         fn_name,
         fn_visibility,
         fn_asyncness,
+        &fn_qualifiers,
+        fn_generics.clone(),
+        &storage_generics,
         fn_inputs.clone(),
         fn_output,
         fn_block,
         fake_mod_name.clone(),
+        boxed_stream_type,
+        &doubles_cfg,
     );
 
     let fake_module = create_fake_module(
         fake_mod_name,
+        function_type,
+        closure_trait,
+        async_function_type,
+        async_closure_trait,
         params_type,
         return_type,
         &fn_inputs,
-        fn_asyncness
+        fn_asyncness,
+        args.count_calls,
+        &fn_generics,
+        &storage_generics,
+        &args.vis.unwrap_or_else(doubles_vis),
     );
 
     Ok(quote! {
         #fake_function
 
-        #[cfg(test)]
+        #manifest_entry
+
+        #(#cfg_attrs)*
+        #[cfg(#doubles_cfg)]
         #fake_module
     })
 }
@@ -1,4 +1,4 @@
-/// Generates documentation strings for fake proxy functions based on actual function parameters.
+//! Generates documentation strings for fake proxy functions based on actual function parameters.
 
 use quote::quote;
 
@@ -10,6 +10,7 @@ pub(crate) struct FakeProxyDocs {
     param_docs: Vec<String>,
     return_type_str: String,
     setup_example: Vec<String>,
+    setup_async_example: Vec<String>,
     is_async: bool,
 }
 
@@ -63,11 +64,8 @@ impl FakeProxyDocs {
                 .map(|(name, _)| quote::quote!(#name))
                 .collect();
             
-            let params_pattern = if example_params.len() == 1 {
-                quote::quote!(#(#example_params)*)
-            } else {
-                quote::quote!((#(#example_params),*))
-            };
+            // The fake implementation takes the same arguments as the original function
+            let params_pattern = quote::quote!(#(#example_params),*);
             
             vec![
                 format!("{}::setup(|{}| {{", fake_fn_name, quote::quote!(#params_pattern)),
@@ -81,14 +79,42 @@ impl FakeProxyDocs {
             ]
         };
 
+        let example_params: Vec<_> = all_params
+            .iter()
+            .map(|(name, _)| quote::quote!(#name))
+            .collect();
+        let setup_async_example = vec![
+            format!("{}::setup_async(|{}| Box::pin(async move {{", fake_fn_name, quote::quote!(#(#example_params),*)),
+            "    // Full custom implementation, which may await".to_string(),
+            format!("    {}", quote::quote!(#return_type)),
+            "}));".to_string(),
+        ];
+
         Self {
             param_docs,
             return_type_str,
             setup_example,
+            setup_async_example,
             is_async: fn_asyncness.is_some(),
         }
     }
 
+    /// Generates documentation attributes for the `Params` type alias.
+    pub(crate) fn params_type_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "The parameters of the function."]
+            #[doc = ""]
+            #[doc = "A single parameter is used directly, multiple parameters are combined into a tuple."]
+        }
+    }
+
+    /// Generates documentation attributes for the `Return` type alias.
+    pub(crate) fn return_type_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "The return type of the function."]
+        }
+    }
+
     /// Generates documentation attributes for the `setup` function.
     pub(crate) fn setup_docs(&self) -> proc_macro2::TokenStream {
         let return_type_str = &self.return_type_str;
@@ -97,6 +123,8 @@ impl FakeProxyDocs {
             quote! { #[doc = "Sets up the fake's implementation."] },
             quote! { #[doc = ""] },
             quote! { #[doc = "Configures the function that will be executed when the fake is called."] },
+            quote! { #[doc = "The implementation is a closure, which may capture and mutate test state (e.g. a `Vec`"] },
+            quote! { #[doc = "recording side effects). Use `setup_fn()` for implementations calling the faked function recursively."] },
             quote! { #[doc = "Unlike mocks (which track calls) or stubs (which return fixed values),"] },
             quote! { #[doc = "fakes provide full custom implementations that can contain complex logic."] },
         ];
@@ -108,6 +136,7 @@ impl FakeProxyDocs {
                 quote! { #[doc = ""] },
                 quote! { #[doc = "This function is async, but the fake implementation function must be sync."] },
                 quote! { #[doc = "The fake will automatically wrap the return value."] },
+                quote! { #[doc = "Use `setup_async()` for an implementation that awaits."] },
             ]);
         }
         
@@ -145,6 +174,74 @@ impl FakeProxyDocs {
         quote! { #(#docs)* }
     }
 
+    /// Generates documentation attributes for the `setup_fn` function.
+    pub(crate) fn setup_fn_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up a function pointer or a closure not capturing any variables as the fake's implementation."]
+            #[doc = ""]
+            #[doc = "The function pointer is called without any borrow checks, so it may call the faked function recursively,"]
+            #[doc = "which panics for closures set up with `setup()`."]
+            #[doc = "Replaces an implementation configured with `setup()`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `new_f` - The function called with the parameters of the function"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_scoped` function.
+    pub(crate) fn setup_scoped_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the fake like `setup()` and returns a guard clearing it when dropped."]
+            #[doc = ""]
+            #[doc = "The fake is cleared even if the test panics, so the setup can't leak into"]
+            #[doc = "later phases of the test."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `new_f` - The fake implementation, like for `setup()`"]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`fnmock::guard::MockGuard` - the guard calling `clear()` on drop"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_with_delay` function.
+    pub(crate) fn setup_with_delay_docs(&self) -> proc_macro2::TokenStream {
+        let sleep = if self.is_async {
+            "The returned future sleeps with a timer that works with every executor."
+        } else {
+            "The call blocks the thread while sleeping."
+        };
+
+        quote! {
+            #[doc = "Sets up the fake like `setup()` and how long the function sleeps before returning, simulating latency."]
+            #[doc = ""]
+            #[doc = "The implementation runs before the delay, so tests can exercise timeouts and races"]
+            #[doc = "of the code under test."]
+            #[doc = #sleep]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `delay` - How long the function sleeps before returning"]
+            #[doc = "* `new_f` - The fake implementation, like for `setup()`"]
+        }
+    }
+
+    /// Generates documentation attributes for the `delay` function.
+    pub(crate) fn delay_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Gets how long the function sleeps before returning."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the fake function."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "The delay configured with `setup_with_delay()`, `None` otherwise"]
+        }
+    }
+
     /// Generates documentation attributes for the `clear` function.
     pub(crate) fn clear_docs(&self) -> proc_macro2::TokenStream {
         quote! {
@@ -156,6 +253,34 @@ impl FakeProxyDocs {
         }
     }
 
+    /// Generates documentation attributes for the `snapshot` function.
+    pub(crate) fn snapshot_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Saves the current fake state."]
+            #[doc = ""]
+            #[doc = "The snapshot contains the configured implementations (sync and async) and the delay."]
+            #[doc = "Pass it to `restore()` to put the fake back into this state,"]
+            #[doc = "e.g. after a test helper temporarily reconfigured the fake."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`fnmock::snapshot::StateSnapshot` - the saved state"]
+        }
+    }
+
+    /// Generates documentation attributes for the `restore` function.
+    pub(crate) fn restore_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Restores the fake to a state saved with `snapshot()`."]
+            #[doc = ""]
+            #[doc = "Everything configured after the snapshot was taken is discarded."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the snapshot was taken from a different double"]
+        }
+    }
+
     /// Generates documentation attributes for the `is_set` function.
     pub(crate) fn is_set_docs(&self) -> proc_macro2::TokenStream {
         quote! {
@@ -170,21 +295,109 @@ impl FakeProxyDocs {
         }
     }
 
-    /// Generates documentation attributes for the `get_implementation` function.
-    pub(crate) fn get_implementation_docs(&self) -> proc_macro2::TokenStream {
+    /// Generates documentation attributes for the `call` function.
+    pub(crate) fn call_docs(&self) -> proc_macro2::TokenStream {
         quote! {
-            #[doc = "Gets the configured implementation."]
+            #[doc = "Calls the configured implementation."]
             #[doc = ""]
-            #[doc = "This function is used internally by the fake function to retrieve"]
+            #[doc = "This function is used internally by the fake function to execute"]
             #[doc = "the implementation that was configured via `setup()`."]
             #[doc = ""]
             #[doc = "# Returns"]
             #[doc = ""]
-            #[doc = "The configured function implementation"]
+            #[doc = "The result of the configured implementation"]
             #[doc = ""]
             #[doc = "# Panics"]
             #[doc = ""]
             #[doc = "Panics if `setup()` has not been called before calling the fake function"]
         }
     }
+
+    /// Generates documentation attributes for the `times_called` function.
+    pub(crate) fn times_called_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the number of calls of the fake implementation."]
+            #[doc = ""]
+            #[doc = "Calls executing the original function, since the fake isn't set up, aren't counted."]
+            #[doc = "The counter is reset by `clear()`."]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_async` function.
+    pub(crate) fn setup_async_docs(&self) -> proc_macro2::TokenStream {
+        let return_type_str = &self.return_type_str;
+
+        let mut docs = vec![
+            quote! { #[doc = "Sets up an async implementation for the fake."] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Configures a function returning a boxed future, which is awaited when the fake is called."] },
+            quote! { #[doc = "The future doesn't depend on a specific runtime, so it works with tokio, embassy,"] },
+            quote! { #[doc = "`block_on` or any other executor."] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Replaces an implementation configured with `setup()`."] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "# Parameters"] },
+            quote! { #[doc = ""] },
+        ];
+
+        if self.param_docs.is_empty() {
+            docs.push(quote! { #[doc = "No parameters"] });
+        } else {
+            for param in &self.param_docs {
+                docs.push(quote! { #[doc = #param] });
+            }
+        }
+
+        docs.extend(vec![
+            quote! { #[doc = ""] },
+            quote! { #[doc = "# Returns"] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "A boxed future resolving to:"] },
+            quote! { #[doc = #return_type_str] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "# Examples"] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "```ignore"] },
+        ]);
+
+        for line in &self.setup_async_example {
+            docs.push(quote! { #[doc = #line] });
+        }
+
+        docs.push(quote! { #[doc = "```"] });
+
+        quote! { #(#docs)* }
+    }
+
+    /// Generates documentation attributes for the `is_async_set` function.
+    pub(crate) fn is_async_set_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Checks if the fake has been configured with an async implementation."]
+            #[doc = ""]
+            #[doc = "Returns `true` if `setup_async()` has been called and the fake is ready to use,"]
+            #[doc = "or `false` if the fake has not been set up, has been cleared or was set up with `setup()`."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`bool` - `true` if configured, `false` otherwise"]
+        }
+    }
+
+    /// Generates documentation attributes for the `call_async` function.
+    pub(crate) fn call_async_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Calls the configured async implementation."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the fake function to execute"]
+            #[doc = "the implementation that was configured via `setup_async()`."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "The boxed future returned by the configured implementation"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if `setup_async()` has not been called before calling the fake function"]
+        }
+    }
 }
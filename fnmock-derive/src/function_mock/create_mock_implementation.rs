@@ -1,5 +1,12 @@
 use quote::quote;
+use crate::function_mock::forwarding::ForwardedParam;
+use crate::function_mock::named_params::create_params_struct;
 use crate::function_mock::proxy_docs::MockProxyDocs;
+use crate::generic_utils::{create_alias_generics, create_monomorphization_key, has_defaults, is_generic};
+use crate::param_utils::{create_params_matchers, filter_params, is_nameable};
+use crate::return_utils::extract_result_types;
+use crate::cfg_utils::inner_vis;
+use crate::arbitrary_utils::create_arbitrary_proxies;
 
 /// Generates the original function with mock checking logic injected.
 ///
@@ -7,133 +14,973 @@ use crate::function_mock::proxy_docs::MockProxyDocs;
 /// configured via the mock module. If a mock is set, it calls the mock implementation.
 /// Otherwise, it executes the original function body.
 ///
+/// Async functions yield to the executor as often as configured with `setup_with_yields`
+/// after calling the mock implementation. Mocks set up with `setup_blocks` / `setup_pending`
+/// additionally wait for their release handle before returning. Mocks falling back to the
+/// original implementation call the mock with clones of the parameters and only return if
+/// a mock implementation handled the call. Otherwise, the original implementation is timed
+/// by a `fnmock::timing::CallTimer`, which records its duration in the mock.
+///
+/// Mocks forwarding parameters call the forwarding implementation first, if it is set up,
+/// passing the recorded parameters and the forwarded ones.
+///
+/// For generic functions the test version of the function has to carry the additional
+/// recording bounds, so separate versions are generated for test and non-test builds.
+/// Default type parameters are kept on the non-test version, which therefore allows
+/// `invalid_type_param_default` like the original function had to.
+///
 /// # Arguments
 ///
+/// * `fn_attrs` - The attributes of the original function (doc comments, `cfg`, ...)
 /// * `fn_name` - The name of the original function
 /// * `fn_visibility` - The visibility modifier of the function (pub, pub(crate), etc.)
 /// * `fn_asyncness` - Optional async keyword if the function is async
+/// * `fn_qualifiers` - The `unsafe` keyword and the ABI of the function (see `fn_qualifiers`)
+/// * `fn_generics` - The generics of the original function
+/// * `recording_generics` - The generics including the bounds required for recording
 /// * `fn_inputs` - The function parameters
 /// * `fn_output` - The return type
 /// * `fn_block` - The original function body to execute when mock is not set
 /// * `mock_mod_name` - The name of the mock module containing the mock infrastructure
 /// * `params_to_tuple` - Token stream that converts parameters into a tuple for the mock
+/// * `params_to_cloned_tuple` - Token stream that converts clones of the parameters into a tuple for the mock
+/// * `forwarded_params` - The parameters passed to the forwarding implementation
+/// * `doubles_cfg` - The `cfg` predicate the mock check is compiled for (see `doubles_cfg`)
 ///
 /// # Returns
 ///
 /// Generated token stream for the function with injected mock checking logic
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create_mock_function(
+    fn_attrs: &[syn::Attribute],
     fn_name: syn::Ident,
     fn_visibility: syn::Visibility,
     fn_asyncness: Option<syn::token::Async>,
+    fn_qualifiers: &proc_macro2::TokenStream,
+    fn_generics: syn::Generics,
+    recording_generics: &syn::Generics,
     fn_inputs: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
     fn_output: syn::ReturnType,
-    fn_block: Box<syn::Block>,
+    fn_block: syn::Block,
     mock_mod_name: syn::Ident,
     params_to_tuple: proc_macro2::TokenStream,
+    params_to_cloned_tuple: proc_macro2::TokenStream,
+    forwarded_params: &[ForwardedParam],
+    doubles_cfg: &proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
     let original_fn_stmts = &fn_block.stmts;
-    
-    quote! {
-        #[allow(unused_variables)]
-        #fn_visibility #fn_asyncness fn #fn_name(#fn_inputs) #fn_output {
-            // Call the mock implementation if set (only in test mode)
-            #[cfg(test)]
-            if #mock_mod_name::is_set() {
-                return #mock_mod_name::call(#params_to_tuple);
+    let (_, recording_ty_generics, _) = recording_generics.split_for_impl();
+    let recording_turbofish = recording_ty_generics.as_turbofish();
+    let return_mock_result = create_return_mock_result(&mock_mod_name, quote! { #recording_turbofish }, fn_asyncness);
+    let call_mock = quote! {
+        if #mock_mod_name::falls_back_to_original #recording_turbofish() {
+            if let Some(mock_result) = #mock_mod_name::try_call #recording_turbofish(#params_to_cloned_tuple) {
+                #return_mock_result
             }
+        } else {
+            let mock_result = #mock_mod_name::call #recording_turbofish(#params_to_tuple);
+            #return_mock_result
+        }
+    };
+
+    if !is_generic(&fn_generics) {
+        let where_clause = &fn_generics.where_clause;
+
+        let call_forwarding_mock = if forwarded_params.is_empty() {
+            quote! {}
+        } else {
+            let forwarded_names: Vec<_> = forwarded_params.iter().map(|param| &param.name).collect();
+            let forwarded_args: Vec<_> = forwarded_params.iter().map(|param| &param.arg).collect();
+            quote! {
+                // Call the forwarding implementation with the not recorded params if set (only in test mode)
+                #[cfg(#doubles_cfg)]
+                if let Some(forwarding_implementation) = #mock_mod_name::forwarding_implementation() {
+                    #(
+                        #[allow(unused_mut)]
+                        let mut #forwarded_names = #forwarded_names;
+                    )*
+                    let mock_result = #mock_mod_name::call_with(
+                        #params_to_tuple,
+                        |params| forwarding_implementation(params, #(#forwarded_args),*),
+                    );
+                    #return_mock_result
+                }
+            }
+        };
+
+        return quote! {
+            #(#fn_attrs)*
+            #[allow(unused_variables)]
+            #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #fn_generics (#fn_inputs) #fn_output #where_clause {
+                #call_forwarding_mock
+
+                // Call the mock implementation if set (only in test mode)
+                #[cfg(#doubles_cfg)]
+                let _fnmock_call_timer = if #mock_mod_name::is_set() {
+                    #call_mock
+                    // Only reached if the mock falls back to the original implementation, which is timed
+                    Some(fnmock::timing::CallTimer::start(#mock_mod_name::record_duration))
+                } else {
+                    None
+                };
+
+                #(#original_fn_stmts)*
+            }
+        };
+    }
+
+    let where_clause = &fn_generics.where_clause;
+    let allow_defaults = if has_defaults(&fn_generics) {
+        quote! { #[allow(invalid_type_param_default)] }
+    } else {
+        quote! {}
+    };
+    let recording_where_clause = &recording_generics.where_clause;
+
+    quote! {
+        #(#fn_attrs)*
+        #[cfg(not(#doubles_cfg))]
+        #allow_defaults
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #fn_generics (#fn_inputs) #fn_output #where_clause {
+            #(#original_fn_stmts)*
+        }
+
+        #(#fn_attrs)*
+        #[cfg(#doubles_cfg)]
+        #[allow(unused_variables, clippy::multiple_bound_locations)]
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #recording_generics (#fn_inputs) #fn_output #recording_where_clause {
+            // Call the mock implementation of this monomorphization if set
+            let _fnmock_call_timer = if #mock_mod_name::is_set #recording_turbofish() {
+                #call_mock
+                // Only reached if the mock falls back to the original implementation, which is timed
+                Some(fnmock::timing::CallTimer::start(#mock_mod_name::record_duration #recording_turbofish))
+            } else {
+                None
+            };
 
             #(#original_fn_stmts)*
         }
     }
 }
 
+/// Generates the statements returning the result of the mock implementation (`mock_result`).
+///
+/// For async functions, the result is returned after yielding as often as configured
+/// with `setup_with_yields`, so tests can cancel the future at a deterministic point.
+/// If the mock is set up with a delay, the function sleeps before returning
+/// (blocking the thread for sync functions, with a runtime independent timer for async functions).
+/// If the mock is set up to hang, the result is returned after the release handle was released
+/// (blocking the thread for sync functions, pending for async functions).
+fn create_return_mock_result(
+    mock_mod_name: &syn::Ident,
+    turbofish: proc_macro2::TokenStream,
+    fn_asyncness: Option<syn::token::Async>,
+) -> proc_macro2::TokenStream {
+    if fn_asyncness.is_none() {
+        return quote! {
+            if let Some(delay) = #mock_mod_name::delay #turbofish() {
+                ::std::thread::sleep(delay);
+            }
+            if let Some(release_handle) = #mock_mod_name::release_handle #turbofish() {
+                release_handle.wait();
+            }
+            return mock_result;
+        };
+    }
+
+    quote! {
+        for _ in 0..#mock_mod_name::yields #turbofish() {
+            fnmock::future::yield_now().await;
+        }
+        if let Some(delay) = #mock_mod_name::delay #turbofish() {
+            fnmock::future::sleep(delay).await;
+        }
+        if let Some(release_handle) = #mock_mod_name::release_handle #turbofish() {
+            release_handle.wait_async().await;
+        }
+        return mock_result;
+    }
+}
+
 /// Generates a mock module containing the mock infrastructure.
 ///
 /// Creates a module with the same name as the mock function that contains:
-/// - Type aliases for parameters and return type
+/// - Public `Params` and `Return` type aliases for the recorded parameters and the return type
+///   (generic over the type parameters they use, `Return` is omitted if the type can't be named)
 /// - Thread-local storage for the FunctionMock instance
+///   (or a `GenericDoubles` holding one FunctionMock per monomorphization for generic functions),
+///   registered with `fnmock::registry` on first use
 /// - Proxy functions for all mock operations
 ///
 /// # Arguments
 ///
 /// * `mock_fn_name` - The name of the mock module (same as mock function name)
-/// * `params_type` - The type representing the function parameters (single type or tuple)
+/// * `params_type` - The type representing the function parameters (single type, tuple or params struct)
+/// * `params_struct` - The name of the params struct to generate, for `named_params` mocks (see `create_params_struct`)
 /// * `return_type` - The return type of the function
-/// * `fn_inputs` - The original function parameters (for documentation)
-/// * `ignore_indices` - Indices of parameters to ignore (for documentation)
+/// * `recording_generics` - The generics of the function including the bounds required for recording
+/// * `fn_inputs` - The function parameters with the recorded types (for documentation and matchers)
+/// * `ignore_indices` - Indices of parameters to ignore (for documentation and matchers)
 /// * `params_to_tuple` - Token stream that converts parameters into a tuple
-/// * `filtered_fn_inputs` - Function parameters excluding ignored ones
+/// * `filtered_fn_inputs` - Original function parameters excluding ignored ones
+/// * `forwarded_params` - The parameters passed to the forwarding implementation
+/// * `module_vis` - The visibility of the mock module (see `doubles_vis`)
+/// * `no_history` - Whether the mock only counts its calls instead of recording their params
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create_mock_module(
     mock_fn_name: syn::Ident,
     params_type: syn::Type,
+    params_struct: Option<&syn::Ident>,
     return_type: syn::Type,
+    recording_generics: &syn::Generics,
     fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
     ignore_indices: &[usize],
     fn_asyncness: Option<syn::token::Async>,
     params_to_tuple: proc_macro2::TokenStream,
     filtered_fn_inputs: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    forwarded_params: &[ForwardedParam],
+    module_vis: &syn::Visibility,
+    no_history: bool,
 ) -> proc_macro2::TokenStream {
+    let doubles_vis = inner_vis(module_vis);
     // Generate documentation using the proxy_docs module
-    let docs = MockProxyDocs::new(&mock_fn_name, fn_inputs, ignore_indices, &return_type, fn_asyncness);
+    let docs = MockProxyDocs::new(&mock_fn_name, fn_inputs, ignore_indices, &return_type, fn_asyncness, params_struct.is_some());
     let call_docs = docs.call_docs();
     let setup_docs = docs.setup_docs();
+    let setup_scoped_docs = docs.setup_scoped_docs();
+    let setup_panics_docs = docs.setup_panics_docs();
+    let setup_times_docs = docs.setup_times_docs();
+    let setup_once_docs = docs.setup_once_docs();
+    let enqueue_return_docs = docs.enqueue_return_docs();
     let clear_docs = docs.clear_docs();
+    let clear_history_docs = docs.clear_history_docs();
     let is_set_docs = docs.is_set_docs();
+    let on_call_docs = docs.on_call_docs();
+    let setup_when_docs = docs.setup_when_docs();
+    let fallback_to_original_docs = docs.fallback_to_original_docs();
+    let spy_docs = docs.spy_docs();
+    let falls_back_to_original_docs = docs.falls_back_to_original_docs();
+    let try_call_docs = docs.try_call_docs();
+    let after_call_docs = docs.after_call_docs();
+    let capture_docs = docs.capture_docs();
+    let capture_into_docs = docs.capture_into_docs();
+    let in_sequence_docs = docs.in_sequence_docs();
+    let expect_times_docs = docs.expect_times_docs();
+    let expect_with_docs = docs.expect_with_docs();
+    let verify_docs = docs.verify_docs();
+    let verify_on_drop_docs = docs.verify_on_drop_docs();
     let assert_times_docs = docs.assert_times_docs();
+    let assert_times_eventually_docs = docs.assert_times_eventually_docs();
+    let assert_times_at_least_docs = docs.assert_times_at_least_docs();
+    let assert_times_at_most_docs = docs.assert_times_at_most_docs();
+    let assert_times_between_docs = docs.assert_times_between_docs();
+    let assert_times_matching_docs = docs.assert_times_matching_docs();
+    let assert_panicked_times_docs = docs.assert_panicked_times_docs();
     let assert_with_docs = docs.assert_with_docs();
+    let assert_last_call_with_docs = docs.assert_last_call_with_docs();
+    let assert_never_called_with_docs = docs.assert_never_called_with_docs();
+    let assert_nth_call_with_docs = docs.assert_nth_call_with_docs();
+    let assert_with_matching_docs = docs.assert_with_matching_docs();
+    let assert_with_matchers_docs = docs.assert_with_matchers_docs();
+    let try_assert_times_docs = docs.try_assert_docs("assert_times");
+    let try_assert_with_docs = docs.try_assert_docs("assert_with");
+    let try_assert_with_matchers_docs = docs.try_assert_docs("assert_with_matchers");
+    let calls_docs = docs.calls_docs();
+    let times_called_docs = docs.times_called_docs();
+    let first_call_index_docs = docs.first_call_index_docs();
+    let last_call_params_docs = docs.last_call_params_docs();
+    let unique_calls_docs = docs.unique_calls_docs();
+    let formatted_calls_docs = docs.formatted_calls_docs();
+    let record_duration_docs = docs.record_duration_docs();
+    let call_durations_docs = docs.call_durations_docs();
+    let assert_no_duplicate_calls_docs = docs.assert_no_duplicate_calls_docs();
+    let snapshot_docs = docs.snapshot_docs();
+    let restore_docs = docs.restore_docs();
+
+    let params_alias_generics = create_alias_generics(recording_generics, &params_type);
+    let (_, params_alias_ty_generics, _) = params_alias_generics.split_for_impl();
+    let params_alias = quote! { Params #params_alias_ty_generics };
+    let params_type_docs = docs.params_type_docs();
+    let params_struct_definition = params_struct
+        .map(|struct_name| create_params_struct(struct_name, &filter_params(fn_inputs, ignore_indices), &doubles_vis, &docs));
+    let (matcher_params, combined_matcher) = create_params_matchers(&filter_params(fn_inputs, ignore_indices), &params_alias, params_struct.is_some());
+
+    let (return_alias, return_alias_definition) = if is_nameable(&return_type) {
+        let return_alias_generics = create_alias_generics(recording_generics, &return_type);
+        let (_, return_alias_ty_generics, _) = return_alias_generics.split_for_impl();
+        let return_type_docs = docs.return_type_docs();
+        (
+            quote! { Return #return_alias_ty_generics },
+            quote! {
+                #return_type_docs
+                #doubles_vis type Return #return_alias_generics = #return_type;
+            },
+        )
+    } else {
+        (quote! { #return_type }, quote! {})
+    };
+
+    let mock_type = quote! {
+        fnmock::function_mock::FunctionMock<#params_alias, #return_alias>
+    };
+
+    // Mocks without history only count their calls
+    let new_mock = if no_history {
+        quote! { fnmock::function_mock::FunctionMock::new_without_history }
+    } else {
+        quote! { fnmock::function_mock::FunctionMock::new }
+    };
+
+    // Generic functions store one mock per monomorphization and pass their generics to the proxy functions
+    let (storage_type, new_storage, mock, mock_mut, proxy_generics) = if is_generic(recording_generics) {
+        let key = create_monomorphization_key(recording_generics);
+        let mock_of_monomorphization = quote! {
+            mock.borrow_mut().get_or_create::<#key, #mock_type>(#new_mock)
+        };
+        (
+            quote! { fnmock::generic_doubles::GenericDoubles },
+            quote! { fnmock::generic_doubles::GenericDoubles::new },
+            mock_of_monomorphization.clone(),
+            mock_of_monomorphization,
+            recording_generics.clone(),
+        )
+    } else {
+        (
+            mock_type.clone(),
+            new_mock,
+            quote! { mock.borrow() },
+            quote! { mock.borrow_mut() },
+            syn::Generics::default(),
+        )
+    };
+    let (impl_generics, proxy_ty_generics, where_clause) = proxy_generics.split_for_impl();
+    let proxy_turbofish = proxy_ty_generics.as_turbofish();
+
+    // Returning a constant (or repeating a value) requires a cloneable return type, which can't be checked by the macro.
+    // The bound is higher-ranked, so it's only checked when the proxy is used instead of
+    // failing to compile for every non-cloneable return type.
+    let setup_returns_proxy = if is_nameable(&return_type) {
+        let setup_returns_docs = docs.setup_returns_docs();
+        let setup_returns_sequence_docs = docs.setup_returns_sequence_docs();
+        let setup_returns_sequence_repeat_last_docs = docs.setup_returns_sequence_repeat_last_docs();
+        let mut setup_returns_generics = proxy_generics.clone();
+        setup_returns_generics.make_where_clause().predicates.push(syn::parse_quote! { for<'a> #return_alias: Clone });
+        let setup_returns_where_clause = &setup_returns_generics.where_clause;
+        quote! {
+            #setup_returns_docs
+            #doubles_vis fn setup_returns #impl_generics (return_value: #return_alias) #setup_returns_where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_returns(return_value)
+                })
+            }
+
+            #setup_returns_sequence_docs
+            #doubles_vis fn setup_returns_sequence #impl_generics (return_values: Vec<#return_alias>) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_returns_sequence(return_values)
+                })
+            }
+
+            #setup_returns_sequence_repeat_last_docs
+            #doubles_vis fn setup_returns_sequence_repeat_last #impl_generics (return_values: Vec<#return_alias>) #setup_returns_where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_returns_sequence_repeat_last(return_values)
+                })
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // The case set up by `fnmock::when!`, which needs the return type for the pending `When`
+    let when_proxy = if is_nameable(&return_type) {
+        let when_docs = docs.when_docs();
+        quote! {
+            #when_docs
+            #doubles_vis fn when #impl_generics (#matcher_params) -> fnmock::when::When<#params_alias, #return_alias> #where_clause {
+                let matcher: fnmock::matchers::Matcher<#params_alias> = #combined_matcher;
+                fnmock::when::when_matching(matcher, |matcher, new_f| MOCK.with(|mock| {
+                    #mock_mut.setup_when(matcher, new_f)
+                }))
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let arbitrary_proxies = if is_nameable(&return_type) {
+        create_arbitrary_proxies(&doubles_vis, &return_alias, &proxy_generics, false, quote! {
+            MOCK.with(|mock| {
+                #mock_mut.setup(move |_| generator())
+            })
+        })
+    } else {
+        quote! {}
+    };
+
+    // Functions returning a `Result` get shortcuts for the success and the error case.
+    // Like for `setup_returns`, the `Clone` bounds are only checked when the shortcut is used.
+    let result_shortcuts = match extract_result_types(&return_type).filter(|_| is_nameable(&return_type)) {
+        Some((ok_type, err_type)) => {
+            let setup_ok_docs = docs.setup_ok_docs();
+            let setup_err_docs = docs.setup_err_docs();
+            let mut setup_ok_generics = proxy_generics.clone();
+            setup_ok_generics.make_where_clause().predicates.push(syn::parse_quote! { for<'a> #ok_type: Clone });
+            let setup_ok_where_clause = &setup_ok_generics.where_clause;
+            let mut setup_err_generics = proxy_generics.clone();
+            setup_err_generics.make_where_clause().predicates.push(syn::parse_quote! { for<'a> #err_type: Clone });
+            let setup_err_where_clause = &setup_err_generics.where_clause;
+            quote! {
+                #setup_ok_docs
+                #doubles_vis fn setup_ok #impl_generics (value: #ok_type) #setup_ok_where_clause {
+                    MOCK.with(|mock| {
+                        #mock_mut.setup(move |_| Ok(::std::clone::Clone::clone(&value)))
+                    })
+                }
+
+                #setup_err_docs
+                #doubles_vis fn setup_err #impl_generics (error: #err_type) #setup_err_where_clause {
+                    MOCK.with(|mock| {
+                        #mock_mut.setup(move |_| Err(::std::clone::Clone::clone(&error)))
+                    })
+                }
+            }
+        }
+        None => quote! {},
+    };
+
+    // Only async functions can yield to the executor, sync functions block instead of being pending
+    let setup_hanging_docs = docs.setup_hanging_docs();
+    let release_handle_docs = docs.release_handle_docs();
+    let setup_with_delay_docs = docs.setup_with_delay_docs();
+    let delay_docs = docs.delay_docs();
+    let delay_proxies = quote! {
+        #setup_with_delay_docs
+        #doubles_vis fn setup_with_delay #impl_generics (delay: ::std::time::Duration, new_f: impl FnMut(#params_alias) -> #return_alias + 'static) #where_clause {
+            MOCK.with(|mock| {
+                #mock_mut.setup_with_delay(delay, new_f)
+            })
+        }
+
+        #delay_docs
+        #doubles_vis fn delay #impl_generics () -> Option<::std::time::Duration> #where_clause {
+            MOCK.with(|mock| {
+                #mock.delay()
+            })
+        }
+    };
+    let timing_proxies = if fn_asyncness.is_some() {
+        let setup_with_yields_docs = docs.setup_with_yields_docs();
+        let yields_docs = docs.yields_docs();
+        quote! {
+            #setup_hanging_docs
+            #doubles_vis fn setup_pending #impl_generics (new_f: impl FnMut(#params_alias) -> #return_alias + 'static) -> fnmock::hang::ReleaseHandle #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_hanging(new_f)
+                })
+            }
+
+            #setup_with_yields_docs
+            #doubles_vis fn setup_with_yields #impl_generics (yields: u32, new_f: impl FnMut(#params_alias) -> #return_alias + 'static) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_with_yields(yields, new_f)
+                })
+            }
+
+            #yields_docs
+            #doubles_vis fn yields #impl_generics () -> u32 #where_clause {
+                MOCK.with(|mock| {
+                    #mock.yields()
+                })
+            }
+        }
+    } else {
+        quote! {
+            #setup_hanging_docs
+            #doubles_vis fn setup_blocks #impl_generics (new_f: impl FnMut(#params_alias) -> #return_alias + 'static) -> fnmock::hang::ReleaseHandle #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_hanging(new_f)
+                })
+            }
+        }
+    };
+
+    // Mocks forwarding params store the forwarding implementation next to the mock
+    let (forwarding_proxies, clear_forwarding, snapshot_state, restore_state) = if forwarded_params.is_empty() {
+        (
+            quote! {},
+            quote! {},
+            quote! { mock.borrow().clone() },
+            quote! { *mock.borrow_mut() = snapshot.into_state(stringify!(#mock_fn_name)) },
+        )
+    } else {
+        let forwarded_types: Vec<_> = forwarded_params.iter().map(|param| &param.ty).collect();
+        let forwarding_type_docs = docs.forwarding_type_docs();
+        let setup_forwarding_docs = docs.setup_forwarding_docs();
+        let forwarding_implementation_docs = docs.forwarding_implementation_docs();
+        let call_with_docs = docs.call_with_docs();
+        (
+            quote! {
+                #forwarding_type_docs
+                #doubles_vis type Forwarding = fn(#params_alias, #(#forwarded_types),*) -> #return_alias;
+
+                thread_local! {
+                    static FORWARDING: std::cell::Cell<Option<Forwarding>> = const { std::cell::Cell::new(None) };
+                }
+
+                #setup_forwarding_docs
+                #doubles_vis fn setup_forwarding(new_f: Forwarding) {
+                    // Accessing MOCK registers the module, so the forwarding implementation is cleared with it
+                    MOCK.with(|_| {
+                        FORWARDING.with(|forwarding| forwarding.set(Some(new_f)))
+                    })
+                }
+
+                #forwarding_implementation_docs
+                #doubles_vis fn forwarding_implementation() -> Option<Forwarding> {
+                    FORWARDING.with(|forwarding| forwarding.get())
+                }
+
+                #call_with_docs
+                #doubles_vis fn call_with(params: #params_alias, implementation: impl FnOnce(#params_alias) -> #return_alias) -> #return_alias {
+                    let call = MOCK.with(|mock| {
+                        mock.borrow_mut().prepare_forwarded_call(params)
+                    });
+                    call.run_with(implementation, |message| MOCK.with(|mock| {
+                        mock.borrow_mut().record_panic(message)
+                    }))
+                }
+            },
+            quote! { FORWARDING.with(|forwarding| forwarding.set(None)); },
+            quote! { (mock.borrow().clone(), FORWARDING.with(|forwarding| forwarding.get())) },
+            quote! {
+                let (state, forwarding_state): (#mock_type, Option<Forwarding>) = snapshot.into_state(stringify!(#mock_fn_name));
+                *mock.borrow_mut() = state;
+                FORWARDING.with(|forwarding| forwarding.set(forwarding_state));
+            },
+        )
+    };
 
     quote! {
-        pub(crate) mod #mock_fn_name {
+        #[allow(clippy::multiple_bound_locations)]
+        #module_vis mod #mock_fn_name {
             use super::*;
 
+            #params_type_docs
+            #doubles_vis type Params #params_alias_generics = #params_type;
+
+            #params_struct_definition
+
+            #return_alias_definition
+
             thread_local! {
-                static MOCK: std::cell::RefCell<fnmock::function_mock::FunctionMock<
-                    #params_type,
-                    #return_type,
-                >> = std::cell::RefCell::new(fnmock::function_mock::FunctionMock::new(stringify!(#mock_fn_name)));
+                static MOCK: std::cell::RefCell<#storage_type> = {
+                    fnmock::registry::register(stringify!(#mock_fn_name), snapshot, restore, clear, control);
+                    std::cell::RefCell::new(#new_storage(stringify!(#mock_fn_name)))
+                };
+            }
+
+            // Gives the registry access to the stored mock
+            fn control(f: &mut dyn FnMut(&dyn fnmock::registry::MockControl)) {
+                MOCK.with(|mock| f(&*mock.borrow()))
             }
 
             #call_docs
-            pub(crate) fn call(params: #params_type) -> #return_type {
+            #doubles_vis fn call #impl_generics (params: #params_alias) -> #return_alias #where_clause {
+                // The mock isn't borrowed while the implementation runs, so it may inspect the mock
+                let call = MOCK.with(|mock| {
+                    #mock_mut.prepare_call(params)
+                });
+                call.run(|message| MOCK.with(|mock| {
+                    #mock_mut.record_panic(message)
+                }))
+            }
+
+            #try_call_docs
+            #doubles_vis fn try_call #impl_generics (params: #params_alias) -> Option<#return_alias> #where_clause {
+                let call = MOCK.with(|mock| {
+                    #mock_mut.prepare_call(params)
+                });
+                call.try_run(|message| MOCK.with(|mock| {
+                    #mock_mut.record_panic(message)
+                }))
+            }
+
+            #setup_docs
+            #doubles_vis fn setup #impl_generics (new_f: impl FnMut(#params_alias) -> #return_alias + 'static) #where_clause {
                 MOCK.with(|mock| {
-                    mock.borrow_mut().call(params)
+                    #mock_mut.setup(new_f)
                 })
             }
 
-            #setup_docs
-            pub(crate) fn setup(new_f: fn(#params_type) -> #return_type) {
+            #setup_scoped_docs
+            #doubles_vis fn setup_scoped #impl_generics (new_f: impl FnMut(#params_alias) -> #return_alias + 'static) -> fnmock::guard::MockGuard #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup(new_f)
+                });
+                fnmock::guard::MockGuard::new(clear)
+            }
+
+            #setup_panics_docs
+            #doubles_vis fn setup_panics #impl_generics (message: impl Into<String>) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_panics(message)
+                })
+            }
+
+            #setup_times_docs
+            #doubles_vis fn setup_times #impl_generics (times: u32, new_f: impl FnMut(#params_alias) -> #return_alias + 'static) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_times(times, new_f)
+                })
+            }
+
+            #setup_once_docs
+            #doubles_vis fn setup_once #impl_generics (new_f: impl FnMut(#params_alias) -> #return_alias + 'static) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_times(1, new_f)
+                })
+            }
+
+            #enqueue_return_docs
+            #doubles_vis fn enqueue_return #impl_generics (return_value: impl FnOnce() -> #return_alias + 'static) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.enqueue_return(return_value)
+                })
+            }
+
+            #setup_when_docs
+            #doubles_vis fn setup_when #impl_generics (matcher: fnmock::matchers::Matcher<#params_alias>, new_f: impl FnMut(#params_alias) -> #return_alias + 'static) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.setup_when(matcher, new_f)
+                })
+            }
+
+            #fallback_to_original_docs
+            #doubles_vis fn fallback_to_original #impl_generics () #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.fallback_to_original()
+                })
+            }
+
+            #spy_docs
+            #doubles_vis fn spy #impl_generics () #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.spy()
+                })
+            }
+
+            #falls_back_to_original_docs
+            #doubles_vis fn falls_back_to_original #impl_generics () -> bool #where_clause {
+                MOCK.with(|mock| {
+                    #mock.falls_back_to_original()
+                })
+            }
+
+            #when_proxy
+
+            #setup_returns_proxy
+
+            #arbitrary_proxies
+
+            #result_shortcuts
+
+            #delay_proxies
+
+            #timing_proxies
+
+            #forwarding_proxies
+
+            #release_handle_docs
+            #doubles_vis fn release_handle #impl_generics () -> Option<fnmock::hang::ReleaseHandle> #where_clause {
                 MOCK.with(|mock| {
-                    mock.borrow_mut().setup(new_f)
+                    #mock.release_handle()
                 })
             }
 
             #clear_docs
-            pub(crate) fn clear() {
+            #doubles_vis fn clear() {
+                #clear_forwarding
                 MOCK.with(|mock|{
                     mock.borrow_mut().clear()
                 })
             }
 
+            #clear_history_docs
+            #doubles_vis fn clear_history #impl_generics () #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.clear_history()
+                })
+            }
+
             #is_set_docs
-            pub(crate) fn is_set() -> bool {
+            #doubles_vis fn is_set #impl_generics () -> bool #where_clause {
+                MOCK.with(|mock| {
+                    #mock.is_set()
+                })
+            }
+
+            #on_call_docs
+            #doubles_vis fn on_call #impl_generics (hook: impl Fn(&#params_alias) + 'static) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.on_call(hook)
+                })
+            }
+
+            #after_call_docs
+            #doubles_vis fn after_call #impl_generics (hook: impl Fn(&#params_alias, &#return_alias) + 'static) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.after_call(hook)
+                })
+            }
+
+            #capture_docs
+            #doubles_vis fn capture #impl_generics () -> fnmock::captor::Captor<#params_alias> #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.capture()
+                })
+            }
+
+            #capture_into_docs
+            #doubles_vis fn capture_into #impl_generics (collector: ::std::rc::Rc<::std::cell::RefCell<impl Extend<#params_alias> + 'static>>) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.capture_into(collector)
+                })
+            }
+
+            #in_sequence_docs
+            #doubles_vis fn in_sequence #impl_generics (sequence: &fnmock::sequence::Sequence) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.in_sequence(sequence)
+                })
+            }
+
+            #calls_docs
+            #doubles_vis fn calls #impl_generics () -> Vec<#params_alias> #where_clause {
+                MOCK.with(|mock| {
+                    #mock.calls()
+                })
+            }
+
+            #last_call_params_docs
+            #doubles_vis fn last_call_params #impl_generics () -> Option<#params_alias> #where_clause {
                 MOCK.with(|mock| {
-                    mock.borrow().is_set()
+                    #mock.last_call_params()
                 })
             }
 
+            #times_called_docs
+            #doubles_vis fn times_called #impl_generics () -> u32 #where_clause {
+                MOCK.with(|mock| {
+                    #mock.times()
+                })
+            }
+
+            #first_call_index_docs
+            #doubles_vis fn first_call_index #impl_generics () -> Option<u64> #where_clause {
+                MOCK.with(|mock| {
+                    #mock.first_call_index()
+                })
+            }
+
+            #formatted_calls_docs
+            #doubles_vis fn formatted_calls #impl_generics () -> String #where_clause {
+                MOCK.with(|mock| {
+                    #mock.formatted_calls()
+                })
+            }
+
+            #record_duration_docs
+            #doubles_vis fn record_duration #impl_generics (duration: ::std::time::Duration) #where_clause {
+                MOCK.with(|mock| {
+                    #mock_mut.record_duration(duration)
+                })
+            }
+
+            #call_durations_docs
+            #doubles_vis fn call_durations #impl_generics () -> Vec<::std::time::Duration> #where_clause {
+                MOCK.with(|mock| {
+                    #mock.call_durations()
+                })
+            }
+
+            #unique_calls_docs
+            #doubles_vis fn unique_calls #impl_generics () -> Vec<#params_alias> #where_clause {
+                MOCK.with(|mock| {
+                    #mock.unique_calls()
+                })
+            }
+
+            #expect_times_docs
+            #doubles_vis fn expect_times #impl_generics (expected_num_of_calls: u32) #where_clause {
+                MOCK.with(|mock| {
+                    // Lets a surrounding test scope verify the expectations at the end of the test
+                    if !#mock.has_expectations() {
+                        fnmock::registry::register_verification(verify #proxy_turbofish);
+                    }
+                    #mock_mut.expect_times(expected_num_of_calls)
+                })
+            }
+
+            #expect_with_docs
+            #doubles_vis fn expect_with #impl_generics (#filtered_fn_inputs) #where_clause {
+                MOCK.with(|mock| {
+                    // Lets a surrounding test scope verify the expectations at the end of the test
+                    if !#mock.has_expectations() {
+                        fnmock::registry::register_verification(verify #proxy_turbofish);
+                    }
+                    #mock_mut.expect_with(#params_to_tuple)
+                })
+            }
+
+            #verify_docs
+            #doubles_vis fn verify #impl_generics () #where_clause {
+                MOCK.with(|mock| {
+                    #mock.verify()
+                })
+            }
+
+            #verify_on_drop_docs
+            #doubles_vis fn verify_on_drop #impl_generics () -> fnmock::guard::Checkpoint #where_clause {
+                fnmock::guard::Checkpoint::new(verify #proxy_turbofish)
+            }
+
             #assert_times_docs
-            pub(crate) fn assert_times(expected_num_of_calls: u32) {
+            #doubles_vis fn assert_times #impl_generics (expected_num_of_calls: u32) #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_times(expected_num_of_calls)
+                })
+            }
+
+            #assert_times_eventually_docs
+            #doubles_vis async fn assert_times_eventually #impl_generics (expected_num_of_calls: u32, timeout: std::time::Duration) #where_clause {
+                // Calls are only added, so the count can't become the expected one after exceeding it
+                fnmock::future::wait_until(|| MOCK.with(|mock| { #mock.times() >= expected_num_of_calls }), timeout).await;
+                MOCK.with(|mock| {
+                    #mock.assert_times(expected_num_of_calls)
+                })
+            }
+
+            #assert_times_at_least_docs
+            #doubles_vis fn assert_times_at_least #impl_generics (min_num_of_calls: u32) #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_times_at_least(min_num_of_calls)
+                })
+            }
+
+            #assert_times_at_most_docs
+            #doubles_vis fn assert_times_at_most #impl_generics (max_num_of_calls: u32) #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_times_at_most(max_num_of_calls)
+                })
+            }
+
+            #assert_times_between_docs
+            #doubles_vis fn assert_times_between #impl_generics (min_num_of_calls: u32, max_num_of_calls: u32) #where_clause {
                 MOCK.with(|mock| {
-                    mock.borrow().assert_times(expected_num_of_calls)
+                    #mock.assert_times_between(min_num_of_calls, max_num_of_calls)
+                })
+            }
+
+            #assert_times_matching_docs
+            #doubles_vis fn assert_times_matching #impl_generics (predicate: impl Fn(u32) -> bool, description: &str) #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_times_matching(predicate, description)
+                })
+            }
+
+            #assert_panicked_times_docs
+            #doubles_vis fn assert_panicked_times #impl_generics (expected_num_of_panics: u32) #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_panicked_times(expected_num_of_panics)
                 })
             }
 
             #assert_with_docs
-            pub(crate) fn assert_with(#filtered_fn_inputs) {
+            #doubles_vis fn assert_with #impl_generics (#filtered_fn_inputs) #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_with(#params_to_tuple)
+                })
+            }
+
+            #assert_with_matching_docs
+            #doubles_vis fn assert_with_matching #impl_generics (matcher: fnmock::matchers::Matcher<#params_alias>) #where_clause {
                 MOCK.with(|mock| {
-                    mock.borrow().assert_with(#params_to_tuple)
+                    #mock.assert_with_matching(&matcher)
+                })
+            }
+
+            #assert_with_matchers_docs
+            #doubles_vis fn assert_with_matchers #impl_generics (#matcher_params) #where_clause {
+                let matcher: fnmock::matchers::Matcher<#params_alias> = #combined_matcher;
+                MOCK.with(|mock| {
+                    #mock.assert_with_matching(&matcher)
+                })
+            }
+
+            #assert_no_duplicate_calls_docs
+            #doubles_vis fn assert_no_duplicate_calls #impl_generics () #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_no_duplicate_calls()
+                })
+            }
+
+            #try_assert_times_docs
+            #doubles_vis fn try_assert_times #impl_generics (expected_num_of_calls: u32) -> Result<(), fnmock::failure::VerificationFailure> #where_clause {
+                fnmock::failure::try_verify(|| MOCK.with(|mock| {
+                    #mock.assert_times(expected_num_of_calls)
+                }))
+            }
+
+            #try_assert_with_docs
+            #doubles_vis fn try_assert_with #impl_generics (#filtered_fn_inputs) -> Result<(), fnmock::failure::VerificationFailure> #where_clause {
+                fnmock::failure::try_verify(|| MOCK.with(|mock| {
+                    #mock.assert_with(#params_to_tuple)
+                }))
+            }
+
+            #try_assert_with_matchers_docs
+            #doubles_vis fn try_assert_with_matchers #impl_generics (#matcher_params) -> Result<(), fnmock::failure::VerificationFailure> #where_clause {
+                let matcher: fnmock::matchers::Matcher<#params_alias> = #combined_matcher;
+                fnmock::failure::try_verify(|| MOCK.with(|mock| {
+                    #mock.assert_with_matching(&matcher)
+                }))
+            }
+
+            #assert_never_called_with_docs
+            #doubles_vis fn assert_never_called_with #impl_generics (#filtered_fn_inputs) #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_never_called_with(#params_to_tuple)
+                })
+            }
+
+            #assert_last_call_with_docs
+            #doubles_vis fn assert_last_call_with #impl_generics (#filtered_fn_inputs) #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_last_call_with(#params_to_tuple)
+                })
+            }
+
+            #assert_nth_call_with_docs
+            #doubles_vis fn assert_nth_call_with #impl_generics (call_index: usize, #filtered_fn_inputs) #where_clause {
+                MOCK.with(|mock| {
+                    #mock.assert_nth_call_with(call_index, #params_to_tuple)
+                })
+            }
+
+            #snapshot_docs
+            #doubles_vis fn snapshot() -> fnmock::snapshot::StateSnapshot {
+                MOCK.with(|mock| {
+                    fnmock::snapshot::StateSnapshot::new(stringify!(#mock_fn_name), #snapshot_state)
+                })
+            }
+
+            #restore_docs
+            #doubles_vis fn restore(snapshot: fnmock::snapshot::StateSnapshot) {
+                MOCK.with(|mock| {
+                    #restore_state
                 })
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,246 @@
+use quote::quote;
+use crate::function_mock::proxy_docs::MockProxyDocs;
+use crate::cfg_utils::inner_vis;
+use crate::storage_utils::Storage;
+use crate::arbitrary_utils::create_arbitrary_proxies;
+
+/// Generates the original function with the shared mock checking logic injected.
+///
+/// Like `create_mock_function`, but only for non-generic functions: in test mode,
+/// the function calls the shared mock if it is set up (on any thread).
+/// Otherwise, it executes the original function body.
+///
+/// For async functions, the mock is checked when the future is polled, so the mock applies
+/// even if a multi-threaded executor resumed the calling future on another thread.
+/// Like for thread local mocks, the mock implementation itself is sync.
+///
+/// # Arguments
+///
+/// * `fn_attrs` - The attributes of the original function (doc comments, `cfg`, ...)
+/// * `fn_name` - The name of the original function
+/// * `fn_visibility` - The visibility modifier of the function (pub, pub(crate), etc.)
+/// * `fn_asyncness` - Optional async keyword if the function is async
+/// * `fn_qualifiers` - The `unsafe` keyword and the ABI of the function (see `fn_qualifiers`)
+/// * `fn_inputs` - The function parameters
+/// * `fn_output` - The return type
+/// * `fn_block` - The original function body to execute when mock is not set
+/// * `mock_mod_name` - The name of the mock module containing the mock infrastructure
+/// * `params_to_tuple` - Token stream that converts parameters into a tuple for the mock
+/// * `doubles_cfg` - The `cfg` predicate the mock check is compiled for (see `doubles_cfg`)
+///
+/// # Returns
+///
+/// Generated token stream for the function with injected mock checking logic
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_shared_mock_function(
+    fn_attrs: &[syn::Attribute],
+    fn_name: syn::Ident,
+    fn_visibility: syn::Visibility,
+    fn_asyncness: Option<syn::token::Async>,
+    fn_qualifiers: &proc_macro2::TokenStream,
+    fn_inputs: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    fn_output: syn::ReturnType,
+    fn_block: syn::Block,
+    mock_mod_name: syn::Ident,
+    params_to_tuple: proc_macro2::TokenStream,
+    doubles_cfg: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let original_fn_stmts = &fn_block.stmts;
+
+    quote! {
+        #(#fn_attrs)*
+        #[allow(unused_variables)]
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name (#fn_inputs) #fn_output {
+            // Call the shared mock implementation if set (only in test mode)
+            #[cfg(#doubles_cfg)]
+            if #mock_mod_name::is_set() {
+                return #mock_mod_name::call(#params_to_tuple);
+            }
+
+            #(#original_fn_stmts)*
+        }
+    }
+}
+
+/// Generates a mock module containing the shared mock infrastructure.
+///
+/// Creates a module with the same name as the mock function that contains:
+/// - Public `Params` and `Return` type aliases for the recorded parameters and the return type
+/// - A static `SharedFunctionMock`, accessible from all threads.
+///   It isn't registered with `fnmock::registry`, since the registry only tracks the doubles of the current thread
+/// - Proxy functions for the operations supported by shared mocks
+///
+/// With `storage = task_local`, the `SharedFunctionMock` is stored in a tokio task local instead (see `fnmock::task_local`).
+/// Every `scope()` gets its own mock, which `propagate()` passes on to spawned tasks,
+/// and the function executes its original implementation outside of a scope.
+///
+/// # Arguments
+///
+/// * `mock_fn_name` - The name of the mock module (same as mock function name)
+/// * `params_type` - The type representing the function parameters (single type or tuple)
+/// * `return_type` - The return type of the function
+/// * `fn_inputs` - The function parameters with the recorded types (for documentation)
+/// * `ignore_indices` - Indices of parameters to ignore (for documentation)
+/// * `params_to_tuple` - Token stream that converts parameters into a tuple
+/// * `filtered_fn_inputs` - Original function parameters excluding ignored ones
+/// * `module_vis` - The visibility of the mock module (see `doubles_vis`)
+/// * `storage` - The storage backend, `Global` stores the mock in a static and `TaskLocal` in a task local
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_shared_mock_module(
+    mock_fn_name: syn::Ident,
+    params_type: syn::Type,
+    return_type: syn::Type,
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    ignore_indices: &[usize],
+    params_to_tuple: proc_macro2::TokenStream,
+    filtered_fn_inputs: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    module_vis: &syn::Visibility,
+    storage: Storage,
+) -> proc_macro2::TokenStream {
+    let doubles_vis = inner_vis(module_vis);
+    let docs = MockProxyDocs::new(&mock_fn_name, fn_inputs, ignore_indices, &return_type, None, false);
+    let params_type_docs = docs.params_type_docs();
+    let return_type_docs = docs.return_type_docs();
+    let call_docs = docs.call_docs();
+    let setup_docs = docs.setup_docs();
+    let setup_note_docs = if storage == Storage::TaskLocal {
+        docs.task_local_setup_note_docs()
+    } else {
+        docs.shared_setup_note_docs()
+    };
+    let clear_docs = docs.clear_docs();
+    let is_set_docs = docs.is_set_docs();
+    let calls_docs = docs.calls_docs();
+    let times_called_docs = docs.times_called_docs();
+    let first_call_index_docs = docs.first_call_index_docs();
+    let formatted_calls_docs = docs.formatted_calls_docs();
+    let last_call_params_docs = docs.last_call_params_docs();
+    let assert_times_docs = docs.assert_times_docs();
+    let assert_with_docs = docs.assert_with_docs();
+    let try_assert_times_docs = docs.try_assert_docs("assert_times");
+    let try_assert_with_docs = docs.try_assert_docs("assert_with");
+
+    // Task local mocks are looked up in the current scope and can be entered with `scope()` / `propagate()`
+    let (storage, mock, is_set, scope_fns) = if storage == Storage::TaskLocal {
+        let scope_docs = docs.scope_docs();
+        let propagate_docs = docs.propagate_docs();
+        (
+            quote! {
+                fnmock::task_local::task_local! {
+                    static MOCK: ::std::sync::Arc<fnmock::shared_mock::SharedFunctionMock<Params, Return>>;
+                }
+            },
+            quote! { fnmock::task_local::current(&MOCK, stringify!(#mock_fn_name)) },
+            quote! { fnmock::task_local::try_current(&MOCK).is_some_and(|mock| mock.is_set()) },
+            quote! {
+                #scope_docs
+                #doubles_vis fn scope<F: ::std::future::Future>(future: F) -> impl ::std::future::Future<Output = F::Output> {
+                    fnmock::task_local::scope(&MOCK, stringify!(#mock_fn_name), future)
+                }
+
+                #propagate_docs
+                #doubles_vis fn propagate<F: ::std::future::Future>(future: F) -> impl ::std::future::Future<Output = F::Output> {
+                    fnmock::task_local::propagate(&MOCK, stringify!(#mock_fn_name), future)
+                }
+            },
+        )
+    } else {
+        (
+            quote! {
+                static MOCK: fnmock::shared_mock::SharedFunctionMock<Params, Return> =
+                    fnmock::shared_mock::SharedFunctionMock::new(stringify!(#mock_fn_name));
+            },
+            quote! { MOCK },
+            quote! { MOCK.is_set() },
+            quote! {},
+        )
+    };
+
+    let arbitrary_proxies = create_arbitrary_proxies(&doubles_vis, &quote! { Return }, &syn::Generics::default(), true, quote! {
+        #mock.setup(move |_| generator())
+    });
+
+    quote! {
+        #module_vis mod #mock_fn_name {
+            use super::*;
+
+            #params_type_docs
+            #doubles_vis type Params = #params_type;
+
+            #return_type_docs
+            #doubles_vis type Return = #return_type;
+
+            #storage
+
+            #scope_fns
+
+            #call_docs
+            #doubles_vis fn call(params: Params) -> Return {
+                #mock.call(params)
+            }
+
+            #setup_docs
+            #setup_note_docs
+            #doubles_vis fn setup(new_f: impl FnMut(Params) -> Return + Send + 'static) {
+                #mock.setup(new_f)
+            }
+
+            #arbitrary_proxies
+
+            #clear_docs
+            #doubles_vis fn clear() {
+                #mock.clear()
+            }
+
+            #is_set_docs
+            #doubles_vis fn is_set() -> bool {
+                #is_set
+            }
+
+            #calls_docs
+            #doubles_vis fn calls() -> Vec<Params> {
+                #mock.calls()
+            }
+
+            #last_call_params_docs
+            #doubles_vis fn last_call_params() -> Option<Params> {
+                #mock.last_call_params()
+            }
+
+            #times_called_docs
+            #doubles_vis fn times_called() -> u32 {
+                #mock.times()
+            }
+
+            #first_call_index_docs
+            #doubles_vis fn first_call_index() -> Option<u64> {
+                #mock.first_call_index()
+            }
+
+            #formatted_calls_docs
+            #doubles_vis fn formatted_calls() -> String {
+                #mock.formatted_calls()
+            }
+
+            #assert_times_docs
+            #doubles_vis fn assert_times(expected_num_of_calls: u32) {
+                #mock.assert_times(expected_num_of_calls)
+            }
+
+            #assert_with_docs
+            #doubles_vis fn assert_with(#filtered_fn_inputs) {
+                #mock.assert_with(#params_to_tuple)
+            }
+
+            #try_assert_times_docs
+            #doubles_vis fn try_assert_times(expected_num_of_calls: u32) -> Result<(), fnmock::failure::VerificationFailure> {
+                fnmock::failure::try_verify(|| #mock.assert_times(expected_num_of_calls))
+            }
+
+            #try_assert_with_docs
+            #doubles_vis fn try_assert_with(#filtered_fn_inputs) -> Result<(), fnmock::failure::VerificationFailure> {
+                fnmock::failure::try_verify(|| #mock.assert_with(#params_to_tuple))
+            }
+        }
+    }
+}
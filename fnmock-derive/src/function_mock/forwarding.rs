@@ -0,0 +1,76 @@
+use quote::quote;
+
+/// A parameter forwarded to the mock implementation instead of being recorded
+///
+/// # Fields
+///
+/// - `name` - the name of the parameter
+/// - `ty` - the type the parameter is passed to the forwarding implementation as
+/// - `arg` - the expression passing the parameter to the forwarding implementation
+pub(crate) struct ForwardedParam {
+    pub(crate) name: syn::Ident,
+    pub(crate) ty: syn::Type,
+    pub(crate) arg: proc_macro2::TokenStream,
+}
+
+/// Gets the forwarded parameters of a function.
+///
+/// `impl Trait` parameters can't be named in the function pointer type of the forwarding implementation,
+/// so closures are forwarded as trait objects. Other parameters are forwarded by value.
+///
+/// # Examples
+///
+/// - `op: impl Fn(u32) -> bool` → `&dyn Fn(u32) -> bool`, passed as `&op`
+/// - `op: impl FnMut() -> Result<u32, String>` → `&mut dyn FnMut() -> Result<u32, String>`, passed as `&mut op`
+/// - `op: impl FnOnce() + Send` → `Box<dyn FnOnce() + Send + '_>`, passed as `Box::new(op)`
+/// - `op: Box<dyn Fn()>` → `Box<dyn Fn()>`, passed as `op`
+///
+/// # Returns
+///
+/// - `Ok(Vec<ForwardedParam>)` - the forwarded parameters in the order of the signature
+/// - `Err(syn::Error)` - if a parameter isn't a plain identifier or is an `impl Trait` other than a closure
+pub(crate) fn get_forwarded_params(
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    forward_indices: &[usize],
+) -> syn::Result<Vec<ForwardedParam>> {
+    let mut forwarded_params = Vec::new();
+
+    for (idx, arg) in fn_inputs.iter().enumerate() {
+        if !forward_indices.contains(&idx) {
+            continue;
+        }
+        let syn::FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let syn::Pat::Ident(pat_ident) = &*pat_type.pat else {
+            return Err(syn::Error::new_spanned(&pat_type.pat, "forwarded parameters must be identifiers"));
+        };
+        let name = pat_ident.ident.clone();
+
+        let syn::Type::ImplTrait(impl_trait) = &*pat_type.ty else {
+            forwarded_params.push(ForwardedParam { name: name.clone(), ty: (*pat_type.ty).clone(), arg: quote! { #name } });
+            continue;
+        };
+
+        let bounds = &impl_trait.bounds;
+        let closure_trait = bounds.iter().find_map(|bound| match bound {
+            syn::TypeParamBound::Trait(trait_bound) => trait_bound.path.segments.last()
+                .map(|segment| segment.ident.to_string())
+                .filter(|ident| ident == "Fn" || ident == "FnMut" || ident == "FnOnce"),
+            _ => None,
+        });
+        let (ty, arg) = match closure_trait.as_deref() {
+            Some("Fn") => (syn::parse_quote! { &(dyn #bounds) }, quote! { &#name }),
+            Some("FnMut") => (syn::parse_quote! { &mut (dyn #bounds) }, quote! { &mut #name }),
+            Some(_) => (syn::parse_quote! { Box<dyn #bounds + '_> }, quote! { Box::new(#name) }),
+            None => return Err(syn::Error::new_spanned(
+                &pat_type.ty,
+                "forwarded impl Trait parameters must be closures (impl Fn, impl FnMut or impl FnOnce). \
+                 Consider using a trait object instead."
+            )),
+        };
+        forwarded_params.push(ForwardedParam { name, ty, arg });
+    }
+
+    Ok(forwarded_params)
+}
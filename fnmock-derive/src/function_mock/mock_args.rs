@@ -1,29 +1,130 @@
-use syn::parse::{Parse, ParseStream};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream, Parser};
 use syn::punctuated::Punctuated;
-use syn::Token;
+use syn::{Token, WherePredicate};
+use crate::attr_utils::parse_name_arg;
+use crate::cfg_utils::{parse_cfg_arg, parse_vis_arg};
+use crate::storage_utils::{check_storage_unset, parse_storage_arg, Storage};
 
 /// Structure to parse the mock_function attribute arguments
+#[derive(Default, Clone)]
 pub(crate) struct MockFunctionArgs {
     pub(crate) ignore: Vec<String>,
+    pub(crate) forward: Vec<String>,
+    pub(crate) own: Vec<String>,
+    pub(crate) no_debug: Vec<String>,
+    pub(crate) compare: Vec<(String, syn::Expr)>,
+    pub(crate) project: Vec<(String, syn::Expr)>,
+    pub(crate) bounds: Option<Punctuated<WherePredicate, Token![,]>>,
+    pub(crate) interpose: bool,
+    pub(crate) interpose_symbol: Option<syn::LitStr>,
+    pub(crate) storage: Option<Storage>,
+    pub(crate) named_params: bool,
+    pub(crate) no_history: bool,
+    pub(crate) cfg: Option<proc_macro2::TokenStream>,
+    pub(crate) vis: Option<syn::Visibility>,
+    pub(crate) name: Option<syn::Ident>,
 }
 
 impl Parse for MockFunctionArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let mut ignore = Vec::new();
+        let mut args = MockFunctionArgs::default();
 
         if input.is_empty() {
-            return Ok(MockFunctionArgs { ignore });
+            return Ok(args);
         }
 
-        // Parse "ignore = [...]" syntax
+        // Parse "ignore = [...]", "forward = [...]", "own = [...]", "no_debug = [...]", "compare(param = "...")", "project(param = "...")", "bounds = "..."", "interpose (= "...")", "shared", "storage = ...", "named_params", "no_history", "cfg = "...", "vis = "..."" and "name = "..."" syntax
         while !input.is_empty() {
             let key: syn::Ident = input.parse()?;
             if key == "ignore" {
+                input.parse::<Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                let patterns: Punctuated<String, Token![,]> = content.parse_terminated(parse_ignore_pattern, Token![,])?;
+                args.ignore = patterns.into_iter().collect();
+            } else if key == "forward" {
+                input.parse::<Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                let names: Punctuated<syn::Ident, Token![,]> = content.parse_terminated(syn::Ident::parse, Token![,])?;
+                args.forward = names.into_iter().map(|id| id.to_string()).collect();
+            } else if key == "own" {
                 input.parse::<Token![=]>()?;
                 let content;
                 syn::bracketed!(content in input);
                 let names: Punctuated<syn::Ident, Token![,]> = content.parse_terminated(syn::Ident::parse, Token![,])?;
-                ignore = names.into_iter().map(|id| id.to_string()).collect();
+                args.own = names.into_iter().map(|id| id.to_string()).collect();
+            } else if key == "no_debug" {
+                input.parse::<Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                let names: Punctuated<syn::Ident, Token![,]> = content.parse_terminated(syn::Ident::parse, Token![,])?;
+                args.no_debug = names.into_iter().map(|id| id.to_string()).collect();
+            } else if key == "compare" {
+                let content;
+                syn::parenthesized!(content in input);
+                while !content.is_empty() {
+                    let name: syn::Ident = content.parse()?;
+                    content.parse::<Token![=]>()?;
+                    let comparator: syn::LitStr = content.parse()?;
+                    let comparator: syn::Expr = comparator.parse()
+                        .map_err(|_| syn::Error::new_spanned(&comparator, "compare expects a closure like \"|a, b| a.id == b.id\""))?;
+                    args.compare.push((name.to_string(), comparator));
+                    if content.peek(Token![,]) {
+                        content.parse::<Token![,]>()?;
+                    }
+                }
+            } else if key == "project" {
+                let content;
+                syn::parenthesized!(content in input);
+                while !content.is_empty() {
+                    let name: syn::Ident = content.parse()?;
+                    content.parse::<Token![=]>()?;
+                    let projection: syn::LitStr = content.parse()?;
+                    let projection: syn::Expr = projection.parse()
+                        .map_err(|_| syn::Error::new_spanned(&projection, "project expects an expression of the parameter like \"db.name()\""))?;
+                    args.project.push((name.to_string(), projection));
+                    if content.peek(Token![,]) {
+                        content.parse::<Token![,]>()?;
+                    }
+                }
+            } else if key == "bounds" {
+                input.parse::<Token![=]>()?;
+                let bounds: syn::LitStr = input.parse()?;
+                let parser = Punctuated::<WherePredicate, Token![,]>::parse_terminated;
+                args.bounds = Some(parser.parse_str(&bounds.value())
+                    .map_err(|e| syn::Error::new_spanned(&bounds, e))?);
+            } else if key == "interpose" {
+                args.interpose = true;
+                if input.peek(Token![=]) {
+                    input.parse::<Token![=]>()?;
+                    let symbol: syn::LitStr = input.parse()?;
+                    symbol.parse::<syn::Ident>()
+                        .map_err(|_| syn::Error::new_spanned(&symbol, "interpose symbol must be a valid identifier"))?;
+                    args.interpose_symbol = Some(symbol);
+                }
+            } else if key == "shared" {
+                // Shorthand of `storage = global`
+                check_storage_unset(&key, args.storage)?;
+                args.storage = Some(Storage::Global);
+            } else if key == "storage" {
+                args.storage = Some(parse_storage_arg(input, args.storage)?);
+            } else if key == "named_params" {
+                args.named_params = true;
+            } else if key == "no_history" {
+                args.no_history = true;
+            } else if key == "cfg" {
+                args.cfg = Some(parse_cfg_arg(input)?);
+            } else if key == "vis" {
+                args.vis = Some(parse_vis_arg(input)?);
+            } else if key == "name" {
+                args.name = Some(parse_name_arg(input)?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    &key,
+                    format!("Unknown mock_function argument '{}'. Expected 'ignore', 'forward', 'own', 'no_debug', 'compare', 'project', 'bounds', 'interpose', 'shared', 'storage', 'named_params', 'no_history', 'cfg', 'vis' or 'name'", key)
+                ));
             }
 
             // Allow trailing comma or end of input
@@ -32,6 +133,37 @@ impl Parse for MockFunctionArgs {
             }
         }
 
-        Ok(MockFunctionArgs { ignore })
+        Ok(args)
+    }
+}
+
+impl MockFunctionArgs {
+    /// The selected storage backend, thread local by default.
+    pub(crate) fn storage(&self) -> Storage {
+        self.storage.unwrap_or_default()
+    }
+}
+
+/// Parses a parameter name of the ignore list, which can contain `*` wildcards (`*_pool`, `ctx*`).
+fn parse_ignore_pattern(input: ParseStream) -> syn::Result<String> {
+    let mut pattern = String::new();
+    let mut last_was_name = false;
+
+    while !input.is_empty() && !input.peek(Token![,]) {
+        if input.peek(Token![*]) {
+            input.parse::<Token![*]>()?;
+            pattern.push('*');
+            last_was_name = false;
+        } else if input.peek(syn::Ident::peek_any) && !last_was_name {
+            pattern.push_str(&input.call(syn::Ident::parse_any)?.to_string());
+            last_was_name = true;
+        } else {
+            return Err(input.error("Expected a parameter name, optionally with '*' wildcards like '*_pool'"));
+        }
+    }
+
+    if pattern.is_empty() {
+        return Err(input.error("Expected a parameter name"));
     }
-}
\ No newline at end of file
+    Ok(pattern)
+}
@@ -1,11 +1,25 @@
 use quote::quote;
 use syn::__private::TokenStream2;
+use crate::abi_utils::{fn_qualifiers, validate_preservable};
 use crate::function_mock::create_mock_implementation::{create_mock_function, create_mock_module};
-use crate::function_mock::validate_function::validate_function_mockable;
-use crate::param_utils::{create_param_type, create_tuple_from_param_names, get_param_names};
+use crate::function_mock::create_shared_mock_implementation::{create_shared_mock_function, create_shared_mock_module};
+use crate::function_mock::forwarding::get_forwarded_params;
+use crate::function_mock::mock_args::MockFunctionArgs;
+use crate::function_mock::named_params::{create_struct_from_param_names, params_struct_name, validate_named_params};
+use crate::function_mock::validate_function::{validate_function_mockable, validate_function_shareable};
+use crate::generic_utils::{add_bounds, create_recording_bounds, is_generic, strip_defaults};
+use crate::interpose_utils::{create_interposable_function, default_interpose_symbol, validate_interposable};
+use crate::attr_utils::{add_cfg_attrs, double_mod_name, get_cfg_attrs};
+use crate::manifest_utils::create_manifest_entry;
+use crate::param_utils::{bind_param_patterns, create_cloned_tuple_from_param_names, create_owned_params, create_param_type, create_tuple_from_param_names, get_impl_trait_indices, get_param_names, ParamWrappers};
 use crate::return_utils::extract_return_type;
+use crate::cfg_utils::{doubles_cfg, doubles_vis};
+use crate::storage_utils::Storage;
 
-mod create_mock_implementation;
+pub(crate) mod create_mock_implementation;
+mod create_shared_mock_implementation;
+mod forwarding;
+pub(crate) mod named_params;
 mod validate_function;
 mod proxy_docs;
 pub(crate) mod mock_args;
@@ -33,67 +47,322 @@ pub(crate) mod mock_args;
 /// The function validates that:
 /// - All parameters are 'static (no references)
 /// - Parameters can be cloned, compared, and debugged
-pub(crate) fn process_mock_function(mock_function: syn::ItemFn, ignore_params: Vec<String>) -> syn::Result<TokenStream2> {
+///
+/// # Generic Functions
+///
+/// For generic functions the bounds required for recording (see `create_recording_bounds`)
+/// or the custom `bounds` from the attribute are added to the test version of the function
+/// and to all proxy functions.
+///
+/// # Forwarding
+///
+/// Parameters listed in `forward` aren't recorded, like ignored parameters, but passed to the
+/// implementation set up with `setup_forwarding` (see `get_forwarded_params`).
+/// Generic functions can't forward parameters.
+///
+/// # Owned Parameters
+///
+/// Reference parameters listed in `own` are recorded as owned copies (see `create_owned_params`),
+/// so the function keeps its reference signature.
+///
+/// # Parameters without `Debug` or `PartialEq`
+///
+/// Parameters listed in `no_debug` are recorded wrapped in `fnmock::no_debug::NoDebug`,
+/// which formats them as `<non-debug T>` in failure messages, and parameters listed in `compare`
+/// wrapped in `fnmock::compare::Compared`, which compares them with the given closure (see `ParamWrappers`).
+/// Parameters listed in `project` are recorded wrapped in `fnmock::compare::Projected`, which compares and
+/// formats the value of the given expression, while the implementation still receives the parameter.
+/// This way they don't have to be ignored. Generic functions can't use `no_debug`, `compare` and `project`.
+///
+/// # Destructured Parameters
+///
+/// Parameters with destructuring patterns, `_` or `mut` are bound to plain names before the function is processed
+/// (see `bind_param_patterns`), so they are recorded as a whole.
+///
+/// # `impl Trait` Parameters
+///
+/// Parameters with an `impl Trait` type can't be named in the `Params` type, so they are
+/// ignored automatically (see `get_impl_trait_indices`), without rewriting the signature.
+///
+/// # Named Parameters
+///
+/// With `named_params`, the recorded parameters are stored in a `<Function>Params` struct with one field
+/// per parameter instead of a tuple (see `create_params_struct`), and an `assert_with_<field>` proxy
+/// function is generated per field. Generic, shared and task local mocks can't use named parameters.
+///
+/// # Mocks without History
+///
+/// With `no_history`, the mock is created with `FunctionMock::new_without_history`, so it only counts
+/// its calls instead of cloning their params into the history. The assertions of the params panic.
+///
+/// # Interposition
+///
+/// With `interpose`, the function body is additionally exported as a weak symbol
+/// (see `create_interposable_function`), if the `interpose` feature is enabled.
+///
+/// # Shared Mocks
+///
+/// With `shared`, the mock is stored in a static `SharedFunctionMock` instead of thread local storage
+/// (see `create_shared_mock_module`), so calls from threads spawned by the code under test, or from
+/// futures resumed on another thread of a multi-threaded executor, reach it.
+/// Shared mocks only support non-generic functions and a subset of the proxy functions.
+///
+/// With `storage = task_local`, the `SharedFunctionMock` is stored in a tokio task local instead,
+/// so every test gets its own mock by running in its own `scope()`, even on a multi-threaded runtime.
+/// Task local mocks support the same functions and proxy functions as shared mocks.
+///
+/// # Manifest
+///
+/// With the `manifest` feature, an entry describing the function is submitted to
+/// `fnmock::manifest` (see `create_manifest_entry`).
+pub(crate) fn process_mock_function(mut mock_function: syn::ItemFn, args: MockFunctionArgs) -> syn::Result<TokenStream2> {
+    // Destructured params are bound to names, so they can be passed on
+    bind_param_patterns(&mut mock_function.sig, &mut mock_function.block);
+
     // Extract function details
     let fn_visibility = mock_function.vis.clone();
     let fn_asyncness = mock_function.sig.asyncness;
+    let fn_qualifiers = fn_qualifiers(&mock_function.sig);
     let fn_name = mock_function.sig.ident.clone();
+    let fn_generics = mock_function.sig.generics.clone();
     let fn_inputs = mock_function.sig.inputs.clone();
     let fn_output = mock_function.sig.output.clone();
-    let fn_block = mock_function.block.clone();
+    let mut fn_block = (*mock_function.block).clone();
+    let cfg_attrs = get_cfg_attrs(&mock_function.attrs);
+
+    // The `unsafe` keyword, the ABI and attributes like `no_mangle` are kept on the generated function
+    validate_preservable(&mock_function.sig, "mock_function")?;
 
     // Generate mock module name
-    let mock_mod_name = syn::Ident::new(&format!("{}_mock", &fn_name), fn_name.span());
+    let mock_mod_name = double_mod_name(&fn_name, args.name.as_ref(), "_mock");
 
     // Convert ignore param names to indices
-    let ignore_indices = get_ignore_indices(&fn_inputs, &ignore_params)?;
+    let mut ignore_indices = get_ignore_indices(&fn_inputs, &args.ignore)?;
+
+    // Forwarded params aren't recorded either
+    let forward_indices = get_ignore_indices(&fn_inputs, &args.forward)?;
+    if !forward_indices.is_empty() && is_generic(&fn_generics) {
+        return Err(syn::Error::new_spanned(
+            &fn_generics,
+            "forward can't be used on functions with type or const parameters"
+        ));
+    }
+    let forwarded_params = get_forwarded_params(&fn_inputs, &forward_indices)?;
+    ignore_indices.extend(forward_indices);
+
+    // `impl Trait` params can't be recorded, so they are ignored as well
+    for idx in get_impl_trait_indices(&fn_inputs) {
+        if !ignore_indices.contains(&idx) {
+            ignore_indices.push(idx);
+        }
+    }
+
+    // Reference params listed in `own` are recorded as owned copies
+    let own_indices = get_ignore_indices(&fn_inputs, &args.own)?;
+    let owned_fn_inputs = create_owned_params(&fn_inputs, &own_indices)?;
+
+    // Params listed in `no_debug`, `compare` or `project` are recorded in wrappers implementing `Debug` / `PartialEq`
+    let param_wrappers = get_param_wrappers(&fn_inputs, &args)?;
+    if !param_wrappers.is_empty() && is_generic(&fn_generics) {
+        return Err(syn::Error::new_spanned(
+            &fn_generics,
+            "no_debug, compare and project can't be used on functions with type or const parameters"
+        ));
+    }
+    let owned_fn_inputs = param_wrappers.wrap_params(&owned_fn_inputs);
 
     // Validate function is suitable for mocking (only non-ignored params)
-    validate_function_mockable(&mock_function, &ignore_indices)?;
+    validate_function_mockable(&mock_function, &owned_fn_inputs, &ignore_indices)?;
 
     // Only add the not ignored parameters to the param_types / params_to_tuple
-    let params_type = create_param_type(&fn_inputs, &ignore_indices);
-    let params_to_tuple = create_tuple_from_param_names(&fn_inputs, &ignore_indices);
+    let mut params_type = create_param_type(&owned_fn_inputs, &ignore_indices);
+    let mut params_to_tuple = create_tuple_from_param_names(&fn_inputs, &ignore_indices, &own_indices, &param_wrappers);
+    let mut params_to_cloned_tuple = create_cloned_tuple_from_param_names(&fn_inputs, &ignore_indices, &own_indices, &param_wrappers);
+
+    // Named params are recorded in a struct defined in the mock module.
+    // The function constructs it through the mock module, the proxy functions through the `Params` alias.
+    let mut module_params_to_tuple = params_to_tuple.clone();
+    let params_struct = if args.named_params {
+        if is_generic(&fn_generics) || args.storage() != Storage::ThreadLocal {
+            return Err(syn::Error::new_spanned(
+                &fn_name,
+                "named_params can't be used on generic functions, shared or task local mocks"
+            ));
+        }
+        validate_named_params(&fn_inputs, &ignore_indices)?;
+
+        let struct_name = params_struct_name(&fn_name);
+        params_type = syn::parse_quote! { #struct_name };
+        params_to_tuple = create_struct_from_param_names(quote! { #mock_mod_name::Params }, &fn_inputs, &ignore_indices, &own_indices, &param_wrappers, false);
+        params_to_cloned_tuple = create_struct_from_param_names(quote! { #mock_mod_name::Params }, &fn_inputs, &ignore_indices, &own_indices, &param_wrappers, true);
+        module_params_to_tuple = create_struct_from_param_names(quote! { Params }, &fn_inputs, &ignore_indices, &own_indices, &param_wrappers, false);
+        Some(struct_name)
+    } else {
+        None
+    };
 
     let return_type = extract_return_type(&mock_function.sig.output);
 
     let filtered_fn_inputs = crate::param_utils::filter_params(&fn_inputs, &ignore_indices);
 
+    // Generic functions need additional bounds to record calls per monomorphization.
+    // Default type parameters are stripped, since the proxy functions always get them passed explicitly.
+    let recording_generics = strip_defaults(&fn_generics);
+    let recording_generics = match (&args.bounds, is_generic(&recording_generics)) {
+        (Some(bounds), true) => add_bounds(&recording_generics, bounds),
+        (None, true) => add_bounds(&recording_generics, &create_recording_bounds(&recording_generics, &params_type)),
+        (Some(bounds), false) => return Err(syn::Error::new_spanned(
+            bounds,
+            "bounds can only be used on functions with type or const parameters"
+        )),
+        (None, false) => recording_generics,
+    };
+
+    // The mock check and module are compiled for the custom cfg predicate or for tests
+    let doubles_cfg = args.cfg.clone().unwrap_or_else(doubles_cfg);
+    let module_vis = args.vis.clone().unwrap_or_else(doubles_vis);
+    let manifest_entry = add_cfg_attrs(&cfg_attrs, create_manifest_entry("Mock", &mock_function.sig));
+
+    if args.storage() != Storage::ThreadLocal {
+        validate_function_shareable(&mock_function.sig, &return_type, &args)?;
+
+        let mock_function = create_shared_mock_function(
+            &mock_function.attrs,
+            fn_name,
+            fn_visibility,
+            fn_asyncness,
+            &fn_qualifiers,
+            fn_inputs.clone(),
+            fn_output,
+            fn_block,
+            mock_mod_name.clone(),
+            params_to_tuple.clone(),
+            &doubles_cfg,
+        );
+
+        let mock_module = create_shared_mock_module(
+            mock_mod_name,
+            params_type,
+            return_type,
+            &owned_fn_inputs,
+            &ignore_indices,
+            params_to_tuple,
+            filtered_fn_inputs,
+            &module_vis,
+            args.storage(),
+        );
+
+        return Ok(quote! {
+            #mock_function
+
+            #manifest_entry
+
+            #(#cfg_attrs)*
+            #[cfg(#doubles_cfg)]
+            #mock_module
+        });
+    }
+
+    // Move the body into a weak symbol, which can be replaced at link time
+    let mut interpose_symbol_fn = quote! {};
+    if args.interpose {
+        validate_interposable(&mock_function.sig)?;
+
+        let symbol = args.interpose_symbol
+            .map(|symbol| symbol.value())
+            .unwrap_or_else(|| default_interpose_symbol(&fn_name));
+        let symbol_fn;
+        (fn_block, symbol_fn) = create_interposable_function(
+            &symbol,
+            &fn_visibility,
+            &fn_inputs,
+            &fn_output,
+            fn_block,
+        );
+        interpose_symbol_fn = add_cfg_attrs(&cfg_attrs, symbol_fn);
+    }
+
     let mock_function = create_mock_function(
+        &mock_function.attrs,
         fn_name,
         fn_visibility,
         fn_asyncness,
+        &fn_qualifiers,
+        fn_generics,
+        &recording_generics,
         fn_inputs.clone(),
         fn_output,
         fn_block,
         mock_mod_name.clone(),
-        params_to_tuple.clone()
+        params_to_tuple.clone(),
+        params_to_cloned_tuple,
+        &forwarded_params,
+        &doubles_cfg,
     );
 
     let mock_module = create_mock_module(
         mock_mod_name,
         params_type,
+        params_struct.as_ref(),
         return_type,
-        &fn_inputs,
+        &recording_generics,
+        &owned_fn_inputs,
         &ignore_indices,
         fn_asyncness,
-        params_to_tuple,
-        filtered_fn_inputs
+        module_params_to_tuple,
+        filtered_fn_inputs,
+        &forwarded_params,
+        &module_vis,
+        args.no_history,
     );
 
     // Generate the original function and the mock module
     Ok(quote! {
         #mock_function
 
-        #[cfg(test)]
+        #interpose_symbol_fn
+
+        #manifest_entry
+
+        #(#cfg_attrs)*
+        #[cfg(#doubles_cfg)]
         #mock_module
     })
 }
 
+/// Resolves the parameters listed in `no_debug`, `compare` and `project` to the wrappers of their recorded values.
+///
+/// # Returns
+///
+/// - `Ok(ParamWrappers)` - The indices of the wrapped parameters
+/// - `Err(syn::Error)` - If a listed parameter isn't found in the function signature
+pub(crate) fn get_param_wrappers(
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    args: &MockFunctionArgs,
+) -> syn::Result<ParamWrappers> {
+    let compare_names: Vec<String> = args.compare.iter().map(|(name, _)| name.clone()).collect();
+    let compare_indices = get_ignore_indices(fn_inputs, &compare_names)?;
+    let project_names: Vec<String> = args.project.iter().map(|(name, _)| name.clone()).collect();
+    let project_indices = get_ignore_indices(fn_inputs, &project_names)?;
+
+    Ok(ParamWrappers {
+        no_debug: get_ignore_indices(fn_inputs, &args.no_debug)?,
+        compare: compare_indices.into_iter()
+            .zip(args.compare.iter().map(|(_, comparator)| comparator.clone()))
+            .collect(),
+        project: project_indices.into_iter()
+            .zip(args.project.iter())
+            .map(|(idx, (name, projection))| (idx, quote::format_ident!("{}", name), projection.clone()))
+            .collect(),
+    })
+}
+
 /// Converts parameter names to their indices.
 ///
 /// Maps each ignored parameter name to its position in the function signature.
-fn get_ignore_indices(
+/// Names with `*` wildcards select every matching parameter and may match none,
+/// so the same pattern can be used on functions without such parameters.
+pub(crate) fn get_ignore_indices(
     fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
     ignore_params: &[String]
 ) -> syn::Result<Vec<usize>> {
@@ -101,6 +370,16 @@ fn get_ignore_indices(
     let mut indices = Vec::new();
 
     for ignore_name in ignore_params {
+        if ignore_name.contains('*') {
+            for (i, param) in param_names.iter().enumerate() {
+                if let syn::Pat::Ident(pat_ident) = param {
+                    if matches_wildcard(ignore_name, &pat_ident.ident.to_string()) && !indices.contains(&i) {
+                        indices.push(i);
+                    }
+                }
+            }
+            continue;
+        }
         let mut found = false;
         for (i, param) in param_names.iter().enumerate() {
             if let syn::Pat::Ident(pat_ident) = param {
@@ -120,4 +399,24 @@ fn get_ignore_indices(
     }
 
     Ok(indices)
+}
+
+/// Checks if the name matches the pattern, where `*` matches any sequence of characters.
+fn matches_wildcard(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // The pattern contains at least one '*', so there is a first and a last part
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = parts.collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
 }
\ No newline at end of file
@@ -0,0 +1,165 @@
+use quote::{format_ident, quote};
+use crate::function_mock::proxy_docs::MockProxyDocs;
+use crate::param_utils::{filter_params, get_param_names, ParamWrappers};
+
+/// Names of the generated proxy functions starting with `assert_with_`,
+/// which would collide with the field assertions of parameters with the same name.
+const RESERVED_FIELD_NAMES: [&str; 2] = ["matching", "matchers"];
+
+/// Creates the name of the params struct of a function.
+///
+/// # Examples
+///
+/// - `fetch_user` → `FetchUserParams`
+/// - `send` → `SendParams`
+pub(crate) fn params_struct_name(fn_name: &syn::Ident) -> syn::Ident {
+    let upper_camel_case: String = fn_name
+        .to_string()
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect();
+
+    format_ident!("{}Params", upper_camel_case, span = fn_name.span())
+}
+
+/// Validates that the recorded parameters can be used as the fields of the params struct.
+///
+/// # Returns
+///
+/// - `Ok(())` if every recorded parameter can be a field
+/// - `Err(syn::Error)` if no parameter is recorded, a recorded parameter isn't a plain identifier
+///   or its field assertion would collide with another proxy function
+pub(crate) fn validate_named_params(
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    ignore_indices: &[usize],
+) -> syn::Result<()> {
+    let recorded_params = filter_params(fn_inputs, ignore_indices);
+    if recorded_params.is_empty() {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "named_params requires the function to have recorded parameters"
+        ));
+    }
+
+    for param in get_param_names(&recorded_params) {
+        let syn::Pat::Ident(pat_ident) = param else {
+            return Err(syn::Error::new_spanned(param, "named_params requires the recorded parameters to be identifiers"));
+        };
+        if RESERVED_FIELD_NAMES.iter().any(|name| pat_ident.ident == name) {
+            return Err(syn::Error::new_spanned(
+                &pat_ident.ident,
+                format!("named_params can't be used with a parameter named '{}', since `assert_with_{}` is already generated", pat_ident.ident, pat_ident.ident)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates a struct expression of the params struct from the parameter names (excluding ignored parameters).
+///
+/// This is the named counterpart of `create_tuple_from_param_names` and `create_cloned_tuple_from_param_names`.
+///
+/// # Examples
+///
+/// - `fn foo(x: i32, y: String)` → `Params { x, y }`
+/// - `fn foo(x: i32, y: &str)` with `own_indices = [1]` → `Params { x, y: y.to_owned() }`
+/// - `fn foo(x: i32, y: String)` with `cloned` → `Params { x: x.clone(), y: y.clone() }`
+/// - `fn foo(conn: Conn)` with `conn` listed in `wrappers.no_debug` → `Params { conn: fnmock::no_debug::NoDebug(conn) }`
+pub(crate) fn create_struct_from_param_names(
+    struct_path: proc_macro2::TokenStream,
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    ignore_indices: &[usize],
+    own_indices: &[usize],
+    wrappers: &ParamWrappers,
+    cloned: bool,
+) -> proc_macro2::TokenStream {
+    let fields: Vec<_> = get_param_names(fn_inputs)
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| !ignore_indices.contains(idx))
+        .filter_map(|(idx, pat)| match pat {
+            syn::Pat::Ident(pat_ident) => Some((idx, &pat_ident.ident)),
+            _ => None,
+        })
+        .map(|(idx, name)| {
+            let value = if own_indices.contains(&idx) {
+                quote! { #name.to_owned() }
+            } else if cloned {
+                quote! { #name.clone() }
+            } else {
+                quote! { #name }
+            };
+            let value = wrappers.wrap_expr(value, idx);
+            quote! { #name: #value }
+        })
+        .collect();
+
+    quote! { #struct_path { #(#fields),* } }
+}
+
+/// Generates the params struct and the field assertions of the mock module.
+///
+/// The struct has one public field per recorded parameter, with the recorded (owned) type.
+/// For every field an `assert_with_<field>()` proxy function asserts the field on its own,
+/// so a test only has to spell out the parameters it cares about.
+///
+/// # Arguments
+///
+/// * `struct_name` - The name of the params struct (see `params_struct_name`)
+/// * `recorded_inputs` - The recorded parameters, with their recorded types
+/// * `doubles_vis` - The visibility of the items in the mock module
+/// * `docs` - The documentation of the mock module
+pub(crate) fn create_params_struct(
+    struct_name: &syn::Ident,
+    recorded_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    doubles_vis: &syn::Visibility,
+    docs: &MockProxyDocs,
+) -> proc_macro2::TokenStream {
+    let params_struct_docs = docs.params_struct_docs();
+    let fields: Vec<_> = recorded_inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(pat_type) => match &*pat_type.pat {
+                syn::Pat::Ident(pat_ident) => Some((&pat_ident.ident, &pat_type.ty)),
+                _ => None,
+            },
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect();
+
+    let field_definitions = fields.iter().map(|(name, ty)| {
+        let field_doc = format!("The `{}` parameter of the call.", name);
+        quote! {
+            #[doc = #field_doc]
+            #doubles_vis #name: #ty
+        }
+    });
+
+    let field_assertions = fields.iter().map(|(name, ty)| {
+        let assert_with_field_docs = docs.assert_with_field_docs(name);
+        let assertion_name = format_ident!("assert_with_{}", name);
+        let description = format!("{{{{ {}: {{:?}}, .. }}}}", name);
+        quote! {
+            #assert_with_field_docs
+            #doubles_vis fn #assertion_name(#name: #ty) {
+                let matcher = fnmock::matchers::Matcher::new(format!(#description, #name), move |params: &Params| params.#name == #name);
+                assert_with_matching(matcher)
+            }
+        }
+    });
+
+    quote! {
+        #params_struct_docs
+        #[derive(Clone, Debug, PartialEq)]
+        #doubles_vis struct #struct_name {
+            #(#field_definitions),*
+        }
+
+        #(#field_assertions)*
+    }
+}
@@ -1,4 +1,4 @@
-/// Generates documentation strings for mock proxy functions based on actual function parameters.
+//! Generates documentation strings for mock proxy functions based on actual function parameters.
 
 use quote::quote;
 
@@ -11,6 +11,7 @@ pub(crate) struct MockProxyDocs {
     ignored_param_docs: Vec<String>,
     setup_example: Vec<String>,
     is_async: bool,
+    named_params: bool,
 }
 
 impl MockProxyDocs {
@@ -23,12 +24,14 @@ impl MockProxyDocs {
     /// * `ignore_indices` - Indices of parameters to ignore
     /// * `return_type` - The return type of the function
     /// * `fn_asyncness` - Whether the function is async
+    /// * `named_params` - Whether the parameters are recorded in a params struct instead of a tuple
     pub(crate) fn new(
         mock_fn_name: &syn::Ident,
         fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
         ignore_indices: &[usize],
         return_type: &syn::Type,
         fn_asyncness: Option<syn::token::Async>,
+        named_params: bool,
     ) -> Self {
         let all_params: Vec<_> = fn_inputs
             .iter()
@@ -76,7 +79,9 @@ impl MockProxyDocs {
                 .map(|(name, _, _)| quote::quote!(#name))
                 .collect();
             
-            let params_pattern = if example_params.len() == 1 {
+            let params_pattern = if named_params {
+                quote::quote!(params)
+            } else if example_params.len() == 1 {
                 quote::quote!(#(#example_params)*)
             } else {
                 quote::quote!((#(#example_params),*))
@@ -95,6 +100,54 @@ impl MockProxyDocs {
             ignored_param_docs,
             setup_example,
             is_async: fn_asyncness.is_some(),
+            named_params,
+        }
+    }
+
+    /// Generates documentation attributes for the `Params` type alias.
+    pub(crate) fn params_type_docs(&self) -> proc_macro2::TokenStream {
+        if self.named_params {
+            return quote! {
+                #[doc = "The recorded (not ignored) parameters of the function, as a struct with one field per parameter."]
+                #[doc = ""]
+                #[doc = "This is the type passed to the `setup()` implementation and returned by the call history."]
+            };
+        }
+
+        quote! {
+            #[doc = "The recorded (not ignored) parameters of the function."]
+            #[doc = ""]
+            #[doc = "A single parameter is used directly, multiple parameters are combined into a tuple."]
+            #[doc = "This is the type passed to the `setup()` implementation and returned by the call history."]
+        }
+    }
+
+    /// Generates documentation attributes for the params struct of `named_params` mocks.
+    pub(crate) fn params_struct_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "The recorded parameters of a call, with one field per recorded parameter."]
+            #[doc = ""]
+            #[doc = "Use `Params { .. }` in assertions to name the compared parameters."]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_with_<field>` function of a field of the params struct.
+    pub(crate) fn assert_with_field_docs(&self, field: &syn::Ident) -> proc_macro2::TokenStream {
+        let summary = format!("Asserts that the mock was called at least once with the specified `{}`, whatever the other parameters were.", field);
+        let panics = format!("Panics if no call in the call history has a matching `{}`", field);
+        quote! {
+            #[doc = #summary]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = #panics]
+        }
+    }
+
+    /// Generates documentation attributes for the `Return` type alias.
+    pub(crate) fn return_type_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "The return type of the function."]
         }
     }
 
@@ -107,6 +160,9 @@ impl MockProxyDocs {
             quote! { #[doc = "to the mock implementation. If no mock behavior has been set up using `setup()`,"] },
             quote! { #[doc = "this will panic."] },
             quote! { #[doc = ""] },
+            quote! { #[doc = "The mock is released while the implementation runs, so the implementation may use"] },
+            quote! { #[doc = "the other proxy functions (e.g. `times_called()`) or call the mocked function again."] },
+            quote! { #[doc = ""] },
             quote! { #[doc = "# Parameters"] },
             quote! { #[doc = ""] },
         ];
@@ -149,7 +205,8 @@ impl MockProxyDocs {
             quote! { #[doc = ""] },
             quote! { #[doc = "Configures the function that will be called when the mock is invoked."] },
             quote! { #[doc = "The provided function receives the parameters (excluding ignored ones) and"] },
-            quote! { #[doc = "must return the expected return type."] },
+            quote! { #[doc = "must return the expected return type. Closures may capture test-local state,"] },
+            quote! { #[doc = "e.g. a counter to return a different value on every call."] },
         ];
         
         if self.is_async {
@@ -201,6 +258,217 @@ impl MockProxyDocs {
         quote! { #(#docs)* }
     }
 
+    /// Generates documentation attributes for the `setup_returns` function.
+    pub(crate) fn setup_returns_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock to return a clone of the value for every call."]
+            #[doc = ""]
+            #[doc = "Shorthand for `setup(move |_| return_value.clone())`, the calls are still recorded."]
+            #[doc = "Only available if the return type implements `Clone`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `return_value` - The value returned by every call"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_panics` function.
+    pub(crate) fn setup_panics_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock to panic with the message when it is called."]
+            #[doc = ""]
+            #[doc = "Meant for functions the code under test must not call. The panic message names"]
+            #[doc = "the mock and the parameters of the call, and the call is still recorded."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `message` - The reason the function must not be called"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_ok` function.
+    pub(crate) fn setup_ok_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock to return a clone of the value wrapped in `Ok` for every call."]
+            #[doc = ""]
+            #[doc = "Shorthand for `setup(move |_| Ok(value.clone()))`, the calls are still recorded."]
+            #[doc = "Only available if the success type implements `Clone`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `value` - The success value returned by every call"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_err` function.
+    pub(crate) fn setup_err_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock to return a clone of the error wrapped in `Err` for every call."]
+            #[doc = ""]
+            #[doc = "Shorthand for `setup(move |_| Err(error.clone()))`, the calls are still recorded."]
+            #[doc = "Only available if the error type implements `Clone`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `error` - The error returned by every call"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_returns_sequence` function.
+    pub(crate) fn setup_returns_sequence_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock to return the values in order, one per call."]
+            #[doc = ""]
+            #[doc = "Useful for retry loops, e.g. failing twice before succeeding. The calls are still recorded."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `return_values` - The values returned by the consecutive calls"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Calls after the last value panic, use `setup_returns_sequence_repeat_last()` to repeat it instead"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_returns_sequence_repeat_last` function.
+    pub(crate) fn setup_returns_sequence_repeat_last_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock to return the values in order, one per call, and the last value for all further calls."]
+            #[doc = ""]
+            #[doc = "Only available if the return type implements `Clone`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `return_values` - The values returned by the consecutive calls"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Calls panic if `return_values` is empty"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_times` function.
+    pub(crate) fn setup_times_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock behavior for the next calls only, later calls execute the original implementation."]
+            #[doc = ""]
+            #[doc = "All calls are recorded, so e.g. a failing first attempt followed by a retry"]
+            #[doc = "against the real logic can be tested and asserted."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `times` - How many calls the mock implementation handles"]
+            #[doc = "* `new_f` - The mock implementation, like for `setup()`"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_once` function.
+    pub(crate) fn setup_once_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock behavior for the next call only, later calls execute the original implementation."]
+            #[doc = ""]
+            #[doc = "Shorthand for `setup_times(1, new_f)`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `new_f` - The mock implementation, like for `setup()`"]
+        }
+    }
+
+    /// Generates documentation attributes for the `enqueue_return` function.
+    pub(crate) fn enqueue_return_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Queues the return value of a single call."]
+            #[doc = ""]
+            #[doc = "Queued values are returned in order, one per call, before the implementations configured"]
+            #[doc = "via `setup_when()` and `setup()`. The closure runs once, so it may move non-cloneable"]
+            #[doc = "values (e.g. a oneshot sender) into the result. The calls are recorded like every other call."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `return_value` - The closure producing the return value of the call"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_scoped` function.
+    pub(crate) fn setup_scoped_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock like `setup()` and returns a guard clearing it when dropped."]
+            #[doc = ""]
+            #[doc = "The mock is cleared even if the test panics, so the setup can't leak into"]
+            #[doc = "later phases of the test."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `new_f` - The mock implementation, like for `setup()`"]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`fnmock::guard::MockGuard` - the guard calling `clear()` on drop"]
+        }
+    }
+
+    /// Generates the documentation note added to the `setup` function of shared mocks.
+    pub(crate) fn shared_setup_note_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = ""]
+            #[doc = "# Shared Mock"]
+            #[doc = ""]
+            #[doc = "The mock is shared by all threads, so calls from threads spawned by the code under test"]
+            #[doc = "use this implementation as well. Therefore it must be `Send`, and tests using the mock"]
+            #[doc = "mustn't run in parallel."]
+        }
+    }
+
+    /// Generates the documentation note added to the `setup` function of task local mocks.
+    pub(crate) fn task_local_setup_note_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = ""]
+            #[doc = "# Task Local Mock"]
+            #[doc = ""]
+            #[doc = "The mock belongs to the current `scope()`, so it must be set up inside of it."]
+            #[doc = "The implementation must be `Send`, since the task may continue on another thread."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if called outside of `scope()` or `propagate()`"]
+        }
+    }
+
+    /// Generates documentation attributes for the `scope` function of task local mocks.
+    pub(crate) fn scope_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Runs the future with a new mock, which is only visible to the future."]
+            #[doc = ""]
+            #[doc = "The mock follows the task across the threads of a multi-threaded runtime,"]
+            #[doc = "and other tests using the mock in their own scope don't interfere with it."]
+            #[doc = "Outside of a scope, the function executes its original implementation."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `future` - The future using the mock, usually the body of the test"]
+        }
+    }
+
+    /// Generates documentation attributes for the `propagate` function of task local mocks.
+    pub(crate) fn propagate_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Runs the future with the mock of the current scope."]
+            #[doc = ""]
+            #[doc = "Spawned tasks don't inherit task locals, so futures passed to e.g. `tokio::spawn`"]
+            #[doc = "have to be wrapped to use the mock set up by the test."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `future` - The future to run with the mock"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if called outside of `scope()` or `propagate()`"]
+        }
+    }
+
     /// Generates documentation attributes for the `clear` function.
     pub(crate) fn clear_docs(&self) -> proc_macro2::TokenStream {
         quote! {
@@ -215,6 +483,44 @@ impl MockProxyDocs {
         }
     }
 
+    /// Generates documentation attributes for the `clear_history` function.
+    pub(crate) fn clear_history_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Clears the call history, keeping the configured behavior."]
+            #[doc = ""]
+            #[doc = "Resets the recorded calls and panics, while the implementation, cases and hooks stay in place."]
+            #[doc = "Useful for verifying each phase of a multi-phase test independently."]
+        }
+    }
+
+    /// Generates documentation attributes for the `snapshot` function.
+    pub(crate) fn snapshot_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Saves the current mock state."]
+            #[doc = ""]
+            #[doc = "The snapshot contains the configured behavior and the call history."]
+            #[doc = "Pass it to `restore()` to put the mock back into this state,"]
+            #[doc = "e.g. after a test helper temporarily reconfigured the mock."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`fnmock::snapshot::StateSnapshot` - the saved state"]
+        }
+    }
+
+    /// Generates documentation attributes for the `restore` function.
+    pub(crate) fn restore_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Restores the mock to a state saved with `snapshot()`."]
+            #[doc = ""]
+            #[doc = "Everything configured after the snapshot was taken is discarded."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the snapshot was taken from a different double"]
+        }
+    }
+
     /// Generates documentation attributes for the `is_set` function.
     pub(crate) fn is_set_docs(&self) -> proc_macro2::TokenStream {
         quote! {
@@ -250,46 +556,765 @@ impl MockProxyDocs {
         }
     }
 
-    /// Generates documentation attributes for the `assert_with` function.
-    pub(crate) fn assert_with_docs(&self) -> proc_macro2::TokenStream {
+    /// Generates documentation attributes for the `expect_times` function.
+    pub(crate) fn expect_times_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Expects the mock to be called exactly the given number of times."]
+            #[doc = ""]
+            #[doc = "The expectation is checked by `verify()` (or the checkpoint of `verify_on_drop()`),"]
+            #[doc = "so it can be registered before the code under test runs."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `expected_num_of_calls` - The expected number of calls"]
+        }
+    }
+
+    /// Generates documentation attributes for the `expect_with` function.
+    pub(crate) fn expect_with_docs(&self) -> proc_macro2::TokenStream {
         let mut docs = vec![
-            quote! { #[doc = "Asserts that the mock was called at least once with the specified parameters."] },
-            quote! { #[doc = ""] },
-            quote! { #[doc = "Checks the call history to verify that at least one call was made with"] },
-            quote! { #[doc = "parameters matching the provided values. Only non-ignored parameters need"] },
-            quote! { #[doc = "to be provided."] },
+            quote! { #[doc = "Expects the mock to be called at least once with the specified parameters."] },
             quote! { #[doc = ""] },
-            quote! { #[doc = "# Parameters"] },
+            quote! { #[doc = "The expectation is checked by `verify()` (or the checkpoint of `verify_on_drop()`)."] },
+            quote! { #[doc = "Only non-ignored parameters need to be provided."] },
             quote! { #[doc = ""] },
         ];
-        
-        if self.param_docs.is_empty() {
-            docs.push(quote! { #[doc = "No parameters"] });
-        } else {
-            for param in &self.param_docs {
-                docs.push(quote! { #[doc = #param] });
-            }
+
+        docs.extend(self.assert_params_docs());
+
+        quote! { #(#docs)* }
+    }
+
+    /// Generates documentation attributes for the `verify` function.
+    pub(crate) fn verify_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Checks all expectations registered with `expect_times()` and `expect_with()`."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if an expectation isn't met"]
         }
-        
-        if !self.ignored_param_docs.is_empty() {
-            docs.push(quote! { #[doc = ""] });
-            docs.push(quote! { #[doc = "# Ignored Parameters (not required)"] });
-            docs.push(quote! { #[doc = ""] });
-            docs.push(quote! { #[doc = "The following parameters are ignored and do not need to be provided:"] });
-            docs.push(quote! { #[doc = ""] });
-            for param in &self.ignored_param_docs {
-                docs.push(quote! { #[doc = #param] });
-            }
+    }
+
+    /// Generates documentation attributes for the `verify_on_drop` function.
+    pub(crate) fn verify_on_drop_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns a checkpoint calling `verify()` when it is dropped, e.g. at the end of the test."]
+            #[doc = ""]
+            #[doc = "Keeps tests from silently passing if an expected call never happened."]
+            #[doc = "The expectations aren't verified if the test already panicked."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`fnmock::guard::Checkpoint` - the checkpoint verifying the expectations on drop"]
         }
-        
-        docs.extend(vec![
-            quote! { #[doc = ""] },
-            quote! { #[doc = "# Panics"] },
-            quote! { #[doc = ""] },
-            quote! { #[doc = "Panics if no call with matching parameters is found in the call history"] },
-            quote! { #[doc = ""] },
-        ]);
-        
-        quote! { #(#docs)* }
+    }
+
+    /// Generates documentation attributes for the `assert_times_at_least` function.
+    pub(crate) fn assert_times_at_least_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that the mock was called at least the given number of times."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `min_num_of_calls` - The minimum number of calls"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the mock was called fewer times"]
+            #[doc = ""]
+            #[doc = "# Examples"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = "my_function_mock::assert_times_at_least(1); // Expects the function to be called"]
+            #[doc = "```"]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_times_at_most` function.
+    pub(crate) fn assert_times_at_most_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that the mock was called at most the given number of times."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `max_num_of_calls` - The maximum number of calls"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the mock was called more often"]
+            #[doc = ""]
+            #[doc = "# Examples"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = "my_function_mock::assert_times_at_most(3); // Expects 0 to 3 calls"]
+            #[doc = "```"]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_times_between` function.
+    pub(crate) fn assert_times_between_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that the number of calls is within the given bounds (both inclusive)."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `min_num_of_calls` - The minimum number of calls"]
+            #[doc = "* `max_num_of_calls` - The maximum number of calls"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the number of calls is outside of the bounds"]
+            #[doc = ""]
+            #[doc = "# Examples"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = "my_function_mock::assert_times_between(2, 5); // Expects 2 to 5 calls"]
+            #[doc = "```"]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_times_matching` function.
+    pub(crate) fn assert_times_matching_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that the number of calls is accepted by the predicate."]
+            #[doc = ""]
+            #[doc = "Useful for call counts that depend on the test data and can't be expressed"]
+            #[doc = "as a constant or a range."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `predicate` - Checks the number of calls"]
+            #[doc = "* `description` - Describes the accepted counts in the failure message, completing \"to be called ... times\""]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the predicate rejects the number of calls"]
+            #[doc = ""]
+            #[doc = "# Examples"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = "my_function_mock::assert_times_matching(|n| n % 2 == 0, \"an even number of\");"]
+            #[doc = "```"]
+        }
+    }
+
+    /// Generates documentation attributes for the `try_call` function.
+    pub(crate) fn try_call_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Calls the mock like `call()`, but returns `None` if the original implementation should handle the call."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the mocked function, if the mock falls back to the original"]
+            #[doc = "implementation. The call is recorded in both cases."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "The result of the mock implementation, or `None` if no implementation handles the parameters"]
+        }
+    }
+
+    /// Generates documentation attributes for the `Forwarding` type alias.
+    pub(crate) fn forwarding_type_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "The implementation of mocks forwarding parameters."]
+            #[doc = ""]
+            #[doc = "It receives the recorded parameters (`Params`) followed by the forwarded parameters."]
+            #[doc = "Closures (`impl Fn` / `impl FnMut` / `impl FnOnce`) are forwarded as trait objects."]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_forwarding` function.
+    pub(crate) fn setup_forwarding_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock behavior with an implementation receiving the forwarded parameters."]
+            #[doc = ""]
+            #[doc = "The forwarded parameters (e.g. callbacks) aren't recorded, so assertions only see the recorded parameters."]
+            #[doc = "The forwarding implementation takes precedence over the implementation set up with `setup()`."]
+            #[doc = ""]
+            #[doc = "# Example"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = "retry_mock::setup_forwarding(|max_attempts, op| {"]
+            #[doc = "    // Invoke the callback like the real implementation would"]
+            #[doc = "    op()"]
+            #[doc = "});"]
+            #[doc = "```"]
+        }
+    }
+
+    /// Generates documentation attributes for the `forwarding_implementation` function.
+    pub(crate) fn forwarding_implementation_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the implementation set up with `setup_forwarding()`, if any."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the mocked function."]
+        }
+    }
+
+    /// Generates documentation attributes for the `call_with` function.
+    pub(crate) fn call_with_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Records a call with the parameters and runs `implementation` instead of the mock implementation."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the mocked function to call the forwarding implementation."]
+        }
+    }
+
+    /// Generates documentation attributes for the `calls` function.
+    pub(crate) fn record_duration_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Records the wall-clock duration of a call executing the original implementation."]
+            #[doc = ""]
+            #[doc = "Called by the generated function when the mock falls back to the original implementation (see `fnmock::timing`)."]
+        }
+    }
+
+    pub(crate) fn call_durations_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the wall-clock durations of the calls executing the original implementation, in call order."]
+            #[doc = ""]
+            #[doc = "Only calls falling back to the original implementation (`spy()` / `fallback_to_original()`) are timed."]
+            #[doc = "Use it to assert that a slow dependency isn't called in a hot loop, e.g. by summing up the durations."]
+        }
+    }
+
+    pub(crate) fn formatted_calls_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the recorded parameters of all calls formatted one call per line, after the name and the number of calls."]
+            #[doc = ""]
+            #[doc = "Used by `fnmock::assert_calls_snapshot!` (`insta` feature) to review the call history as a snapshot."]
+        }
+    }
+
+    pub(crate) fn calls_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the recorded parameters of all calls, in call order."]
+            #[doc = ""]
+            #[doc = "Multiple parameters are returned as a tuple (see `Params`)."]
+            #[doc = "Use it to inspect every call when `assert_with()` isn't specific enough."]
+        }
+    }
+
+    /// Generates documentation attributes for the `last_call_params` function.
+    pub(crate) fn last_call_params_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the recorded parameters of the most recent call, or `None` if the mock wasn't called."]
+            #[doc = ""]
+            #[doc = "Multiple parameters are returned as a tuple (see `Params`)."]
+            #[doc = "Use it for custom assertions on the latest arguments."]
+        }
+    }
+
+    /// Generates documentation attributes for the `times_called` function.
+    pub(crate) fn times_called_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the number of recorded calls."]
+            #[doc = ""]
+            #[doc = "Use it for computed assertions, e.g. comparing the calls with the number of processed items."]
+        }
+    }
+
+    /// Generates documentation attributes for the `first_call_index` function.
+    pub(crate) fn first_call_index_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the index of the first call among the calls of all mocks, or `None` if the mock wasn't called."]
+            #[doc = ""]
+            #[doc = "The indices increase monotonically across all mocks and threads."]
+            #[doc = "Used by `fnmock::assert_call_order!` to verify the order of the first calls of several mocks."]
+        }
+    }
+
+    /// Generates documentation attributes for the `unique_calls` function.
+    pub(crate) fn unique_calls_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the distinct recorded parameters of all calls, in the order of their first call."]
+            #[doc = ""]
+            #[doc = "Multiple parameters are returned as a tuple (see `Params`)."]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_no_duplicate_calls` function.
+    pub(crate) fn assert_no_duplicate_calls_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that the mock was never called twice with the same recorded parameters."]
+            #[doc = ""]
+            #[doc = "Useful for caching layers, which should only invoke the underlying function once per key."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the mock was called multiple times with the same parameters, listing them with their call counts"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_when` function.
+    pub(crate) fn when_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Creates a pending case for calls with parameters accepted by the matchers, used by `fnmock::when!`."]
+            #[doc = ""]
+            #[doc = "The case is set up with `setup_when` by one of the `then` functions of the returned `fnmock::when::When`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "One matcher per parameter (see `fnmock::matchers`)"]
+        }
+    }
+
+    pub(crate) fn setup_when_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock behavior for calls with parameters accepted by the matcher."]
+            #[doc = ""]
+            #[doc = "Cases are checked in the order they were set up, before the implementation set up with `setup()`."]
+            #[doc = "Calls matching no case panic, unless `fallback_to_original()` was called."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `matcher` - The condition on the parameters (see `fnmock::matchers`)"]
+            #[doc = "* `new_f` - The mock implementation for matching calls"]
+        }
+    }
+
+    /// Generates documentation attributes for the `fallback_to_original` function.
+    pub(crate) fn fallback_to_original_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Makes calls matching no case execute the original implementation."]
+            #[doc = ""]
+            #[doc = "The calls are still recorded, so the function can be mocked for specific parameters only"]
+            #[doc = "(see `setup_when()`). Without cases, the mock only records the calls."]
+        }
+    }
+
+    /// Generates documentation attributes for the `spy` function.
+    pub(crate) fn spy_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Turns the mock into a spy, executing the original implementation for every call."]
+            #[doc = ""]
+            #[doc = "The calls are still recorded, so they can be asserted like the calls of a mock."]
+            #[doc = "Removes all mock implementations, but keeps the call history and the hooks."]
+        }
+    }
+
+    /// Generates documentation attributes for the `falls_back_to_original` function.
+    pub(crate) fn falls_back_to_original_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Checks if calls matching no case execute the original implementation."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the mocked function."]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_with_yields` function (async functions only).
+    pub(crate) fn setup_with_yields_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the mock behavior and how often the function yields to the executor before returning."]
+            #[doc = ""]
+            #[doc = "The call is recorded and the mock implementation runs before the yields, so tests can"]
+            #[doc = "deterministically cancel the calling future or let it lose a `select!` race"]
+            #[doc = "while the call is in flight."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `yields` - How often the function yields before returning"]
+            #[doc = "* `new_f` - The mock implementation, like for `setup()`"]
+        }
+    }
+
+    /// Generates documentation attributes for the `yields` function (async functions only).
+    pub(crate) fn yields_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Gets how often the function yields to the executor before returning."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the mocked function."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "The number of yields configured with `setup_with_yields()`, 0 otherwise"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_with_delay` function.
+    pub(crate) fn setup_with_delay_docs(&self) -> proc_macro2::TokenStream {
+        let sleep = if self.is_async {
+            "The returned future sleeps with a timer that works with every executor."
+        } else {
+            "The call blocks the thread while sleeping."
+        };
+
+        quote! {
+            #[doc = "Sets up the mock behavior and how long the function sleeps before returning, simulating latency."]
+            #[doc = ""]
+            #[doc = "The call is recorded and the mock implementation runs before the delay, so tests can"]
+            #[doc = "exercise timeouts and races of the code under test."]
+            #[doc = #sleep]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `delay` - How long the function sleeps before returning"]
+            #[doc = "* `new_f` - The mock implementation, like for `setup()`"]
+        }
+    }
+
+    /// Generates documentation attributes for the `delay` function.
+    pub(crate) fn delay_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Gets how long the function sleeps before returning."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the mocked function."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "The delay configured with `setup_with_delay()`, `None` otherwise"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_blocks` function (sync functions)
+    /// or the `setup_pending` function (async functions).
+    pub(crate) fn setup_hanging_docs(&self) -> proc_macro2::TokenStream {
+        let hang = if self.is_async {
+            "The returned future stays pending until the returned handle is released."
+        } else {
+            "The call blocks the thread until the returned handle is released."
+        };
+
+        quote! {
+            #[doc = "Sets up the mock behavior and makes the function hang, simulating an unresponsive dependency."]
+            #[doc = ""]
+            #[doc = #hang]
+            #[doc = "The call is recorded and the mock implementation runs before hanging, so the call can be"]
+            #[doc = "asserted even if the caller gave up on it. Use `release_after` on the handle as a safety net,"]
+            #[doc = "so a test can't hang the suite."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `new_f` - The mock implementation, like for `setup()`"]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "The handle releasing the hanging calls"]
+        }
+    }
+
+    /// Generates documentation attributes for the `release_handle` function.
+    pub(crate) fn release_handle_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Gets the handle the function waits for before returning, if the mock is set up to hang."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the mocked function."]
+        }
+    }
+
+    /// Generates documentation attributes for the `on_call` function.
+    pub(crate) fn on_call_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Registers a hook invoked before the mock implementation runs."]
+            #[doc = ""]
+            #[doc = "The hook receives the parameters of every call (excluding ignored ones) and doesn't"]
+            #[doc = "replace the implementation, so it can be used for logging, latching or notifying"]
+            #[doc = "a test that the call happened. Hooks run in registration order and are removed by `clear()`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `hook` - The closure invoked with the parameters"]
+            #[doc = ""]
+            #[doc = "The mock is released while the hook runs, so the hook may use the other proxy functions"]
+            #[doc = "(e.g. `times_called()`), which already include the current call."]
+        }
+    }
+
+    /// Generates documentation attributes for the `capture` function.
+    pub(crate) fn capture_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Captures the parameters of every following call (excluding ignored ones)."]
+            #[doc = ""]
+            #[doc = "The returned captor keeps the captured parameters after the mock is cleared,"]
+            #[doc = "so tests can run domain-specific checks on them."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`fnmock::captor::Captor<Params>` - the handle collecting the parameters"]
+        }
+    }
+
+    /// Generates documentation attributes for the `capture_into` function.
+    pub(crate) fn capture_into_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Adds the parameters of every following call (excluding ignored ones) to the collector."]
+            #[doc = ""]
+            #[doc = "The collector may be any collection implementing `Extend<Params>` shared with the test,"]
+            #[doc = "e.g. `Rc<RefCell<Vec<Params>>>`. It is removed by `clear()` like the hooks."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `collector` - The collection the parameters are added to"]
+        }
+    }
+
+    /// Generates documentation attributes for the `after_call` function.
+    pub(crate) fn after_call_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Registers a hook invoked after the mock implementation returned."]
+            #[doc = ""]
+            #[doc = "The hook receives the parameters (excluding ignored ones) and the return value of every call."]
+            #[doc = "It isn't invoked if the implementation panicked."]
+            #[doc = "Hooks run in registration order and are removed by `clear()`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `hook` - The closure invoked with the parameters and the return value"]
+            #[doc = ""]
+            #[doc = "The mock is released while the hook runs, so the hook may use the other proxy functions"]
+            #[doc = "(e.g. `times_called()`), which already include the current call."]
+        }
+    }
+
+    /// Generates documentation attributes for the `in_sequence` function.
+    pub(crate) fn in_sequence_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Joins a sequence, which logs every following call of the mock."]
+            #[doc = ""]
+            #[doc = "Join the same sequence with several mocks to assert the order of their calls."]
+            #[doc = "The calls are logged with the name of this module, e.g. `fetch_user_mock`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `sequence` - The sequence logging the calls (see `fnmock::sequence::Sequence`)"]
+            #[doc = ""]
+            #[doc = "# Examples"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = "let sequence = fnmock::sequence::Sequence::new();"]
+            #[doc = "fetch_user_mock::in_sequence(&sequence);"]
+            #[doc = "send_email_mock::in_sequence(&sequence);"]
+            #[doc = ""]
+            #[doc = "welcome_user(42);"]
+            #[doc = ""]
+            #[doc = "sequence.assert_called_before(\"fetch_user_mock\", \"send_email_mock\");"]
+            #[doc = "```"]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_times_eventually` function.
+    pub(crate) fn assert_times_eventually_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Waits until the mock was called the expected number of times, then asserts it like `assert_times()`."]
+            #[doc = ""]
+            #[doc = "The call count is checked with exponential backoff until the timeout expires, while the executor"]
+            #[doc = "runs other tasks - e.g. a background task calling the mocked function on the same thread."]
+            #[doc = "Works with every executor, since it doesn't depend on a runtime timer."]
+            #[doc = ""]
+            #[doc = "# Example"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = "tokio::task::spawn_local(sync_users());"]
+            #[doc = "fetch_user_mock::assert_times_eventually(3, Duration::from_secs(1)).await;"]
+            #[doc = "```"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the call count differs from the expected count when the timeout expires"]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_panicked_times` function.
+    pub(crate) fn assert_panicked_times_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that the mock implementation panicked exactly the specified number of times."]
+            #[doc = ""]
+            #[doc = "Panics of the implementation are recorded and then passed on to the caller,"]
+            #[doc = "so panic isolation code (e.g. `catch_unwind` in a worker supervisor) can be tested"]
+            #[doc = "without additional plumbing."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `expected_num_of_panics` - The expected number of panics"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the actual number of panics doesn't match the expected count,"]
+            #[doc = "listing the recorded panic messages"]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_with` function.
+    pub(crate) fn assert_with_docs(&self) -> proc_macro2::TokenStream {
+        let mut docs = vec![
+            quote! { #[doc = "Asserts that the mock was called at least once with the specified parameters."] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Checks the call history to verify that at least one call was made with"] },
+            quote! { #[doc = "parameters matching the provided values. Only non-ignored parameters need"] },
+            quote! { #[doc = "to be provided."] },
+            quote! { #[doc = ""] },
+        ];
+
+        docs.extend(self.assert_params_docs());
+
+        docs.extend(vec![
+            quote! { #[doc = ""] },
+            quote! { #[doc = "# Panics"] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Panics if no call with matching parameters is found in the call history"] },
+            quote! { #[doc = ""] },
+        ]);
+        
+        quote! { #(#docs)* }
+    }
+
+    /// Generates documentation attributes for the `assert_with_matching` function.
+    pub(crate) fn assert_with_matching_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that the mock was called at least once with parameters accepted by the matcher."]
+            #[doc = ""]
+            #[doc = "The matcher receives the recorded parameters (see `Params`), so complex conditions"]
+            #[doc = "can be expressed with the combinators of `fnmock::matchers`."]
+            #[doc = ""]
+            #[doc = "# Examples"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = "use fnmock::matchers::{all_of, gt, lt};"]
+            #[doc = ""]
+            #[doc = "get_mock::assert_with_matching(all_of([gt(0), lt(100)]));"]
+            #[doc = "```"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if no call in the call history is accepted by the matcher"]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_with_matchers` function.
+    pub(crate) fn assert_with_matchers_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that the mock was called at least once with parameters accepted by one matcher per parameter."]
+            #[doc = ""]
+            #[doc = "Takes a matcher for every non-ignored parameter, so some parameters can be checked exactly"]
+            #[doc = "while others are only constrained or not checked at all (`any()`)."]
+            #[doc = ""]
+            #[doc = "# Examples"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = "use fnmock::matchers::{any, eq, predicate};"]
+            #[doc = ""]
+            #[doc = "send_mock::assert_with_matchers(eq(42), any());"]
+            #[doc = "send_mock::assert_with_matchers(any(), predicate(\"non empty\", |body: &String| !body.is_empty()));"]
+            #[doc = "```"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if no call in the call history is accepted by all matchers"]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_never_called_with` function.
+    pub(crate) fn assert_never_called_with_docs(&self) -> proc_macro2::TokenStream {
+        let mut docs = vec![
+            quote! { #[doc = "Asserts that the mock was never called with the specified parameters."] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Calls with other parameters are allowed, so this verifies that e.g. a specific"] },
+            quote! { #[doc = "record was skipped while the others were processed. Only non-ignored parameters need"] },
+            quote! { #[doc = "to be provided."] },
+            quote! { #[doc = ""] },
+        ];
+
+        docs.extend(self.assert_params_docs());
+
+        docs.extend(vec![
+            quote! { #[doc = ""] },
+            quote! { #[doc = "# Panics"] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Panics if any call in the call history was made with the parameters, listing the matching calls"] },
+            quote! { #[doc = ""] },
+        ]);
+
+        quote! { #(#docs)* }
+    }
+
+    /// Generates documentation attributes for the `assert_last_call_with` function.
+    pub(crate) fn assert_last_call_with_docs(&self) -> proc_macro2::TokenStream {
+        let mut docs = vec![
+            quote! { #[doc = "Asserts that the most recent call of the mock was made with the specified parameters."] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Only the last entry of the call history is checked, which is useful for"] },
+            quote! { #[doc = "verifying each step of a test flow. Only non-ignored parameters need"] },
+            quote! { #[doc = "to be provided."] },
+            quote! { #[doc = ""] },
+        ];
+
+        docs.extend(self.assert_params_docs());
+
+        docs.extend(vec![
+            quote! { #[doc = ""] },
+            quote! { #[doc = "# Panics"] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Panics if the mock was never called or the last call was made with different parameters"] },
+            quote! { #[doc = ""] },
+        ]);
+
+        quote! { #(#docs)* }
+    }
+
+    /// Generates documentation attributes for the `assert_nth_call_with` function.
+    pub(crate) fn assert_nth_call_with_docs(&self) -> proc_macro2::TokenStream {
+        let mut docs = vec![
+            quote! { #[doc = "Asserts that a specific call of the mock was made with the specified parameters."] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "`call_index` selects the call in the call history, starting at 0 for the first call."] },
+            quote! { #[doc = "Only non-ignored parameters need to be provided."] },
+            quote! { #[doc = ""] },
+        ];
+
+        docs.extend(self.assert_params_docs());
+
+        docs.extend(vec![
+            quote! { #[doc = ""] },
+            quote! { #[doc = "# Panics"] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Panics if the mock was called fewer than `call_index + 1` times or the call was made with different parameters"] },
+            quote! { #[doc = ""] },
+        ]);
+
+        quote! { #(#docs)* }
+    }
+
+    /// Generates documentation attributes for the `try_assert_*` functions, e.g. `try_assert_times` for `assert_times`.
+    pub(crate) fn try_assert_docs(&self, assertion: &str) -> proc_macro2::TokenStream {
+        let summary = format!("Checks the mock like `{}()`, but returns the failure instead of panicking.", assertion);
+        quote! {
+            #[doc = #summary]
+            #[doc = ""]
+            #[doc = "Useful in helper functions and custom harnesses, which aggregate the failures or pass them on with `?`."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "- `Ok(())` if the assertion holds"]
+            #[doc = "- `Err(VerificationFailure)` describing the failed assertion"]
+        }
+    }
+
+    /// Generates the parameter sections shared by the assertion functions taking parameters.
+    fn assert_params_docs(&self) -> Vec<proc_macro2::TokenStream> {
+        let mut docs = vec![
+            quote! { #[doc = "# Parameters"] },
+            quote! { #[doc = ""] },
+        ];
+        
+        if self.param_docs.is_empty() {
+            docs.push(quote! { #[doc = "No parameters"] });
+        } else {
+            for param in &self.param_docs {
+                docs.push(quote! { #[doc = #param] });
+            }
+        }
+        
+        if !self.ignored_param_docs.is_empty() {
+            docs.push(quote! { #[doc = ""] });
+            docs.push(quote! { #[doc = "# Ignored Parameters (not required)"] });
+            docs.push(quote! { #[doc = ""] });
+            docs.push(quote! { #[doc = "The following parameters are ignored and do not need to be provided:"] });
+            docs.push(quote! { #[doc = ""] });
+            for param in &self.ignored_param_docs {
+                docs.push(quote! { #[doc = #param] });
+            }
+        }
+
+        docs
     }
 }
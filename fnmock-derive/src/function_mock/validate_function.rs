@@ -1,22 +1,66 @@
-use crate::param_utils::validate_static_params;
+use crate::generic_utils::validate_generics;
+use crate::function_mock::mock_args::MockFunctionArgs;
+use crate::param_utils::{is_nameable, validate_static_params};
 
 /// Validates that a function is suitable for mocking.
 ///
 /// Performs the following checks:
 /// - All non-ignored parameters are 'static (no references allowed)
+/// - Generic functions don't have lifetime parameters
 ///
 /// # Arguments
 ///
 /// * `input` - The function item to validate
+/// * `fn_inputs` - The parameters of the function, with the owned types of the `own` parameters
 /// * `ignore_indices` - Indices of parameters to skip validation for
 ///
 /// # Returns
 ///
 /// - `Ok(())` if the function is valid for mocking
 /// - `Err(syn::Error)` with a descriptive error message if validation fails
-pub(crate) fn validate_function_mockable(input: &syn::ItemFn, ignore_indices: &[usize]) -> syn::Result<()> {
+pub(crate) fn validate_function_mockable(
+    input: &syn::ItemFn,
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    ignore_indices: &[usize],
+) -> syn::Result<()> {
     // Validate that all non-ignored parameters are 'static (no references)
-    validate_static_params(&input.sig.inputs, ignore_indices)?;
+    validate_static_params(fn_inputs, ignore_indices)?;
+
+    // Validate that the generic parameters can be forwarded to the proxy functions
+    validate_generics(&input.sig.generics)?;
+
+    Ok(())
+}
+
+/// Validates that a function can be mocked with a shared or task local mock.
+///
+/// Shared mocks live in a static (task local mocks in a task local), so the mocked function can't be generic
+/// and its return type must be nameable. Forwarding and interposition aren't supported by these mocks.
+///
+/// # Arguments
+///
+/// * `sig` - The signature of the function
+/// * `return_type` - The return type of the function
+/// * `args` - The arguments of the mock_function attribute
+///
+/// # Returns
+///
+/// - `Ok(())` if the function can be mocked with a shared or task local mock
+/// - `Err(syn::Error)` with a descriptive error message if validation fails
+pub(crate) fn validate_function_shareable(sig: &syn::Signature, return_type: &syn::Type, args: &MockFunctionArgs) -> syn::Result<()> {
+    let storage = args.storage().arg();
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(&sig.generics, format!("{} can't be used on generic functions", storage)));
+    }
+    if !is_nameable(return_type) {
+        return Err(syn::Error::new_spanned(return_type, format!("{} can't be used on functions returning `impl Trait`", storage)));
+    }
+    if !args.forward.is_empty() || args.interpose || args.no_history {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!("{} can't be combined with forward, interpose or no_history", storage)
+        ));
+    }
 
     Ok(())
 }
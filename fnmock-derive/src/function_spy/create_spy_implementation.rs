@@ -0,0 +1,200 @@
+use quote::quote;
+use crate::function_spy::proxy_docs::SpyProxyDocs;
+use crate::param_utils::{create_cloned_tuple_from_param_names, create_tuple_from_param_names, get_param_names, ParamWrappers};
+use crate::cfg_utils::inner_vis;
+
+/// Generates the original function, which records its calls in test mode.
+///
+/// Outside of test mode, the function is emitted unchanged. In test mode, the original
+/// body is moved into a nested function, so `return` and `?` keep working, and the
+/// parameters, the result and the wall-clock duration of every call are recorded by the spy module.
+///
+/// # Arguments
+///
+/// * `fn_attrs` - The attributes of the original function (doc comments, `cfg`, ...)
+/// * `fn_name` - The name of the original function
+/// * `fn_visibility` - The visibility modifier of the function (pub, pub(crate), etc.)
+/// * `fn_asyncness` - Optional async keyword if the function is async
+/// * `fn_qualifiers` - The `unsafe` keyword and the ABI of the function (see `fn_qualifiers`)
+/// * `fn_inputs` - The function parameters
+/// * `fn_output` - The return type
+/// * `fn_block` - The original function body, which is always executed
+/// * `spy_mod_name` - The name of the spy module containing the spy infrastructure
+/// * `doubles_cfg` - The `cfg` predicate the recording is compiled for (see `doubles_cfg`)
+///
+/// # Returns
+///
+/// Generated token stream for the function with injected recording logic
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_spy_function(
+    fn_attrs: &[syn::Attribute],
+    fn_name: syn::Ident,
+    fn_visibility: syn::Visibility,
+    fn_asyncness: Option<syn::token::Async>,
+    fn_qualifiers: &proc_macro2::TokenStream,
+    fn_inputs: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    fn_output: syn::ReturnType,
+    fn_block: syn::Block,
+    spy_mod_name: syn::Ident,
+    doubles_cfg: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let param_names = get_param_names(&fn_inputs);
+    let params_to_cloned_tuple = create_cloned_tuple_from_param_names(&fn_inputs, &[], &[], &ParamWrappers::default());
+    let await_result = fn_asyncness.map(|_| quote! { .await });
+
+    quote! {
+        #(#fn_attrs)*
+        #[cfg(not(#doubles_cfg))]
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name(#fn_inputs) #fn_output #fn_block
+
+        #(#fn_attrs)*
+        #[cfg(#doubles_cfg)]
+        #[allow(unused_mut)]
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name(#fn_inputs) #fn_output {
+            #fn_asyncness #fn_qualifiers fn original_implementation(#fn_inputs) #fn_output #fn_block
+
+            // Record the call with a copy of the params, since the original implementation consumes them
+            let params = #params_to_cloned_tuple;
+            let started = ::std::time::Instant::now();
+            let result = original_implementation(#(#param_names),*) #await_result;
+            #spy_mod_name::record_duration(started.elapsed());
+            #spy_mod_name::record(params, ::std::clone::Clone::clone(&result));
+            result
+        }
+    }
+}
+
+/// Generates a spy module containing the spy infrastructure.
+///
+/// Creates a module with the same name as the spy function that contains:
+/// - Public `Params` and `Return` type aliases for the recorded parameters and the return type
+/// - Thread-local storage for the FunctionSpy instance, registered with `fnmock::registry` on first use
+/// - Proxy functions for inspecting and asserting the recorded calls
+///
+/// # Arguments
+///
+/// * `spy_fn_name` - The name of the spy module (same as spy function name)
+/// * `params_type` - The type representing the function parameters (single type or tuple)
+/// * `return_type` - The return type of the function
+/// * `fn_inputs` - The original function parameters (for `assert_with` and documentation)
+/// * `module_vis` - The visibility of the spy module (see `doubles_vis`)
+pub(crate) fn create_spy_module(
+    spy_fn_name: syn::Ident,
+    params_type: syn::Type,
+    return_type: syn::Type,
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    module_vis: &syn::Visibility,
+) -> proc_macro2::TokenStream {
+    let doubles_vis = inner_vis(module_vis);
+    let params_to_tuple = create_tuple_from_param_names(fn_inputs, &[], &[], &ParamWrappers::default());
+    // Generate documentation using the proxy_docs module
+    let docs = SpyProxyDocs::new(fn_inputs, &return_type);
+    let params_type_docs = docs.params_type_docs();
+    let return_type_docs = docs.return_type_docs();
+    let record_docs = docs.record_docs();
+    let clear_docs = docs.clear_docs();
+    let calls_docs = docs.calls_docs();
+    let results_docs = docs.results_docs();
+    let last_call_params_docs = docs.last_call_params_docs();
+    let times_called_docs = docs.times_called_docs();
+    let record_duration_docs = docs.record_duration_docs();
+    let call_durations_docs = docs.call_durations_docs();
+    let assert_times_docs = docs.assert_times_docs();
+    let assert_with_docs = docs.assert_with_docs();
+    let assert_returned_docs = docs.assert_returned_docs();
+    let snapshot_docs = docs.snapshot_docs();
+    let restore_docs = docs.restore_docs();
+
+    quote! {
+        #module_vis mod #spy_fn_name {
+            use super::*;
+
+            #params_type_docs
+            #doubles_vis type Params = #params_type;
+
+            #return_type_docs
+            #doubles_vis type Return = #return_type;
+
+            thread_local! {
+                static SPY: std::cell::RefCell<fnmock::function_spy::FunctionSpy<Params, Return>> = {
+                    fnmock::registry::register(stringify!(#spy_fn_name), snapshot, restore, clear, control);
+                    std::cell::RefCell::new(fnmock::function_spy::FunctionSpy::new(stringify!(#spy_fn_name)))
+                };
+            }
+
+            // Gives the registry access to the stored spy
+            fn control(f: &mut dyn FnMut(&dyn fnmock::registry::MockControl)) {
+                SPY.with(|spy| f(&*spy.borrow()))
+            }
+
+            #record_docs
+            #doubles_vis fn record(params: Params, result: Return) {
+                SPY.with(|spy| { spy.borrow_mut().record(params, result) })
+            }
+
+            #record_duration_docs
+            #doubles_vis fn record_duration(duration: ::std::time::Duration) {
+                SPY.with(|spy| { spy.borrow_mut().record_duration(duration) })
+            }
+
+            #clear_docs
+            #doubles_vis fn clear() {
+                SPY.with(|spy| { spy.borrow_mut().clear() })
+            }
+
+            #calls_docs
+            #doubles_vis fn calls() -> Vec<Params> {
+                SPY.with(|spy| { spy.borrow().calls() })
+            }
+
+            #results_docs
+            #doubles_vis fn results() -> Vec<Return> {
+                SPY.with(|spy| { spy.borrow().results() })
+            }
+
+            #last_call_params_docs
+            #doubles_vis fn last_call_params() -> Option<Params> {
+                SPY.with(|spy| { spy.borrow().last_call_params() })
+            }
+
+            #times_called_docs
+            #doubles_vis fn times_called() -> u32 {
+                SPY.with(|spy| { spy.borrow().times() })
+            }
+
+            #call_durations_docs
+            #doubles_vis fn call_durations() -> Vec<::std::time::Duration> {
+                SPY.with(|spy| { spy.borrow().call_durations() })
+            }
+
+            #assert_times_docs
+            #doubles_vis fn assert_times(expected_num_of_calls: u32) {
+                SPY.with(|spy| { spy.borrow().assert_times(expected_num_of_calls) })
+            }
+
+            #assert_with_docs
+            #doubles_vis fn assert_with(#fn_inputs) {
+                SPY.with(|spy| { spy.borrow().assert_with(#params_to_tuple) })
+            }
+
+            #assert_returned_docs
+            #doubles_vis fn assert_returned(result: Return) {
+                SPY.with(|spy| { spy.borrow().assert_returned(result) })
+            }
+
+            #snapshot_docs
+            #doubles_vis fn snapshot() -> fnmock::snapshot::StateSnapshot {
+                SPY.with(|spy| {
+                    fnmock::snapshot::StateSnapshot::new(stringify!(#spy_fn_name), spy.borrow().clone())
+                })
+            }
+
+            #restore_docs
+            #doubles_vis fn restore(snapshot: fnmock::snapshot::StateSnapshot) {
+                SPY.with(|spy| {
+                    *spy.borrow_mut() = snapshot.into_state(stringify!(#spy_fn_name))
+                })
+            }
+        }
+    }
+}
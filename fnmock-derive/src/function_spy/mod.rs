@@ -0,0 +1,113 @@
+use quote::quote;
+use syn::__private::TokenStream2;
+use crate::abi_utils::{fn_qualifiers, validate_preservable};
+use crate::function_spy::create_spy_implementation::{create_spy_function, create_spy_module};
+use crate::function_spy::spy_args::SpyFunctionArgs;
+use crate::attr_utils::{add_cfg_attrs, double_mod_name, get_cfg_attrs};
+use crate::manifest_utils::create_manifest_entry;
+use crate::param_utils::{bind_param_patterns, create_param_type, is_nameable};
+use crate::return_utils::extract_return_type;
+use crate::cfg_utils::{doubles_cfg, doubles_vis};
+
+mod create_spy_implementation;
+mod proxy_docs;
+pub(crate) mod spy_args;
+
+/// Processes a function and generates the complete spy infrastructure.
+///
+/// This is the main entry point for the spy_function attribute macro. It takes a function
+/// definition and generates:
+/// 1. The original function, which records the parameters and the return value of every call
+///    in test mode, but always executes the original implementation
+/// 2. A spy module with inspection and assertion methods (test-only), e.g. `calls()`,
+///    `assert_with()` and `assert_returned()`
+///
+/// # Arguments
+///
+/// * `spy_function` - The function item to create a spy for
+/// * `args` - The parsed spy_function attribute arguments
+///
+/// # Returns
+///
+/// - `Ok(TokenStream2)` - The complete generated code including original and spy infrastructure
+/// - `Err(syn::Error)` - If validation fails or the function cannot be spied on
+pub(crate) fn process_spy_function(mut spy_function: syn::ItemFn, args: SpyFunctionArgs) -> syn::Result<TokenStream2> {
+    // Destructured params are bound to names, so they can be passed on
+    bind_param_patterns(&mut spy_function.sig, &mut spy_function.block);
+
+    // Extract function details
+    let fn_visibility = spy_function.vis.clone();
+    let fn_asyncness = spy_function.sig.asyncness;
+    let fn_qualifiers = fn_qualifiers(&spy_function.sig);
+    let fn_name = spy_function.sig.ident.clone();
+    let fn_inputs = spy_function.sig.inputs.clone();
+    let fn_output = spy_function.sig.output.clone();
+    let fn_block = (*spy_function.block).clone();
+    let cfg_attrs = get_cfg_attrs(&spy_function.attrs);
+
+    // The `unsafe` keyword, the ABI and attributes like `no_mangle` are kept on the generated function
+    validate_preservable(&spy_function.sig, "spy_function")?;
+
+    // Generate spy module name
+    let spy_mod_name = double_mod_name(&fn_name, args.name.as_ref(), "_spy");
+
+    let params_type = create_param_type(&fn_inputs, &[]);
+    let return_type = extract_return_type(&spy_function.sig.output);
+
+    // Spies store the parameters and the return values, so they have to be owned
+    if !spy_function.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &spy_function.sig.generics,
+            "spy_function can't be used on generic functions"
+        ));
+    }
+    if !is_nameable(&params_type) {
+        return Err(syn::Error::new_spanned(
+            &fn_inputs,
+            "spy_function requires all parameters to be 'static. \
+             Consider using owned types like String instead of &str."
+        ));
+    }
+    if !is_nameable(&return_type) {
+        return Err(syn::Error::new_spanned(
+            &spy_function.sig.output,
+            "spy_function requires the return type to be 'static and nameable"
+        ));
+    }
+
+    // The recording and the module are compiled for the custom cfg predicate or for tests
+    let doubles_cfg = args.cfg.clone().unwrap_or_else(doubles_cfg);
+    let manifest_entry = add_cfg_attrs(&cfg_attrs, create_manifest_entry("Spy", &spy_function.sig));
+
+    let spy_function = create_spy_function(
+        &spy_function.attrs,
+        fn_name,
+        fn_visibility,
+        fn_asyncness,
+        &fn_qualifiers,
+        fn_inputs.clone(),
+        fn_output,
+        fn_block,
+        spy_mod_name.clone(),
+        &doubles_cfg,
+    );
+
+    let spy_module = create_spy_module(
+        spy_mod_name,
+        params_type,
+        return_type,
+        &fn_inputs,
+        &args.vis.clone().unwrap_or_else(doubles_vis),
+    );
+
+    // Generate the original function and the spy module
+    Ok(quote! {
+        #spy_function
+
+        #manifest_entry
+
+        #(#cfg_attrs)*
+        #[cfg(#doubles_cfg)]
+        #spy_module
+    })
+}
@@ -0,0 +1,206 @@
+//! Generates documentation strings for spy proxy functions based on actual function parameters.
+
+use quote::quote;
+
+/// Builds documentation for spy proxy functions.
+///
+/// Generates parameter documentation and other descriptive text based on
+/// the actual function signature.
+pub(crate) struct SpyProxyDocs {
+    param_docs: Vec<String>,
+    return_type_str: String,
+}
+
+impl SpyProxyDocs {
+    /// Creates documentation for spy proxy functions.
+    ///
+    /// # Arguments
+    ///
+    /// * `fn_inputs` - The original function parameters
+    /// * `return_type` - The return type of the function
+    pub(crate) fn new(
+        fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+        return_type: &syn::Type,
+    ) -> Self {
+        let param_docs: Vec<String> = fn_inputs
+            .iter()
+            .filter_map(|arg| {
+                if let syn::FnArg::Typed(pat_type) = arg {
+                    let name = &pat_type.pat;
+                    let ty = &pat_type.ty;
+                    Some(format!("* `{}: {}` - Parameter value", quote!(#name), quote!(#ty)))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            param_docs,
+            return_type_str: quote!(#return_type).to_string(),
+        }
+    }
+
+    /// Generates documentation attributes for the `Params` type alias.
+    pub(crate) fn params_type_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "The recorded parameters of the function."]
+            #[doc = ""]
+            #[doc = "A single parameter is used directly, multiple parameters are combined into a tuple."]
+        }
+    }
+
+    /// Generates documentation attributes for the `Return` type alias.
+    pub(crate) fn return_type_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "The return type of the function."]
+        }
+    }
+
+    /// Generates documentation attributes for the `record` function.
+    pub(crate) fn record_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Records a call with its parameters and its return value."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the spied function after the original"]
+            #[doc = "implementation returned."]
+        }
+    }
+
+    /// Generates documentation attributes for the `clear` function.
+    pub(crate) fn clear_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Clears the recorded calls."]
+        }
+    }
+
+    /// Generates documentation attributes for the `calls` function.
+    pub(crate) fn calls_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the recorded parameters of all calls, in call order."]
+            #[doc = ""]
+            #[doc = "Multiple parameters are returned as a tuple (see `Params`)."]
+        }
+    }
+
+    /// Generates documentation attributes for the `results` function.
+    pub(crate) fn results_docs(&self) -> proc_macro2::TokenStream {
+        let return_type_str = format!("`Vec<{}>` - the return values, in call order", self.return_type_str);
+
+        quote! {
+            #[doc = "Returns the return values of all calls, in call order."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = #return_type_str]
+        }
+    }
+
+    /// Generates documentation attributes for the `last_call_params` function.
+    pub(crate) fn last_call_params_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the recorded parameters of the most recent call, or `None` if the function wasn't called."]
+        }
+    }
+
+    /// Generates documentation attributes for the `record_duration` function.
+    pub(crate) fn record_duration_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Records the wall-clock duration of a call of the original implementation."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the spied function after the original"]
+            #[doc = "implementation returned."]
+        }
+    }
+
+    /// Generates documentation attributes for the `call_durations` function.
+    pub(crate) fn call_durations_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the wall-clock durations of all recorded calls, in the order they happened."]
+            #[doc = ""]
+            #[doc = "Use it to assert that the function isn't called in a hot loop, e.g. by summing up the durations."]
+        }
+    }
+
+    /// Generates documentation attributes for the `times_called` function.
+    pub(crate) fn times_called_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Returns the number of recorded calls."]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_times` function.
+    pub(crate) fn assert_times_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that the function was called exactly the expected number of times."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the actual number of calls does not match the expected number"]
+        }
+    }
+
+    /// Generates documentation attributes for the `assert_with` function.
+    pub(crate) fn assert_with_docs(&self) -> proc_macro2::TokenStream {
+        let mut docs = vec![
+            quote! { #[doc = "Asserts that the function was called at least once with the specified parameters."] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "# Parameters"] },
+            quote! { #[doc = ""] },
+        ];
+
+        if self.param_docs.is_empty() {
+            docs.push(quote! { #[doc = "No parameters"] });
+        } else {
+            for param in &self.param_docs {
+                docs.push(quote! { #[doc = #param] });
+            }
+        }
+
+        docs.extend(vec![
+            quote! { #[doc = ""] },
+            quote! { #[doc = "# Panics"] },
+            quote! { #[doc = ""] },
+            quote! { #[doc = "Panics if no call with matching parameters was recorded"] },
+        ]);
+
+        quote! { #(#docs)* }
+    }
+
+    /// Generates documentation attributes for the `assert_returned` function.
+    pub(crate) fn assert_returned_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Asserts that at least one call returned the specified value."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if no call returned a matching value"]
+        }
+    }
+
+    /// Generates documentation attributes for the `snapshot` function.
+    pub(crate) fn snapshot_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Saves the recorded calls."]
+            #[doc = ""]
+            #[doc = "Pass the snapshot to `restore()` to put the spy back into this state."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`fnmock::snapshot::StateSnapshot` - the saved state"]
+        }
+    }
+
+    /// Generates documentation attributes for the `restore` function.
+    pub(crate) fn restore_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Restores the spy to a state saved with `snapshot()`."]
+            #[doc = ""]
+            #[doc = "Calls recorded after the snapshot was taken are discarded."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the snapshot was taken from a different double"]
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use syn::parse::{Parse, ParseStream};
+use syn::Token;
+use crate::attr_utils::parse_name_arg;
+use crate::cfg_utils::{parse_cfg_arg, parse_vis_arg};
+
+/// Structure to parse the spy_function attribute arguments
+#[derive(Default)]
+pub(crate) struct SpyFunctionArgs {
+    pub(crate) cfg: Option<proc_macro2::TokenStream>,
+    pub(crate) vis: Option<syn::Visibility>,
+    pub(crate) name: Option<syn::Ident>,
+}
+
+impl Parse for SpyFunctionArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = SpyFunctionArgs::default();
+
+        // Parse "cfg = "...", "vis = "..."" and "name = "..."" syntax
+        while !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key == "cfg" {
+                args.cfg = Some(parse_cfg_arg(input)?);
+            } else if key == "vis" {
+                args.vis = Some(parse_vis_arg(input)?);
+            } else if key == "name" {
+                args.name = Some(parse_name_arg(input)?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    &key,
+                    format!("Unknown spy_function argument '{}'. Expected 'cfg', 'vis' or 'name'", key)
+                ));
+            }
+
+            // Allow trailing comma or end of input
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
@@ -1,5 +1,12 @@
 use quote::quote;
 use crate::function_stub::proxy_docs::StubProxyDocs;
+use crate::generic_utils::{create_alias_generics, create_monomorphization_key, has_defaults, is_generic};
+use crate::param_utils::{create_params_matcher, is_nameable};
+use crate::stream_utils::create_stream_function;
+use crate::return_utils::extract_result_types;
+use crate::cfg_utils::inner_vis;
+use crate::storage_utils::Storage;
+use crate::arbitrary_utils::create_arbitrary_proxies;
 
 /// Generates the original function with stub checking logic injected.
 ///
@@ -7,37 +14,136 @@ use crate::function_stub::proxy_docs::StubProxyDocs;
 /// configured via the stub module. If a stub is set, it calls the stub implementation.
 /// Otherwise, it executes the original function body.
 ///
+/// Keyed stubs look up the return value by the parameters of the call.
+/// Default stubs never execute the original function body in test mode,
+/// they return `Default::default()` if no return value is configured.
+///
+/// Functions returning `impl Stream` box the returned `fnmock::stream::StubStream`
+/// in test mode (see [`create_stream_function`]).
+///
+/// For generic functions the test version of the function has to carry the additional
+/// storage bounds, so separate versions are generated for test and non-test builds.
+///
 /// # Arguments
 ///
+/// * `fn_attrs` - The attributes of the original function (doc comments, `cfg`, ...)
 /// * `fn_name` - The name of the original function
 /// * `fn_visibility` - The visibility modifier of the function (pub, pub(crate), etc.)
 /// * `fn_asyncness` - Optional async keyword if the function is async
+/// * `fn_qualifiers` - The `unsafe` keyword and the ABI of the function (see `fn_qualifiers`)
+/// * `fn_generics` - The generics of the original function, including the where clause
+/// * `storage_generics` - The generics including the bounds required to store the return value per monomorphization
 /// * `fn_inputs` - The function parameters
 /// * `fn_output` - The return type
 /// * `fn_block` - The original function body to execute when stub is not set
 /// * `stub_mod_name` - The name of the stub module containing the stub infrastructure
+/// * `keyed` - Whether the return values are looked up by the parameters
+/// * `default` - Whether `Default::default()` is returned if no return value is configured
+/// * `boxed_stream_type` - The boxed stream type, if the function returns `impl Stream`
+/// * `doubles_cfg` - The `cfg` predicate the stub check is compiled for (see `doubles_cfg`)
 ///
 /// # Returns
 ///
 /// Generated token stream for the function with injected stub checking logic
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create_stub_function(
+    fn_attrs: &[syn::Attribute],
     fn_name: syn::Ident,
     fn_visibility: syn::Visibility,
     fn_asyncness: Option<syn::token::Async>,
+    fn_qualifiers: &proc_macro2::TokenStream,
+    fn_generics: syn::Generics,
+    storage_generics: &syn::Generics,
     fn_inputs: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
     fn_output: syn::ReturnType,
-    fn_block: Box<syn::Block>,
+    fn_block: syn::Block,
     stub_mod_name: syn::Ident,
+    keyed: bool,
+    default: bool,
+    boxed_stream_type: Option<syn::Type>,
+    doubles_cfg: &proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
     let original_fn_stmts = &fn_block.stmts;
+    let where_clause = &fn_generics.where_clause;
+
+    // Generic functions select the stub of their monomorphization, the generics are only used in test mode
+    if is_generic(&fn_generics) {
+        let (_, storage_ty_generics, _) = storage_generics.split_for_impl();
+        let turbofish = storage_ty_generics.as_turbofish();
+        let storage_where_clause = &storage_generics.where_clause;
+        let allow_defaults = if has_defaults(&fn_generics) {
+            quote! { #[allow(invalid_type_param_default)] }
+        } else {
+            quote! {}
+        };
+
+        return quote! {
+            #(#fn_attrs)*
+            #[cfg(not(#doubles_cfg))]
+            #allow_defaults
+            #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #fn_generics (#fn_inputs) #fn_output #where_clause {
+                #(#original_fn_stmts)*
+            }
+
+            #(#fn_attrs)*
+            #[cfg(#doubles_cfg)]
+            #[allow(unused_variables, clippy::multiple_bound_locations)]
+            #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #storage_generics (#fn_inputs) #fn_output #storage_where_clause {
+                // Call the stub implementation of this monomorphization if set
+                if #stub_mod_name::is_set #turbofish() {
+                    return #stub_mod_name::get_return_value #turbofish();
+                }
+
+                #(#original_fn_stmts)*
+            }
+        };
+    }
+
+    let get_return_value = if keyed {
+        let params_matcher = create_params_matcher(&fn_inputs);
+        quote! { #stub_mod_name::get_return_value_for(#params_matcher) }
+    } else {
+        quote! { #stub_mod_name::get_return_value() }
+    };
     
+    if let Some(boxed_stream_type) = boxed_stream_type {
+        let stub_check = quote! {
+            // Return the stub stream if set
+            if #stub_mod_name::is_set() {
+                let stub_stream: #boxed_stream_type = ::std::boxed::Box::pin(#get_return_value);
+                return stub_stream;
+            }
+        };
+
+        return create_stream_function(
+            fn_attrs, &fn_name, &fn_visibility, fn_asyncness, fn_qualifiers, &fn_inputs, &fn_output, &fn_block,
+            &boxed_stream_type, stub_check, doubles_cfg,
+        );
+    }
+
+    if default {
+        return quote! {
+            #(#fn_attrs)*
+            #[allow(unused_variables)]
+            #[cfg_attr(#doubles_cfg, allow(unreachable_code))]
+            #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #fn_generics (#fn_inputs) #fn_output #where_clause {
+                // Return the stubbed or the default value (only in test mode)
+                #[cfg(#doubles_cfg)]
+                return #get_return_value;
+
+                #(#original_fn_stmts)*
+            }
+        };
+    }
+
     quote! {
+        #(#fn_attrs)*
         #[allow(unused_variables)]
-        #fn_visibility #fn_asyncness fn #fn_name(#fn_inputs) #fn_output {
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name #fn_generics (#fn_inputs) #fn_output #where_clause {
             // Call the stub implementation if set (only in test mode)
-            #[cfg(test)]
+            #[cfg(#doubles_cfg)]
             if #stub_mod_name::is_set() {
-                return #stub_mod_name::get_return_value();
+                return #get_return_value;
             }
 
             #(#original_fn_stmts)*
@@ -48,48 +154,274 @@ pub(crate) fn create_stub_function(
 /// Generates a stub module containing the stub infrastructure.
 ///
 /// Creates a module with the same name as the stub function that contains:
-/// - Thread-local storage for the FunctionStub instance
+/// - Public `Params` and `Return` type aliases (omitted if the type can't be named, e.g. for references)
+/// - The slot of the storage backend holding the FunctionStub instance (see `fnmock::storage`).
+///   Thread local stubs are registered with `fnmock::registry` on first use,
+///   task local stubs get `scope()` and `propagate()` functions
 /// - Proxy functions for stub operations
+/// - Proxy functions for the return values keyed by parameters (only for keyed stubs)
+/// - `setup_ok` and `setup_err` shortcuts (only for functions returning a `Result`, see `extract_result_types`)
 ///
 /// # Arguments
 ///
 /// * `stub_fn_name` - The name of the stub module (same as stub function name)
+/// * `params_type` - The type representing the function parameters (single type or tuple)
 /// * `return_type` - The return type of the function
-pub(crate) fn create_stub_module(stub_fn_name: syn::Ident, return_type: syn::Type) -> proc_macro2::TokenStream {
+/// * `keyed` - Whether the return values are looked up by the parameters
+/// * `default` - Whether the stub returns `Default::default()` if no return value is configured
+/// * `storage_generics` - The generics including the storage bounds (see `create_stub_function`)
+/// * `module_vis` - The visibility of the stub module (see `doubles_vis`)
+/// * `storage` - The storage backend of the stub
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_stub_module(
+    stub_fn_name: syn::Ident,
+    params_type: syn::Type,
+    return_type: syn::Type,
+    keyed: bool,
+    default: bool,
+    storage_generics: &syn::Generics,
+    module_vis: &syn::Visibility,
+    storage: Storage,
+) -> proc_macro2::TokenStream {
+    let doubles_vis = inner_vis(module_vis);
     // Generate documentation using the proxy_docs module
     let docs = StubProxyDocs::new(&stub_fn_name, &return_type);
     let setup_docs = docs.setup_docs();
+    let setup_scoped_docs = docs.setup_scoped_docs();
+    let setup_once_docs = docs.setup_once_docs();
+    let setup_factory_docs = docs.setup_factory_docs();
     let clear_docs = docs.clear_docs();
     let is_set_docs = docs.is_set_docs();
-    let get_return_value_docs = docs.get_return_value_docs();
+    let get_return_value_docs = docs.get_return_value_docs(default);
+    let snapshot_docs = docs.snapshot_docs();
+    let restore_docs = docs.restore_docs();
+
+    let params_alias = if is_nameable(&params_type) {
+        let params_type_docs = docs.params_type_docs();
+        let params_alias_generics = create_alias_generics(storage_generics, &params_type);
+        quote! {
+            #params_type_docs
+            #doubles_vis type Params #params_alias_generics = #params_type;
+        }
+    } else {
+        quote! {}
+    };
+    let return_alias = if is_nameable(&return_type) {
+        let return_type_docs = docs.return_type_docs();
+        let return_alias_generics = create_alias_generics(storage_generics, &return_type);
+        quote! {
+            #return_type_docs
+            #doubles_vis type Return #return_alias_generics = #return_type;
+        }
+    } else {
+        quote! {}
+    };
     
+    // Values returned by every call are cloned, which requires a cloneable return type.
+    // The bound is higher-ranked, so it's only checked when the proxy is used instead of
+    // failing to compile for every non-cloneable return type, which can still be set up with `setup_once`.
+    let clone_bound: Option<syn::WherePredicate> = if is_nameable(&return_type) {
+        Some(syn::parse_quote! { for<'a> #return_type: Clone })
+    } else {
+        None
+    };
+    let clone_where_clause = clone_bound.as_ref().map(|bound| quote! { where #bound });
+
+    let (stub_type, keyed_stub) = if keyed {
+        let setup_for_docs = docs.setup_for_docs();
+        let get_return_value_for_docs = docs.get_return_value_for_docs();
+
+        (
+            quote! { fnmock::function_stub::FunctionStub<#return_type, #params_type> },
+            quote! {
+                #setup_for_docs
+                #doubles_vis fn setup_for(params: #params_type, return_value: #return_type) #clone_where_clause {
+                    with_stub(|stub| { stub.setup_for(params, return_value) })
+                }
+
+                #get_return_value_for_docs
+                #doubles_vis fn get_return_value_for(matches: impl Fn(&#params_type) -> bool) -> #return_type {
+                    with_stub(|stub| { stub.get_return_value_for(matches) })
+                }
+            },
+        )
+    } else {
+        (quote! { fnmock::function_stub::FunctionStub<#return_type> }, quote! {})
+    };
+
+    let new_stub = if default {
+        quote! { fnmock::function_stub::FunctionStub::with_default(stringify!(#stub_fn_name)) }
+    } else {
+        quote! { fnmock::function_stub::FunctionStub::new(stringify!(#stub_fn_name)) }
+    };
+
+    // Generic functions store one stub per monomorphization and pass their generics to the proxy functions
+    let (storage_type, new_storage, stub, stub_mut, proxy_generics) = if is_generic(storage_generics) {
+        let key = create_monomorphization_key(storage_generics);
+        let stub_of_monomorphization = quote! {
+            stub.get_or_create::<#key, #stub_type>(fnmock::function_stub::FunctionStub::new)
+        };
+        (
+            quote! { fnmock::generic_doubles::GenericDoubles },
+            quote! { fnmock::generic_doubles::GenericDoubles::new(stringify!(#stub_fn_name)) },
+            stub_of_monomorphization.clone(),
+            stub_of_monomorphization,
+            storage_generics.clone(),
+        )
+    } else {
+        (
+            stub_type,
+            new_stub,
+            quote! { stub },
+            quote! { stub },
+            syn::Generics::default(),
+        )
+    };
+    let (impl_generics, proxy_ty_generics, where_clause) = proxy_generics.split_for_impl();
+    let proxy_turbofish = proxy_ty_generics.as_turbofish();
+    let mut setup_generics = proxy_generics.clone();
+    setup_generics.make_where_clause().predicates.extend(clone_bound);
+    let setup_where_clause = &setup_generics.where_clause;
+
+    let arbitrary_proxies = if is_nameable(&return_type) {
+        create_arbitrary_proxies(&doubles_vis, &quote! { #return_type }, &proxy_generics, true, quote! {
+            with_stub(|stub| { #stub_mut.setup_factory(generator) })
+        })
+    } else {
+        quote! {}
+    };
+
+    let result_shortcuts = match extract_result_types(&return_type).filter(|_| is_nameable(&return_type)) {
+        Some((ok_type, err_type)) => {
+            let setup_ok_docs = docs.setup_ok_docs();
+            let setup_err_docs = docs.setup_err_docs();
+            quote! {
+                #setup_ok_docs
+                #doubles_vis fn setup_ok #impl_generics (value: #ok_type) #setup_where_clause {
+                    setup #proxy_turbofish(Ok(value))
+                }
+
+                #setup_err_docs
+                #doubles_vis fn setup_err #impl_generics (error: #err_type) #setup_where_clause {
+                    setup #proxy_turbofish(Err(error))
+                }
+            }
+        }
+        None => quote! {},
+    };
+
+    // Only thread local stubs are registered, since the registry tracks the doubles of the current thread
+    let slot = match storage {
+        Storage::ThreadLocal => quote! {
+            thread_local! {
+                static STUB: std::cell::RefCell<#storage_type> = {
+                    fnmock::registry::register(stringify!(#stub_fn_name), snapshot, restore, clear, control);
+                    std::cell::RefCell::new(#new_storage)
+                };
+            }
+
+            // Gives the registry access to the stored stub
+            fn control(f: &mut dyn FnMut(&dyn fnmock::registry::MockControl)) {
+                STUB.with(|stub| f(&*stub.borrow()))
+            }
+        },
+        Storage::Global => quote! {
+            static STUB: ::std::sync::LazyLock<::std::sync::Mutex<#storage_type>> =
+                ::std::sync::LazyLock::new(|| ::std::sync::Mutex::new(#new_storage));
+        },
+        Storage::TaskLocal => {
+            let scope_docs = docs.scope_docs();
+            let propagate_docs = docs.propagate_docs();
+            quote! {
+                fnmock::task_local::task_local! {
+                    static STUB: ::std::sync::Arc<::std::sync::Mutex<#storage_type>>;
+                }
+
+                #scope_docs
+                #doubles_vis fn scope<F: ::std::future::Future>(future: F) -> impl ::std::future::Future<Output = F::Output> {
+                    fnmock::storage::TaskLocal::scope(&STUB, #new_storage, future)
+                }
+
+                #propagate_docs
+                #doubles_vis fn propagate<F: ::std::future::Future>(future: F) -> impl ::std::future::Future<Output = F::Output> {
+                    fnmock::storage::TaskLocal::propagate(&STUB, stringify!(#stub_fn_name), future)
+                }
+            }
+        }
+    };
+    let backend = storage.backend();
+
     quote! {
-        pub(crate) mod #stub_fn_name {
+        #[allow(clippy::multiple_bound_locations)]
+        #module_vis mod #stub_fn_name {
             use super::*;
 
-            thread_local! {
-                static STUB: std::cell::RefCell<fnmock::function_stub::FunctionStub<#return_type>> =
-                    std::cell::RefCell::new(fnmock::function_stub::FunctionStub::new(stringify!(#stub_fn_name)));
+            #params_alias
+
+            #return_alias
+
+            #slot
+
+            // Accesses the stored stub through the storage backend
+            fn with_stub<R>(f: impl FnOnce(&mut #storage_type) -> R) -> R {
+                <#backend as fnmock::storage::Storage>::with(&STUB, stringify!(#stub_fn_name), f)
             }
 
             #setup_docs
-            pub(crate) fn setup(return_value: #return_type) {
-                STUB.with(|stub| { stub.borrow_mut().setup(return_value) })
+            #doubles_vis fn setup #impl_generics (return_value: #return_type) #setup_where_clause {
+                with_stub(|stub| { #stub_mut.setup(return_value) })
+            }
+
+            #setup_scoped_docs
+            #doubles_vis fn setup_scoped #impl_generics (return_value: #return_type) -> fnmock::guard::MockGuard #setup_where_clause {
+                setup #proxy_turbofish(return_value);
+                fnmock::guard::MockGuard::new(clear)
+            }
+
+            #setup_once_docs
+            #doubles_vis fn setup_once #impl_generics (return_value: #return_type) #where_clause {
+                with_stub(|stub| { #stub_mut.setup_once(return_value) })
+            }
+
+            #setup_factory_docs
+            #doubles_vis fn setup_factory #impl_generics (factory: impl FnMut() -> #return_type + Send + 'static) #where_clause {
+                with_stub(|stub| { #stub_mut.setup_factory(factory) })
             }
 
+            #arbitrary_proxies
+
+            #result_shortcuts
+
             #clear_docs
-            pub(crate) fn clear() {
-                STUB.with(|stub| { stub.borrow_mut().clear() })
+            #doubles_vis fn clear() {
+                with_stub(|stub| { stub.clear() })
             }
 
             #is_set_docs
-            pub(crate) fn is_set() -> bool {
-                STUB.with(|stub| { stub.borrow().is_set() })
+            #doubles_vis fn is_set #impl_generics () -> bool #where_clause {
+                // Outside of a task local scope, the function executes its original implementation
+                <#backend as fnmock::storage::Storage>::try_with(&STUB, |stub| { #stub.is_set() }).unwrap_or(false)
             }
 
             #get_return_value_docs
-            pub(crate) fn get_return_value() -> #return_type {
-                STUB.with(|stub| { stub.borrow().get_return_value() })
+            #doubles_vis fn get_return_value #impl_generics () -> #return_type #where_clause {
+                with_stub(|stub| { #stub.get_return_value() })
+            }
+
+            #keyed_stub
+
+            #snapshot_docs
+            #doubles_vis fn snapshot() -> fnmock::snapshot::StateSnapshot {
+                with_stub(|stub| {
+                    fnmock::snapshot::StateSnapshot::new(stringify!(#stub_fn_name), stub.clone())
+                })
+            }
+
+            #restore_docs
+            #doubles_vis fn restore(snapshot: fnmock::snapshot::StateSnapshot) {
+                with_stub(|stub| {
+                    *stub = snapshot.into_state(stringify!(#stub_fn_name))
+                })
             }
         }
     }
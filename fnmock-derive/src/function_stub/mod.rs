@@ -1,62 +1,167 @@
 use quote::quote;
 use syn::__private::TokenStream2;
+use crate::abi_utils::{fn_qualifiers, validate_preservable};
 use crate::function_stub::create_stub_implementation::{create_stub_function, create_stub_module};
+use crate::function_stub::stub_args::StubFunctionArgs;
+use crate::attr_utils::{add_cfg_attrs, double_mod_name, get_cfg_attrs};
+use crate::generic_utils::{add_bounds, create_storage_bounds, is_generic, strip_defaults, validate_generics};
+use crate::manifest_utils::create_manifest_entry;
+use crate::param_utils::{bind_param_patterns, create_param_type, is_nameable};
 use crate::return_utils::extract_return_type;
+use crate::stream_utils::{get_boxed_stream_type, get_stub_stream_type};
+use crate::cfg_utils::{doubles_cfg, doubles_vis};
+use crate::storage_utils::Storage;
 
 mod create_stub_implementation;
 mod proxy_docs;
+pub(crate) mod stub_args;
 
 /// Processes a function and generates the complete stub infrastructure.
 ///
 /// This is the main entry point for the stub_function attribute macro. It takes a function
 /// definition and generates:
 /// 1. The original function with stub checking logic injected (in test mode, checks if a stub
-///    is configured and calls it; otherwise executes the original implementation)
+///    is configured and calls it; otherwise executes the original implementation,
+///    or returns `Default::default()` for `default` stubs)
 /// 2. A stub module with control methods (test-only) containing `setup()`, `clear()`,
-///    `is_set()`, and `get_return_value()` functions
+///    `is_set()`, and `get_return_value()` functions (and `setup_for()` for keyed stubs)
 ///
 /// # Arguments
 ///
 /// * `stub_function` - The function item to create stubs for
+/// * `args` - The parsed stub_function attribute arguments
 ///
 /// # Returns
 ///
 /// - `Ok(TokenStream2)` - The complete generated code including original and stub infrastructure
 /// - `Err(syn::Error)` - If validation fails or the function cannot be stubbed
-pub(crate) fn process_stub_function(stub_function: syn::ItemFn) -> syn::Result<TokenStream2> {
+pub(crate) fn process_stub_function(mut stub_function: syn::ItemFn, args: StubFunctionArgs) -> syn::Result<TokenStream2> {
+    // Destructured params are bound to names, so they can be passed on
+    bind_param_patterns(&mut stub_function.sig, &mut stub_function.block);
+
     // Extract function details
     let fn_visibility = stub_function.vis.clone();
     let fn_asyncness = stub_function.sig.asyncness;
+    let fn_qualifiers = fn_qualifiers(&stub_function.sig);
     let fn_name = stub_function.sig.ident.clone();
+    let fn_generics = stub_function.sig.generics.clone();
     let fn_inputs = stub_function.sig.inputs.clone();
     let fn_output = stub_function.sig.output.clone();
-    let fn_block = stub_function.block.clone();
+    let fn_block = (*stub_function.block).clone();
+    let cfg_attrs = get_cfg_attrs(&stub_function.attrs);
+
+    // The `unsafe` keyword, the ABI and attributes like `no_mangle` are kept on the generated function
+    validate_preservable(&stub_function.sig, "stub_function")?;
+    validate_generics(&fn_generics)?;
 
     // Generate stub module name
-    let stub_mod_name = syn::Ident::new(&format!("{}_stub", &fn_name), fn_name.span());
+    let stub_mod_name = double_mod_name(&fn_name, args.name.as_ref(), "_stub");
 
+    let params_type = create_param_type(&fn_inputs, &[]);
     let return_type = extract_return_type(&stub_function.sig.output);
 
+    // Stubs of functions returning `impl Stream` store a `StubStream`, which is boxed when returned
+    let boxed_stream_type = get_boxed_stream_type(&return_type);
+    let return_type = get_stub_stream_type(&return_type).unwrap_or(return_type);
+
+    // Keyed stubs store the parameters, so they have to be owned
+    if args.keyed {
+        if fn_inputs.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &stub_function.sig,
+                "stub_function(keyed) requires the function to have parameters"
+            ));
+        }
+        if !is_nameable(&params_type) {
+            return Err(syn::Error::new_spanned(
+                &fn_inputs,
+                "stub_function(keyed) requires all parameters to be 'static. \
+                 Consider using owned types like String instead of &str."
+            ));
+        }
+    }
+
+    // Default stubs return `Default::default()` in place of the original stream, which can't be stored
+    if args.default && boxed_stream_type.is_some() {
+        return Err(syn::Error::new_spanned(
+            &stub_function.sig.output,
+            "stub_function(default) can't be used on functions returning `impl Stream`"
+        ));
+    }
+
+    // Generic functions store one return value per monomorphization,
+    // which requires 'static type parameters and a cloneable return type
+    if is_generic(&fn_generics) && (args.keyed || args.default) {
+        return Err(syn::Error::new_spanned(
+            &fn_generics,
+            "stub_function(keyed) and stub_function(default) can't be used on generic functions"
+        ));
+    }
+    if boxed_stream_type.is_some() && !fn_generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &fn_generics,
+            "stub_function does not support generic functions returning impl Stream"
+        ));
+    }
+
+    // Only thread local stubs can store one return value per monomorphization,
+    // and default stubs have to be reachable from every call
+    let storage = args.storage.unwrap_or_default();
+    if storage != Storage::ThreadLocal && is_generic(&fn_generics) {
+        return Err(syn::Error::new_spanned(
+            &fn_generics,
+            format!("stub_function({}) can't be used on generic functions", storage.arg())
+        ));
+    }
+    if storage == Storage::TaskLocal && args.default {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "stub_function(default) can't be combined with storage = task_local, since the stub is unreachable outside of a scope"
+        ));
+    }
+    let storage_generics = add_bounds(&strip_defaults(&fn_generics), &create_storage_bounds(&fn_generics, &return_type));
+
+    // The stub check and module are compiled for the custom cfg predicate or for tests
+    let doubles_cfg = args.cfg.clone().unwrap_or_else(doubles_cfg);
+    let manifest_entry = add_cfg_attrs(&cfg_attrs, create_manifest_entry("Stub", &stub_function.sig));
+
     let stub_function = create_stub_function(
+        &stub_function.attrs,
         fn_name,
         fn_visibility,
         fn_asyncness,
+        &fn_qualifiers,
+        fn_generics,
+        &storage_generics,
         fn_inputs,
         fn_output,
         fn_block,
         stub_mod_name.clone(),
+        args.keyed,
+        args.default,
+        boxed_stream_type,
+        &doubles_cfg,
     );
 
     let stub_module = create_stub_module(
         stub_mod_name,
-        return_type
+        params_type,
+        return_type,
+        args.keyed,
+        args.default,
+        &storage_generics,
+        &args.vis.clone().unwrap_or_else(doubles_vis),
+        storage,
     );
 
     // Generate the original function and the stub module
     Ok(quote! {
         #stub_function
 
-        #[cfg(test)]
+        #manifest_entry
+
+        #(#cfg_attrs)*
+        #[cfg(#doubles_cfg)]
         #stub_module
     })
 }
@@ -1,4 +1,4 @@
-/// Generates documentation strings for stub proxy functions based on actual return type.
+//! Generates documentation strings for stub proxy functions based on actual return type.
 
 use quote::quote;
 
@@ -8,6 +8,7 @@ use quote::quote;
 pub(crate) struct StubProxyDocs {
     return_type_str: String,
     setup_example: String,
+    setup_for_example: String,
 }
 
 impl StubProxyDocs {
@@ -23,10 +24,28 @@ impl StubProxyDocs {
     ) -> Self {
         let return_type_str = quote::quote!(#return_type).to_string();
         let setup_example = format!("{}::setup(/* value of type {} */);", stub_fn_name, return_type_str);
+        let setup_for_example = format!("{}::setup_for(/* params */, /* value of type {} */);", stub_fn_name, return_type_str);
         
         Self {
             return_type_str,
             setup_example,
+            setup_for_example,
+        }
+    }
+
+    /// Generates documentation attributes for the `Params` type alias.
+    pub(crate) fn params_type_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "The parameters of the function."]
+            #[doc = ""]
+            #[doc = "A single parameter is used directly, multiple parameters are combined into a tuple."]
+        }
+    }
+
+    /// Generates documentation attributes for the `Return` type alias.
+    pub(crate) fn return_type_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "The return type of the function."]
         }
     }
 
@@ -39,7 +58,8 @@ impl StubProxyDocs {
             #[doc = "Sets up the stub's return value."]
             #[doc = ""]
             #[doc = "Configures the value that will be returned every time the stub function is called."]
-            #[doc = "The value must implement `Clone` since it may be returned multiple times."]
+            #[doc = "The value must implement `Clone` since it may be returned multiple times,"]
+            #[doc = "non-cloneable values can be set up with `setup_once()`."]
             #[doc = ""]
             #[doc = "# Returns"]
             #[doc = ""]
@@ -53,6 +73,161 @@ impl StubProxyDocs {
         }
     }
 
+    /// Generates documentation attributes for the `setup_once` function.
+    pub(crate) fn setup_once_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up a return value, which is moved out by the next call instead of being cloned."]
+            #[doc = ""]
+            #[doc = "Allows stubbing functions returning non-cloneable types like files or channel senders."]
+            #[doc = "The value takes precedence over the values configured via `setup()` and `setup_for()`,"]
+            #[doc = "which are returned by the following calls."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `return_value` - The value returned by the next call"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "The stub function panics if it's called again and no other value is configured"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_factory` function.
+    pub(crate) fn setup_factory_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up a factory producing a fresh return value for every call."]
+            #[doc = ""]
+            #[doc = "Every call gets its own value (a new id, a new connection handle, ...),"]
+            #[doc = "so the return type doesn't have to implement `Clone`."]
+            #[doc = "Replaces the value configured via `setup()`, values configured via `setup_for()` take precedence."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `factory` - The closure producing the return values, it must be `Send`"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_for` function.
+    pub(crate) fn setup_for_docs(&self) -> proc_macro2::TokenStream {
+        let return_type_str = &self.return_type_str;
+        let setup_for_example = &self.setup_for_example;
+
+        quote! {
+            #[doc = "Sets up the stub's return value for specific parameters."]
+            #[doc = ""]
+            #[doc = "Calls with matching parameters return this value, all other calls return the"]
+            #[doc = "value configured via `setup()`. Setting up the same parameters again replaces the value."]
+            #[doc = "Multiple parameters are passed as a tuple."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = #return_type_str]
+            #[doc = ""]
+            #[doc = "# Examples"]
+            #[doc = ""]
+            #[doc = "```ignore"]
+            #[doc = #setup_for_example]
+            #[doc = "```"]
+        }
+    }
+
+    /// Generates documentation attributes for the `get_return_value_for` function.
+    pub(crate) fn get_return_value_for_docs(&self) -> proc_macro2::TokenStream {
+        let return_type_str = &self.return_type_str;
+
+        quote! {
+            #[doc = "Gets the return value configured for the parameters accepted by `matches`."]
+            #[doc = ""]
+            #[doc = "This function is used internally by the stub function to look up the return value"]
+            #[doc = "that was configured via `setup_for()`, falling back to the value configured via `setup()`."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = #return_type_str]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if no value was set up for the parameters and `setup()` has not been called"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_scoped` function.
+    pub(crate) fn setup_scoped_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the stub like `setup()` and returns a guard clearing it when dropped."]
+            #[doc = ""]
+            #[doc = "The stub is cleared even if the test panics, so the setup can't leak into"]
+            #[doc = "later phases of the test."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `return_value` - The return value, like for `setup()`"]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`fnmock::guard::MockGuard` - the guard calling `clear()` on drop"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_ok` function.
+    pub(crate) fn setup_ok_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the stub to return the value wrapped in `Ok`."]
+            #[doc = ""]
+            #[doc = "Shorthand for `setup(Ok(value))`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `value` - The success value returned by the stub"]
+        }
+    }
+
+    /// Generates documentation attributes for the `setup_err` function.
+    pub(crate) fn setup_err_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Sets up the stub to return the error wrapped in `Err`."]
+            #[doc = ""]
+            #[doc = "Shorthand for `setup(Err(error))`."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `error` - The error returned by the stub"]
+        }
+    }
+
+    /// Generates documentation attributes for the `scope` function of task local stubs.
+    pub(crate) fn scope_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Runs the future with a new stub, which is only visible to the future."]
+            #[doc = ""]
+            #[doc = "The stub follows the task across the threads of a multi-threaded runtime,"]
+            #[doc = "and other tests using the stub in their own scope don't interfere with it."]
+            #[doc = "Outside of a scope, the function executes its original implementation."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `future` - The future using the stub, usually the body of the test"]
+        }
+    }
+
+    /// Generates documentation attributes for the `propagate` function of task local stubs.
+    pub(crate) fn propagate_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Runs the future with the stub of the current scope."]
+            #[doc = ""]
+            #[doc = "Spawned tasks don't inherit task locals, so futures passed to e.g. `tokio::spawn`"]
+            #[doc = "have to be wrapped to use the stub set up by the test."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "* `future` - The future to run with the stub"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if called outside of `scope()` or `propagate()`"]
+        }
+    }
+
     /// Generates documentation attributes for the `clear` function.
     pub(crate) fn clear_docs(&self) -> proc_macro2::TokenStream {
         quote! {
@@ -64,6 +239,34 @@ impl StubProxyDocs {
         }
     }
 
+    /// Generates documentation attributes for the `snapshot` function.
+    pub(crate) fn snapshot_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Saves the current stub state."]
+            #[doc = ""]
+            #[doc = "The snapshot contains the configured return value."]
+            #[doc = "Pass it to `restore()` to put the stub back into this state,"]
+            #[doc = "e.g. after a test helper temporarily reconfigured the stub."]
+            #[doc = ""]
+            #[doc = "# Returns"]
+            #[doc = ""]
+            #[doc = "`fnmock::snapshot::StateSnapshot` - the saved state"]
+        }
+    }
+
+    /// Generates documentation attributes for the `restore` function.
+    pub(crate) fn restore_docs(&self) -> proc_macro2::TokenStream {
+        quote! {
+            #[doc = "Restores the stub to a state saved with `snapshot()`."]
+            #[doc = ""]
+            #[doc = "Everything configured after the snapshot was taken is discarded."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "Panics if the snapshot was taken from a different double"]
+        }
+    }
+
     /// Generates documentation attributes for the `is_set` function.
     pub(crate) fn is_set_docs(&self) -> proc_macro2::TokenStream {
         quote! {
@@ -78,9 +281,20 @@ impl StubProxyDocs {
         }
     }
 
-    /// Generates documentation attributes for the `get_return_value` function.
-    pub(crate) fn get_return_value_docs(&self) -> proc_macro2::TokenStream {
+    /// Generates documentation attributes for the `get_return_value` function of a stub with or without `default`.
+    pub(crate) fn get_return_value_docs(&self, default: bool) -> proc_macro2::TokenStream {
         let return_type_str = &self.return_type_str;
+        let missing_setup_docs = if default {
+            quote! {
+                #[doc = "Returns `Default::default()` if `setup()` has not been called"]
+            }
+        } else {
+            quote! {
+                #[doc = "# Panics"]
+                #[doc = ""]
+                #[doc = "Panics if `setup()` has not been called before calling the stub function"]
+            }
+        };
         
         quote! {
             #[doc = "Gets the configured return value."]
@@ -92,9 +306,7 @@ impl StubProxyDocs {
             #[doc = ""]
             #[doc = #return_type_str]
             #[doc = ""]
-            #[doc = "# Panics"]
-            #[doc = ""]
-            #[doc = "Panics if `setup()` has not been called before calling the stub function"]
+            #missing_setup_docs
         }
     }
 }
@@ -0,0 +1,52 @@
+use syn::parse::{Parse, ParseStream};
+use syn::Token;
+use crate::attr_utils::parse_name_arg;
+use crate::cfg_utils::{parse_cfg_arg, parse_vis_arg};
+use crate::storage_utils::{parse_storage_arg, Storage};
+
+/// Structure to parse the stub_function attribute arguments
+#[derive(Default)]
+pub(crate) struct StubFunctionArgs {
+    pub(crate) keyed: bool,
+    pub(crate) default: bool,
+    pub(crate) storage: Option<Storage>,
+    pub(crate) cfg: Option<proc_macro2::TokenStream>,
+    pub(crate) vis: Option<syn::Visibility>,
+    pub(crate) name: Option<syn::Ident>,
+}
+
+impl Parse for StubFunctionArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = StubFunctionArgs::default();
+
+        // Parse "keyed", "default", "storage = ...", "cfg = "...", "vis = "..."" and "name = "..."" syntax
+        while !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key == "keyed" {
+                args.keyed = true;
+            } else if key == "default" {
+                args.default = true;
+            } else if key == "storage" {
+                args.storage = Some(parse_storage_arg(input, args.storage)?);
+            } else if key == "cfg" {
+                args.cfg = Some(parse_cfg_arg(input)?);
+            } else if key == "vis" {
+                args.vis = Some(parse_vis_arg(input)?);
+            } else if key == "name" {
+                args.name = Some(parse_name_arg(input)?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    &key,
+                    format!("Unknown stub_function argument '{}'. Expected 'keyed', 'default', 'storage', 'cfg', 'vis' or 'name'", key)
+                ));
+            }
+
+            // Allow trailing comma or end of input
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
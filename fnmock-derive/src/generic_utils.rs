@@ -0,0 +1,229 @@
+//! Utilities for handling the **generic parameters** of doubled functions.
+//!
+//! Generic functions can't store their double in a single `thread_local!` static.
+//! The generated proxy functions take the type parameters of the original function instead
+//! and select the double of the current monomorphization at runtime.
+
+use proc_macro2::TokenTree;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{GenericParam, Generics, Type, WherePredicate};
+
+/// Checks if the function has type or const parameters.
+///
+/// Lifetime parameters don't count, since they don't change the stored double.
+pub(crate) fn is_generic(generics: &Generics) -> bool {
+    generics
+        .params
+        .iter()
+        .any(|param| !matches!(param, GenericParam::Lifetime(_)))
+}
+
+/// Validates that the generic parameters of a function can be used for doubling.
+///
+/// # Returns
+///
+/// - `Ok(())` if the generics are supported
+/// - `Err(syn::Error)` if the function combines lifetime parameters with type or const parameters,
+///   since the generated proxy functions can't name the lifetimes
+pub(crate) fn validate_generics(generics: &Generics) -> syn::Result<()> {
+    if !is_generic(generics) {
+        return Ok(());
+    }
+
+    for param in &generics.params {
+        if let GenericParam::Lifetime(lifetime) = param {
+            return Err(syn::Error::new_spanned(
+                lifetime,
+                "Lifetime parameters are not supported on generic functions. \
+                 Consider using owned types or 'static instead."
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks if a type mentions the given identifier anywhere in its tokens.
+///
+/// # Examples
+///
+/// - `T` mentions `T`
+/// - `Vec<Option<T>>` mentions `T`
+/// - `String` doesn't mention `T`
+pub(crate) fn type_mentions(ty: &Type, ident: &syn::Ident) -> bool {
+    fn tokens_mention(tokens: proc_macro2::TokenStream, ident: &syn::Ident) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(token_ident) => token_ident == *ident,
+            TokenTree::Group(group) => tokens_mention(group.stream(), ident),
+            _ => false,
+        })
+    }
+
+    tokens_mention(quote! { #ty }, ident)
+}
+
+/// Creates the bounds required to record calls of a generic function.
+///
+/// Every type parameter has to be `'static`, since the doubles are stored per monomorphization.
+/// Type parameters that are part of the recorded parameters additionally have to implement
+/// `Clone`, `Debug` and `PartialEq`, as required by `FunctionMock`.
+///
+/// # Arguments
+///
+/// * `generics` - The generics of the original function
+/// * `params_type` - The type of the recorded (not ignored) parameters
+///
+/// # Examples
+///
+/// - `fn parse<T: FromStr>(s: String) -> T` → `T: 'static`
+/// - `fn save<T: Serialize>(value: T)` → `T: Clone + Debug + PartialEq + 'static`
+pub(crate) fn create_recording_bounds(
+    generics: &Generics,
+    params_type: &Type,
+) -> Punctuated<WherePredicate, Comma> {
+    generics
+        .type_params()
+        .map(|type_param| -> WherePredicate {
+            let ident = &type_param.ident;
+            if type_mentions(params_type, ident) {
+                syn::parse_quote! {
+                    #ident: ::std::clone::Clone + ::std::fmt::Debug + ::std::cmp::PartialEq + 'static
+                }
+            } else {
+                syn::parse_quote! { #ident: 'static }
+            }
+        })
+        .collect()
+}
+
+/// Creates the bounds required to store the double of a generic function that doesn't record calls.
+///
+/// Every type parameter has to be `'static`, since the doubles are stored per monomorphization.
+/// Type parameters that are part of the stored type (e.g. the return value of a stub) additionally
+/// have to implement `Clone`, so the value can be returned more than once.
+///
+/// # Arguments
+///
+/// * `generics` - The generics of the original function
+/// * `stored_type` - The type stored by the double, `()` if it doesn't store values
+///
+/// # Examples
+///
+/// - `fn parse<T: FromStr>(s: &str) -> Option<T>` with stored type `Option<T>` → `T: Clone + 'static`
+/// - `fn log<T: Display>(value: T)` with stored type `()` → `T: 'static`
+pub(crate) fn create_storage_bounds(
+    generics: &Generics,
+    stored_type: &Type,
+) -> Punctuated<WherePredicate, Comma> {
+    generics
+        .type_params()
+        .map(|type_param| -> WherePredicate {
+            let ident = &type_param.ident;
+            if type_mentions(stored_type, ident) {
+                syn::parse_quote! { #ident: ::std::clone::Clone + 'static }
+            } else {
+                syn::parse_quote! { #ident: 'static }
+            }
+        })
+        .collect()
+}
+
+/// Adds the given bounds to the where clause of a copy of the generics.
+pub(crate) fn add_bounds(
+    generics: &Generics,
+    bounds: &Punctuated<WherePredicate, Comma>,
+) -> Generics {
+    let mut generics = generics.clone();
+    generics.make_where_clause().predicates.extend(bounds.iter().cloned());
+    generics
+}
+
+/// Checks if any type or const parameter has a default value.
+///
+/// Defaults on function generics are only accepted with `#[allow(invalid_type_param_default)]`.
+pub(crate) fn has_defaults(generics: &Generics) -> bool {
+    generics.params.iter().any(|param| match param {
+        GenericParam::Type(type_param) => type_param.default.is_some(),
+        GenericParam::Const(const_param) => const_param.default.is_some(),
+        GenericParam::Lifetime(_) => false,
+    })
+}
+
+/// Removes the default values of all type and const parameters from a copy of the generics.
+///
+/// The proxy functions always receive their type parameters explicitly from the original function,
+/// so the defaults aren't needed there and would only trigger `invalid_type_param_default`.
+///
+/// # Examples
+///
+/// - `<T: Config = DefaultConfig>` → `<T: Config>`
+/// - `<T, const N: usize = 4>` → `<T, const N: usize>`
+pub(crate) fn strip_defaults(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.params.iter_mut() {
+        match param {
+            GenericParam::Type(type_param) => {
+                type_param.eq_token = None;
+                type_param.default = None;
+            }
+            GenericParam::Const(const_param) => {
+                const_param.eq_token = None;
+                const_param.default = None;
+            }
+            GenericParam::Lifetime(_) => {}
+        }
+    }
+    generics
+}
+
+/// Creates the generics of a type alias for the given type.
+///
+/// Type aliases must use all of their generic parameters and don't enforce bounds,
+/// so only the type and const parameters mentioned in the type are kept, without bounds or defaults.
+///
+/// # Examples
+///
+/// - `<T: FromStr, U>` for `Option<T>` → `<T>`
+/// - `<T: FromStr>` for `String` → no generics
+pub(crate) fn create_alias_generics(generics: &Generics, ty: &Type) -> Generics {
+    let mut params: Punctuated<GenericParam, Comma> = Punctuated::new();
+
+    for param in &generics.params {
+        match param {
+            GenericParam::Type(type_param) if type_mentions(ty, &type_param.ident) => {
+                let ident = &type_param.ident;
+                params.push(syn::parse_quote! { #ident });
+            }
+            GenericParam::Const(const_param) if type_mentions(ty, &const_param.ident) => {
+                let ident = &const_param.ident;
+                let const_ty = &const_param.ty;
+                params.push(syn::parse_quote! { const #ident: #const_ty });
+            }
+            _ => {}
+        }
+    }
+
+    if params.is_empty() {
+        return Generics::default();
+    }
+
+    Generics {
+        lt_token: Some(Default::default()),
+        params,
+        gt_token: Some(Default::default()),
+        where_clause: None,
+    }
+}
+
+/// Creates the key type identifying a monomorphization of a generic function.
+///
+/// # Returns
+///
+/// A tuple of all type parameters, e.g. `(T, U,)` for `fn foo<T, U>()`.
+pub(crate) fn create_monomorphization_key(generics: &Generics) -> proc_macro2::TokenStream {
+    let type_params = generics.type_params().map(|type_param| &type_param.ident);
+
+    quote! { (#(#type_params,)*) }
+}
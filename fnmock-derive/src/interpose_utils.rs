@@ -0,0 +1,91 @@
+//! Utilities for **link-time interposition** of doubled functions.
+//!
+//! With the `interpose` feature, the body of an annotated function is moved into a weak,
+//! unmangled symbol. A test harness linked into a release binary can define a strong symbol
+//! with the same name and signature, which replaces the original implementation at link time
+//! without any changes to the source code.
+
+use quote::quote;
+use crate::param_utils::get_param_names;
+
+/// Creates the default name of the interposable symbol, e.g. `fnmock_fetch_user`.
+pub(crate) fn default_interpose_symbol(fn_name: &syn::Ident) -> String {
+    format!("fnmock_{}", fn_name)
+}
+
+/// Validates that the function can be exported as an unmangled symbol.
+///
+/// # Returns
+///
+/// - `Ok(())` if the function can be interposed
+/// - `Err(syn::Error)` if the function is generic or async, since neither has a single symbol to replace
+pub(crate) fn validate_interposable(signature: &syn::Signature) -> syn::Result<()> {
+    if !signature.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &signature.generics,
+            "interpose can't be used on generic functions, since they have no single symbol to replace"
+        ));
+    }
+    if let Some(asyncness) = &signature.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "interpose can't be used on async functions, since they have no single symbol to replace"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Moves the function body into a weak, unmangled symbol.
+///
+/// Outside of tests the returned body forwards to the symbol, in tests it keeps the original
+/// statements, so the cfg based doubles work as before. The symbol is only emitted if the
+/// `interpose` feature is enabled, since weak linkage requires `#![feature(linkage)]` (nightly).
+///
+/// # Arguments
+///
+/// * `symbol` - The name of the exported symbol
+/// * `fn_visibility` - The visibility modifier of the function
+/// * `fn_inputs` - The function parameters
+/// * `fn_output` - The return type
+/// * `fn_block` - The original function body
+///
+/// # Returns
+///
+/// A tuple of the new function body and the definition of the symbol.
+pub(crate) fn create_interposable_function(
+    symbol: &str,
+    fn_visibility: &syn::Visibility,
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    fn_output: &syn::ReturnType,
+    fn_block: syn::Block,
+) -> (syn::Block, proc_macro2::TokenStream) {
+    if !cfg!(feature = "interpose") {
+        return (fn_block, quote! {});
+    }
+
+    let symbol_ident = syn::Ident::new(symbol, proc_macro2::Span::call_site());
+    let param_names = get_param_names(fn_inputs);
+    let original_fn_stmts = &fn_block.stmts;
+
+    let block = syn::parse_quote! {{
+        // Forward to the interposable symbol (only outside of test mode)
+        if cfg!(not(test)) {
+            return #symbol_ident(#(#param_names),*);
+        }
+
+        #(#original_fn_stmts)*
+    }};
+
+    let symbol_fn = quote! {
+        #[doc(hidden)]
+        #[unsafe(no_mangle)]
+        #[linkage = "weak"]
+        #[inline(never)]
+        #fn_visibility fn #symbol_ident(#fn_inputs) #fn_output {
+            #(#original_fn_stmts)*
+        }
+    };
+
+    (block, symbol_fn)
+}
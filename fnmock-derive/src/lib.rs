@@ -2,20 +2,53 @@ use proc_macro::TokenStream;
 use syn::{parse_macro_input};
 
 mod param_utils;
+mod attr_utils;
 mod use_tree_processor;
 mod use_statement_processor;
-mod inline_processor;
+mod use_doubles_args;
 mod function_mock;
 mod function_fake;
 mod function_stub;
+mod function_spy;
+mod function_dummy;
 mod return_utils;
+mod stream_utils;
+mod generic_utils;
+mod interpose_utils;
+mod abi_utils;
+mod manifest_utils;
+mod cfg_utils;
+mod storage_utils;
+mod arbitrary_utils;
+mod traitify;
+mod mock_trait;
+mod mock_impl;
+mod mock_module;
+mod reset_doubles;
+mod test_scope;
+mod when_dsl;
+mod assert_with_macro;
 
 use crate::function_mock::{process_mock_function};
 use crate::function_fake::{process_fake_function};
+use crate::function_fake::fake_args::FakeFunctionArgs;
 use crate::function_mock::mock_args::MockFunctionArgs;
 use crate::function_stub::{process_stub_function};
-use crate::inline_processor::process_inline;
-use crate::use_statement_processor::process_use_statement;
+use crate::function_stub::stub_args::StubFunctionArgs;
+use crate::function_spy::process_spy_function;
+use crate::function_spy::spy_args::SpyFunctionArgs;
+use crate::function_dummy::process_dummy_function;
+use crate::function_dummy::dummy_args::DummyFunctionArgs;
+use crate::traitify::process_traitify;
+use crate::mock_trait::process_mock_trait;
+use crate::mock_impl::process_mock_impl;
+use crate::mock_module::{process_mock_module, MockModuleArgs};
+use crate::reset_doubles::process_reset_doubles;
+use crate::test_scope::process_test;
+use crate::when_dsl::process_when;
+use crate::assert_with_macro::{process_assert_with, AssertWithInput};
+use crate::use_statement_processor::{process_use_doubles, process_use_statement};
+use crate::use_doubles_args::{UseDoublesArgs, UseFunctionArgs};
 
 /// Attribute macro that generates a mockable version of a function.
 ///
@@ -27,10 +60,53 @@ use crate::use_statement_processor::process_use_statement;
 /// # Generated Mock Module Methods
 ///
 /// - `setup(fn)` - Sets a custom implementation for the mock
+/// - `setup_returns(value)` - Sets an implementation returning a clone of the value (cloneable return types only)
+/// - `setup_ok(value)` / `setup_err(error)` - Sets an implementation returning a clone of the value wrapped in `Ok` / `Err` (`Result` return types only)
+/// - `setup_panics(message)` - Sets an implementation panicking with the message, the mock name and the parameters of the call
+/// - `setup_returns_sequence(values)` - Sets an implementation returning the values in order, panicking when they are exhausted
+/// - `setup_returns_sequence_repeat_last(values)` - Sets an implementation returning the values in order, repeating the last one (cloneable return types only)
+/// - `setup_arbitrary()` / `setup_strategy(strategy)` - Sets an implementation returning values drawn from a proptest strategy (`proptest` feature)
+/// - `setup_once(fn)` / `setup_times(n, fn)` - Sets the implementation for the next call / n calls, later calls execute the original implementation
+/// - `setup_scoped(fn)` - Sets the implementation and returns a guard clearing the mock when dropped
+/// - `enqueue_return(fn)` - Queues the return value of a single call, produced by a closure running once
 /// - `clear()` - Resets the mock to its uninitialized state
+/// - `clear_history()` - Resets the recorded calls, keeping the configured behavior
 /// - `is_set()` - Checks if the mock has been configured
+/// - `setup_when(matcher, fn)` - Sets the implementation for calls with parameters accepted by the matcher
+/// - `when(matchers...)` - Creates a pending case with one matcher per parameter, used by `fnmock::when!`
+/// - `setup_forwarding(fn)` - Sets an implementation receiving the recorded and the forwarded parameters (only with `forward`)
+/// - `fallback_to_original()` - Executes the original implementation for calls matching no case (still recording them)
+/// - `spy()` - Executes the original implementation for every call (still recording them)
+/// - `call_durations()` - Returns the wall-clock durations of the calls executing the original implementation
+/// - `setup_with_yields(n, fn)` - Sets the implementation and yields to the executor n times before returning (async functions only)
+/// - `setup_blocks(fn)` / `setup_pending(fn)` - Sets the implementation and hangs until the returned handle is released (sync / async functions)
+/// - `setup_with_delay(delay, fn)` - Sets the implementation and sleeps for the delay before returning
+/// - `on_call(hook)` - Registers a hook invoked with the parameters before the mock implementation runs
+/// - `after_call(hook)` - Registers a hook invoked with the parameters and the return value after the mock implementation returned
+/// - `capture()` / `capture_into(collector)` - Collects the parameters of the following calls in a captor / any shared collection implementing `Extend`
+/// - `in_sequence(&sequence)` - Logs the calls in a `fnmock::sequence::Sequence` shared with other mocks to verify their order
+/// - `expect_times(n)` / `expect_with(params)` - Registers an expectation checked by `verify()`
+/// - `verify()` - Checks all registered expectations
+/// - `verify_on_drop()` - Returns a checkpoint calling `verify()` when dropped
 /// - `assert_times(n)` - Verifies the function was called exactly n times
+/// - `assert_times_at_least(n)` / `assert_times_at_most(n)` / `assert_times_between(min, max)` - Verifies the number of calls is within bounds (inclusive)
+/// - `assert_times_matching(predicate, description)` - Verifies the number of calls is accepted by the predicate
+/// - `assert_times_eventually(n, timeout).await` - Waits until the function was called n times (e.g. by a background task), then verifies the count
+/// - `assert_panicked_times(n)` - Verifies the mock implementation panicked exactly n times
 /// - `assert_with(params)` - Verifies the function was called with specific parameters
+/// - `assert_with_matching(matcher)` - Verifies the function was called with parameters accepted by a matcher
+/// - `assert_with_matchers(matchers)` - Verifies the function was called with parameters accepted by one matcher per parameter
+/// - `assert_never_called_with(params)` - Verifies the function was never called with specific parameters
+/// - `assert_last_call_with(params)` - Verifies the most recent call was made with specific parameters
+/// - `assert_nth_call_with(index, params)` - Verifies a specific call (starting at 0) was made with specific parameters
+/// - `assert_no_duplicate_calls()` - Verifies the function was never called twice with the same parameters
+/// - `try_assert_times(n)` / `try_assert_with(params)` / `try_assert_with_matchers(matchers)` - Returns the failure of the assertion as `Err(VerificationFailure)` instead of panicking
+/// - `calls()` - Returns the parameters of all calls in call order
+/// - `last_call_params()` - Returns the parameters of the most recent call, if any
+/// - `times_called()` - Returns the number of calls
+/// - `first_call_index()` - Returns the index of the first call among the calls of all mocks (see `fnmock::assert_call_order!`)
+/// - `unique_calls()` - Returns the distinct parameters of all calls in the order of their first call
+/// - `formatted_calls()` - Returns the parameters of all calls formatted one call per line (see `fnmock::assert_calls_snapshot!`)
 ///
 /// # Ignoring of parameters
 ///
@@ -45,11 +121,257 @@ use crate::use_statement_processor::process_use_statement;
 /// }
 /// ```
 ///
+/// Names can contain `*` wildcards, like `ignore = [*_pool, ctx*]`, to ignore every matching parameter.
+/// Unlike plain names, patterns don't have to match any parameter.
+///
+/// # Owned copies of reference parameters
+///
+/// Reference parameters listed in `own` are recorded as owned copies (`&str` as `String`, `&[T]` as `Vec<T>`,
+/// other references as `<T as ToOwned>::Owned`), so the function keeps its signature.
+/// The implementation receives the owned copies, while the assertions take the references like the function:
+///
+/// ```ignore
+/// #[mock_function(own = [host, payload])]
+/// pub(crate) fn send_packet(host: &str, port: u16, payload: &[u8]) -> Result<usize, String> {
+///     // Real implementation
+/// }
+///
+/// send_packet_mock::setup(|(host, port, payload): (String, u16, Vec<u8>)| Ok(payload.len()));
+/// send_packet_mock::assert_with("10.0.0.1", 9000, b"hello");
+/// ```
+///
+/// # Parameters without `Debug`
+///
+/// Parameters listed in `no_debug` are recorded in a `fnmock::no_debug::NoDebug` wrapper, which formats them
+/// as `<non-debug T>` in failure messages. They still have to implement `Clone` and `PartialEq`,
+/// so the assertions keep checking them:
+///
+/// ```ignore
+/// #[mock_function(no_debug = [conn])]
+/// pub(crate) fn query(conn: Connection /* Doesn't implement Debug */, sql: String) -> Vec<String> {
+///     // Real implementation
+/// }
+///
+/// query_mock::setup(|(conn, sql)| vec![format!("{}: {}", conn.name(), sql)]);
+/// query_mock::assert_with(Connection::new("db"), "SELECT 1".to_string());
+/// ```
+///
+/// # Custom comparison of parameters
+///
+/// Parameters listed in `compare` are compared with the given closure instead of `PartialEq`, so parameters
+/// without `PartialEq` or with fields that differ between runs can still be checked by the assertions.
+/// They are recorded in a `fnmock::compare::Compared` wrapper, and the closure can't capture variables:
+///
+/// ```ignore
+/// #[mock_function(compare(db = "|a, b| a.url == b.url"))]
+/// pub(crate) fn save_order(db: Database, order_id: u32) -> Result<(), String> {
+///     // Real implementation
+/// }
+///
+/// save_order_mock::assert_with(Database::connect("postgres://orders"), 1);
+/// ```
+///
+/// # Projection of parameters
+///
+/// Parameters listed in `project` are recorded together with the value of the given expression,
+/// which the assertions compare and format instead of the parameter. The implementation still receives the parameter,
+/// wrapped in `fnmock::compare::Projected`:
+///
+/// ```ignore
+/// #[mock_function(project(cache = "cache.name.clone()"))]
+/// pub(crate) fn warm_up(cache: Cache, keys: Vec<String>) -> usize {
+///     // Real implementation
+/// }
+///
+/// warm_up_mock::assert_with(Cache::new("products"), vec!["book".to_string()]);
+/// ```
+///
+/// # Named parameters
+///
+/// With `named_params`, the recorded parameters are stored in a `<Function>Params` struct instead of a tuple,
+/// and an `assert_with_<field>` proxy function is generated per parameter:
+///
+/// ```ignore
+/// #[mock_function(named_params)]
+/// pub(crate) fn send_email(recipient: String, subject: String, priority: u8) -> Result<(), String> {
+///     // Real implementation
+/// }
+///
+/// send_email_mock::setup(|params| if params.priority == 1 { Err("rate limited".to_string()) } else { Ok(()) });
+/// send_email_mock::assert_with_recipient("alice@example.com".to_string());
+/// ```
+///
+/// # Mocks without history
+///
+/// With `no_history`, the mock only counts its calls instead of recording their parameters, so hot functions
+/// (e.g. called by property tests) don't clone their parameters for every call.
+/// `assert_times` and the other call count assertions work, while `assert_with` and the other parameter assertions panic:
+///
+/// ```ignore
+/// #[mock_function(no_history)]
+/// pub(crate) fn hash_block(block: Vec<u8>) -> u64 {
+///     // Real implementation
+/// }
+///
+/// hash_block_mock::assert_times(100_000);
+/// ```
+///
+/// # Forwarding of parameters
+///
+/// Parameters which can't be recorded, but have to be invoked by the mock implementation (e.g. callbacks),
+/// can be forwarded. They are excluded from the assertions like ignored parameters, and passed to the
+/// implementation set up with `setup_forwarding` (closures as `&dyn Fn`, `&mut dyn FnMut` or `Box<dyn FnOnce>`):
+///
+/// ```ignore
+/// #[mock_function(forward = [op])]
+/// pub(crate) fn retry(max_attempts: u32, op: impl FnMut() -> Result<String, String>) -> Result<String, String> {
+///     // Real implementation
+/// }
+///
+/// retry_mock::setup_forwarding(|max_attempts, op| op());
+/// ```
+///
+/// # Generic functions
+///
+/// Generic functions are mocked per monomorphization - a mock set up for `parse::<u16>` doesn't
+/// affect `parse::<i64>`. All proxy functions except `clear()` take the type parameters of the function,
+/// `clear()` resets the mocks of all monomorphizations.
+///
+/// The bounds required for recording calls are added to the test version of the function automatically:
+/// every type parameter must be `'static` and type parameters used in not ignored parameters
+/// must implement `Clone`, `Debug`, and `PartialEq`. You can replace them with custom bounds:
+///
+/// ```ignore
+/// #[mock_function(bounds = "T: Clone + std::fmt::Debug + PartialEq + 'static")]
+/// pub(crate) fn describe<T: ToString>(value: T) -> String {
+///     value.to_string()
+/// }
+///
+/// describe_mock::setup::<u32>(|value| format!("#{}", value));
+/// ```
+///
+/// # Link-time interposition
+///
+/// For black-box tests of release binaries, `interpose` exports the function body as a weak,
+/// unmangled symbol (`fnmock_<function_name>` or the given name). A test harness linked into the
+/// binary can replace it by defining a `#[no_mangle]` function with the same name and signature.
+///
+/// ```ignore
+/// #![feature(linkage)]
+///
+/// #[mock_function(interpose = "fetch_user_impl")]
+/// pub fn fetch_user(id: u32) -> Result<String, String> {
+///     Ok(format!("user_{}", id))
+/// }
+/// ```
+///
+/// The symbol is only emitted with the `interpose` feature of fnmock, since weak linkage requires
+/// the nightly `linkage` feature. Without it, the argument has no effect.
+/// The harness has to be linked into the binary, e.g. by calling one of its functions,
+/// otherwise the linker has no reason to pull in the replacement.
+/// Generic and async functions can't be interposed.
+///
+/// # Custom cfg gate
+///
+/// The mock check and the mock module are compiled for `cfg(test)` by default.
+/// `cfg = "..."` replaces the predicate with any cfg expression, e.g. a feature or `debug_assertions`:
+///
+/// ```ignore
+/// #[mock_function(cfg = "any(test, feature = \"simulation\")")]
+/// pub(crate) fn authorize(card: String, amount: u32) -> Result<String, String> {
+///     // Real implementation
+/// }
+/// ```
+///
+/// The predicate has to hold wherever the mock is set up, and functions with a custom predicate
+/// can't be part of a `#[traitify]` module. `#[fake_function]` and `#[stub_function]` take the same argument.
+///
+/// # Module visibility
+///
+/// The mock module is `pub(crate)` by default (`pub` with the `test-doubles` or `runtime-doubles` feature).
+/// `vis = "..."` chooses another visibility, e.g. `pub` to export it to other crates
+/// or `pub(self)` to keep it in the current module:
+///
+/// ```ignore
+/// #[mock_function(vis = "pub(super)")]
+/// pub(crate) fn read_block(index: u64) -> Vec<u8> {
+///     // Real implementation
+/// }
+/// ```
+///
+/// The proxy functions are visible wherever the module is. `#[fake_function]` and `#[stub_function]` take the same argument.
+///
+/// # Module name
+///
+/// `name = "..."` replaces the name of the mock module (`<function_name>_mock` by default),
+/// e.g. to avoid a clash with an existing item. `#[fake_function]` and `#[stub_function]` take the same argument:
+///
+/// ```ignore
+/// #[mock_function(name = "price_api")]
+/// pub(crate) fn fetch_price(item: String) -> Result<u32, String> {
+///     // Real implementation
+/// }
+///
+/// price_api::setup(|_| Ok(1000));
+/// ```
+///
+/// # Shared mocks
+///
+/// Mocks are thread local, so calls from threads spawned by the code under test don't reach the mock
+/// set up by the test. With `shared`, the mock is stored in a `Mutex`-protected static instead,
+/// so the worker threads call it and its calls can be asserted from any thread:
+///
+/// ```ignore
+/// #[mock_function(shared)]
+/// pub(crate) fn upload_chunk(file: String, index: usize) -> Result<(), String> {
+///     // Real implementation
+/// }
+///
+/// upload_chunk_mock::setup(|_| Ok(()));
+/// upload_file("video.mp4", 4); // uploads every chunk on its own thread
+/// upload_chunk_mock::assert_times(4);
+/// ```
+///
+/// The implementation and the recorded parameters must be `Send`, and tests using the same shared mock
+/// mustn't run in parallel. Shared mocks only provide `setup`, `clear`, `is_set`, `call`, `calls`,
+/// `assert_times` and `assert_with`, and aren't cleared by `fnmock::registry`.
+/// Generic functions can't be shared, and `shared` can't be combined with `forward` or `interpose`.
+///
+/// Shared mocks of async functions also work with multi-threaded executors like
+/// `#[tokio::test(flavor = "multi_thread")]`, where the test future may continue on another thread after an `.await`.
+///
+/// # Task local mocks
+///
+/// With `storage = task_local` (requires the `task-local` feature of fnmock), the mock is stored in a tokio
+/// task local instead. Every `scope()` gets its own mock, so tests using it can run in parallel,
+/// and `propagate()` passes the mock on to spawned tasks:
+///
+/// ```ignore
+/// #[mock_function(storage = task_local)]
+/// pub(crate) async fn fetch_rate(currency: String) -> Result<f64, String> {
+///     // Real implementation
+/// }
+///
+/// fetch_rate_mock::scope(async {
+///     fetch_rate_mock::setup(|_| Ok(2.0));
+///     tokio::spawn(fetch_rate_mock::propagate(convert(10.0, "USD"))).await.unwrap();
+///     fetch_rate_mock::assert_times(1);
+/// }).await;
+/// ```
+///
+/// `shared` is the shorthand of `storage = global`, and `storage = thread_local` selects the default storage
+/// (see `fnmock::storage`).
+///
+/// Task local mocks provide the same proxy functions as shared mocks, plus `scope` and `propagate`.
+///
 /// # Requirements
 ///
-/// - Function must not have `self` parameters (standalone functions only)
+/// - Function must not have `self` parameters (standalone functions only, use `#[mock_impl]` for methods)
+/// - Associated functions can only be mocked inside a `#[mock_impl]` block
 /// - Not ignored function parameters must implement `Clone`, `Debug`, and `PartialEq` (for assertions)
-/// - Not ignored function parameters must be `'static` (no references allowed - use owned types like `String` instead of `&str`)
+/// - Not ignored function parameters must be `'static` (no references allowed - use owned types like `String` instead of `&str`,
+///   or record owned copies with `own`)
+/// - Generic functions must not have lifetime parameters
 ///
 /// # Example
 ///
@@ -101,12 +423,12 @@ use crate::use_statement_processor::process_use_statement;
 pub fn mock_function(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as syn::ItemFn);
     let args = if attr.is_empty() {
-        MockFunctionArgs { ignore: Vec::new() }
+        MockFunctionArgs::default()
     } else {
         parse_macro_input!(attr as MockFunctionArgs)
     };
 
-    match process_mock_function(input, args.ignore) {
+    match process_mock_function(input, args) {
         Ok(expanded) => TokenStream::from(expanded),
         Err(e) => e.to_compile_error().into(),
     }
@@ -121,10 +443,14 @@ pub fn mock_function(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// # Generated Fake Module Methods
 ///
-/// - `setup(fn)` - Sets a custom implementation for the fake
+/// - `setup(closure)` - Sets a closure, which may capture and mutate test state, as the implementation of the fake
+/// - `setup_fn(fn)` - Sets a function pointer as the implementation, which may call the faked function recursively
+/// - `setup_scoped(closure)` - Sets the implementation and returns a guard clearing the fake when dropped
+/// - `setup_with_delay(delay, closure)` - Sets the implementation and sleeps for the delay before returning
 /// - `clear()` - Resets the fake to its uninitialized state
 /// - `is_set()` - Checks if the fake has been configured
-/// - `get_implementation()` - Gets the current fake implementation
+/// - `call(params)` - Calls the current fake implementation
+/// - `times_called()` - Gets the number of calls of the fake implementation (only with `count_calls`)
 ///
 /// # Difference from Mocks
 ///
@@ -174,10 +500,15 @@ pub fn mock_function(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// between tests but **not thread-safe** if the same function is faked in parallel
 /// test threads.
 #[proc_macro_attribute]
-pub fn fake_function(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn fake_function(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as syn::ItemFn);
+    let args = if attr.is_empty() {
+        FakeFunctionArgs::default()
+    } else {
+        parse_macro_input!(attr as FakeFunctionArgs)
+    };
 
-    match process_fake_function(input) {
+    match process_fake_function(input, args) {
         Ok(expanded) => TokenStream::from(expanded),
         Err(e) => e.to_compile_error().into(),
     }
@@ -193,10 +524,69 @@ pub fn fake_function(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// # Generated Stub Module Methods
 ///
 /// - `setup(return_value)` - Sets the predetermined return value for the stub
+/// - `setup_scoped(return_value)` - Sets the return value and returns a guard clearing the stub when dropped
+/// - `setup_once(return_value)` - Sets a return value, which is moved out by the next call instead of being cloned
+/// - `setup_factory(factory)` - Sets a factory producing a fresh return value for every call
+/// - `setup_arbitrary()` / `setup_strategy(strategy)` - Returns values drawn from a proptest strategy (`proptest` feature)
+/// - `setup_ok(value)` / `setup_err(error)` - Sets the return value to `Ok(value)` / `Err(error)` (`Result` return types only)
 /// - `clear()` - Resets the stub to its uninitialized state
 /// - `is_set()` - Checks if the stub has been configured
 /// - `get_return_value()` - Gets the current stubbed return value
 ///
+/// # Keyed return values
+///
+/// With `keyed`, the stub additionally returns values set up for specific parameters,
+/// falling back to the value set up with `setup()`:
+///
+/// ```ignore
+/// #[stub_function(keyed)]
+/// pub(crate) fn get_user(id: u32) -> String {
+///     // Real implementation
+///     format!("user_{}", id)
+/// }
+///
+/// get_user_stub::setup_for(42, "alice".to_string());
+/// get_user_stub::setup_for(7, "bob".to_string());
+/// get_user_stub::setup("unknown".to_string());
+/// ```
+///
+/// Multiple parameters are passed to `setup_for` as a tuple. The parameters of keyed stubs
+/// must implement `Clone`, `Debug`, and `PartialEq` and must be `'static`.
+///
+/// # Default return values
+///
+/// With `default`, the stub returns `Default::default()` instead of executing the original
+/// function body if no return value is set up. The return type must implement `Default`:
+///
+/// ```ignore
+/// #[stub_function(default)]
+/// pub(crate) fn related_products(id: u32) -> Vec<String> {
+///     // Real implementation querying the recommendation service
+///     vec![format!("accessory_{}", id)]
+/// }
+///
+/// assert_eq!(related_products(1), Vec::<String>::new());
+/// ```
+///
+/// # Storage
+///
+/// Stubs are thread local by default. With `storage = global`, the stub is stored in a `Mutex`-protected
+/// static shared by all threads, and with `storage = task_local` (requires the `task-local` feature of fnmock)
+/// in a tokio task local, entered with `scope()` and passed on to spawned tasks with `propagate()`
+/// (see `fnmock::storage`):
+///
+/// ```ignore
+/// #[stub_function(storage = global)]
+/// pub(crate) fn region() -> String {
+///     // Real implementation
+/// }
+///
+/// region_stub::setup("us-east-2".to_string());
+/// service_endpoints(&["auth", "billing"]); // builds every endpoint on its own thread
+/// ```
+///
+/// The return value must be `Send`. Global and task local stubs can't be generic and aren't cleared by `fnmock::registry`.
+///
 /// # Difference from Mocks and Fakes
 ///
 /// Stubs - in contrast to mocks and fakes - provide canned responses without behavior verification or custom logic.
@@ -209,7 +599,8 @@ pub fn fake_function(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// # Requirements
 ///
 /// - Function must not have `self` parameters (standalone functions only)
-/// - Return type must implement `Clone` (since the stub may be called multiple times)
+/// - Return type must implement `Clone` to be set up with `setup()` (since the stub may be called multiple times),
+///   non-cloneable values can be set up with `setup_once()` or `setup_factory()`
 ///
 /// # Example
 ///
@@ -244,14 +635,650 @@ pub fn fake_function(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// ```
 /// # Note
 ///
-/// The stub module uses thread-local storage, so stubs are isolated
+/// By default, the stub module uses thread-local storage, so stubs are isolated
 /// between tests but **not thread-safe** if the same function is stubbed in parallel
 /// test threads.
 #[proc_macro_attribute]
-pub fn stub_function(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn stub_function(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::ItemFn);
+    let args = if attr.is_empty() {
+        StubFunctionArgs::default()
+    } else {
+        parse_macro_input!(attr as StubFunctionArgs)
+    };
+
+    match process_stub_function(input, args) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Attribute macro that generates a spy for a function.
+///
+/// A spy always runs the real implementation, but records the parameters and the return value
+/// of every call (in test mode). It fills the gap between mocks, which replace the behavior,
+/// and plain functions, which can't be observed. This macro generates:
+/// 1. The original function, recording its calls in test mode
+/// 2. A `<function_name>_spy` module containing inspection and assertion methods
+///
+/// # Generated Spy Module Methods
+///
+/// - `calls()` - Gets the parameters of all calls
+/// - `results()` - Gets the return values of all calls
+/// - `last_call_params()` - Gets the parameters of the most recent call
+/// - `times_called()` - Gets the number of calls
+/// - `call_durations()` - Gets the wall-clock durations of all calls
+/// - `assert_times(n)` - Asserts the function was called `n` times
+/// - `assert_with(params)` - Asserts the function was called with the parameters
+/// - `assert_returned(value)` - Asserts a call returned the value
+/// - `clear()` - Removes the recorded calls
+/// - `snapshot()` / `restore(snapshot)` - Saves and restores the recorded calls
+///
+/// # Requirements
+///
+/// - Function must not have `self` parameters and must not be generic
+/// - Parameters must be `'static` and implement `Clone`, `Debug` and `PartialEq`
+/// - Return type must be `'static` and implement `Clone`, `Debug` and `PartialEq`
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::spy_function;
+///
+/// #[spy_function]
+/// pub(crate) fn apply_discount(price: u32, percent: u32) -> u32 {
+///     price - price * percent / 100
+/// }
+///
+/// #[test]
+/// fn test_checkout_applies_discount() {
+///     assert_eq!(checkout(200), 180);
+///
+///     apply_discount_spy::assert_with(200, 10);
+///     apply_discount_spy::assert_returned(180);
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn spy_function(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::ItemFn);
+    let args = if attr.is_empty() {
+        SpyFunctionArgs::default()
+    } else {
+        parse_macro_input!(attr as SpyFunctionArgs)
+    };
+
+    match process_spy_function(input, args) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Attribute macro that replaces a function by a dummy in test builds.
+///
+/// In test mode, every call returns `Default::default()` without any setup and without executing
+/// the original function body. Use it to silence side-effectful functions (logging, metrics, ...)
+/// across the whole test suite. Outside of test mode, the function is unchanged.
+///
+/// # Arguments
+///
+/// - `returns = "..."` - An expression returned instead of `Default::default()`, e.g. `"Ok(())"`
+/// - `cfg = "..."` - The `cfg` predicate the dummy is compiled for, like for the other macros
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::dummy_function;
+///
+/// #[dummy_function]
+/// pub(crate) fn record_metric(name: &str, value: u64) {
+///     metrics_client::send(name, value);
+/// }
+///
+/// #[dummy_function(returns = "Ok(())")]
+/// pub(crate) fn write_audit_log(entry: &str) -> Result<(), std::io::Error> {
+///     std::fs::write("audit.log", entry)
+/// }
+/// ```
+///
+/// # Requirements
+///
+/// - Function must not have `self` parameters (standalone functions only)
+/// - Return type must implement `Default`, unless `returns` is set
+#[proc_macro_attribute]
+pub fn dummy_function(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::ItemFn);
+    let args = if attr.is_empty() {
+        DummyFunctionArgs::default()
+    } else {
+        parse_macro_input!(attr as DummyFunctionArgs)
+    };
+
+    match process_dummy_function(input, args) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Attribute macro that generates a dependency injection trait from the doubled functions of a module.
+///
+/// Bridges free functions and trait based dependency injection. Every function in the module annotated
+/// with `#[mock_function]`, `#[fake_function]` or `#[stub_function]` becomes a `&self` method of the trait.
+/// Next to the module, the macro generates:
+/// 1. The trait with the given name
+/// 2. A `Real<Trait>` struct implementing the trait by calling the functions
+/// 3. A `Test<Trait>` struct (test-only) implementing the trait by calling the doubles directly
+///
+/// In contrast to calling the functions, `Test<Trait>` panics if a double isn't set up,
+/// instead of falling back to the real implementation.
+///
+/// # Requirements
+///
+/// - The module must be inline (`mod users { ... }`)
+/// - The doubled functions must be visible to the parent module (e.g. `pub` or `pub(super)`)
+/// - The doubled functions must not be generic
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::{mock_function, stub_function, traitify};
+///
+/// #[traitify(UserRepository)]
+/// pub mod users {
+///     use super::*;
+///
+///     #[mock_function]
+///     pub fn fetch_user(id: u32) -> Result<String, String> {
+///         Ok(format!("user_{}", id))
+///     }
+///
+///     #[stub_function]
+///     pub fn count_users() -> usize {
+///         42
+///     }
+/// }
+///
+/// pub fn greet(repository: &impl UserRepository, id: u32) -> String {
+///     format!("Hello {}", repository.fetch_user(id).unwrap())
+/// }
+///
+/// greet(&RealUserRepository, 1);
+///
+/// #[test]
+/// fn test_greet() {
+///     users::fetch_user_mock::setup(|_| Ok("alice".to_string()));
+///
+///     assert_eq!(greet(&TestUserRepository, 1), "Hello alice");
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn traitify(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let module = parse_macro_input!(item as syn::ItemMod);
+    let trait_name = parse_macro_input!(attr as syn::Ident);
+
+    match process_traitify(trait_name, module) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Attribute macro that generates a mock implementation of a trait.
+///
+/// For code already using trait based dependency injection. Next to the trait, the macro generates (test-only):
+/// 1. A `Mock<Trait>` struct implementing the trait
+/// 2. A `mock_<trait>` module with a `<method>_mock` module per method, containing the same
+///    proxy functions as the mock module of `#[mock_function]` (`setup()`, `assert_with()`, ...)
+///
+/// The methods of `Mock<Trait>` ignore the receiver, so all instances share the mocks of the current thread.
+/// Calling a method without setting up its mock panics.
+///
+/// # Requirements
+///
+/// - The trait must not be generic and must only contain methods
+/// - Every method must have a `self` receiver and must not be generic
+/// - The parameters must meet the requirements of `#[mock_function]`
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::mock_trait;
+///
+/// #[mock_trait]
+/// pub trait UserRepository {
+///     fn fetch_user(&self, id: u32) -> Result<String, String>;
+/// }
+///
+/// pub fn greet(repository: &impl UserRepository, id: u32) -> String {
+///     format!("Hello {}", repository.fetch_user(id).unwrap())
+/// }
+///
+/// #[test]
+/// fn test_greet() {
+///     mock_user_repository::fetch_user_mock::setup(|_| Ok("alice".to_string()));
+///
+///     assert_eq!(greet(&MockUserRepository, 1), "Hello alice");
+///
+///     mock_user_repository::fetch_user_mock::assert_with(1);
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn mock_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "mock_trait doesn't take arguments"
+        ).to_compile_error().into();
+    }
+    let input = parse_macro_input!(item as syn::ItemTrait);
+
+    match process_mock_trait(input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Attribute macro that makes the methods of an inherent impl block mockable.
+///
+/// Every method with a `self` receiver is mocked like a function annotated with `#[mock_function]`:
+/// in test mode, it calls the mock if it is set up and executes the original body otherwise.
+/// Next to the impl block, a `<Type>_<method>_mock` module (test-only) is generated per method,
+/// containing the same proxy functions as the mock module of `#[mock_function]`.
+///
+/// The mocks are type-level: the receiver isn't recorded and all instances of the type share
+/// the mocks of the current thread.
+///
+/// Associated functions without a receiver, like constructors, are only mocked if they are annotated
+/// with `#[mock_function]`, generating a `<Type>_<function>_mock` module as well. Inside the impl block,
+/// `#[mock_function]` also customizes methods with the `ignore`, `own`, `cfg`, `vis` and `name` arguments.
+///
+/// # Requirements
+///
+/// - The impl block must not implement a trait (use `#[mock_trait]` on the trait instead) and must not be generic
+/// - The methods must not be generic
+/// - The parameters must meet the requirements of `#[mock_function]`
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::mock_impl;
+///
+/// pub struct Client {
+///     url: String,
+/// }
+///
+/// #[mock_impl]
+/// impl Client {
+///     #[mock_function(own = [url])]
+///     pub fn connect(url: &str) -> Client {
+///         Client { url: url.to_string() }
+///     }
+///
+///     pub fn get(&self, path: String) -> Result<String, String> {
+///         http::get(&format!("{}/{}", self.url, path))
+///     }
+/// }
+///
+/// #[test]
+/// fn test_get() {
+///     Client_get_mock::setup(|path| Ok(format!("mocked {}", path)));
+///
+///     let client = Client::connect("localhost");
+///     assert_eq!(client.get("users".to_string()), Ok("mocked users".to_string()));
+///
+///     Client_connect_mock::assert_with("localhost".to_string());
+///     Client_get_mock::assert_with("users".to_string());
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn mock_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "mock_impl doesn't take arguments"
+        ).to_compile_error().into();
+    }
+    let input = parse_macro_input!(item as syn::ItemImpl);
+
+    match process_mock_impl(input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Attribute macro that mocks every function of a module.
+///
+/// Applies `#[mock_function]` to every free function of an inline module, so wrapper modules around
+/// external services don't need an attribute on each function. The attribute takes the arguments of
+/// `#[mock_function]`, which are applied to every function (wildcards like `ignore = [*_pool]` don't have to match),
+/// and `skip = [...]` to leave functions untouched.
+///
+/// Functions annotated with a double macro are left to their own attribute, so a function's own
+/// `#[mock_function(...)]` replaces the arguments of the module, and `#[fake_function]` or `#[stub_function]`
+/// double it differently.
+///
+/// # Requirements
+///
+/// - The module must be inline (`mod weather { ... }`)
+/// - The functions must meet the requirements of `#[mock_function]`
+/// - `name` and interpose symbols can only be given per function
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::{mock_function, mock_module};
+///
+/// #[mock_module(ignore = [*_client], skip = [base_url])]
+/// pub mod weather {
+///     pub fn temperature(http_client: &Client, city: String) -> Result<f32, String> {
+///         // Real implementation
+///     }
+///
+///     #[mock_function(named_params)]
+///     pub fn forecast(city: String, days: u8) -> Result<Vec<f32>, String> {
+///         // Real implementation
+///     }
+///
+///     fn base_url() -> &'static str {
+///         "https://weather.example.com"
+///     }
+/// }
+///
+/// #[test]
+/// fn test_temperature() {
+///     weather::temperature_mock::setup(|_| Ok(21.5));
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn mock_module(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let module = parse_macro_input!(item as syn::ItemMod);
+    let args = if attr.is_empty() {
+        MockModuleArgs::default()
+    } else {
+        parse_macro_input!(attr as MockModuleArgs)
+    };
+
+    match process_mock_module(args, module) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Attribute macro that clears all doubles used on the current thread around a test.
+///
+/// Parameterized tests (`test_case`, `rstest` cases, ...) reuse the same test body for every case,
+/// so a double set up by one case can leak into the next one if they run on the same thread.
+/// This macro inserts a `fnmock::registry::reset_doubles()` guard at the start of the function,
+/// which clears every registered double before the body runs and again when it returns or panics.
+///
+/// Place it below the parameterized test attribute, so it is applied to every generated case.
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::reset_doubles;
+/// use test_case::test_case;
+///
+/// #[test_case(1, "alice")]
+/// #[test_case(2, "bob")]
+/// #[reset_doubles]
+/// fn test_greet(id: u32, name: &str) {
+///     get_user_name_stub::setup(name.to_string());
+///
+///     assert_eq!(greet(id), format!("Hello {}", name));
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn reset_doubles(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "reset_doubles doesn't take arguments"
+        ).to_compile_error().into();
+    }
+    let input = parse_macro_input!(item as syn::ItemFn);
+
+    TokenStream::from(process_reset_doubles(input))
+}
+
+/// Attribute macro that turns a function into a test clearing all doubles and verifying expectations.
+///
+/// The function is marked with `#[test]` and a `fnmock::registry::test_scope()` guard is inserted
+/// at the start of its body. The guard clears every double used on the current thread before the body runs.
+/// When the body returns, the expectations registered with `expect_times` / `expect_with` are verified
+/// and every double is cleared again, so no `clear()` calls are needed at the end of the test.
+/// If the test panics, the doubles are still cleared, but the expectations aren't verified.
+///
+/// Async tests and parameterized tests keep their own test attribute (`#[tokio::test]`, `#[test_case]`, ...),
+/// which has to be placed above this one.
+///
+/// # Example
+///
+/// ```ignore
+/// #[fnmock::test]
+/// fn test_welcome_user() {
+///     send_email_mock::setup(|_| Ok(()));
+///     send_email_mock::expect_times(1);
+///
+///     welcome_user(42);
+///     // fails here if send_email wasn't called exactly once
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "test doesn't take arguments"
+        ).to_compile_error().into();
+    }
     let input = parse_macro_input!(item as syn::ItemFn);
 
-    match process_stub_function(input) {
+    TokenStream::from(process_test(input))
+}
+
+/// Function-like macro turning a call of a mocked function into a case of its mock.
+///
+/// The arguments are compared with `fnmock::matchers::eq_to`, `_` accepts every value of its parameter.
+/// The macro returns a `fnmock::when::When`, which sets up the case with one of its `then` functions:
+///
+/// - `then_return(value)` - Returns a clone of the value for matching calls
+/// - `then(fn)` - Runs the implementation for matching calls
+/// - `then_panic(message)` - Panics for matching calls
+///
+/// The case is set up with the `when()` proxy function of the mock module next to the called function
+/// (`fetch_user` -> `fetch_user_mock::when(...)`), which works like `setup_when`:
+/// cases are checked in the order they were set up, before the implementation set up with `setup()`.
+/// Generic mocks aren't supported, since their proxy functions need the type parameters.
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::when;
+///
+/// #[test]
+/// fn test_greet() {
+///     when!(fetch_user(42)).then_return(Ok("alice".to_string()));
+///     when!(fetch_user(_)).then_return(Err("not found".to_string()));
+///
+///     assert_eq!(greet(42), "Hello alice");
+///     assert_eq!(greet(7), "Hello stranger");
+/// }
+/// ```
+#[proc_macro]
+pub fn when(input: TokenStream) -> TokenStream {
+    let call = parse_macro_input!(input as syn::Expr);
+
+    match process_when(call) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Function-like macro asserting a call of a mock, with `_` accepting every value of its parameter.
+///
+/// The first argument is the mock module, followed by one argument per non-ignored parameter.
+/// The arguments are compared with `fnmock::matchers::eq_to`, so no dummy values are needed for the
+/// parameters the test doesn't care about. The macro calls the `assert_with_matchers()` proxy function
+/// of the mock module, so it passes if at least one recorded call matches.
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::assert_with;
+///
+/// #[test]
+/// fn test_welcome_user() {
+///     send_email_mock::setup(|_| Ok(()));
+///
+///     welcome_user(42);
+///
+///     assert_with!(send_email_mock, "bob@example.com", _);
+/// }
+/// ```
+#[proc_macro]
+pub fn assert_with(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as AssertWithInput);
+
+    TokenStream::from(process_assert_with(input))
+}
+
+/// Attribute macro that imports the mock modules of the imported functions next to them in test builds.
+///
+/// The mocked functions check their mocks themselves, so the use statement is kept as it is.
+/// Additionally, the `<function_name>_mock` module of every imported function is imported in test builds,
+/// so the tests of the importing module (`use super::*`) can configure the mocks without naming their paths.
+///
+/// Supported forms are single imports, groups (`use service::{fetch_user, fetch_notes};`), nested groups
+/// and renamed imports, whose mock module is renamed as well (`use service::fetch_user as fetch;`
+/// imports `fetch_mock`). Mock modules generated with `name = "..."` are imported by that name,
+/// given as an argument per function (`#[use_function_mock(fetch_price(name = "price_api"))]`).
+/// Glob imports aren't supported, since the imported functions aren't known:
+///
+/// ```compile_fail
+/// mod service {
+///     pub fn fetch_user(id: u32) -> String {
+///         format!("user_{}", id)
+///     }
+/// }
+///
+/// #[fnmock_derive::use_function_mock]
+/// use service::*;
+///
+/// fn main() {
+///     fetch_user(1);
+/// }
+/// ```
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::use_function_mock;
+///
+/// #[use_function_mock]
+/// use crate::service::{fetch_user, fetch_notes as notes};
+///
+/// #[cfg(test)]
+/// mod tests {
+///     use super::*;
+///
+///     #[test]
+///     fn test_handle_user() {
+///         fetch_user_mock::setup(|_| Ok("alice".to_string()));
+///         notes_mock::setup(|_| Ok(Vec::new()));
+///         // ...
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn use_function_mock(attr: TokenStream, item: TokenStream) -> TokenStream {
+    process_use_attribute(attr, item, "_mock")
+}
+
+/// Attribute macro that imports the fake modules of the imported functions next to them in test builds.
+///
+/// Works like `use_function_mock` for the `<function_name>_fake` modules.
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::use_function_fake;
+///
+/// #[use_function_fake]
+/// use crate::db::save_user;
+///
+/// #[test]
+/// fn test_register() {
+///     save_user_fake::setup(|name| !name.is_empty());
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn use_function_fake(attr: TokenStream, item: TokenStream) -> TokenStream {
+    process_use_attribute(attr, item, "_fake")
+}
+
+/// Attribute macro that imports the stub modules of the imported functions next to them in test builds.
+///
+/// Works like `use_function_mock` for the `<function_name>_stub` modules.
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::use_function_stub;
+///
+/// #[use_function_stub]
+/// use crate::config::get_config;
+///
+/// #[test]
+/// fn test_banner() {
+///     get_config_stub::setup(Config::default());
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn use_function_stub(attr: TokenStream, item: TokenStream) -> TokenStream {
+    process_use_attribute(attr, item, "_stub")
+}
+
+/// Attribute macro that imports the double modules of functions with different double kinds in test builds.
+///
+/// Works like `use_function_mock`, but the kind of every function is given by the arguments,
+/// so a single use statement can import functions doubled in different ways.
+/// The kinds are `mock`, `fake`, `stub` and `spy`. Functions without a kind are imported without a double module.
+/// Double modules generated with `name = "..."` are imported by that name, e.g. `fetch_price = mock(name = "price_api")`.
+///
+/// # Example
+///
+/// ```ignore
+/// use fnmock::derive::use_doubles;
+///
+/// #[use_doubles(fetch_user = mock, get_config = stub)]
+/// use crate::service::{fetch_user, get_config};
+///
+/// #[test]
+/// fn test_handle_user() {
+///     fetch_user_mock::setup(|_| Ok("alice".to_string()));
+///     get_config_stub::setup(Config::default());
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn use_doubles(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as UseDoublesArgs);
+    let input = parse_macro_input!(item as syn::ItemUse);
+
+    match process_use_doubles(args, input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Expands a `use_function_*` attribute, importing the double modules with the suffix.
+fn process_use_attribute(attr: TokenStream, item: TokenStream, suffix: &str) -> TokenStream {
+    let args = parse_macro_input!(attr as UseFunctionArgs);
+    let input = parse_macro_input!(item as syn::ItemUse);
+
+    match process_use_statement(args, input, suffix) {
         Ok(expanded) => TokenStream::from(expanded),
         Err(e) => e.to_compile_error().into(),
     }
@@ -0,0 +1,63 @@
+//! Utilities for the **manifest** of doubled functions.
+//!
+//! With the `manifest` feature, every doubled function submits a `fnmock::manifest::ManifestEntry`,
+//! which is collected at link time and can be listed at runtime by tooling.
+
+use quote::quote;
+
+/// Creates the manifest entry of a doubled function.
+///
+/// The entry is only emitted if the `manifest` feature is enabled, since it requires the
+/// `inventory` dependency of fnmock. It isn't limited to test builds, so the manifest can also
+/// be inspected from the binary itself.
+///
+/// # Arguments
+///
+/// * `kind` - The variant of `fnmock::manifest::DoubleKind`, e.g. `Mock`
+/// * `signature` - The signature of the doubled function
+pub(crate) fn create_manifest_entry(kind: &str, signature: &syn::Signature) -> proc_macro2::TokenStream {
+    if !cfg!(feature = "manifest") {
+        return quote! {};
+    }
+
+    let kind = syn::Ident::new(kind, proc_macro2::Span::call_site());
+    let name = signature.ident.to_string();
+    let signature = format_signature(signature);
+
+    quote! {
+        fnmock::inventory::submit! {
+            fnmock::manifest::ManifestEntry {
+                name: #name,
+                kind: fnmock::manifest::DoubleKind::#kind,
+                module_path: module_path!(),
+                signature: #signature,
+            }
+        }
+    }
+}
+
+/// Converts a signature to a string close to how it's written in the source,
+/// e.g. `fn fetch_user(id: u32) -> Result<String, String>` instead of the spacing of the token stream.
+fn format_signature(signature: &syn::Signature) -> String {
+    let mut formatted = quote! { #signature }.to_string();
+    for (spaced, compact) in [
+        (" :: ", "::"),
+        (":: ", "::"),
+        (" (", "("),
+        ("( ", "("),
+        (" )", ")"),
+        (" ,", ","),
+        (" :", ":"),
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        ("& ", "&"),
+        (" ;", ";"),
+        (" [", "["),
+        ("[ ", "["),
+        (" ]", "]"),
+    ] {
+        formatted = formatted.replace(spaced, compact);
+    }
+    formatted
+}
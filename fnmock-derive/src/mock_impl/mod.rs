@@ -0,0 +1,261 @@
+use quote::quote;
+use syn::__private::TokenStream2;
+use crate::abi_utils::{fn_qualifiers, validate_preservable};
+use crate::attr_utils::{add_cfg_attrs, double_mod_name, get_cfg_attrs};
+use crate::function_mock::create_mock_implementation::{create_mock_function, create_mock_module};
+use crate::function_mock::{get_ignore_indices, get_param_wrappers};
+use crate::function_mock::mock_args::MockFunctionArgs;
+use crate::manifest_utils::create_manifest_entry;
+use crate::param_utils::{bind_param_patterns, create_cloned_tuple_from_param_names, create_owned_params, create_param_type, create_tuple_from_param_names, filter_params, get_impl_trait_indices, validate_static_params};
+use crate::return_utils::extract_return_type;
+use crate::cfg_utils::{doubles_cfg, doubles_vis};
+use crate::storage_utils::Storage;
+
+/// Processes an inherent impl block and makes its methods mockable.
+///
+/// This is the main entry point for the mock_impl attribute macro. Every method with a `self`
+/// receiver is regenerated with `create_mock_function`, so in test mode it calls the mock if
+/// it is set up and executes the original body otherwise. Next to the impl block, a
+/// `<Type>_<method>_mock` module (test-only) is generated per method with `create_mock_module`.
+///
+/// The mocks are type-level: the receiver isn't recorded and all instances of the type share
+/// the mocks of the current thread. Associated functions without a receiver (e.g. constructors)
+/// are only mocked if they are annotated with `mock_function`, which also takes the `ignore`, `own`, `no_debug`, `compare`,
+/// `project`, `no_history`, `cfg`, `vis` and `name` arguments for methods.
+///
+/// # Arguments
+///
+/// * `item_impl` - The impl block to make mockable
+///
+/// # Returns
+///
+/// - `Ok(TokenStream2)` - The impl block with mockable methods followed by the mock modules
+/// - `Err(syn::Error)` - If the impl block implements a trait or is generic, or a method can't be mocked
+///   (see `create_mocked_method`)
+pub(crate) fn process_mock_impl(mut item_impl: syn::ItemImpl) -> syn::Result<TokenStream2> {
+    if let Some((_, trait_path, _)) = &item_impl.trait_ {
+        return Err(syn::Error::new_spanned(
+            trait_path,
+            "mock_impl can only be used on inherent impl blocks, use mock_trait for traits"
+        ));
+    }
+    if !item_impl.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item_impl.generics,
+            "mock_impl can't be used on generic impl blocks"
+        ));
+    }
+    let type_name = get_type_name(&item_impl.self_ty)?;
+    let self_ty = (*item_impl.self_ty).clone();
+
+    let mut mock_modules = Vec::new();
+    let mut attr_paths = Vec::new();
+
+    for item in item_impl.items.iter_mut() {
+        let syn::ImplItem::Fn(method) = item else {
+            continue;
+        };
+        let args = take_mock_args(method)?;
+        // Associated functions are only mocked if annotated with `mock_function`
+        let args = match (args, method.sig.receiver()) {
+            (Some((args, attr_path)), _) => {
+                attr_paths.push(attr_path);
+                args
+            }
+            (None, Some(_)) => MockFunctionArgs::default(),
+            (None, None) => continue,
+        };
+
+        mock_modules.push(create_mocked_method(method, &type_name, &self_ty, args)?);
+    }
+
+    Ok(quote! {
+        #item_impl
+
+        #(#mock_modules)*
+
+        // The removed `mock_function` attributes are referenced, so their imports aren't reported as unused
+        #(
+            const _: () = {
+                #[allow(unused_imports)]
+                use #attr_paths as _;
+            };
+        )*
+    })
+}
+
+/// Makes a method or an associated function of an impl block mockable.
+///
+/// The function is replaced by its mockable version (see `create_mock_function`) and the mock module
+/// (see `create_mock_module`) is returned, which has to be placed next to the impl block.
+///
+/// # Arguments
+///
+/// * `method` - The method or associated function, replaced in place
+/// * `type_name` - The name of the type of the impl block, used for the module name
+/// * `self_ty` - The type of the impl block, replacing `Self` in the mock module
+/// * `args` - The arguments of the `mock_function` attribute of the function, if it has one
+///
+/// # Returns
+///
+/// - `Ok(TokenStream2)` - The mock module
+/// - `Err(syn::Error)` - If the function is generic, uses arguments not supported in impl blocks
+///   or has parameters that can't be recorded
+fn create_mocked_method(
+    method: &mut syn::ImplItemFn,
+    type_name: &syn::Ident,
+    self_ty: &syn::Type,
+    args: MockFunctionArgs,
+) -> syn::Result<TokenStream2> {
+    if !method.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &method.sig.generics,
+            "mock_impl does not support generic methods"
+        ));
+    }
+    if !args.forward.is_empty() || args.bounds.is_some() || args.interpose || args.storage() != Storage::ThreadLocal || args.named_params {
+        return Err(syn::Error::new_spanned(
+            &method.sig.ident,
+            "forward, bounds, interpose, shared, storage and named_params can't be used in impl blocks"
+        ));
+    }
+
+    validate_preservable(&method.sig, "mock_impl")?;
+    bind_param_patterns(&mut method.sig, &mut method.block);
+
+    let fn_name = method.sig.ident.clone();
+    let fn_asyncness = method.sig.asyncness;
+    let fn_qualifiers = fn_qualifiers(&method.sig);
+    let cfg_attrs = get_cfg_attrs(&method.attrs);
+    let mock_mod_name = double_mod_name(&syn::Ident::new(&format!("{}_{}", type_name, fn_name), fn_name.span()), args.name.as_ref(), "_mock");
+
+    // The receiver isn't recorded, so the mock only sees the other params.
+    // `Self` can't be named in the mock module, so it's replaced by the type.
+    let fn_inputs: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma> = method.sig.inputs.iter()
+        .filter(|arg| matches!(arg, syn::FnArg::Typed(_)))
+        .cloned()
+        .collect();
+    let mut ignore_indices = get_ignore_indices(&fn_inputs, &args.ignore)?;
+    for idx in get_impl_trait_indices(&fn_inputs) {
+        if !ignore_indices.contains(&idx) {
+            ignore_indices.push(idx);
+        }
+    }
+    let own_indices = get_ignore_indices(&fn_inputs, &args.own)?;
+    let owned_fn_inputs = create_owned_params(&fn_inputs, &own_indices)?;
+    let param_wrappers = get_param_wrappers(&fn_inputs, &args)?;
+    let owned_fn_inputs = param_wrappers.wrap_params(&owned_fn_inputs);
+    let recorded_inputs: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma> = syn::parse::Parser::parse2(
+        syn::punctuated::Punctuated::parse_terminated,
+        replace_self(quote! { #owned_fn_inputs }, self_ty),
+    )?;
+    validate_static_params(&recorded_inputs, &ignore_indices)?;
+
+    let params_type = create_param_type(&recorded_inputs, &ignore_indices);
+    let params_to_tuple = create_tuple_from_param_names(&fn_inputs, &ignore_indices, &own_indices, &param_wrappers);
+    let params_to_cloned_tuple = create_cloned_tuple_from_param_names(&fn_inputs, &ignore_indices, &own_indices, &param_wrappers);
+    let return_type = extract_return_type(&method.sig.output);
+    let return_type: syn::Type = syn::parse2(replace_self(quote! { #return_type }, self_ty))?;
+    let filtered_fn_inputs = filter_params(&recorded_inputs, &ignore_indices);
+
+    let doubles_cfg = args.cfg.clone().unwrap_or_else(doubles_cfg);
+    let module_vis = args.vis.clone().unwrap_or_else(doubles_vis);
+
+    let mock_method = create_mock_function(
+        &method.attrs,
+        fn_name,
+        method.vis.clone(),
+        fn_asyncness,
+        &fn_qualifiers,
+        syn::Generics::default(),
+        &syn::Generics::default(),
+        method.sig.inputs.clone(),
+        method.sig.output.clone(),
+        method.block.clone(),
+        mock_mod_name.clone(),
+        params_to_tuple.clone(),
+        params_to_cloned_tuple,
+        &[],
+        &doubles_cfg,
+    );
+    let manifest_entry = add_cfg_attrs(&cfg_attrs, create_manifest_entry("Mock", &method.sig));
+    *method = syn::parse2(mock_method)?;
+
+    let mock_module = create_mock_module(
+        mock_mod_name,
+        params_type,
+        None,
+        return_type,
+        &syn::Generics::default(),
+        &recorded_inputs,
+        &ignore_indices,
+        fn_asyncness,
+        params_to_tuple,
+        filtered_fn_inputs,
+        &[],
+        &module_vis,
+        args.no_history,
+    );
+
+    Ok(quote! {
+        #manifest_entry
+
+        #(#cfg_attrs)*
+        #[cfg(#doubles_cfg)]
+        #[allow(non_snake_case)]
+        #mock_module
+    })
+}
+
+/// Removes the `mock_function` attribute from a function of the impl block and parses its arguments.
+///
+/// The attribute can't be expanded on its own inside an impl block, since the mock module
+/// has to be placed next to the impl block.
+///
+/// # Returns
+///
+/// - `Ok(Some((MockFunctionArgs, Path)))` - If the function is annotated with `mock_function`, including the path of the attribute
+/// - `Ok(None)` - If the function isn't annotated
+/// - `Err(syn::Error)` - If the arguments of the attribute can't be parsed
+fn take_mock_args(method: &mut syn::ImplItemFn) -> syn::Result<Option<(MockFunctionArgs, syn::Path)>> {
+    let Some(position) = method.attrs.iter().position(|attr| {
+        attr.path().segments.last().is_some_and(|segment| segment.ident == "mock_function")
+    }) else {
+        return Ok(None);
+    };
+
+    let attr = method.attrs.remove(position);
+    let args = match &attr.meta {
+        syn::Meta::List(list) => syn::parse2(list.tokens.clone())?,
+        _ => MockFunctionArgs::default(),
+    };
+    Ok(Some((args, attr.path().clone())))
+}
+
+/// Gets the name of the type of an impl block, e.g. `Client` for `impl Client` or `impl crate::net::Client`.
+fn get_type_name(self_ty: &syn::Type) -> syn::Result<syn::Ident> {
+    if let syn::Type::Path(type_path) = self_ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return Ok(segment.ident.clone());
+        }
+    }
+    Err(syn::Error::new_spanned(
+        self_ty,
+        "mock_impl can only be used on impl blocks of named types"
+    ))
+}
+
+/// Replaces every `Self` in the tokens with the type of the impl block.
+fn replace_self(tokens: proc_macro2::TokenStream, self_ty: &syn::Type) -> proc_macro2::TokenStream {
+    tokens.into_iter()
+        .map(|token| match token {
+            proc_macro2::TokenTree::Ident(ident) if ident == "Self" => quote! { #self_ty },
+            proc_macro2::TokenTree::Group(group) => {
+                let mut replaced = proc_macro2::Group::new(group.delimiter(), replace_self(group.stream(), self_ty));
+                replaced.set_span(group.span());
+                quote! { #replaced }
+            }
+            token => quote! { #token },
+        })
+        .collect()
+}
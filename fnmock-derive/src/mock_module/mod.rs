@@ -0,0 +1,127 @@
+use quote::quote;
+use syn::__private::TokenStream2;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::Token;
+use crate::function_mock::mock_args::MockFunctionArgs;
+use crate::function_mock::process_mock_function;
+
+/// Structure to parse the mock_module attribute arguments
+///
+/// # Fields
+///
+/// - `skip` - the names of the functions, which aren't mocked
+/// - `function_args` - the `mock_function` arguments applied to every mocked function
+#[derive(Default)]
+pub(crate) struct MockModuleArgs {
+    pub(crate) skip: Vec<String>,
+    pub(crate) function_args: MockFunctionArgs,
+}
+
+impl Parse for MockModuleArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = MockModuleArgs::default();
+        let mut function_args = TokenStream2::new();
+
+        // Parse "skip = [...]", all other arguments are passed on to every mocked function
+        while !input.is_empty() {
+            if input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == "skip" {
+                input.parse::<syn::Ident>()?;
+                input.parse::<Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                let names: Punctuated<syn::Ident, Token![,]> = content.parse_terminated(syn::Ident::parse, Token![,])?;
+                args.skip.extend(names.into_iter().map(|id| id.to_string()));
+            } else {
+                while !input.is_empty() && !input.peek(Token![,]) {
+                    function_args.extend([input.parse::<proc_macro2::TokenTree>()?]);
+                }
+                function_args.extend(quote! { , });
+            }
+
+            // Allow trailing comma or end of input
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        args.function_args = syn::parse2(function_args)?;
+        if let Some(name) = &args.function_args.name {
+            return Err(syn::Error::new_spanned(
+                name,
+                "name can't be used on modules, annotate the function with mock_function instead"
+            ));
+        }
+        if let Some(symbol) = &args.function_args.interpose_symbol {
+            return Err(syn::Error::new_spanned(
+                symbol,
+                "interpose symbols can't be used on modules, annotate the function with mock_function instead"
+            ));
+        }
+
+        Ok(args)
+    }
+}
+
+/// Processes a module and mocks every function inside it.
+///
+/// This is the main entry point for the mock_module attribute macro. Every free function of the
+/// inline module is processed with `process_mock_function` and the arguments of the module, so
+/// wrapper modules around external services don't need an attribute per function.
+///
+/// Functions annotated with a double macro (`mock_function`, `fake_function`, ...) are left to
+/// their own attribute, so `#[mock_function(...)]` replaces the arguments of the module for a function.
+/// Functions listed in `skip` and items other than functions are left untouched.
+///
+/// # Arguments
+///
+/// * `args` - The arguments of the attribute
+/// * `module` - The module containing the functions
+///
+/// # Returns
+///
+/// - `Ok(TokenStream2)` - The module with the mocked functions and their mock modules
+/// - `Err(syn::Error)` - If the module isn't inline, a skipped function doesn't exist, or a function can't be mocked
+pub(crate) fn process_mock_module(args: MockModuleArgs, mut module: syn::ItemMod) -> syn::Result<TokenStream2> {
+    let Some((_, items)) = &mut module.content else {
+        return Err(syn::Error::new_spanned(
+            &module,
+            "mock_module can only be used on inline modules"
+        ));
+    };
+
+    for name in &args.skip {
+        let exists = items.iter().any(|item| matches!(item, syn::Item::Fn(function) if function.sig.ident == name));
+        if !exists {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!("Function '{}' not found in module", name)
+            ));
+        }
+    }
+
+    for item in items.iter_mut() {
+        let syn::Item::Fn(function) = item else {
+            continue;
+        };
+        if is_doubled(function) || args.skip.iter().any(|name| function.sig.ident == name) {
+            continue;
+        }
+
+        let mocked = process_mock_function(function.clone(), args.function_args.clone())?;
+        *item = syn::Item::Verbatim(mocked);
+    }
+
+    Ok(quote! { #module })
+}
+
+/// Checks if a function is annotated with a double macro, which generates its double itself.
+fn is_doubled(function: &syn::ItemFn) -> bool {
+    function.attrs.iter().any(|attr| {
+        attr.path().segments.last().is_some_and(|segment| {
+            ["mock_function", "fake_function", "stub_function", "spy_function", "dummy_function"]
+                .iter()
+                .any(|double| segment.ident == double)
+        })
+    })
+}
@@ -0,0 +1,164 @@
+use quote::quote;
+use syn::__private::TokenStream2;
+use crate::attr_utils::get_cfg_attrs;
+use crate::function_mock::mock_args::MockFunctionArgs;
+use crate::function_mock::process_mock_function;
+use crate::param_utils::{create_tuple_from_param_names, get_param_names, ParamWrappers};
+use crate::cfg_utils::{doubles_cfg, doubles_vis};
+
+/// Processes a trait definition and generates a mock implementation of it.
+///
+/// This is the main entry point for the mock_trait attribute macro. It takes a trait
+/// definition and generates next to it:
+/// 1. A `Mock<Trait>` struct (test-only) implementing the trait
+/// 2. A `mock_<trait>` module (test-only) containing one mocked function per method.
+///    Each function is generated with `process_mock_function`, so every method gets
+///    a `<method>_mock` module with the same proxy functions as a mocked free function.
+///
+/// The methods of the mock struct ignore the receiver and call the mocked function, which panics
+/// if its mock isn't set up, since a trait method has no original implementation to fall back to.
+///
+/// # Arguments
+///
+/// * `mock_trait` - The trait to create a mock for
+///
+/// # Returns
+///
+/// - `Ok(TokenStream2)` - The unchanged trait followed by the mock struct and the mock module
+/// - `Err(syn::Error)` - If the trait is generic, has items other than methods, or a method
+///   has no receiver, is generic or can't be mocked (see `process_mock_function`)
+pub(crate) fn process_mock_trait(mock_trait: syn::ItemTrait) -> syn::Result<TokenStream2> {
+    if !mock_trait.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &mock_trait.generics,
+            "mock_trait can't be used on generic traits"
+        ));
+    }
+
+    let trait_name = &mock_trait.ident;
+    let visibility = &mock_trait.vis;
+    let mock_name = syn::Ident::new(&format!("Mock{}", trait_name), trait_name.span());
+    let mock_mod_name = syn::Ident::new(&format!("mock_{}", to_snake_case(&trait_name.to_string())), trait_name.span());
+
+    let mut mock_methods = Vec::new();
+    let mut mock_functions = Vec::new();
+
+    for item in &mock_trait.items {
+        let syn::TraitItem::Fn(method) = item else {
+            return Err(syn::Error::new_spanned(
+                item,
+                "mock_trait only supports traits containing methods"
+            ));
+        };
+        if !method.sig.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &method.sig.generics,
+                "mock_trait does not support generic methods"
+            ));
+        }
+        if method.sig.receiver().is_none() {
+            return Err(syn::Error::new_spanned(
+                &method.sig,
+                "mock_trait only supports methods with a self receiver"
+            ));
+        }
+
+        let fn_asyncness = &method.sig.asyncness;
+        let fn_name = &method.sig.ident;
+        let fn_output = &method.sig.output;
+        let fn_inputs = create_named_params(&method.sig.inputs);
+        let param_names = get_param_names(&fn_inputs);
+        let receiver = method.sig.inputs.first();
+        // Methods of cfg gated trait methods only exist where the method exists
+        let cfg_attrs = get_cfg_attrs(&method.attrs);
+
+        // The mocked function has no original implementation, so it calls the mock unconditionally
+        let mock_fn_mod_name = syn::Ident::new(&format!("{}_mock", fn_name), fn_name.span());
+        let params_to_tuple = create_tuple_from_param_names(&fn_inputs, &[], &[], &ParamWrappers::default());
+        let mock_function: syn::ItemFn = syn::parse_quote! {
+            #(#cfg_attrs)*
+            pub(super) #fn_asyncness fn #fn_name(#fn_inputs) #fn_output {
+                #mock_fn_mod_name::call(#params_to_tuple)
+            }
+        };
+        mock_functions.push(process_mock_function(mock_function, MockFunctionArgs::default())?);
+
+        let await_mock = fn_asyncness.map(|_| quote! { .await });
+        mock_methods.push(quote! {
+            #(#cfg_attrs)*
+            #fn_asyncness fn #fn_name(#receiver, #fn_inputs) #fn_output {
+                #mock_mod_name::#fn_name(#(#param_names),*) #await_mock
+            }
+        });
+    }
+
+    let doubles_cfg = doubles_cfg();
+    let module_vis = doubles_vis();
+    let mock_doc = format!(
+        "Mock implementation of `{}` - set up the methods with the mocks in `{}`",
+        trait_name, mock_mod_name
+    );
+    let mock_mod_doc = format!("The mocked methods of `{}`", mock_name);
+
+    Ok(quote! {
+        #mock_trait
+
+        #[cfg(#doubles_cfg)]
+        #[doc = #mock_doc]
+        #visibility struct #mock_name;
+
+        #[cfg(#doubles_cfg)]
+        impl #trait_name for #mock_name {
+            #(#mock_methods)*
+        }
+
+        #[cfg(#doubles_cfg)]
+        #[doc = #mock_mod_doc]
+        #module_vis mod #mock_mod_name {
+            use super::*;
+
+            #(#mock_functions)*
+        }
+    })
+}
+
+/// Removes the receiver from the method parameters and names the parameters without an identifier pattern.
+///
+/// Trait methods without a body may use `_` as pattern, but the mocked function needs the names
+/// to record the parameters, so they are replaced with `arg<index>`. `mut` is removed as well,
+/// since the parameters are only passed on.
+fn create_named_params(
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>
+) -> syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma> {
+    fn_inputs.iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(pat_type) => Some(pat_type.clone()),
+            syn::FnArg::Receiver(_) => None,
+        })
+        .enumerate()
+        .map(|(i, mut pat_type)| {
+            let name = match pat_type.pat.as_ref() {
+                syn::Pat::Ident(pat_ident) => pat_ident.ident.clone(),
+                _ => syn::Ident::new(&format!("arg{}", i), proc_macro2::Span::call_site()),
+            };
+            pat_type.pat = Box::new(syn::parse_quote! { #name });
+            syn::FnArg::Typed(pat_type)
+        })
+        .collect()
+}
+
+/// Converts a trait name in UpperCamelCase to snake_case, e.g. `UserRepository` to `user_repository`.
+fn to_snake_case(name: &str) -> String {
+    let mut snake_case = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake_case.push('_');
+            }
+            snake_case.extend(c.to_lowercase());
+        } else {
+            snake_case.push(c);
+        }
+    }
+    snake_case
+}
@@ -72,8 +72,68 @@ pub(crate) fn create_param_type(fn_inputs: &Punctuated<FnArg, Comma>, ignore_ind
 pub(crate) fn get_param_names(fn_inputs: &Punctuated<FnArg, Comma>) -> Vec<&syn::Pat> {
     fn_inputs
         .iter()
-        .filter_map(|arg| match arg {
-            syn::FnArg::Typed(pat_type) => Some(&*pat_type.pat),
+        .map(|arg| match arg {
+            syn::FnArg::Typed(pat_type) => &*pat_type.pat,
+            syn::FnArg::Receiver(_) => panic!(
+                "mock_function/fake_function does not support methods with 'self' parameters"
+            ),
+        })
+        .collect()
+}
+
+/// Replaces the patterns of the parameters with plain bindings, which are destructured
+/// at the start of the body instead.
+///
+/// The generated code passes the parameters on by their names, so every parameter needs a plain one.
+/// Destructured and `_` parameters get synthetic bindings named like the parameters of the matchers (`param_<index>`),
+/// so they can be listed in `ignore` and the other arguments by that name. `mut` bindings keep their name.
+///
+/// # Examples
+///
+/// - `fn area((w, h): (u32, u32)) -> u32 { w * h }` → `fn area(param_0: (u32, u32)) -> u32 { let (w, h) = param_0; w * h }`
+/// - `fn len(Size { w, .. }: Size, x: u32)` → `fn len(param_0: Size, x: u32)` with `let Size { w, .. } = param_0;`
+/// - `fn f(mut x: u32, _: String)` → `fn f(x: u32, param_1: String)` with `let mut x = x;`
+pub(crate) fn bind_param_patterns(sig: &mut syn::Signature, block: &mut syn::Block) {
+    let mut bindings = Vec::new();
+    let typed_inputs = sig.inputs.iter_mut().filter_map(|arg| match arg {
+        FnArg::Typed(pat_type) => Some(pat_type),
+        FnArg::Receiver(_) => None,
+    });
+
+    for (idx, pat_type) in typed_inputs.enumerate() {
+        let name = match &*pat_type.pat {
+            syn::Pat::Ident(pat_ident) if pat_ident.mutability.is_none() && pat_ident.by_ref.is_none() && pat_ident.subpat.is_none() => continue,
+            syn::Pat::Ident(pat_ident) => pat_ident.ident.clone(),
+            _ => quote::format_ident!("param_{}", idx),
+        };
+        let pat = std::mem::replace(&mut *pat_type.pat, syn::parse_quote! { #name });
+        // `_` params are still dropped at the end of the function
+        if !matches!(pat, syn::Pat::Wild(_)) {
+            bindings.push(syn::parse_quote! { let #pat = #name; });
+        }
+    }
+
+    bindings.append(&mut block.stmts);
+    block.stmts = bindings;
+}
+
+/// Gets parameter types from function inputs.
+///
+/// Extracts just the parameter types without the patterns, e.g. to build a function pointer type
+/// taking the same arguments as the original function.
+///
+/// # Examples
+///
+/// - `fn foo(x: i32, y: &str)` → `[i32, &str]`
+///
+/// # Panics
+///
+/// Panics if the function has a `self` parameter, as methods cannot be mocked/faked.
+pub(crate) fn get_param_types(fn_inputs: &Punctuated<FnArg, Comma>) -> Vec<&Type> {
+    fn_inputs
+        .iter()
+        .map(|arg| match arg {
+            syn::FnArg::Typed(pat_type) => &*pat_type.ty,
             syn::FnArg::Receiver(_) => panic!(
                 "mock_function/fake_function does not support methods with 'self' parameters"
             ),
@@ -131,19 +191,28 @@ pub(crate) fn filter_params(fn_inputs: &Punctuated<FnArg, Comma>, ignore_indices
 /// - `fn foo(x: i32, y: String)` → `(x, y)`
 /// - `fn foo(x: i32, y: String, z: &str)` with `ignore_indices = [2]` → `(x, y)`
 /// - `fn foo(x: i32, y: &str)` with `ignore_indices = [1]` → `x`
+/// - `fn foo(x: i32, y: &str)` with `own_indices = [1]` → `(x, y.to_owned())`
+/// - `fn foo(x: i32, conn: Conn)` with `conn` listed in `wrappers.no_debug` → `(x, fnmock::no_debug::NoDebug(conn))`
 ///
 /// # Panics
 ///
 /// Panics if the function has a `self` parameter, as methods cannot be mocked.
-pub(crate) fn create_tuple_from_param_names(fn_inputs: &Punctuated<FnArg, Comma>, ignore_indices: &[usize]) -> proc_macro2::TokenStream {
+pub(crate) fn create_tuple_from_param_names(
+    fn_inputs: &Punctuated<FnArg, Comma>,
+    ignore_indices: &[usize],
+    own_indices: &[usize],
+    wrappers: &ParamWrappers,
+) -> proc_macro2::TokenStream {
     let param_names: Vec<_> = get_param_names(fn_inputs)
         .into_iter()
         .enumerate()
         .filter_map(|(idx, name)| {
             if ignore_indices.contains(&idx) {
                 None
+            } else if own_indices.contains(&idx) {
+                Some(wrappers.wrap_expr(quote! { #name.to_owned() }, idx))
             } else {
-                Some(name)
+                Some(wrappers.wrap_expr(quote! { #name }, idx))
             }
         })
         .collect();
@@ -158,6 +227,315 @@ pub(crate) fn create_tuple_from_param_names(fn_inputs: &Punctuated<FnArg, Comma>
     }
 }
 
+/// Creates a tuple of clones of the parameter names (excluding ignored parameters).
+///
+/// Used where the parameters are still needed after being recorded, e.g. by the original implementation.
+///
+/// # Examples
+///
+/// - `fn foo(x: i32, y: String)` → `(x.clone(), y.clone())`
+/// - `fn foo(x: i32)` → `x.clone()`
+/// - `fn foo()` → `()`
+/// - `fn foo(x: &str)` with `own_indices = [0]` → `x.to_owned()`
+/// - `fn foo(conn: Conn)` with `conn` listed in `wrappers.no_debug` → `fnmock::no_debug::NoDebug(conn.clone())`
+pub(crate) fn create_cloned_tuple_from_param_names(
+    fn_inputs: &Punctuated<FnArg, Comma>,
+    ignore_indices: &[usize],
+    own_indices: &[usize],
+    wrappers: &ParamWrappers,
+) -> proc_macro2::TokenStream {
+    let clones: Vec<_> = get_param_names(fn_inputs)
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| !ignore_indices.contains(idx))
+        .map(|(idx, name)| if own_indices.contains(&idx) {
+            wrappers.wrap_expr(quote! { #name.to_owned() }, idx)
+        } else {
+            wrappers.wrap_expr(quote! { #name.clone() }, idx)
+        })
+        .collect();
+
+    if clones.len() == 1 {
+        let clone = &clones[0];
+        quote! { #clone }
+    } else {
+        quote! { (#(#clones),*) }
+    }
+}
+
+/// Creates a closure comparing a stored parameter tuple with the parameters of the current call.
+///
+/// The parameters are compared in place, so they don't have to be cloned into a tuple.
+///
+/// # Examples
+///
+/// - `fn foo(x: i32)` → `|key| *key == x`
+/// - `fn foo(x: i32, y: String)` → `|key| key.0 == x && key.1 == y`
+pub(crate) fn create_params_matcher(fn_inputs: &Punctuated<FnArg, Comma>) -> proc_macro2::TokenStream {
+    let param_names = get_param_names(fn_inputs);
+    let comparisons: Vec<_> = if param_names.len() == 1 {
+        param_names.iter().map(|name| quote! { *key == #name }).collect()
+    } else {
+        param_names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                let idx = syn::Index::from(idx);
+                quote! { key.#idx == #name }
+            })
+            .collect()
+    };
+
+    quote! { |key| #(#comparisons)&&* }
+}
+
+/// Creates the parameters of a function taking one matcher per parameter, and an expression
+/// combining them into a single matcher of the recorded parameters (`Params`).
+///
+/// Parameters are named like the original ones, so the generated signature stays readable.
+/// `params_type` names the type of the recorded parameters in the generated module.
+///
+/// # Examples
+///
+/// - `fn foo(x: i32)` → `x: Matcher<i32>`, combined to `x`
+/// - `fn foo(x: i32, y: String)` → `x: Matcher<i32>, y: Matcher<String>`,
+///   combined to a matcher checking `params.0` with `x` and `params.1` with `y`
+/// - `fn foo(x: i32)` with `named` → `x: Matcher<i32>`, combined to a matcher checking `params.x` with `x`
+pub(crate) fn create_params_matchers(
+    fn_inputs: &Punctuated<FnArg, Comma>,
+    params_type: &proc_macro2::TokenStream,
+    named: bool,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let matcher_names: Vec<syn::Ident> = get_param_names(fn_inputs)
+        .iter()
+        .enumerate()
+        .map(|(idx, pat)| match pat {
+            syn::Pat::Ident(pat_ident) => pat_ident.ident.clone(),
+            _ => quote::format_ident!("param_{}", idx),
+        })
+        .collect();
+    let param_types = get_param_types(fn_inputs);
+    let matcher_params = quote! { #(#matcher_names: fnmock::matchers::Matcher<#param_types>),* };
+
+    if matcher_names.len() == 1 && !named {
+        let matcher_name = &matcher_names[0];
+        return (matcher_params, quote! { #matcher_name });
+    }
+
+    // Params structs are accessed by the field names, tuples by the indices
+    let (accessors, description): (Vec<proc_macro2::TokenStream>, String) = if named {
+        let fields = matcher_names.iter().map(|name| format!("{}: {{:?}}", name)).collect::<Vec<_>>().join(", ");
+        (matcher_names.iter().map(|name| quote! { #name }).collect(), format!("{{{{ {} }}}}", fields))
+    } else {
+        (
+            (0..matcher_names.len()).map(syn::Index::from).map(|idx| quote! { #idx }).collect(),
+            format!("({})", vec!["{:?}"; matcher_names.len()].join(", ")),
+        )
+    };
+    let combined = quote! {
+        fnmock::matchers::Matcher::new(
+            format!(#description, #(#matcher_names),*),
+            move |params: &#params_type| #(#matcher_names.matches(&params.#accessors) &&)* true,
+        )
+    };
+    (matcher_params, combined)
+}
+
+/// Creates one matcher per argument of a call written in a test, e.g. by `when!` or `assert_with!`.
+///
+/// Arguments are compared with `fnmock::matchers::eq_to`, so they can be written like in the call
+/// of the function (e.g. a `&str` for a `String` parameter). `_` accepts every value.
+///
+/// # Examples
+///
+/// - `42` → `fnmock::matchers::eq_to(42)`
+/// - `_` → `fnmock::matchers::any()`
+pub(crate) fn create_argument_matchers<'a>(
+    args: impl IntoIterator<Item = &'a syn::Expr>,
+) -> Vec<proc_macro2::TokenStream> {
+    args.into_iter()
+        .map(|arg| match arg {
+            syn::Expr::Infer(_) => quote! { fnmock::matchers::any() },
+            arg => quote! { fnmock::matchers::eq_to(#arg) },
+        })
+        .collect()
+}
+
+/// Checks if a type can be named by a type alias in the generated module.
+///
+/// Types containing elided or non-'static lifetimes (e.g. `&str` or `Cow<'a, str>`) and
+/// `impl Trait` types can't be named outside of the function signature.
+///
+/// # Examples
+///
+/// - `(u32, String)` → `true`
+/// - `&'static str` → `true`
+/// - `&[u32]` → `false`
+/// - `impl AsRef<str>` → `false`
+pub(crate) fn is_nameable(ty: &Type) -> bool {
+    fn tokens_nameable(tokens: proc_macro2::TokenStream) -> bool {
+        let tokens: Vec<_> = tokens.into_iter().collect();
+        tokens.iter().enumerate().all(|(idx, token)| match token {
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '&' => {
+                // References need an explicit lifetime
+                matches!(tokens.get(idx + 1), Some(proc_macro2::TokenTree::Punct(next)) if next.as_char() == '\'')
+            }
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                // Only the 'static lifetime can be named outside of the function
+                matches!(tokens.get(idx + 1), Some(proc_macro2::TokenTree::Ident(lifetime)) if lifetime == "static")
+            }
+            proc_macro2::TokenTree::Ident(ident) => ident != "impl",
+            proc_macro2::TokenTree::Group(group) => tokens_nameable(group.stream()),
+            _ => true,
+        })
+    }
+
+    tokens_nameable(quote! { #ty })
+}
+
+/// Replaces the reference types of the parameters at `own_indices` with their owned types.
+///
+/// The owned types are recorded by the mock, while the function keeps the reference in its signature.
+/// `&str` and `&[T]` become `String` and `Vec<T>`, other references `<T as ToOwned>::Owned`.
+///
+/// # Examples
+///
+/// - `fn foo(x: &str, y: &[u8])` with `own_indices = [0, 1]` → `(x: String, y: Vec<u8>)`
+/// - `fn foo(path: &Path)` with `own_indices = [0]` → `(path: <Path as ToOwned>::Owned)`
+///
+/// # Returns
+///
+/// - `Ok(Punctuated)` - The parameters with the owned types
+/// - `Err(syn::Error)` - If a parameter at `own_indices` isn't a reference
+pub(crate) fn create_owned_params(fn_inputs: &Punctuated<FnArg, Comma>, own_indices: &[usize]) -> syn::Result<Punctuated<FnArg, Comma>> {
+    let mut fn_inputs = fn_inputs.clone();
+    for (idx, arg) in fn_inputs.iter_mut().enumerate() {
+        if !own_indices.contains(&idx) {
+            continue;
+        }
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let Type::Reference(reference) = pat_type.ty.as_ref() else {
+            return Err(syn::Error::new_spanned(
+                &pat_type.ty,
+                "own can only be used on reference parameters like &str or &[T]"
+            ));
+        };
+
+        let owned_type = match reference.elem.as_ref() {
+            Type::Path(path) if path.path.is_ident("str") => quote! { String },
+            Type::Slice(slice) => {
+                let elem = &slice.elem;
+                quote! { Vec<#elem> }
+            }
+            elem => quote! { <#elem as ::std::borrow::ToOwned>::Owned },
+        };
+        *pat_type.ty = syn::parse2(owned_type)?;
+    }
+    Ok(fn_inputs)
+}
+
+/// Wrappers of the recorded parameters, which lift requirements of the mock from the parameter types.
+///
+/// # Fields
+///
+/// - `no_debug` - the indices of the parameters recorded in `fnmock::no_debug::NoDebug`, which formats them without `Debug`
+/// - `compare` - the indices of the parameters recorded in `fnmock::compare::Compared`, which compares them
+///   with the closure instead of `PartialEq`
+/// - `project` - the parameters recorded in `fnmock::compare::Projected`, which compares and formats
+///   the value of the expression instead of the parameter
+#[derive(Default)]
+pub(crate) struct ParamWrappers {
+    pub(crate) no_debug: Vec<usize>,
+    pub(crate) compare: Vec<(usize, syn::Expr)>,
+    pub(crate) project: Vec<(usize, syn::Ident, syn::Expr)>,
+}
+
+impl ParamWrappers {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.no_debug.is_empty() && self.compare.is_empty() && self.project.is_empty()
+    }
+
+    /// Wraps the expression of the recorded parameter at `idx`.
+    ///
+    /// The comparison is wrapped first, so `NoDebug` doesn't require the compared type to implement `Debug`.
+    ///
+    /// # Examples
+    ///
+    /// - `conn` listed in `compare` → `fnmock::compare::Compared::new(conn, |a, b| a.url() == b.url())`
+    /// - `conn` listed in `project` → `fnmock::compare::Projected::new(conn, |conn: &_| conn.url())`
+    /// - `conn` listed in `no_debug` and `compare` → `fnmock::no_debug::NoDebug(fnmock::compare::Compared::new(conn, ...))`
+    pub(crate) fn wrap_expr(&self, expr: proc_macro2::TokenStream, idx: usize) -> proc_macro2::TokenStream {
+        let mut expr = expr;
+        if let Some((_, compare)) = self.compare.iter().find(|(compare_idx, _)| *compare_idx == idx) {
+            expr = quote! { fnmock::compare::Compared::new(#expr, #compare) };
+        }
+        if let Some((_, name, projection)) = self.project.iter().find(|(project_idx, _, _)| *project_idx == idx) {
+            expr = quote! { fnmock::compare::Projected::new(#expr, |#name: &_| #projection) };
+        }
+        if self.no_debug.contains(&idx) {
+            expr = quote! { fnmock::no_debug::NoDebug(#expr) };
+        }
+        expr
+    }
+
+    /// Wraps the types of the recorded parameters like `wrap_expr` wraps their values.
+    ///
+    /// Owned parameters have to be converted with `create_owned_params` first, so the owned type is wrapped.
+    ///
+    /// # Examples
+    ///
+    /// - `fn foo(x: i32, conn: Conn)` with `conn` listed in `no_debug` → `(x: i32, conn: fnmock::no_debug::NoDebug<Conn>)`
+    pub(crate) fn wrap_params(&self, fn_inputs: &Punctuated<FnArg, Comma>) -> Punctuated<FnArg, Comma> {
+        let mut fn_inputs = fn_inputs.clone();
+        for (idx, arg) in fn_inputs.iter_mut().enumerate() {
+            let FnArg::Typed(pat_type) = arg else {
+                continue;
+            };
+            let ty = &pat_type.ty;
+            let mut wrapped: Type = syn::parse_quote! { #ty };
+            if self.compare.iter().any(|(compare_idx, _)| *compare_idx == idx) {
+                wrapped = syn::parse_quote! { fnmock::compare::Compared<#wrapped> };
+            }
+            if self.project.iter().any(|(project_idx, _, _)| *project_idx == idx) {
+                wrapped = syn::parse_quote! { fnmock::compare::Projected<#wrapped> };
+            }
+            if self.no_debug.contains(&idx) {
+                wrapped = syn::parse_quote! { fnmock::no_debug::NoDebug<#wrapped> };
+            }
+            *pat_type.ty = wrapped;
+        }
+        fn_inputs
+    }
+}
+
+/// Returns the indices of the parameters with an `impl Trait` type.
+///
+/// Their types can't be named in the `Params` type of the mock, so they are ignored like the parameters
+/// listed in `ignore`. The signature of the function stays untouched.
+///
+/// # Examples
+///
+/// - `fn load(path: impl AsRef<Path>, retries: u32)` → `[0]`
+pub(crate) fn get_impl_trait_indices(fn_inputs: &Punctuated<FnArg, Comma>) -> Vec<usize> {
+    get_param_types(fn_inputs)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, ty)| contains_impl_trait(quote! { #ty }))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Checks if the tokens of a type contain an `impl Trait` type (e.g. `impl AsRef<str>` or `Option<impl Fn()>`).
+fn contains_impl_trait(tokens: proc_macro2::TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => ident == "impl",
+        proc_macro2::TokenTree::Group(group) => contains_impl_trait(group.stream()),
+        _ => false,
+    })
+}
+
 /// Checks if a type contains references (fails the 'static bound).
 ///
 /// Returns true if the type is a reference or contains references that would
@@ -165,7 +543,7 @@ pub(crate) fn create_tuple_from_param_names(fn_inputs: &Punctuated<FnArg, Comma>
 fn contains_reference(ty: &Type) -> bool {
     match ty {
         Type::Reference(_) => true,
-        Type::Tuple(tuple) => tuple.elems.iter().any(|t| contains_reference(t)),
+        Type::Tuple(tuple) => tuple.elems.iter().any(contains_reference),
         Type::Array(arr) => contains_reference(&arr.elem),
         Type::Slice(slice) => contains_reference(&slice.elem),
         Type::Paren(paren) => contains_reference(&paren.elem),
@@ -200,7 +578,8 @@ pub(crate) fn validate_static_params(fn_inputs: &Punctuated<FnArg, Comma>, ignor
                     "mock_function requires all non-ignored parameters to be 'static. \
                      Parameters cannot contain references. \
                      Consider using owned types like String instead of &str, \
-                     or Vec<T> instead of &[T], or mark the parameter with #[mock_function(ignore=[param])]."
+                     or Vec<T> instead of &[T], record an owned copy with #[mock_function(own=[param])] \
+                     or mark the parameter with #[mock_function(ignore=[param])]."
                 ));
             }
         }
@@ -0,0 +1,26 @@
+use quote::quote;
+use syn::__private::TokenStream2;
+
+/// Processes a test function and resets all registered doubles around its body.
+///
+/// This is the main entry point for the reset_doubles attribute macro. It inserts a
+/// `fnmock::registry::reset_doubles()` guard as the first statement of the function,
+/// so every double used on the current thread is cleared before the body runs and after it returns
+/// (or panics). All other attributes are kept, so the function can still be expanded into
+/// cases by parameterized test macros like `test_case` or `rstest`.
+///
+/// # Arguments
+///
+/// * `function` - The test function
+///
+/// # Returns
+///
+/// The function with the guard inserted
+pub(crate) fn process_reset_doubles(mut function: syn::ItemFn) -> TokenStream2 {
+    let guard: syn::Stmt = syn::parse_quote! {
+        let _fnmock_reset_guard = fnmock::registry::reset_doubles();
+    };
+    function.block.stmts.insert(0, guard);
+
+    quote! { #function }
+}
@@ -21,3 +21,41 @@ pub(crate) fn extract_return_type(return_type: &syn::ReturnType) -> syn::Type {
         syn::ReturnType::Type(_, ty) => (**ty).clone(),
     }
 }
+
+/// Extracts the success and the error type of a `Result` return type.
+///
+/// The `Result` is detected by the name of the last path segment, so `std::result::Result`
+/// is detected as well. Aliases with a fixed error type (e.g. `io::Result<T>`) aren't detected,
+/// since the error type can't be named by the macro.
+///
+/// # Returns
+///
+/// - `Some((T, E))` - For return types like `Result<T, E>`
+/// - `None` - For all other return types
+///
+/// # Examples
+///
+/// - `Result<String, Error>` → `Some((String, Error))`
+/// - `std::result::Result<(), String>` → `Some(((), String))`
+/// - `io::Result<String>` → `None`
+pub(crate) fn extract_result_types(return_type: &syn::Type) -> Option<(syn::Type, syn::Type)> {
+    let syn::Type::Path(type_path) = return_type else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if type_path.qself.is_some() || segment.ident != "Result" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let mut types = args.args.iter().filter_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    });
+
+    match (types.next(), types.next(), types.next()) {
+        (Some(ok_type), Some(err_type), None) => Some((ok_type, err_type)),
+        _ => None,
+    }
+}
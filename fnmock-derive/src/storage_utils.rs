@@ -0,0 +1,78 @@
+//! Utilities for the **storage backends** of the generated doubles (see `fnmock::storage`).
+
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::Token;
+
+/// Where the generated module stores its double, selected with `storage = ...`
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Storage {
+    /// Every thread has its own double (`storage = thread_local`, default)
+    #[default]
+    ThreadLocal,
+    /// One double shared by all threads (`storage = global`, or the `shared` shorthand of mocks)
+    Global,
+    /// Every tokio task running inside its own `scope()` has its own double (`storage = task_local`)
+    TaskLocal,
+}
+
+impl Storage {
+    /// The `fnmock::storage` backend accessing the double.
+    pub(crate) fn backend(&self) -> proc_macro2::TokenStream {
+        match self {
+            Storage::ThreadLocal => quote! { fnmock::storage::ThreadLocal },
+            Storage::Global => quote! { fnmock::storage::Global },
+            Storage::TaskLocal => quote! { fnmock::storage::TaskLocal },
+        }
+    }
+
+    /// The attribute argument selecting the backend, for error messages.
+    pub(crate) fn arg(&self) -> &'static str {
+        match self {
+            Storage::ThreadLocal => "storage = thread_local",
+            Storage::Global => "storage = global",
+            Storage::TaskLocal => "storage = task_local",
+        }
+    }
+}
+
+/// Parses the value of a `storage = ...` attribute argument.
+///
+/// # Arguments
+///
+/// * `input` - The attribute arguments, positioned after the `storage` key
+/// * `previous` - The storage selected by a previous argument, which mustn't be set
+///
+/// # Returns
+///
+/// - `Ok(Storage)` - The selected backend
+/// - `Err(syn::Error)` - If the value isn't `thread_local`, `global` or `task_local`, or the storage was already selected
+pub(crate) fn parse_storage_arg(input: ParseStream, previous: Option<Storage>) -> syn::Result<Storage> {
+    input.parse::<Token![=]>()?;
+    let storage: syn::Ident = input.parse()?;
+    let selected = if storage == "thread_local" {
+        Storage::ThreadLocal
+    } else if storage == "global" {
+        Storage::Global
+    } else if storage == "task_local" {
+        Storage::TaskLocal
+    } else {
+        return Err(syn::Error::new_spanned(
+            &storage,
+            format!("Unknown storage '{}'. Expected 'thread_local', 'global' or 'task_local'", storage)
+        ));
+    };
+    check_storage_unset(&storage, previous)?;
+    Ok(selected)
+}
+
+/// Fails if the storage was already selected, e.g. by `shared` and `storage = task_local`.
+pub(crate) fn check_storage_unset(key: &syn::Ident, previous: Option<Storage>) -> syn::Result<()> {
+    match previous {
+        Some(previous) => Err(syn::Error::new_spanned(
+            key,
+            format!("The storage is already selected ({}), it can only be selected once", previous.arg())
+        )),
+        None => Ok(()),
+    }
+}
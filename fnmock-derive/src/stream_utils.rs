@@ -0,0 +1,133 @@
+//! Utilities for doubles of functions returning **`impl Stream`**.
+//!
+//! `impl Stream` can't be named in the function pointer types and storages of the doubles,
+//! so in test builds the streams are boxed - the one of the original implementation as well,
+//! since every return of the function needs the same type.
+
+use quote::quote;
+
+/// Gets the item type of an `impl Stream<Item = T>` return type and whether the stream has to be `Send`.
+///
+/// # Examples
+///
+/// - `impl Stream<Item = u32>` → `Some((u32, false))`
+/// - `impl futures::Stream<Item = u32> + Send` → `Some((u32, true))`
+/// - `Vec<u32>` → `None`
+fn get_stream_item(return_type: &syn::Type) -> Option<(&syn::Type, bool)> {
+    let syn::Type::ImplTrait(impl_trait) = return_type else {
+        return None;
+    };
+
+    let mut item = None;
+    let mut send = false;
+    for bound in &impl_trait.bounds {
+        let syn::TypeParamBound::Trait(trait_bound) = bound else {
+            continue;
+        };
+        let Some(segment) = trait_bound.path.segments.last() else {
+            continue;
+        };
+        if segment.ident == "Send" {
+            send = true;
+        }
+        if segment.ident != "Stream" {
+            continue;
+        }
+        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+            item = args.args.iter().find_map(|arg| match arg {
+                syn::GenericArgument::AssocType(assoc) if assoc.ident == "Item" => Some(&assoc.ty),
+                _ => None,
+            });
+        }
+    }
+
+    item.map(|item| (item, send))
+}
+
+/// Gets the boxed stream type used by the doubles instead of an `impl Stream<Item = T>` return type.
+///
+/// # Examples
+///
+/// - `impl Stream<Item = u32>` → `Some(fnmock::stream::LocalBoxStream<u32>)`
+/// - `impl Stream<Item = u32> + Send` → `Some(fnmock::stream::BoxStream<u32>)`
+/// - `Vec<u32>` → `None`
+pub(crate) fn get_boxed_stream_type(return_type: &syn::Type) -> Option<syn::Type> {
+    get_stream_item(return_type).map(|(item, send)| {
+        if send {
+            syn::parse_quote! { fnmock::stream::BoxStream<#item> }
+        } else {
+            syn::parse_quote! { fnmock::stream::LocalBoxStream<#item> }
+        }
+    })
+}
+
+/// Gets the type of the values stored by stubs instead of an `impl Stream<Item = T>` return type.
+///
+/// # Examples
+///
+/// - `impl Stream<Item = u32>` → `Some(fnmock::stream::StubStream<u32>)`
+/// - `Vec<u32>` → `None`
+pub(crate) fn get_stub_stream_type(return_type: &syn::Type) -> Option<syn::Type> {
+    get_stream_item(return_type).map(|(item, _)| syn::parse_quote! { fnmock::stream::StubStream<#item> })
+}
+
+/// Generates a function returning `impl Stream`, which boxes its streams in test builds.
+///
+/// Outside of test builds, the original function is kept as is. In test builds, `double_check`
+/// returns the boxed stream of the double if it is set, otherwise the stream of the original
+/// implementation is boxed.
+///
+/// # Arguments
+///
+/// * `fn_attrs` - The attributes of the original function
+/// * `fn_name` - The name of the original function
+/// * `fn_visibility` - The visibility modifier of the function
+/// * `fn_asyncness` - Optional async keyword if the function is async
+/// * `fn_qualifiers` - The `unsafe` keyword and the ABI of the function
+/// * `fn_inputs` - The function parameters
+/// * `fn_output` - The `impl Stream` return type
+/// * `fn_block` - The original function body
+/// * `boxed_type` - The boxed stream type (see [`get_boxed_stream_type`])
+/// * `double_check` - The statements returning the stream of the double if it is set
+/// * `doubles_cfg` - The `cfg` predicate of the test builds (see `doubles_cfg`)
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_stream_function(
+    fn_attrs: &[syn::Attribute],
+    fn_name: &syn::Ident,
+    fn_visibility: &syn::Visibility,
+    fn_asyncness: Option<syn::token::Async>,
+    fn_qualifiers: &proc_macro2::TokenStream,
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+    fn_output: &syn::ReturnType,
+    fn_block: &syn::Block,
+    boxed_type: &syn::Type,
+    double_check: proc_macro2::TokenStream,
+    doubles_cfg: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let original_fn_stmts = &fn_block.stmts;
+
+    // The body runs in a closure (or an async block), so its `return`s don't leave the function unboxed
+    let original_stream = if fn_asyncness.is_some() {
+        quote! { async move { #(#original_fn_stmts)* }.await }
+    } else {
+        quote! { (move || { #(#original_fn_stmts)* })() }
+    };
+
+    quote! {
+        #(#fn_attrs)*
+        #[cfg(not(#doubles_cfg))]
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name(#fn_inputs) #fn_output {
+            #(#original_fn_stmts)*
+        }
+
+        #(#fn_attrs)*
+        #[cfg(#doubles_cfg)]
+        #[allow(unused_variables)]
+        #fn_visibility #fn_asyncness #fn_qualifiers fn #fn_name(#fn_inputs) #fn_output {
+            #double_check
+
+            let original_stream: #boxed_type = ::std::boxed::Box::pin(#original_stream);
+            original_stream
+        }
+    }
+}
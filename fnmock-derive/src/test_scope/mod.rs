@@ -0,0 +1,42 @@
+use quote::quote;
+use syn::__private::TokenStream2;
+
+/// Processes a test function and wraps its body in a test scope.
+///
+/// This is the main entry point for the test attribute macro. It inserts a
+/// `fnmock::registry::test_scope()` guard as the first statement of the function,
+/// so every double used on the current thread is cleared before the body runs, the pending
+/// expectations are verified when it returns and the doubles are cleared again afterward.
+///
+/// The function is marked with `#[test]`, unless another test attribute takes care of that:
+/// async functions need an executor attribute (e.g. `#[tokio::test]`) and functions with
+/// parameters a parameterized test attribute (e.g. `#[test_case]`), which add their own `#[test]`.
+///
+/// # Arguments
+///
+/// * `function` - The test function
+///
+/// # Returns
+///
+/// The function with the guard inserted
+pub(crate) fn process_test(mut function: syn::ItemFn) -> TokenStream2 {
+    let scope: syn::Stmt = syn::parse_quote! {
+        let _fnmock_test_scope = fnmock::registry::test_scope();
+    };
+    function.block.stmts.insert(0, scope);
+
+    if !is_marked_by_other_attribute(&function) {
+        function.attrs.insert(0, syn::parse_quote! { #[::core::prelude::v1::test] });
+    }
+
+    quote! { #function }
+}
+
+/// Checks whether the function is turned into a test by another attribute or can't be a plain `#[test]`.
+fn is_marked_by_other_attribute(function: &syn::ItemFn) -> bool {
+    let has_test_attribute = function.attrs.iter().any(|attr| {
+        attr.path().segments.last().is_some_and(|segment| segment.ident == "test")
+    });
+
+    has_test_attribute || function.sig.asyncness.is_some() || !function.sig.inputs.is_empty()
+}
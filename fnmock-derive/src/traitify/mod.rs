@@ -0,0 +1,250 @@
+use quote::quote;
+use syn::__private::TokenStream2;
+use crate::attr_utils::{double_mod_name, get_cfg_attrs};
+use crate::function_mock::{get_ignore_indices, get_param_wrappers};
+use crate::function_fake::fake_args::FakeFunctionArgs;
+use crate::function_mock::mock_args::MockFunctionArgs;
+use crate::function_mock::named_params::create_struct_from_param_names;
+use crate::function_stub::stub_args::StubFunctionArgs;
+use crate::param_utils::{bind_param_patterns, create_params_matcher, create_tuple_from_param_names, get_impl_trait_indices, get_param_names};
+use crate::cfg_utils::doubles_cfg;
+
+/// The kind of double a function in the traitified module is annotated with
+enum DoubleKind {
+    Mock(MockFunctionArgs),
+    Fake(FakeFunctionArgs),
+    Stub(StubFunctionArgs),
+}
+
+impl DoubleKind {
+    /// The custom `cfg` predicate of the double, if it was given one.
+    fn cfg(&self) -> Option<&TokenStream2> {
+        match self {
+            DoubleKind::Mock(args) => args.cfg.as_ref(),
+            DoubleKind::Fake(args) => args.cfg.as_ref(),
+            DoubleKind::Stub(args) => args.cfg.as_ref(),
+        }
+    }
+}
+
+/// Processes a module and generates a dependency injection trait for its doubled functions.
+///
+/// This is the main entry point for the traitify attribute macro. It takes an inline module
+/// and generates next to it:
+/// 1. A trait with one `&self` method per function annotated with `mock_function`,
+///    `fake_function` or `stub_function`
+/// 2. A `Real<Trait>` struct implementing the trait by calling the functions
+/// 3. A `Test<Trait>` struct (test-only) implementing the trait by calling the doubles directly,
+///    so it panics instead of falling back to the real implementation if a double isn't set up
+///
+/// # Arguments
+///
+/// * `trait_name` - The name of the generated trait
+/// * `module` - The module containing the doubled functions
+///
+/// # Returns
+///
+/// - `Ok(TokenStream2)` - The unchanged module followed by the trait and its implementations
+/// - `Err(syn::Error)` - If the module isn't inline or contains generic doubled functions
+///   or doubles with a custom `cfg` (the test implementation is gated on the default predicate)
+pub(crate) fn process_traitify(trait_name: syn::Ident, module: syn::ItemMod) -> syn::Result<TokenStream2> {
+    let Some((_, items)) = &module.content else {
+        return Err(syn::Error::new_spanned(
+            &module,
+            "traitify can only be used on inline modules"
+        ));
+    };
+
+    let mod_name = &module.ident;
+    let visibility = &module.vis;
+    let real_name = syn::Ident::new(&format!("Real{}", trait_name), trait_name.span());
+    let test_name = syn::Ident::new(&format!("Test{}", trait_name), trait_name.span());
+
+    let mut trait_methods = Vec::new();
+    let mut real_methods = Vec::new();
+    let mut test_methods = Vec::new();
+
+    for item in items {
+        let syn::Item::Fn(function) = item else {
+            continue;
+        };
+        let Some(kind) = get_double_kind(function)? else {
+            continue;
+        };
+        // The methods pass the params on like the doubled function, so destructured params need names
+        let mut function = function.clone();
+        bind_param_patterns(&mut function.sig, &mut function.block);
+
+        if !function.sig.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &function.sig.generics,
+                "traitify does not support generic functions"
+            ));
+        }
+        if let Some(cfg) = kind.cfg() {
+            return Err(syn::Error::new_spanned(
+                cfg,
+                "traitify does not support doubles with a custom cfg"
+            ));
+        }
+
+        let fn_asyncness = &function.sig.asyncness;
+        let fn_name = &function.sig.ident;
+        let fn_inputs = &function.sig.inputs;
+        let fn_output = &function.sig.output;
+        let param_names = get_param_names(fn_inputs);
+        // Methods of cfg gated functions only exist where the function exists
+        let cfg_attrs = get_cfg_attrs(&function.attrs);
+
+        let method_signature = quote! {
+            #fn_asyncness fn #fn_name(&self, #fn_inputs) #fn_output
+        };
+        let declaration_inputs = strip_param_modifiers(fn_inputs);
+        let await_real = fn_asyncness.map(|_| quote! { .await });
+        let test_body = create_test_body(&kind, mod_name, &function)?;
+
+        trait_methods.push(quote! { #(#cfg_attrs)* #fn_asyncness fn #fn_name(&self, #declaration_inputs) #fn_output; });
+        real_methods.push(quote! {
+            #(#cfg_attrs)*
+            #method_signature {
+                #mod_name::#fn_name(#(#param_names),*) #await_real
+            }
+        });
+        test_methods.push(quote! {
+            #(#cfg_attrs)*
+            #[allow(unused_variables)]
+            #method_signature {
+                #test_body
+            }
+        });
+    }
+
+    let doubles_cfg = doubles_cfg();
+    let trait_doc = format!("Dependency injection trait for the doubled functions of `{}`", mod_name);
+    let real_doc = format!("Implementation of `{}` calling the functions of `{}`", trait_name, mod_name);
+    let test_doc = format!(
+        "Implementation of `{}` calling the doubles of `{}` directly - panics if a double isn't set up",
+        trait_name, mod_name
+    );
+
+    Ok(quote! {
+        #module
+
+        #[doc = #trait_doc]
+        #[allow(async_fn_in_trait)]
+        #visibility trait #trait_name {
+            #(#trait_methods)*
+        }
+
+        #[doc = #real_doc]
+        #visibility struct #real_name;
+
+        impl #trait_name for #real_name {
+            #(#real_methods)*
+        }
+
+        #[cfg(#doubles_cfg)]
+        #[doc = #test_doc]
+        #visibility struct #test_name;
+
+        #[cfg(#doubles_cfg)]
+        impl #trait_name for #test_name {
+            #(#test_methods)*
+        }
+    })
+}
+
+/// Removes `mut` and `ref` from the parameters, since they aren't allowed in methods without a body.
+fn strip_param_modifiers(
+    fn_inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>
+) -> syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma> {
+    let mut fn_inputs = fn_inputs.clone();
+    for arg in fn_inputs.iter_mut() {
+        if let syn::FnArg::Typed(pat_type) = arg {
+            if let syn::Pat::Ident(pat_ident) = pat_type.pat.as_mut() {
+                pat_ident.mutability = None;
+                pat_ident.by_ref = None;
+            }
+        }
+    }
+    fn_inputs
+}
+
+/// Gets the kind of double from the attributes of a function.
+///
+/// # Returns
+///
+/// - `Ok(Some(DoubleKind))` - If the function is annotated with a double macro, including its arguments
+/// - `Ok(None)` - If the function isn't doubled
+/// - `Err(syn::Error)` - If the arguments of the double macro can't be parsed
+fn get_double_kind(function: &syn::ItemFn) -> syn::Result<Option<DoubleKind>> {
+    for attr in &function.attrs {
+        let Some(segment) = attr.path().segments.last() else {
+            continue;
+        };
+        let args = match &attr.meta {
+            syn::Meta::List(list) => list.tokens.clone(),
+            _ => proc_macro2::TokenStream::new(),
+        };
+
+        if segment.ident == "mock_function" {
+            return Ok(Some(DoubleKind::Mock(syn::parse2(args)?)));
+        } else if segment.ident == "fake_function" {
+            return Ok(Some(DoubleKind::Fake(syn::parse2(args)?)));
+        } else if segment.ident == "stub_function" {
+            return Ok(Some(DoubleKind::Stub(syn::parse2(args)?)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Generates the body of a test implementation method, which calls the proxy functions of the double.
+///
+/// # Examples
+///
+/// - mock: `users::fetch_user_mock::call(id)`
+/// - fake: `users::add_fake::call(a, b)`
+/// - stub: `users::get_config_stub::get_return_value()`
+fn create_test_body(kind: &DoubleKind, mod_name: &syn::Ident, function: &syn::ItemFn) -> syn::Result<TokenStream2> {
+    let fn_name = &function.sig.ident;
+    let fn_inputs = &function.sig.inputs;
+    let param_names = get_param_names(fn_inputs);
+
+    Ok(match kind {
+        DoubleKind::Mock(args) => {
+            let double_mod = double_mod_name(fn_name, args.name.as_ref(), "_mock");
+            let mut ignore_indices = get_ignore_indices(fn_inputs, &args.ignore)?;
+            ignore_indices.extend(get_impl_trait_indices(fn_inputs));
+            let own_indices = get_ignore_indices(fn_inputs, &args.own)?;
+            let param_wrappers = get_param_wrappers(fn_inputs, args)?;
+            let params_to_tuple = if args.named_params {
+                create_struct_from_param_names(quote! { #mod_name::#double_mod::Params }, fn_inputs, &ignore_indices, &own_indices, &param_wrappers, false)
+            } else {
+                create_tuple_from_param_names(fn_inputs, &ignore_indices, &own_indices, &param_wrappers)
+            };
+            quote! { #mod_name::#double_mod::call(#params_to_tuple) }
+        }
+        DoubleKind::Fake(args) => {
+            let double_mod = double_mod_name(fn_name, args.name.as_ref(), "_fake");
+            let async_fake = function.sig.asyncness.map(|_| quote! {
+                if #mod_name::#double_mod::is_async_set() {
+                    return #mod_name::#double_mod::call_async(#(#param_names),*).await;
+                }
+            });
+            quote! {
+                #async_fake
+                #mod_name::#double_mod::call(#(#param_names),*)
+            }
+        }
+        DoubleKind::Stub(args) => {
+            let double_mod = double_mod_name(fn_name, args.name.as_ref(), "_stub");
+            if args.keyed {
+                let params_matcher = create_params_matcher(fn_inputs);
+                quote! { #mod_name::#double_mod::get_return_value_for(#params_matcher) }
+            } else {
+                quote! { #mod_name::#double_mod::get_return_value() }
+            }
+        }
+    })
+}
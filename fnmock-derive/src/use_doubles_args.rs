@@ -0,0 +1,134 @@
+use syn::parse::{Parse, ParseStream};
+use syn::Token;
+use crate::attr_utils::parse_name_arg;
+
+/// The double of a function imported by a `use_doubles` or `use_function_*` statement
+///
+/// # Fields
+///
+/// - `fn_name` - The name of the imported function (before renaming)
+/// - `suffix` - The suffix of its double module, e.g. `_mock` for `fetch_user = mock`
+/// - `name` - The name of the double module, if the double is generated with `name = "..."`
+pub(crate) struct DoubleImport {
+    pub(crate) fn_name: syn::Ident,
+    pub(crate) suffix: &'static str,
+    pub(crate) name: Option<syn::Ident>,
+}
+
+/// Structure to parse the use_doubles attribute arguments
+#[derive(Default)]
+pub(crate) struct UseDoublesArgs {
+    pub(crate) imports: Vec<DoubleImport>,
+}
+
+impl UseDoublesArgs {
+    /// Gets the double of an imported function, or None if it is imported without its double.
+    pub(crate) fn double_of(&self, fn_name: &syn::Ident) -> Option<&DoubleImport> {
+        self.imports.iter().find(|import| &import.fn_name == fn_name)
+    }
+}
+
+impl Parse for UseDoublesArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = UseDoublesArgs::default();
+
+        // Parse "function = mock|fake|stub|spy" syntax, optionally followed by "(name = "...")"
+        while !input.is_empty() {
+            let fn_name: syn::Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let kind: syn::Ident = input.parse()?;
+            let suffix = match kind.to_string().as_str() {
+                "mock" => "_mock",
+                "fake" => "_fake",
+                "stub" => "_stub",
+                "spy" => "_spy",
+                _ => return Err(syn::Error::new_spanned(
+                    &kind,
+                    format!("Unknown double kind '{}'. Expected 'mock', 'fake', 'stub' or 'spy'", kind)
+                )),
+            };
+            let name = parse_double_name(input)?;
+            if args.double_of(&fn_name).is_some() {
+                return Err(syn::Error::new_spanned(
+                    &fn_name,
+                    format!("The double kind of '{}' is already specified", fn_name)
+                ));
+            }
+            args.imports.push(DoubleImport { fn_name, suffix, name });
+
+            // Allow trailing comma or end of input
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Structure to parse the use_function_mock / use_function_fake / use_function_stub attribute arguments
+///
+/// # Fields
+///
+/// - `names` - The functions whose double module is generated with `name = "..."`, with the name of the module
+#[derive(Default)]
+pub(crate) struct UseFunctionArgs {
+    pub(crate) names: Vec<(syn::Ident, syn::Ident)>,
+}
+
+impl UseFunctionArgs {
+    /// Gets the name of the double module of an imported function, if it is generated with `name = "..."`.
+    pub(crate) fn name_of(&self, fn_name: &syn::Ident) -> Option<&syn::Ident> {
+        self.names.iter()
+            .find(|(function, _)| function == fn_name)
+            .map(|(_, name)| name)
+    }
+}
+
+impl Parse for UseFunctionArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = UseFunctionArgs::default();
+
+        // Parse "function(name = "...")" syntax
+        while !input.is_empty() {
+            let fn_name: syn::Ident = input.parse()?;
+            let Some(name) = parse_double_name(input)? else {
+                return Err(syn::Error::new_spanned(
+                    &fn_name,
+                    format!("Expected the name of the double module of '{}', e.g. `{}(name = \"...\")`", fn_name, fn_name)
+                ));
+            };
+            args.names.push((fn_name, name));
+
+            // Allow trailing comma or end of input
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Parses the optional `(name = "...")` after an imported function or double kind.
+fn parse_double_name(input: ParseStream) -> syn::Result<Option<syn::Ident>> {
+    if !input.peek(syn::token::Paren) {
+        return Ok(None);
+    }
+
+    let content;
+    syn::parenthesized!(content in input);
+    let key: syn::Ident = content.parse()?;
+    if key != "name" {
+        return Err(syn::Error::new_spanned(
+            &key,
+            format!("Unknown argument '{}'. Expected 'name'", key)
+        ));
+    }
+    let name = parse_name_arg(&content)?;
+    if !content.is_empty() {
+        return Err(content.error("Expected only the name of the double module"));
+    }
+
+    Ok(Some(name))
+}
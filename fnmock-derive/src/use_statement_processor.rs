@@ -1,133 +1,149 @@
 use quote::quote;
-use crate::use_tree_processor::process_use_tree;
+use crate::use_tree_processor::{process_use_tree, ImportedFunction};
+use crate::attr_utils::{double_mod_name, get_cfg_attrs};
+use crate::cfg_utils::doubles_cfg;
+use crate::use_doubles_args::{UseDoublesArgs, UseFunctionArgs};
 
-/// Processes a use statement and generates conditional imports for modified versions.
+/// Processes a use statement and additionally imports the double modules of the imported functions.
 ///
-/// This is a shared implementation that can be used for both mocks and fakes.
-/// It analyzes a use statement and generates conditional compilation attributes that:
-/// - Import the original functions in production builds
-/// - Import modified versions (with custom suffix) aliased to original names in test builds
+/// This is a shared implementation for the `use_function_*` macros of all double kinds.
+/// The use statement is kept as it is, since the doubled functions check their doubles themselves.
+/// In test builds the double module of every imported function is imported next to it,
+/// so the tests of the importing module can configure the doubles without naming their paths.
+///
+/// Renamed imports rename their double module as well, e.g. `use service::fetch_user as fetch;`
+/// imports `service::fetch_user_mock as fetch_mock` in test builds.
+/// Double modules generated with `name = "..."` are imported by that name, e.g.
+/// `#[use_function_mock(fetch_price(name = "price_api"))]` imports `service::price_api`.
 ///
 /// # Arguments
 ///
+/// * `args` - The names of the double modules generated with `name = "..."`
 /// * `input` - The use statement to process
-/// * `suffix` - The suffix to append to function names (e.g., "_mock" or "_fake")
+/// * `suffix` - The suffix of the double modules (e.g., "_mock" or "_fake")
 ///
 /// # Returns
 ///
-/// - `Ok(TokenStream2)` - The expanded code with conditional imports
-/// - `Err(syn::Error)` - If the use statement cannot be processed
+/// - `Ok(TokenStream2)` - The use statement followed by the test-mode imports of the double modules
+/// - `Err(syn::Error)` - If the use statement contains glob imports (see `process_use_tree`)
+///   or an argument names a function the statement doesn't import
 pub(crate) fn process_use_statement(
+    args: UseFunctionArgs,
     input: syn::ItemUse,
     suffix: &str,
 ) -> syn::Result<proc_macro2::TokenStream> {
-    // Extract the module path and function name mappings
+    // Extract the imported functions with their full module paths
     let mut base_path = Vec::new();
-    let function_mappings = process_use_tree(&input.tree, &mut base_path);
+    let imported_functions = process_use_tree(&input.tree, &mut base_path)?;
+    check_imported(args.names.iter().map(|(fn_name, _)| fn_name), &imported_functions)?;
 
-    // Generate modified function names with the suffix
-    let modified_mappings: Vec<_> = function_mappings
+    let double_imports: Vec<_> = imported_functions
         .iter()
-        .map(|(fn_name, _)| {
-            let modified_fn_name = syn::Ident::new(
-                &format!("{}{}", fn_name, suffix),
-                fn_name.span(),
-            );
-            (fn_name.clone(), modified_fn_name)
-        })
+        .map(|function| generate_double_import(&input, function, suffix, args.name_of(&function.fn_name)))
         .collect();
 
-    // Reconstruct the module path as tokens
-    let module_path = if base_path.is_empty() {
-        quote! {}
-    } else {
-        quote! { #(#base_path)::* }
-    };
+    Ok(quote! {
+        #input
 
-    Ok(
-        // Generate the appropriate expansion based on number of imports
-        if modified_mappings.len() == 1 {
-            let (fn_name, modified_fn_name) = &modified_mappings[0];
-            generate_single_import(&input, module_path, fn_name, modified_fn_name)
-        } else {
-            generate_multiple_imports(&input, module_path, &modified_mappings)
-        }
-    )
+        #(#double_imports)*
+    })
 }
 
-/// Generates the expanded code for a single function import with modified version.
+/// Processes a use statement importing functions with different double kinds.
 ///
-/// Creates conditional compilation attributes that import the original function
-/// in production builds and the modified version (aliased to the original name) in test builds.
+/// Works like `process_use_statement`, but the suffix of every double module is given by the arguments,
+/// e.g. `#[use_doubles(fetch_user = mock, get_config = stub)]`.
+/// Functions without a double kind are imported without their double module.
 ///
 /// # Arguments
 ///
-/// * `input` - The original use statement
-/// * `module_path` - The module path tokens (empty if importing from current module)
-/// * `fn_name` - The original function name
-/// * `modified_fn_name` - The modified function name (with suffix)
+/// * `args` - The double kinds (and custom module names) of the imported functions
+/// * `input` - The use statement to process
 ///
 /// # Returns
 ///
-/// Token stream containing:
-/// ```ignore
-/// #[cfg(not(test))]
-/// use original::statement;
-/// #[cfg(test)]
-/// use module::path::function_modified as function;
-/// ```
-fn generate_single_import(
-    input: &syn::ItemUse,
-    module_path: proc_macro2::TokenStream,
-    fn_name: &syn::Ident,
-    modified_fn_name: &syn::Ident,
-) -> proc_macro2::TokenStream {
-    quote! {
-        #[cfg(not(test))]
+/// - `Ok(TokenStream2)` - The use statement followed by the test-mode imports of the double modules
+/// - `Err(syn::Error)` - If the use statement contains glob imports or an argument names a function
+///   the statement doesn't import
+pub(crate) fn process_use_doubles(
+    args: UseDoublesArgs,
+    input: syn::ItemUse,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut base_path = Vec::new();
+    let imported_functions = process_use_tree(&input.tree, &mut base_path)?;
+    check_imported(args.imports.iter().map(|import| &import.fn_name), &imported_functions)?;
+
+    let double_imports: Vec<_> = imported_functions
+        .iter()
+        .filter_map(|function| {
+            let double = args.double_of(&function.fn_name)?;
+            Some(generate_double_import(&input, function, double.suffix, double.name.as_ref()))
+        })
+        .collect();
+
+    Ok(quote! {
         #input
-        
-        #[cfg(test)]
-        use #module_path::#modified_fn_name as #fn_name;
+
+        #(#double_imports)*
+    })
+}
+
+/// Checks that every function named in the macro arguments is imported by the use statement.
+fn check_imported<'a>(
+    fn_names: impl Iterator<Item = &'a syn::Ident>,
+    imported_functions: &[ImportedFunction],
+) -> syn::Result<()> {
+    for fn_name in fn_names {
+        if !imported_functions.iter().any(|function| &function.fn_name == fn_name) {
+            return Err(syn::Error::new_spanned(
+                fn_name,
+                format!("'{}' isn't imported by the use statement", fn_name)
+            ));
+        }
     }
+
+    Ok(())
 }
 
-/// Generates the expanded code for multiple function imports with modified versions.
-///
-/// Creates conditional compilation attributes that import the original functions
-/// in production builds and the modified versions (aliased to the original names) in test builds.
+/// Generates the test-mode import of the double module of an imported function.
 ///
 /// # Arguments
 ///
-/// * `input` - The original use statement
-/// * `module_path` - The module path tokens (empty if importing from current module)
-/// * `function_mappings` - Vector of (original_name, modified_name) tuples
+/// * `input` - The original use statement, whose `cfg` attributes and leading `::` are kept
+/// * `function` - The imported function
+/// * `suffix` - The suffix of the double module
+/// * `name` - The name of the double module, if it is generated with `name = "..."`
 ///
 /// # Returns
 ///
 /// Token stream containing:
 /// ```ignore
-/// #[cfg(not(test))]
-/// use original::statement;
 /// #[cfg(test)]
-/// use module::path::{fn1_modified as fn1, fn2_modified as fn2};
+/// use module::path::function_mock as imported_name_mock;
 /// ```
-fn generate_multiple_imports(
+fn generate_double_import(
     input: &syn::ItemUse,
-    module_path: proc_macro2::TokenStream,
-    function_mappings: &[(syn::Ident, syn::Ident)],
+    function: &ImportedFunction,
+    suffix: &str,
+    name: Option<&syn::Ident>,
 ) -> proc_macro2::TokenStream {
-    let alias_mappings: Vec<_> = function_mappings
-        .iter()
-        .map(|(fn_name, modified_fn_name)| {
-            quote! { #modified_fn_name as #fn_name }
-        })
-        .collect();
-    
+    let doubles_cfg = doubles_cfg();
+    let cfg_attrs = get_cfg_attrs(&input.attrs);
+    let leading_colon = &input.leading_colon;
+    let path = &function.path;
+    let double_mod = double_mod_name(&function.fn_name, name, suffix);
+    // Renamed imports rename the double module, others keep its (possibly custom) name
+    let imported_double_mod = if function.imported_name == function.fn_name {
+        double_mod.clone()
+    } else {
+        double_mod_name(&function.imported_name, None, suffix)
+    };
+
+    // The double module is only used by tests, but imported in every build with the `test-doubles` feature
     quote! {
-        #[cfg(not(test))]
-        #input
-        
-        #[cfg(test)]
-        use #module_path::{#(#alias_mappings),*};
+        #(#cfg_attrs)*
+        #[cfg(#doubles_cfg)]
+        #[allow(unused_imports)]
+        use #leading_colon #(#path::)* #double_mod as #imported_double_mod;
     }
 }
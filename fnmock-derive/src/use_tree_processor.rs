@@ -1,15 +1,38 @@
 //! Processing logic for **use statement syntax trees**.
 //!
-//! This module handles the transformation of use statements to extract function names
-//! and generate corresponding mock function names.
+//! This module handles the transformation of use statements to extract the imported functions
+//! with their full paths and the names they are imported as.
+//! Unsupported use trees are reported as `syn::Error`s spanning the offending part of the statement.
 
-use syn;
+/// A function imported by a use statement
+///
+/// # Fields
+///
+/// - `path` - The full module path of the function (e.g., `[crate, module]`)
+/// - `fn_name` - The original function identifier (e.g., `fetch_user`)
+/// - `imported_name` - The name the function is imported as, which differs from `fn_name` for renamed imports
+pub(crate) struct ImportedFunction {
+    pub(crate) path: Vec<syn::Ident>,
+    pub(crate) fn_name: syn::Ident,
+    pub(crate) imported_name: syn::Ident,
+}
 
-/// Recursively processes a use tree to extract function names and generate mock names.
+impl ImportedFunction {
+    fn new(path: &[syn::Ident], fn_name: &syn::Ident, imported_name: &syn::Ident) -> Self {
+        Self {
+            path: path.to_vec(),
+            fn_name: fn_name.clone(),
+            imported_name: imported_name.clone(),
+        }
+    }
+}
+
+/// Recursively processes a use tree to extract the imported functions.
 ///
 /// This function traverses the syntax tree of a use statement, collecting the module path in the `base_path` vector
-/// and extracting function names. For each function, it generates a corresponding mock
-/// function name by appending `_mock`.
+/// and extracting function names. For each function, it keeps the name the function is imported as.
+/// Every group continues with its own copy of the path, so each function gets its full path,
+/// even in nested groups.
 ///
 /// # Arguments
 ///
@@ -18,28 +41,22 @@ use syn;
 ///
 /// # Returns
 ///
-/// A vector of tuples where each tuple contains:
-/// * Original function identifier (e.g., `fetch_user`)
-/// * Generated mock function identifier (e.g., `fetch_user_mock`)
+/// - `Ok(Vec<ImportedFunction>)` - The imported functions, in the order of the use statement
+/// - `Err(syn::Error)` - If the use tree contains glob imports (`*`)
 ///
 /// # Examples
 ///
-/// For `use module::function;`:
-/// - Returns: `[(function, function_mock)]`
-/// - base_path after: `["module"]`
-///
-/// For `use module::{fn1, fn2};`:
-/// - Returns: `[(fn1, fn1_mock), (fn2, fn2_mock)]`
-/// - base_path after: `["module"]`
-///
-/// # Panics
+/// - `use module::function;` → `module::function` imported as `function`
+/// - `use module::{fn1, fn2};` → `module::fn1` and `module::fn2`
+/// - `use module::function as renamed;` → `module::function` imported as `renamed`
+/// - `use a::{b::{c, d}, e};` → `a::b::c`, `a::b::d` and `a::e`
 ///
-/// Panics if the use tree contains unsupported patterns like glob imports (`*`)
-/// or renamed imports (`as`).
+/// Glob imports can't be supported: the test-mode import has to name the double module of every function,
+/// but the functions behind a `*` aren't known to the macro.
 pub(crate) fn process_use_tree(
     tree: &syn::UseTree,
     base_path: &mut Vec<syn::Ident>,
-) -> Vec<(syn::Ident, syn::Ident)> {
+) -> syn::Result<Vec<ImportedFunction>> {
     match tree {
         // Handle path segments: module::submodule::...
         syn::UseTree::Path(path) => {
@@ -48,27 +65,35 @@ pub(crate) fn process_use_tree(
         }
         // Handle individual function name
         syn::UseTree::Name(name) => {
-            let fn_name = name.ident.clone();
-            let mock_fn_name = syn::Ident::new(
-                &format!("{}_mock", fn_name),
-                fn_name.span()
-            );
-            vec![(fn_name, mock_fn_name)]
+            Ok(vec![ImportedFunction::new(base_path, &name.ident, &name.ident)])
+        }
+        // Handle renamed function: function as alias
+        syn::UseTree::Rename(rename) => {
+            Ok(vec![ImportedFunction::new(base_path, &rename.ident, &rename.rename)])
         }
         // Handle grouped imports: {fn1, fn2, fn3}
         syn::UseTree::Group(group) => {
-            let mut function_mappings = Vec::new();
+            let mut imported_functions = Vec::new();
             for item in &group.items {
-                // Clone base_path for each item to handle nested groups correctly
+                // Clone base_path for each item, so the path segments of one item don't leak into the next
                 let mut item_path = base_path.clone();
-                function_mappings.extend(process_use_tree(item, &mut item_path));
+                imported_functions.extend(process_use_tree(item, &mut item_path)?);
             }
-            function_mappings
+            Ok(imported_functions)
+        }
+        // The functions behind a glob can't be listed, so their double modules can't be imported
+        syn::UseTree::Glob(glob) => {
+            let module_path = base_path.iter().map(|ident| ident.to_string()).collect::<Vec<_>>().join("::");
+            Err(syn::Error::new_spanned(
+                glob,
+                format!(
+                    "glob imports can't be doubled, since the imported functions aren't known. \
+                     Import the functions explicitly, e.g. `use {}::{{function_a, function_b}};`. \
+                     Supported forms: `use module::function;`, `use module::{{function_a, function_b}};` \
+                     and `use module::function as alias;`",
+                    module_path
+                )
+            ))
         }
-        // Glob imports and renamed imports are not supported
-        _ => panic!(
-            "use_mock only supports simple path and grouped imports. \
-             Glob imports (*) and renamed imports (as) are not supported."
-        ),
     }
 }
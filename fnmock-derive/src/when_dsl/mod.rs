@@ -0,0 +1,48 @@
+use quote::{format_ident, quote};
+use syn::__private::TokenStream2;
+use crate::param_utils::create_argument_matchers;
+
+/// Processes the call passed to the when macro and turns it into a case of the mock.
+///
+/// This is the main entry point for the when macro. The call `fetch_user(42)` is turned into
+/// `fetch_user_mock::when(fnmock::matchers::eq_to(42))`, so the mock module next to the called function
+/// sets up the case. Every argument is compared with `eq_to`, except `_`, which accepts every value.
+///
+/// # Arguments
+///
+/// * `call` - The call of the mocked function, e.g. `fetch_user(42)` or `users::fetch_user(_)`
+///
+/// # Returns
+///
+/// - `Ok(TokenStream2)` - The call of the `when()` proxy function of the mock module
+/// - `Err(syn::Error)` - If the expression isn't a call of a function path
+pub(crate) fn process_when(call: syn::Expr) -> syn::Result<TokenStream2> {
+    let syn::Expr::Call(call) = call else {
+        return Err(syn::Error::new_spanned(
+            call,
+            "when! expects a call of a mocked function, e.g. `when!(fetch_user(42))`"
+        ));
+    };
+    let syn::Expr::Path(function) = call.func.as_ref() else {
+        return Err(syn::Error::new_spanned(
+            &call.func,
+            "when! expects the mocked function to be called by its path, e.g. `when!(users::fetch_user(42))`"
+        ));
+    };
+
+    let mut mock_module = function.path.clone();
+    let Some(last) = mock_module.segments.last_mut() else {
+        return Err(syn::Error::new_spanned(&function.path, "when! expects a path to the mocked function"));
+    };
+    if !last.arguments.is_none() {
+        return Err(syn::Error::new_spanned(
+            &last.arguments,
+            "when! doesn't support generic mocks, use the `setup_when` proxy function instead"
+        ));
+    }
+    last.ident = format_ident!("{}_mock", last.ident);
+
+    let matchers = create_argument_matchers(&call.args);
+
+    Ok(quote! { #mock_module::when(#(#matchers),*) })
+}
@@ -0,0 +1,85 @@
+pub mod pricing {
+    use fnmock::derive::{mock_function, stub_function};
+
+    #[mock_function]
+    pub fn fetch_unit_price(product: String) -> Result<u32, String> {
+        // Real implementation
+        Err(format!("price service unavailable for {}", product))
+    }
+
+    #[stub_function]
+    pub fn get_discount_percent() -> u32 {
+        // Real implementation
+        10
+    }
+
+    #[mock_function(shared)]
+    pub fn fetch_currency() -> String {
+        // Real implementation
+        "EUR".to_string()
+    }
+}
+
+use pricing::{fetch_currency, fetch_unit_price, get_discount_percent};
+
+/// Calculates the price of the order in cents, or None if the price isn't available
+pub fn order_total(product: &str, quantity: u32) -> Option<u64> {
+    let unit_price = fetch_unit_price(product.to_string()).ok()? as u64;
+    let discount = get_discount_percent().min(100) as u64;
+    Some(unit_price * quantity as u64 * (100 - discount) / 100)
+}
+
+/// Describes the total of the order for the invoice
+pub fn invoice_line(product: &str, quantity: u32) -> String {
+    match order_total(product, quantity) {
+        Some(total) => format!("{} x {}: {} {}", quantity, product, total, fetch_currency()),
+        None => format!("{} x {}: price unavailable", quantity, product),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::pricing::{fetch_currency_mock, fetch_unit_price_mock, get_discount_percent_stub};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_total_never_exceeds_undiscounted_price(quantity in 0u32..1000) {
+            fetch_unit_price_mock::setup_strategy((0u32..10_000).prop_map(Ok));
+            get_discount_percent_stub::setup_arbitrary();
+
+            let total = order_total("book", quantity).unwrap();
+
+            prop_assert!(total <= 10_000 * quantity as u64);
+            fetch_unit_price_mock::clear();
+            get_discount_percent_stub::clear();
+        }
+
+        #[test]
+        fn test_invoice_line_handles_every_response(quantity in 1u32..10) {
+            fetch_unit_price_mock::setup_arbitrary();
+            get_discount_percent_stub::setup_strategy(0u32..=100);
+            fetch_currency_mock::setup_strategy("[A-Z]{3}");
+
+            let line = invoice_line("book", quantity);
+            let prefix = format!("{} x book: ", quantity);
+
+            prop_assert!(line.starts_with(&prefix));
+            fetch_unit_price_mock::clear();
+            get_discount_percent_stub::clear();
+            fetch_currency_mock::clear();
+        }
+    }
+
+    #[test]
+    fn test_every_call_draws_a_new_value() {
+        get_discount_percent_stub::setup_strategy(0u32..=100);
+        fetch_unit_price_mock::setup_strategy(Just(Ok(200)));
+
+        let totals: Vec<Option<u64>> = (0..20).map(|_| order_total("pen", 1)).collect();
+
+        assert!(totals.iter().all(|total| matches!(total, Some(0..=200))));
+        fetch_unit_price_mock::assert_times(20);
+    }
+}
@@ -0,0 +1,62 @@
+use fnmock::derive::mock_function;
+
+#[mock_function]
+pub fn publish_event(topic: String, payload: String) -> Result<(), String> {
+    // Real implementation
+    println!("Publishing to {}: {}", topic, payload);
+    Ok(())
+}
+
+pub fn record_orders(order_ids: &[u32]) -> Result<(), String> {
+    for order_id in order_ids {
+        let topic = if order_id % 2 == 0 { "orders.even" } else { "orders.odd" };
+        publish_event(topic.to_string(), format!("{{\"order_id\":{}}}", order_id))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_captor_collects_payloads() {
+        publish_event_mock::setup(|_| Ok(()));
+        let captor = publish_event_mock::capture();
+
+        record_orders(&[1, 2, 3]).unwrap();
+
+        let payloads: Vec<String> = captor.values().into_iter().map(|(_, payload)| payload).collect();
+        assert_eq!(payloads, vec!["{\"order_id\":1}", "{\"order_id\":2}", "{\"order_id\":3}"]);
+        assert_eq!(captor.value().unwrap().0, "orders.odd");
+    }
+
+    #[test]
+    fn test_capture_into_collects_distinct_topics() {
+        publish_event_mock::setup(|_| Ok(()));
+        let topics = Rc::new(RefCell::new(HashSet::new()));
+        publish_event_mock::capture_into(topics.clone());
+
+        record_orders(&[1, 3, 5, 7]).unwrap();
+
+        let topics: HashSet<String> = topics.borrow().iter().map(|(topic, _)| topic.clone()).collect();
+        assert_eq!(topics, HashSet::from(["orders.odd".to_string()]));
+    }
+
+    #[test]
+    fn test_captor_keeps_values_after_clear() {
+        publish_event_mock::setup(|_| Ok(()));
+        let captor = publish_event_mock::capture();
+
+        record_orders(&[4]).unwrap();
+        publish_event_mock::clear();
+        publish_event_mock::setup(|_| Ok(()));
+        record_orders(&[5]).unwrap();
+
+        assert_eq!(captor.len(), 1);
+        assert_eq!(captor.value().unwrap().0, "orders.even");
+    }
+}
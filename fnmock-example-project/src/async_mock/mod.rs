@@ -16,6 +16,19 @@ pub async fn handle_user(id: u32) {
     // Do something with the user
 }
 
+/// Fetches the user, but falls back to the cached user if the fetch doesn't complete within `budget` yields.
+pub async fn fetch_user_or_cached(id: u32, budget: u32) -> String {
+    tokio::select! {
+        biased;
+        user = fetch_user(id) => user.unwrap_or_else(|_| "cached".to_string()),
+        _ = async {
+            for _ in 0..budget {
+                tokio::task::yield_now().await;
+            }
+        } => "cached".to_string(),
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -39,4 +52,40 @@ mod tests {
 
         // No cleanup needed, since mocks are thread / test specific
     }
+
+    #[tokio::test]
+    async fn test_slow_fetch_loses_select_race() {
+        fetch_user_mock::setup_with_yields(3, |id| Ok(format!("user_{}", id)));
+
+        let result = fetch_user_or_cached(42, 1).await;
+
+        assert_eq!(result, "cached");
+        fetch_user_mock::assert_times(1);
+    }
+
+    #[tokio::test]
+    async fn test_fast_fetch_wins_select_race() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        let result = fetch_user_or_cached(42, 1).await;
+
+        assert_eq!(result, "user_42");
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_fetch_is_recorded() {
+        fetch_user_mock::setup_with_yields(1, |id| Ok(format!("user_{}", id)));
+
+        let mut fetch = Box::pin(handle_user(42));
+        assert!(poll_once(fetch.as_mut()).await.is_pending());
+        drop(fetch);
+
+        fetch_user_mock::assert_times(1);
+        fetch_user_mock::assert_with(42);
+    }
+
+    /// Polls the future exactly once.
+    async fn poll_once<F: std::future::Future + Unpin>(mut future: F) -> std::task::Poll<F::Output> {
+        std::future::poll_fn(|cx| std::task::Poll::Ready(std::pin::Pin::new(&mut future).poll(cx))).await
+    }
 }
\ No newline at end of file
@@ -0,0 +1,91 @@
+pub mod metrics {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub async fn push_metric(name: String, value: u64) -> Result<(), String> {
+        // Real implementation
+        println!("{} = {}", name, value);
+        Ok(())
+    }
+}
+use metrics::push_metric;
+use std::time::Duration;
+
+/// Pushes the metrics from a background task, pausing between them.
+///
+/// The task runs on the runtime of the caller - in tests on the single test thread,
+/// so it sees the mocks of the test.
+pub fn start_reporter(metrics: Vec<(String, u64)>, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        for (name, value) in metrics {
+            tokio::time::sleep(interval).await;
+            let _ = push_metric(name, value).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::metrics::push_metric_mock;
+
+    fn metrics() -> Vec<(String, u64)> {
+        vec![("cpu".to_string(), 80), ("memory".to_string(), 512), ("disk".to_string(), 20)]
+    }
+
+    #[tokio::test]
+    async fn test_reporter_pushes_all_metrics() {
+        push_metric_mock::setup(|_| Ok(()));
+
+        start_reporter(metrics(), Duration::from_millis(5));
+
+        push_metric_mock::assert_times_eventually(3, Duration::from_secs(5)).await;
+        push_metric_mock::assert_last_call_with("disk".to_string(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_reporter_continues_after_failed_push() {
+        push_metric_mock::setup(|(name, _)| if name == "cpu" { Err("rejected".to_string()) } else { Ok(()) });
+
+        start_reporter(metrics(), Duration::ZERO);
+
+        push_metric_mock::assert_times_eventually(3, Duration::from_secs(5)).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Expected push_metric_mock mock to be called 3 times, received 0")]
+    async fn test_times_are_asserted_after_timeout() {
+        push_metric_mock::setup(|_| Ok(()));
+
+        start_reporter(metrics(), Duration::from_secs(60));
+
+        push_metric_mock::assert_times_eventually(3, Duration::from_millis(20)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[should_panic(expected = "Expected push_metric_mock mock to be called 3 times, received 1")]
+    async fn test_timeout_follows_paused_time() {
+        push_metric_mock::setup(|_| Ok(()));
+
+        // Only the first push happens within the timeout on the paused clock, which advances without waiting
+        start_reporter(metrics(), Duration::from_secs(60));
+
+        push_metric_mock::assert_times_eventually(3, Duration::from_secs(90)).await;
+    }
+
+    #[async_std::test]
+    async fn test_eventually_with_async_std() {
+        push_metric_mock::setup(|_| Ok(()));
+
+        // async-std runs tasks on other threads, so the background work runs on the test thread here
+        let background = async {
+            for (name, value) in metrics() {
+                async_std::task::sleep(Duration::from_millis(1)).await;
+                let _ = push_metric(name, value).await;
+            }
+        };
+        let assertion = push_metric_mock::assert_times_eventually(3, Duration::from_secs(5));
+
+        futures::join!(background, assertion);
+    }
+}
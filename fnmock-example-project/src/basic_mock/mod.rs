@@ -51,4 +51,183 @@ mod tests {
 
         // No cleanup needed, since mocks are thread / test specific
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_with_matcher() {
+        use fnmock::matchers::{all_of, eq, gt, lt, not};
+
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        handle_user(7);
+        handle_user(42);
+
+        fetch_user_mock::assert_with_matching(all_of([gt(0), lt(10)]));
+        fetch_user_mock::assert_with_matching(not(eq(1)));
+    }
+
+    #[test]
+    fn test_last_call() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        handle_user(1);
+        fetch_user_mock::assert_last_call_with(1);
+
+        handle_user(2);
+        fetch_user_mock::assert_last_call_with(2);
+    }
+
+    #[test]
+    fn test_setup_returns() {
+        fetch_user_mock::setup_returns(Ok("constant user".to_string()));
+
+        assert_eq!(fetch_user(1), Ok("constant user".to_string()));
+        assert_eq!(fetch_user(2), Ok("constant user".to_string()));
+        fetch_user_mock::assert_times(2);
+        fetch_user_mock::assert_last_call_with(2);
+    }
+
+    #[test]
+    fn test_with_capturing_closure() {
+        let names = ["alice".to_string(), "bob".to_string()];
+        let mut next = 0;
+        fetch_user_mock::setup(move |_| {
+            let name = names[next % names.len()].clone();
+            next += 1;
+            Ok(name)
+        });
+
+        assert_eq!(fetch_user(1), Ok("alice".to_string()));
+        assert_eq!(fetch_user(2), Ok("bob".to_string()));
+        assert_eq!(fetch_user(3), Ok("alice".to_string()));
+        fetch_user_mock::assert_times(3);
+    }
+
+    #[test]
+    fn test_implementation_reads_mock_state() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{} (call {})", id, fetch_user_mock::times_called())));
+
+        assert_eq!(fetch_user(4), Ok("user_4 (call 1)".to_string()));
+        assert_eq!(fetch_user(8), Ok("user_8 (call 2)".to_string()));
+    }
+
+    #[test]
+    fn test_implementation_calls_the_mocked_function() {
+        // The nested call executes the real implementation, since the mock only handles one call
+        fetch_user_mock::setup_once(|id| fetch_user(id + 1).map(|user| format!("cached {}", user)));
+
+        assert_eq!(fetch_user(1), Ok("cached user_2".to_string()));
+        fetch_user_mock::assert_times(2);
+        fetch_user_mock::assert_nth_call_with(1, 2);
+    }
+
+    #[test]
+    fn test_call_count_ranges() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        for id in 0..3 {
+            handle_user(id);
+        }
+
+        fetch_user_mock::assert_times_at_least(1);
+        fetch_user_mock::assert_times_at_most(5);
+        fetch_user_mock::assert_times_between(2, 5);
+    }
+
+    #[test]
+    fn test_call_count_predicate() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        // Every user is handled twice, so the count depends on the data
+        let users = [1, 2, 3];
+        for id in users.iter().chain(users.iter()) {
+            handle_user(*id);
+        }
+
+        fetch_user_mock::assert_times_matching(|n| n % 2 == 0, "an even number of");
+    }
+
+    #[test]
+    fn test_inspect_call_history() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        for id in [3, 1, 2] {
+            handle_user(id);
+        }
+
+        assert_eq!(fetch_user_mock::calls(), vec![3, 1, 2]);
+        assert!(fetch_user_mock::calls().iter().all(|id| *id > 0));
+    }
+
+    #[test]
+    fn test_computed_call_count() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        let ids = vec![4, 8, 15, 16];
+        for id in &ids {
+            handle_user(*id);
+        }
+
+        assert_eq!(fetch_user_mock::times_called() as usize, ids.len());
+    }
+
+    #[test]
+    fn test_custom_assertion_on_last_call() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+        assert_eq!(fetch_user_mock::last_call_params(), None);
+
+        handle_user(12);
+        handle_user(31);
+
+        let last_id = fetch_user_mock::last_call_params().unwrap();
+        assert!(last_id > 12 && last_id % 2 == 1);
+    }
+
+    #[test]
+    fn test_nth_call() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        handle_user(7);
+        handle_user(42);
+
+        fetch_user_mock::assert_nth_call_with(0, 7);
+        fetch_user_mock::assert_nth_call_with(1, 42);
+    }
+
+    #[test]
+    fn test_never_called_with() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        for id in [1, 2, 4] {
+            handle_user(id);
+        }
+
+        fetch_user_mock::assert_times(3);
+        fetch_user_mock::assert_never_called_with(3);
+    }
+
+    #[test]
+    fn test_spy_runs_real_implementation() {
+        fetch_user_mock::spy();
+
+        assert_eq!(fetch_user(5), Ok("user_5".to_string()));
+        fetch_user_mock::assert_times(1);
+        fetch_user_mock::assert_with(5);
+    }
+
+    #[test]
+    fn test_phases_with_clear_history() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+
+        // Phase 1
+        handle_user(1);
+        handle_user(2);
+        fetch_user_mock::assert_times(2);
+
+        fetch_user_mock::clear_history();
+
+        // Phase 2 keeps the setup, but only sees its own calls
+        handle_user(3);
+        fetch_user_mock::assert_times(1);
+        fetch_user_mock::assert_last_call_with(3);
+    }
+}
@@ -9,6 +9,39 @@ pub mod config {
 }
 use config::get_config;
 
+pub mod constants {
+    use fnmock::derive::stub_function;
+
+    // Doesn't implement Clone
+    pub struct Limits {
+        pub max_users: u32,
+    }
+
+    pub static DEFAULT_LIMITS: Limits = Limits { max_users: 100 };
+
+    #[stub_function]
+    pub fn app_name() -> &'static str {
+        // Real implementation
+        "fnmock-example"
+    }
+
+    #[stub_function]
+    pub fn limits() -> &'static Limits {
+        // Real implementation
+        &DEFAULT_LIMITS
+    }
+
+    #[stub_function(keyed)]
+    pub fn status_label(code: u16) -> &'static str {
+        // Real implementation
+        if code < 400 { "ok" } else { "error" }
+    }
+}
+
+pub fn banner(code: u16) -> String {
+    format!("{} [{}] max {} users", constants::app_name(), constants::status_label(code), constants::limits().max_users)
+}
+
 pub fn process_config() -> String {
     get_config()
 }
@@ -32,4 +65,39 @@ mod tests {
         // Clean up
         get_config_stub::clear();
     }
+
+    #[test]
+    fn test_unset_stub_executes_the_original() {
+        assert_eq!(process_config(), "production_config");
+
+        get_config_stub::setup("test_config".to_string());
+        assert_eq!(process_config(), "test_config");
+
+        get_config_stub::clear();
+        assert_eq!(process_config(), "production_config");
+    }
+
+    #[test]
+    fn test_return_alias() {
+        let config: get_config_stub::Return = "aliased_config".to_string();
+        get_config_stub::setup(config.clone());
+
+        assert_eq!(process_config(), config);
+    }
+
+    #[test]
+    fn test_stub_static_references() {
+        use super::constants::{app_name_stub, limits_stub, status_label_stub, Limits};
+        static TEST_LIMITS: Limits = Limits { max_users: 1 };
+
+        assert_eq!(banner(200), "fnmock-example [ok] max 100 users");
+
+        app_name_stub::setup("test-app");
+        limits_stub::setup(&TEST_LIMITS);
+        status_label_stub::setup_for(503, "maintenance");
+        status_label_stub::setup("unknown");
+
+        assert_eq!(banner(503), "test-app [maintenance] max 1 users");
+        assert_eq!(banner(200), "test-app [unknown] max 1 users");
+    }
 }
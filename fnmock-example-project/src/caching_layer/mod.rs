@@ -0,0 +1,80 @@
+pub mod api {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub fn fetch_profile(user_id: u32, locale: String) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("profile {} ({})", user_id, locale))
+    }
+}
+use api::fetch_profile;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Caches the profiles per user and locale
+#[derive(Default)]
+pub struct ProfileCache {
+    profiles: RefCell<HashMap<(u32, String), String>>,
+}
+
+impl ProfileCache {
+    pub fn get(&self, user_id: u32, locale: &str) -> Result<String, String> {
+        let key = (user_id, locale.to_string());
+        if let Some(profile) = self.profiles.borrow().get(&key) {
+            return Ok(profile.clone());
+        }
+
+        let profile = fetch_profile(user_id, locale.to_string())?;
+        self.profiles.borrow_mut().insert(key, profile.clone());
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::api::fetch_profile_mock;
+
+    #[test]
+    fn test_cache_fetches_every_key_once() {
+        fetch_profile_mock::setup(|(user_id, locale)| Ok(format!("{}:{}", user_id, locale)));
+        let cache = ProfileCache::default();
+
+        for _ in 0..3 {
+            assert_eq!(cache.get(1, "en"), Ok("1:en".to_string()));
+            assert_eq!(cache.get(1, "de"), Ok("1:de".to_string()));
+            assert_eq!(cache.get(2, "en"), Ok("2:en".to_string()));
+        }
+
+        fetch_profile_mock::assert_no_duplicate_calls();
+        assert_eq!(fetch_profile_mock::unique_calls(), vec![
+            (1, "en".to_string()),
+            (1, "de".to_string()),
+            (2, "en".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_failed_fetches_are_retried() {
+        fetch_profile_mock::setup(|_| Err("timeout".to_string()));
+        let cache = ProfileCache::default();
+
+        assert!(cache.get(1, "en").is_err());
+        assert!(cache.get(1, "en").is_err());
+
+        fetch_profile_mock::assert_times(2);
+        assert_eq!(fetch_profile_mock::unique_calls().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected fetch_profile_mock mock to be called at most once per params, received (1, \"en\") (2 times)")]
+    fn test_uncached_fetches_are_detected() {
+        fetch_profile_mock::setup(|_| Ok("profile".to_string()));
+
+        // Without the cache, every call fetches the profile
+        let _ = fetch_profile(1, "en".to_string());
+        let _ = fetch_profile(1, "en".to_string());
+
+        fetch_profile_mock::assert_no_duplicate_calls();
+    }
+}
@@ -0,0 +1,43 @@
+pub mod hashing {
+    use fnmock::derive::mock_function;
+
+    // Called for every block, so the mock only counts the calls
+    #[mock_function(no_history)]
+    pub fn hash_block(block: Vec<u8>) -> u64 {
+        // Real implementation
+        block.iter().fold(17, |hash, byte| hash.wrapping_mul(31).wrapping_add(*byte as u64))
+    }
+}
+use hashing::hash_block;
+
+/// Computes the checksum of the data, hashing it in blocks of `block_size` bytes.
+pub fn checksum(data: &[u8], block_size: usize) -> u64 {
+    data.chunks(block_size).map(|block| hash_block(block.to_vec())).fold(0, u64::wrapping_add)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::hashing::hash_block_mock;
+
+    #[test]
+    fn test_checksum_hashes_every_block() {
+        hash_block_mock::setup(|block| block.len() as u64);
+
+        // The params of the 100_000 calls aren't cloned into the history
+        let data = vec![0u8; 400_000];
+        assert_eq!(checksum(&data, 4), 400_000);
+
+        hash_block_mock::assert_times(100_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "hash_block_mock mock doesn't record the params of its calls (no_history), so assert_with can't be used")]
+    fn test_params_are_not_recorded() {
+        hash_block_mock::setup(|_| 0);
+
+        checksum(&[1, 2, 3], 3);
+
+        hash_block_mock::assert_with(vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,109 @@
+pub mod notifications {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub fn send_notification(user_id: u32, message: String) -> Result<(), String> {
+        // Real implementation
+        println!("notify {}: {}", user_id, message);
+        Ok(())
+    }
+}
+use notifications::send_notification;
+
+/// Notifies all users and returns the number of failed notifications.
+pub fn notify_all(user_ids: Vec<u32>, message: &str) -> usize {
+    user_ids
+        .into_iter()
+        .filter(|user_id| send_notification(*user_id, message.to_string()).is_err())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::notifications::send_notification_mock;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_on_call_notifies_the_test() {
+        let (sender, receiver) = mpsc::channel();
+        send_notification_mock::setup(|_| Ok(()));
+        send_notification_mock::on_call(move |(user_id, _)| sender.send(*user_id).unwrap());
+
+        notify_all(vec![1, 2], "maintenance");
+
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_hooks_run_around_the_implementation() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        send_notification_mock::setup(|(user_id, _)| if user_id == 2 { Err("offline".to_string()) } else { Ok(()) });
+
+        let before_log = log.clone();
+        send_notification_mock::on_call(move |(user_id, _)| before_log.borrow_mut().push(format!("sending to {}", user_id)));
+        let after_log = log.clone();
+        send_notification_mock::after_call(move |(user_id, _), result| after_log.borrow_mut().push(format!("{} -> {:?}", user_id, result)));
+
+        assert_eq!(notify_all(vec![1, 2], "maintenance"), 1);
+        assert_eq!(*log.borrow(), vec![
+            "sending to 1",
+            "1 -> Ok(())",
+            "sending to 2",
+            "2 -> Err(\"offline\")",
+        ]);
+    }
+
+    #[test]
+    fn test_hooks_query_the_mock() {
+        let counts = Rc::new(RefCell::new(Vec::new()));
+        send_notification_mock::setup(|_| Ok(()));
+        let hook_counts = counts.clone();
+        send_notification_mock::on_call(move |_| hook_counts.borrow_mut().push(send_notification_mock::times_called()));
+
+        notify_all(vec![1, 2], "maintenance");
+
+        assert_eq!(*counts.borrow(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_clear_removes_hooks() {
+        let calls = Rc::new(RefCell::new(0));
+        send_notification_mock::setup(|_| Ok(()));
+        let hook_calls = calls.clone();
+        send_notification_mock::on_call(move |_| *hook_calls.borrow_mut() += 1);
+
+        send_notification_mock::clear();
+        send_notification_mock::setup(|_| Ok(()));
+        notify_all(vec![1], "maintenance");
+
+        assert_eq!(*calls.borrow(), 0);
+        send_notification_mock::assert_times(1);
+    }
+
+    #[test]
+    fn test_assert_with_matchers_per_parameter() {
+        use fnmock::matchers::{any, eq, gt, predicate};
+
+        send_notification_mock::setup(|_| Ok(()));
+
+        notify_all(vec![1, 42], "maintenance at 10pm");
+
+        send_notification_mock::assert_with_matchers(eq(42), any());
+        send_notification_mock::assert_with_matchers(gt(10), predicate("mentions maintenance", |message: &String| message.contains("maintenance")));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected send_notification_mock mock to be called with params matching (eq(7), any)")]
+    fn test_assert_with_matchers_fails_without_matching_call() {
+        use fnmock::matchers::{any, eq};
+
+        send_notification_mock::setup(|_| Ok(()));
+
+        notify_all(vec![1, 42], "maintenance");
+
+        send_notification_mock::assert_with_matchers(eq(7), any());
+    }
+}
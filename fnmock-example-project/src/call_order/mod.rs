@@ -0,0 +1,102 @@
+pub mod onboarding {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub fn fetch_user(id: u32) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("user_{}", id))
+    }
+
+    #[mock_function]
+    pub fn send_email(to: String, body: String) -> Result<(), String> {
+        // Real implementation
+        println!("Send email to {}: {}", to, body);
+        Ok(())
+    }
+}
+use onboarding::{fetch_user, send_email};
+
+pub fn welcome_user(id: u32) -> Result<(), String> {
+    let user = fetch_user(id)?;
+    send_email(user, "Welcome!".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::onboarding::{fetch_user_mock, send_email_mock};
+    use fnmock::sequence::Sequence;
+
+    #[test]
+    fn test_user_is_fetched_before_email_is_sent() {
+        let sequence = Sequence::new();
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+        send_email_mock::setup(|_| Ok(()));
+        fetch_user_mock::in_sequence(&sequence);
+        send_email_mock::in_sequence(&sequence);
+
+        welcome_user(42).unwrap();
+
+        sequence.assert_called_before("fetch_user_mock", "send_email_mock");
+        sequence.assert_order(&["fetch_user_mock", "send_email_mock"]);
+    }
+
+    #[test]
+    fn test_no_email_without_user() {
+        let sequence = Sequence::new();
+        fetch_user_mock::setup(|_| Err("not found".to_string()));
+        send_email_mock::setup(|_| Ok(()));
+        fetch_user_mock::in_sequence(&sequence);
+        send_email_mock::in_sequence(&sequence);
+
+        assert!(welcome_user(42).is_err());
+
+        sequence.assert_order(&["fetch_user_mock"]);
+    }
+
+    #[test]
+    fn test_first_calls_in_order_without_sequence() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+        send_email_mock::setup(|_| Ok(()));
+
+        welcome_user(42).unwrap();
+        welcome_user(7).unwrap();
+
+        fnmock::assert_call_order!([fetch_user_mock, send_email_mock]);
+        fnmock::assert_call_order!([super::onboarding::fetch_user_mock, super::onboarding::send_email_mock]);
+    }
+
+    #[test]
+    #[should_panic(expected = "but [\"send_email_mock\"] weren't called")]
+    fn test_call_order_fails_without_email() {
+        fetch_user_mock::setup(|_| Err("not found".to_string()));
+        send_email_mock::setup(|_| Ok(()));
+
+        let _ = welcome_user(42);
+
+        fnmock::assert_call_order!([fetch_user_mock, send_email_mock]);
+    }
+
+    #[test]
+    fn test_expectations_verified_on_drop() {
+        let _checkpoint = send_email_mock::verify_on_drop();
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+        send_email_mock::setup(|_| Ok(()));
+        send_email_mock::expect_times(1);
+        send_email_mock::expect_with("user_42".to_string(), "Welcome!".to_string());
+
+        welcome_user(42).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected send_email_mock mock to be called with")]
+    fn test_unmet_expectation_fails_verify() {
+        fetch_user_mock::setup(|_| Err("not found".to_string()));
+        send_email_mock::setup(|_| Ok(()));
+        send_email_mock::expect_with("user_42".to_string(), "Welcome!".to_string());
+
+        let _ = welcome_user(42);
+
+        send_email_mock::verify();
+    }
+}
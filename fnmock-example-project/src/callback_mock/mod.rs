@@ -0,0 +1,119 @@
+pub mod client {
+    use fnmock::derive::mock_function;
+
+    #[mock_function(forward = [op])]
+    pub fn retry(max_attempts: u32, mut op: impl FnMut() -> Result<String, String>) -> Result<String, String> {
+        // Real implementation
+        let mut result = op();
+        for _ in 1..max_attempts {
+            if result.is_ok() {
+                break;
+            }
+            result = op();
+        }
+        result
+    }
+
+    #[mock_function(forward = [on_progress])]
+    pub async fn download(url: String, on_progress: impl Fn(u32)) -> Vec<u8> {
+        // Real implementation
+        on_progress(100);
+        url.into_bytes()
+    }
+
+    #[mock_function(forward = [on_close])]
+    pub fn with_connection(host: String, on_close: impl FnOnce(String) -> usize) -> usize {
+        // Real implementation
+        on_close(host)
+    }
+}
+use client::{download, retry, with_connection};
+
+pub fn fetch_with_retry(url: &str) -> Result<String, String> {
+    let mut attempts = 0;
+    retry(3, || {
+        attempts += 1;
+        if attempts < 2 { Err("timeout".to_string()) } else { Ok(format!("{} after {} attempts", url, attempts)) }
+    })
+}
+
+pub async fn download_with_progress(url: &str) -> (Vec<u8>, Vec<u32>) {
+    let progress = std::cell::RefCell::new(Vec::new());
+    let data = download(url.to_string(), |percent| progress.borrow_mut().push(percent)).await;
+    (data, progress.into_inner())
+}
+
+pub fn close_connection(host: &str) -> usize {
+    with_connection(host.to_string(), |host| host.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::client::{download_mock, retry_mock, with_connection_mock};
+    use fnmock::derive::reset_doubles;
+
+    #[test]
+    fn test_real_implementations() {
+        assert_eq!(fetch_with_retry("example.com"), Ok("example.com after 2 attempts".to_string()));
+        assert_eq!(close_connection("db"), 2);
+    }
+
+    #[test]
+    fn test_forwarding_implementation_invokes_callback() {
+        retry_mock::setup_forwarding(|max_attempts, op| {
+            // Retry without backoff until the last attempt
+            (1..max_attempts).find_map(|_| op().ok()).ok_or_else(|| "gave up".to_string())
+        });
+
+        assert_eq!(fetch_with_retry("example.com"), Ok("example.com after 2 attempts".to_string()));
+
+        // The callback isn't recorded
+        retry_mock::assert_times(1);
+        retry_mock::assert_with(3);
+    }
+
+    #[test]
+    fn test_plain_setup_ignores_callback() {
+        retry_mock::setup(|_| Err("offline".to_string()));
+
+        assert_eq!(fetch_with_retry("example.com"), Err("offline".to_string()));
+        retry_mock::assert_with(3);
+    }
+
+    #[tokio::test]
+    async fn test_forwarding_async_callback() {
+        download_mock::setup_forwarding(|url, on_progress| {
+            on_progress(50);
+            on_progress(100);
+            url.into_bytes()
+        });
+
+        let (data, progress) = download_with_progress("file").await;
+
+        assert_eq!(data, b"file".to_vec());
+        assert_eq!(progress, vec![50, 100]);
+        download_mock::assert_last_call_with("file".to_string());
+    }
+
+    #[test]
+    fn test_forwarding_fn_once() {
+        with_connection_mock::setup_forwarding(|host, on_close| on_close(format!("{}:5432", host)));
+
+        assert_eq!(close_connection("db"), 7);
+        with_connection_mock::assert_with("db".to_string());
+    }
+
+    #[test]
+    #[reset_doubles]
+    fn test_clear_and_restore_forwarding_implementation() {
+        retry_mock::setup_forwarding(|_, op| op());
+        let snapshot = retry_mock::snapshot();
+
+        retry_mock::clear();
+        assert!(retry_mock::forwarding_implementation().is_none());
+
+        retry_mock::restore(snapshot);
+        assert_eq!(fetch_with_retry("example.com"), Err("timeout".to_string()));
+    }
+}
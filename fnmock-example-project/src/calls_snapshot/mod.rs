@@ -0,0 +1,65 @@
+pub mod directory {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub fn fetch_user(id: u32) -> Result<String, String> {
+        // Real implementation
+        Err(format!("directory unavailable for user {}", id))
+    }
+
+    #[mock_function]
+    pub fn store_user(id: u32, name: String, retries: u8) -> Result<(), String> {
+        // Real implementation
+        println!("Storing user {} as {} ({} retries)", id, name, retries);
+        Ok(())
+    }
+}
+
+use directory::{fetch_user, store_user};
+
+/// Copies the users from the directory, retrying failed lookups once
+pub fn sync_users(ids: &[u32]) -> usize {
+    let mut synced = 0;
+    for &id in ids {
+        let user = fetch_user(id).or_else(|_| fetch_user(id));
+        if let Ok(name) = user {
+            if store_user(id, name, 1).is_ok() {
+                synced += 1;
+            }
+        }
+    }
+    synced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::directory::{fetch_user_mock, store_user_mock};
+
+    #[test]
+    fn test_sync_users() {
+        fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+        store_user_mock::setup(|_| Ok(()));
+
+        assert_eq!(sync_users(&[42, 7]), 2);
+
+        fnmock::assert_calls_snapshot!(fetch_user_mock);
+        fnmock::assert_calls_snapshot!(store_user_mock, "stored_users");
+    }
+
+    #[test]
+    fn test_failed_lookups_are_retried() {
+        fetch_user_mock::setup_returns_sequence(vec![
+            Err("timeout".to_string()),
+            Ok("alice".to_string()),
+            Err("timeout".to_string()),
+            Err("not found".to_string()),
+        ]);
+        store_user_mock::setup(|_| Ok(()));
+
+        assert_eq!(sync_users(&[1, 2]), 1);
+
+        fnmock::assert_calls_snapshot!(fetch_user_mock, "fetch_user_with_retries");
+        fnmock::assert_calls_snapshot!(super::directory::store_user_mock, "store_user_with_retries");
+    }
+}
@@ -0,0 +1,72 @@
+// The cfg attributes are placed below the macro attributes, so the macros see them
+// and have to add them to every generated item
+pub mod platform {
+    use fnmock::derive::{mock_function, stub_function};
+
+    /// Directory of the configuration on unix systems.
+    #[mock_function]
+    #[cfg(unix)]
+    pub fn config_dir(app: String) -> String {
+        // Real implementation
+        format!("/etc/{}", app)
+    }
+
+    /// Directory of the configuration on other systems.
+    #[mock_function]
+    #[cfg(not(unix))]
+    pub fn config_dir(app: String) -> String {
+        // Real implementation
+        format!("C:\\ProgramData\\{}", app)
+    }
+
+    #[stub_function]
+    #[cfg(unix)]
+    pub fn line_ending() -> String {
+        // Real implementation
+        "\n".to_string()
+    }
+
+    #[stub_function]
+    #[cfg(not(unix))]
+    pub fn line_ending() -> String {
+        // Real implementation
+        "\r\n".to_string()
+    }
+
+    // Never compiled, so neither the function nor its fake module exist
+    #[fnmock::derive::fake_function]
+    #[cfg(any())]
+    pub fn unsupported_platform() -> bool {
+        true
+    }
+}
+use platform::{config_dir, line_ending};
+
+pub fn config_file(app: &str) -> String {
+    format!("{}/config{}", config_dir(app.to_string()), line_ending())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::platform::{config_dir_mock, line_ending_stub};
+
+    #[test]
+    fn test_doubles_of_the_compiled_variant() {
+        config_dir_mock::setup(|app| format!("/tmp/{}", app));
+        line_ending_stub::setup(String::new());
+
+        assert_eq!(config_file("fnmock"), "/tmp/fnmock/config");
+        config_dir_mock::assert_with("fnmock".to_string());
+    }
+
+    #[test]
+    fn test_gated_out_functions_have_no_manifest_entry() {
+        let names: Vec<&str> = fnmock::manifest::entries_in("fnmock_example_project::cfg_gated")
+            .iter()
+            .map(|entry| entry.name)
+            .collect();
+
+        assert_eq!(names, vec!["config_dir", "line_ending"]);
+    }
+}
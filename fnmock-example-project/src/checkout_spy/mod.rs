@@ -0,0 +1,57 @@
+pub mod pricing {
+    use fnmock::derive::spy_function;
+
+    #[spy_function]
+    pub fn apply_discount(price: u32, percent: u32) -> Result<u32, String> {
+        if percent > 100 {
+            return Err(format!("invalid discount {}", percent));
+        }
+        Ok(price - price * percent / 100)
+    }
+
+    #[spy_function]
+    pub async fn shipping_cost(weight: u32) -> u32 {
+        tokio::task::yield_now().await;
+        weight * 2
+    }
+}
+
+use pricing::{apply_discount, shipping_cost};
+
+pub async fn checkout(price: u32, percent: u32, weight: u32) -> Result<u32, String> {
+    let discounted = apply_discount(price, percent)?;
+    Ok(discounted + shipping_cost(weight).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::pricing::{apply_discount_spy, shipping_cost_spy};
+
+    #[tokio::test]
+    async fn test_spy_runs_the_real_implementation() {
+        assert_eq!(checkout(200, 10, 3).await, Ok(186));
+
+        apply_discount_spy::assert_with(200, 10);
+        apply_discount_spy::assert_returned(Ok(180));
+        shipping_cost_spy::assert_times(1);
+        assert_eq!(shipping_cost_spy::results(), vec![6]);
+    }
+
+    #[tokio::test]
+    async fn test_spy_records_early_returns() {
+        assert_eq!(checkout(200, 150, 3).await, Err("invalid discount 150".to_string()));
+
+        assert_eq!(apply_discount_spy::last_call_params(), Some((200, 150)));
+        apply_discount_spy::assert_returned(Err("invalid discount 150".to_string()));
+        shipping_cost_spy::assert_times(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected apply_discount_spy spy to return Ok(100), returned [Ok(90)]")]
+    fn test_assert_returned_fails_for_other_values() {
+        apply_discount(100, 10).unwrap();
+
+        apply_discount_spy::assert_returned(Ok(100));
+    }
+}
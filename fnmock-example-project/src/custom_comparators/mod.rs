@@ -0,0 +1,106 @@
+pub mod store {
+    use fnmock::derive::mock_function;
+
+    // Doesn't implement PartialEq, and the connection id differs for every connection
+    #[derive(Clone, Debug)]
+    pub struct Database {
+        pub url: String,
+        pub connection_id: u32,
+    }
+
+    impl Database {
+        pub fn connect(url: &str) -> Self {
+            static NEXT_ID: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+            Self { url: url.to_string(), connection_id: NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst) }
+        }
+    }
+
+    #[mock_function(compare(db = "|a, b| a.url == b.url"))]
+    pub fn save_order(db: Database, order_id: u32) -> Result<(), String> {
+        // Real implementation
+        println!("Saving order {} to {} (connection {})", order_id, db.url, db.connection_id);
+        Ok(())
+    }
+
+    // Implements neither Debug nor PartialEq
+    #[derive(Clone)]
+    pub struct Cache {
+        pub name: String,
+        pub capacity: usize,
+    }
+
+    // Only the name of the cache is recorded for the assertions
+    #[mock_function(project(cache = "cache.name.clone()"))]
+    pub fn warm_up(cache: Cache, keys: Vec<String>) -> usize {
+        // Real implementation
+        println!("Warming up {} with {} keys", cache.name, keys.len());
+        keys.len().min(cache.capacity)
+    }
+}
+use store::{save_order, warm_up, Cache, Database};
+
+/// Saves the orders, connecting to the database for every order.
+pub fn save_orders(url: &str, order_ids: &[u32]) -> Result<(), String> {
+    order_ids.iter().try_for_each(|order_id| save_order(Database::connect(url), *order_id))
+}
+
+/// Warms up the product cache, returning the number of cached products.
+pub fn warm_up_products(capacity: usize, products: &[&str]) -> usize {
+    let cache = Cache { name: "products".to_string(), capacity };
+    warm_up(cache, products.iter().map(|product| product.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::store::{save_order_mock, warm_up_mock};
+
+    #[test]
+    fn test_orders_are_saved_to_the_database() {
+        save_order_mock::setup(|_| Ok(()));
+
+        save_orders("postgres://orders", &[1, 2]).unwrap();
+
+        // The connection ids differ, but the databases are compared by their url
+        save_order_mock::assert_with(Database::connect("postgres://orders"), 1);
+        save_order_mock::assert_with(Database::connect("postgres://orders"), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected save_order_mock mock to be called with (Database { url: \"postgres://users\"")]
+    fn test_other_database_fails() {
+        save_order_mock::setup(|_| Ok(()));
+
+        save_orders("postgres://orders", &[1]).unwrap();
+
+        save_order_mock::assert_with(Database::connect("postgres://users"), 1);
+    }
+
+    #[test]
+    fn test_implementation_receives_the_database() {
+        save_order_mock::setup(|(db, _)| if db.url.starts_with("postgres://") { Ok(()) } else { Err("unsupported".to_string()) });
+
+        assert_eq!(save_orders("mysql://orders", &[1]), Err("unsupported".to_string()));
+    }
+
+    #[test]
+    fn test_projected_cache_is_compared_by_name() {
+        // The implementation still receives the cache
+        warm_up_mock::setup(|(cache, keys)| keys.len().min(cache.capacity));
+
+        assert_eq!(warm_up_products(1, &["book", "pen"]), 1);
+
+        let other_cache = Cache { name: "products".to_string(), capacity: 100 };
+        warm_up_mock::assert_with(other_cache, vec!["book".to_string(), "pen".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "[0] (\"products\", [\"book\"])")]
+    fn test_failure_message_formats_the_projection() {
+        warm_up_mock::setup(|_| 0);
+
+        warm_up_products(10, &["book"]);
+
+        warm_up_mock::assert_with(Cache { name: "orders".to_string(), capacity: 10 }, vec!["book".to_string()]);
+    }
+}
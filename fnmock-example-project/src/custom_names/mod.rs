@@ -0,0 +1,44 @@
+pub mod shop {
+    use fnmock::derive::{mock_function, stub_function};
+
+    // The generated modules are named `price_api` and `tax_config` instead of `fetch_price_mock` / `tax_rate_stub`
+    #[mock_function(name = "price_api")]
+    pub fn fetch_price(item: String) -> Result<u32, String> {
+        // Real implementation
+        Ok(item.len() as u32 * 100)
+    }
+
+    #[stub_function(name = "tax_config")]
+    pub fn tax_rate() -> u32 {
+        // Real implementation
+        20
+    }
+}
+
+use shop::{fetch_price, tax_rate};
+
+pub fn gross_price(item: &str) -> Result<u32, String> {
+    let net = fetch_price(item.to_string())?;
+    Ok(net + net * tax_rate() / 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::shop::{price_api, tax_config};
+
+    #[test]
+    fn test_doubles_with_custom_names() {
+        price_api::setup(|_| Ok(1000));
+        tax_config::setup(10);
+
+        assert_eq!(gross_price("book"), Ok(1100));
+        price_api::assert_with("book".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "price_api mock not initialized")]
+    fn test_failures_use_the_custom_name() {
+        price_api::call("book".to_string()).unwrap();
+    }
+}
@@ -0,0 +1,43 @@
+// The doubles are compiled for a custom cfg predicate instead of `cfg(test)`,
+// so they also exist in debug builds of the binary (e.g. to simulate failures by hand)
+pub mod payment {
+    use fnmock::derive::{fake_function, mock_function};
+
+    #[mock_function(cfg = "debug_assertions")]
+    pub fn authorize(card: String, amount: u32) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("auth_{}_{}", card, amount))
+    }
+
+    #[fake_function(cfg = "any(test, debug_assertions)")]
+    pub fn exchange_rate(currency: &str) -> f64 {
+        if currency == "USD" { 1.1 } else { 1.0 }
+    }
+}
+
+use payment::{authorize, exchange_rate};
+
+pub fn pay(card: &str, amount: u32, currency: &str) -> Result<String, String> {
+    let converted = (amount as f64 * exchange_rate(currency)).round() as u32;
+    authorize(card.to_string(), converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::payment::{authorize_mock, exchange_rate_fake};
+
+    #[test]
+    fn test_doubles_with_custom_cfg() {
+        authorize_mock::setup(|(_, amount)| Err(format!("declined {}", amount)));
+        exchange_rate_fake::setup(|_| 2.0);
+
+        assert_eq!(pay("4242", 50, "USD"), Err("declined 100".to_string()));
+        authorize_mock::assert_with("4242".to_string(), 100);
+    }
+
+    #[test]
+    fn test_original_implementation_without_setup() {
+        assert_eq!(pay("4242", 100, "USD"), Ok("auth_4242_110".to_string()));
+    }
+}
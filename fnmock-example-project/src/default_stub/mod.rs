@@ -0,0 +1,58 @@
+pub mod store {
+    use fnmock::derive::stub_function;
+
+    // Most tests don't care about the recommendations, so they are empty unless set up
+    #[stub_function(default)]
+    pub fn related_products(id: u32) -> Vec<String> {
+        // Real implementation that queries the recommendation service
+        vec![format!("accessory_{}", id)]
+    }
+
+    #[stub_function(default, keyed)]
+    pub fn stock(id: u32) -> u32 {
+        // Real implementation that queries the warehouse
+        id * 10
+    }
+}
+
+use store::{related_products, stock};
+
+pub fn product_page(id: u32) -> String {
+    let availability = match stock(id) {
+        0 => "sold out".to_string(),
+        count => format!("{} in stock", count),
+    };
+
+    let related = related_products(id);
+    if related.is_empty() {
+        format!("product {} ({})", id, availability)
+    } else {
+        format!("product {} ({}), see also: {}", id, availability, related.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::store::{related_products_stub, stock_stub};
+
+    #[test]
+    fn test_unset_stubs_return_the_default() {
+        assert_eq!(product_page(1), "product 1 (sold out)");
+    }
+
+    #[test]
+    fn test_set_up_values_are_returned() {
+        related_products_stub::setup(vec!["case".to_string()]);
+        stock_stub::setup_for(1, 3);
+
+        assert_eq!(product_page(1), "product 1 (3 in stock), see also: case");
+        assert_eq!(product_page(2), "product 2 (sold out), see also: case");
+    }
+
+    #[test]
+    fn test_get_return_value_returns_the_default() {
+        assert!(!related_products_stub::is_set());
+        assert_eq!(related_products_stub::get_return_value(), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,101 @@
+pub mod geometry {
+    use fnmock::derive::{fake_function, mock_function};
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Rect {
+        pub width: u32,
+        pub height: u32,
+        pub label: String,
+    }
+
+    // Destructured params are recorded by their synthetic names (`param_0`, ...)
+    #[mock_function]
+    pub fn area((width, height): (u32, u32)) -> u32 {
+        width * height
+    }
+
+    #[mock_function(ignore = [param_1])]
+    pub fn scale(Rect { width, height, .. }: Rect, (factor, _): (u32, bool)) -> (u32, u32) {
+        (width * factor, height * factor)
+    }
+
+    // `mut` params keep their name, `_` params are named `param_<index>`
+    #[mock_function]
+    pub fn round_to_grid(mut length: u32, grid: u32, _: String) -> u32 {
+        length += grid / 2;
+        length - length % grid
+    }
+
+    #[fake_function]
+    pub fn perimeter(Rect { width, height, .. }: Rect) -> u32 {
+        2 * (width + height)
+    }
+}
+use geometry::{area, perimeter, round_to_grid, scale, Rect};
+
+/// Describes the size of the rectangle.
+pub fn describe(rect: Rect) -> String {
+    format!(
+        "{}: area {}, perimeter {}",
+        rect.label,
+        area((rect.width, rect.height)),
+        perimeter(rect.clone())
+    )
+}
+
+/// Doubles the size of the rectangle.
+pub fn double_size(rect: Rect) -> (u32, u32) {
+    scale(rect, (2, true))
+}
+
+/// Rounds the size of the rectangle to the grid.
+pub fn snap_to_grid(rect: Rect, grid: u32) -> (u32, u32) {
+    (round_to_grid(rect.width, grid, rect.label.clone()), round_to_grid(rect.height, grid, rect.label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::geometry::{area_mock, perimeter_fake, round_to_grid_mock, scale_mock};
+
+    fn rect() -> Rect {
+        Rect { width: 3, height: 4, label: "door".to_string() }
+    }
+
+    #[test]
+    fn test_describe() {
+        area_mock::setup(|(width, height)| width * height * 100);
+        perimeter_fake::setup(|rect| rect.width + rect.height);
+
+        assert_eq!(describe(rect()), "door: area 1200, perimeter 7");
+
+        area_mock::assert_with((3, 4));
+    }
+
+    #[test]
+    fn test_double_size() {
+        scale_mock::setup(|rect| (rect.width, rect.height));
+
+        assert_eq!(double_size(rect()), (3, 4));
+
+        scale_mock::assert_with(rect());
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        round_to_grid_mock::setup(|(length, grid, _)| length * grid);
+
+        assert_eq!(snap_to_grid(rect(), 10), (30, 40));
+
+        round_to_grid_mock::assert_with(3, 10, "door".to_string());
+        round_to_grid_mock::assert_with_matchers(fnmock::matchers::eq(4), fnmock::matchers::any(), fnmock::matchers::any());
+    }
+
+    #[test]
+    fn test_original_implementations() {
+        assert_eq!(snap_to_grid(rect(), 2), (4, 4));
+        assert_eq!(area((3, 4)), 12);
+        assert_eq!(perimeter(rect()), 14);
+        assert_eq!(double_size(rect()), (6, 8));
+    }
+}
@@ -0,0 +1,48 @@
+pub mod telemetry {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use fnmock::derive::dummy_function;
+
+    pub static SENT_METRICS: AtomicU64 = AtomicU64::new(0);
+
+    // Tests never send metrics, without setting up anything
+    #[dummy_function]
+    pub fn record_metric(name: &str, value: u64) {
+        // Real implementation that sends the metric
+        println!("{}={}", name, value);
+        SENT_METRICS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[dummy_function(returns = "Ok(())")]
+    pub fn flush_metrics() -> Result<(), String> {
+        // Real implementation
+        Err("metrics backend unreachable".to_string())
+    }
+
+    #[dummy_function]
+    pub fn active_sessions() -> u64 {
+        // Real implementation
+        SENT_METRICS.load(Ordering::SeqCst) + 1
+    }
+}
+
+use telemetry::{active_sessions, flush_metrics, record_metric};
+
+pub fn handle_request(path: &str) -> Result<String, String> {
+    record_metric(path, 1);
+    record_metric("sessions", active_sessions());
+    flush_metrics()?;
+    Ok(format!("handled {}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn test_dummies_need_no_setup() {
+        assert_eq!(handle_request("/home"), Ok("handled /home".to_string()));
+        assert_eq!(active_sessions(), 0);
+        assert_eq!(telemetry::SENT_METRICS.load(Ordering::SeqCst), 0);
+    }
+}
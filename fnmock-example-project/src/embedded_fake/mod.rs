@@ -0,0 +1,78 @@
+pub mod hal {
+    use fnmock::derive::fake_function;
+
+    // On the device these would access the hardware registers
+    #[fake_function]
+    pub async fn read_register(address: u8) -> u8 {
+        address
+    }
+
+    #[fake_function]
+    pub async fn write_register(address: u8, value: u8) -> Result<(), String> {
+        println!("Writing {} to register {}", value, address);
+        Ok(())
+    }
+}
+
+use hal::{read_register, write_register};
+
+pub async fn toggle_led(address: u8) -> Result<u8, String> {
+    let value = read_register(address).await ^ 1;
+    write_register(address, value).await?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::hal::{read_register_fake, write_register_fake};
+    use embassy_futures::{block_on, yield_now};
+
+    // The async fakes don't depend on tokio, so they work with embassy's executor
+    // (or any other executor polling on the test thread)
+    #[test]
+    fn test_with_async_fakes() {
+        read_register_fake::setup_async(|_| Box::pin(async {
+            yield_now().await;
+            0b1010
+        }));
+        write_register_fake::setup_async(|_, value| Box::pin(async move {
+            yield_now().await;
+            if value == 0b1011 { Ok(()) } else { Err("unexpected value".to_string()) }
+        }));
+
+        let result = block_on(toggle_led(3));
+
+        assert_eq!(result, Ok(0b1011));
+    }
+
+    #[test]
+    fn test_sync_setup_replaces_async_setup() {
+        read_register_fake::setup_async(|_| Box::pin(async { 1 }));
+        read_register_fake::setup(|_| 2);
+
+        assert!(!read_register_fake::is_async_set());
+        assert_eq!(block_on(read_register(7)), 2);
+    }
+
+    #[test]
+    fn test_clear_resets_async_fake() {
+        read_register_fake::setup_async(|_| Box::pin(async { 1 }));
+
+        read_register_fake::clear();
+
+        assert_eq!(block_on(read_register(7)), 7);
+    }
+
+    #[test]
+    fn test_restore_resets_async_fake() {
+        read_register_fake::setup_async(|_| Box::pin(async { 1 }));
+        let snapshot = read_register_fake::snapshot();
+
+        read_register_fake::setup(|_| 2);
+        read_register_fake::restore(snapshot);
+
+        assert!(read_register_fake::is_async_set());
+        assert_eq!(block_on(read_register(7)), 1);
+    }
+}
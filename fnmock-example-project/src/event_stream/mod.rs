@@ -0,0 +1,76 @@
+pub mod sensors {
+    use fnmock::derive::{fake_function, stub_function};
+    use futures::Stream;
+
+    #[stub_function]
+    pub fn read_temperatures(sensor_id: u32) -> impl Stream<Item = Result<f32, String>> {
+        // Real implementation
+        futures::stream::iter(vec![Ok(20.0 + sensor_id as f32)])
+    }
+
+    #[fake_function]
+    pub fn read_events(sensor_id: u32) -> impl Stream<Item = String> + Send {
+        // Real implementation
+        if sensor_id == 0 {
+            return futures::stream::iter(Vec::new());
+        }
+        futures::stream::iter(vec![format!("sensor {} online", sensor_id)])
+    }
+}
+use futures::StreamExt;
+use sensors::{read_events, read_temperatures};
+
+/// Averages the temperatures of the sensor until the first error.
+pub async fn average_temperature(sensor_id: u32) -> Result<f32, String> {
+    let temperatures: Vec<Result<f32, String>> = read_temperatures(sensor_id).collect().await;
+    let mut sum = 0.0;
+    for temperature in &temperatures {
+        sum += temperature.clone()?;
+    }
+    Ok(sum / temperatures.len() as f32)
+}
+
+/// Collects the events of the sensor in a spawned task.
+pub async fn collect_events(sensor_id: u32) -> Vec<String> {
+    let events = read_events(sensor_id);
+    tokio::spawn(events.collect()).await.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::sensors::{read_events_fake, read_temperatures_stub};
+    use fnmock::stream::{stub_stream, stub_stream_with_error};
+
+    #[tokio::test]
+    async fn test_stub_stream() {
+        read_temperatures_stub::setup(stub_stream(vec![Ok(20.0), Ok(22.0)]));
+
+        assert_eq!(average_temperature(1).await, Ok(21.0));
+        // Every call gets the full stream
+        assert_eq!(average_temperature(1).await, Ok(21.0));
+    }
+
+    #[tokio::test]
+    async fn test_stub_stream_with_terminal_error() {
+        read_temperatures_stub::setup(stub_stream_with_error(vec![20.0], "sensor offline".to_string()));
+
+        assert_eq!(average_temperature(1).await, Err("sensor offline".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_real_stream_without_stub() {
+        assert_eq!(average_temperature(1).await, Ok(21.0));
+        assert_eq!(collect_events(0).await, Vec::<String>::new());
+        assert_eq!(collect_events(3).await, vec!["sensor 3 online".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_fake_send_stream() {
+        read_events_fake::setup(|sensor_id| {
+            Box::pin(stub_stream(vec![format!("sensor {} online", sensor_id), "sensor overheated".to_string()]))
+        });
+
+        assert_eq!(collect_events(2).await, vec!["sensor 2 online".to_string(), "sensor overheated".to_string()]);
+    }
+}
@@ -0,0 +1,104 @@
+pub mod mailer {
+    use fnmock::derive::{mock_function, stub_function};
+
+    #[mock_function]
+    pub fn send_mail(to: String, subject: String) -> Result<(), String> {
+        // Real implementation
+        println!("Sending '{}' to {}", subject, to);
+        Ok(())
+    }
+
+    #[stub_function]
+    pub fn get_sender() -> String {
+        // Real implementation
+        "noreply@example.com".to_string()
+    }
+}
+use mailer::send_mail;
+
+pub fn send_welcome_mails(users: Vec<String>) -> usize {
+    users.into_iter()
+        .filter(|user| send_mail(user.clone(), "Welcome".to_string()).is_ok())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::mailer::{send_mail_mock, get_sender_stub};
+    use fnmock::failure::{collect_failures, FailureKind, VerificationFailure};
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_all_failed_assertions_are_reported_together() {
+        send_mail_mock::setup(|_| Ok(()));
+
+        send_welcome_mails(vec!["alice".to_string()]);
+
+        let failures = collect_failures(|| {
+            send_mail_mock::assert_times(2);
+            send_mail_mock::assert_with("bob".to_string(), "Welcome".to_string());
+            send_mail_mock::assert_with("alice".to_string(), "Welcome".to_string());
+        });
+
+        let messages: Vec<String> = failures.iter().map(|failure| failure.to_string()).collect();
+        assert_eq!(messages, vec![
+            "Expected send_mail_mock mock to be called 2 times, received 1".to_string(),
+            "Expected send_mail_mock mock to be called with (\"bob\", \"Welcome\")\n\
+             Recorded calls:\n  [0] (\"alice\", \"Welcome\")".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_missing_setup_is_reported_before_panicking() {
+        let failures = collect_failures(|| {
+            let result = std::panic::catch_unwind(get_sender_stub::get_return_value);
+            assert!(result.is_err());
+        });
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].double_name, "get_sender_stub");
+        assert_eq!(failures[0].kind, FailureKind::MissingSetup);
+    }
+
+    thread_local! {
+        static REPORTED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn report_to_framework(failure: VerificationFailure) {
+        REPORTED.with(|reported| reported.borrow_mut().push(format!("[{}] {}", failure.double_name, failure)));
+    }
+
+    #[test]
+    fn test_custom_failure_handler() {
+        fnmock::set_failure_handler(report_to_framework);
+        send_mail_mock::setup(|_| Err("offline".to_string()));
+
+        assert_eq!(send_welcome_mails(vec!["alice".to_string()]), 0);
+        send_mail_mock::assert_times(0);
+
+        fnmock::failure::reset_failure_handler();
+        REPORTED.with(|reported| assert_eq!(*reported.borrow(), vec![
+            "[send_mail_mock] Expected send_mail_mock mock to be called 0 times, received 1".to_string(),
+        ]));
+    }
+
+    fn check_welcome_mail(user: &str) -> Result<(), VerificationFailure> {
+        send_mail_mock::try_assert_times(1)?;
+        send_mail_mock::try_assert_with(user.to_string(), "Welcome".to_string())
+    }
+
+    #[test]
+    fn test_try_assert_returns_failures_to_helpers() {
+        use fnmock::matchers::{any, eq};
+        send_mail_mock::setup(|_| Ok(()));
+
+        send_welcome_mails(vec!["alice".to_string()]);
+
+        assert_eq!(check_welcome_mail("alice"), Ok(()));
+        let failure = check_welcome_mail("bob").unwrap_err();
+        assert_eq!(failure.kind, FailureKind::Assertion);
+        assert!(failure.message.starts_with("Expected send_mail_mock mock to be called with (\"bob\", \"Welcome\")"));
+        assert!(send_mail_mock::try_assert_with_matchers(any(), eq("Goodbye".to_string())).is_err());
+    }
+}
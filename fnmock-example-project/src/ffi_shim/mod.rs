@@ -0,0 +1,73 @@
+pub mod sensor {
+    use fnmock::derive::{fake_function, mock_function, stub_function};
+
+    // The ABI and `no_mangle` are kept, so C code can still call the shims
+    #[mock_function]
+    #[no_mangle]
+    pub extern "C" fn example_sensor_read(channel: u32) -> i32 {
+        // Real implementation talking to the driver
+        channel as i32 * 10
+    }
+
+    #[stub_function]
+    #[no_mangle]
+    pub extern "C" fn example_sensor_count() -> u32 {
+        // Real implementation
+        4
+    }
+
+    /// # Safety
+    ///
+    /// `buffer` must point to `len` readable bytes.
+    #[fake_function]
+    #[no_mangle]
+    pub unsafe extern "C" fn example_sensor_checksum(buffer: *const u8, len: usize) -> u32 {
+        // Real implementation
+        std::slice::from_raw_parts(buffer, len).iter().map(|byte| *byte as u32).sum()
+    }
+}
+
+use sensor::{example_sensor_checksum, example_sensor_count, example_sensor_read};
+
+pub fn read_all_channels() -> Vec<i32> {
+    (0..example_sensor_count()).map(|channel| example_sensor_read(channel)).collect()
+}
+
+pub fn checksum(data: &[u8]) -> u32 {
+    // SAFETY: the pointer and the length are taken from the same slice
+    unsafe { example_sensor_checksum(data.as_ptr(), data.len()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::sensor::{example_sensor_checksum_fake, example_sensor_count_stub, example_sensor_read_mock};
+
+    #[test]
+    fn test_extern_functions_are_doubled() {
+        example_sensor_count_stub::setup(2);
+        example_sensor_read_mock::setup(|channel| -(channel as i32));
+
+        let readings = read_all_channels();
+
+        assert_eq!(readings, vec![0, -1]);
+        example_sensor_read_mock::assert_times(2);
+        example_sensor_read_mock::assert_with(1);
+    }
+
+    #[test]
+    fn test_extern_functions_keep_their_abi() {
+        let read: extern "C" fn(u32) -> i32 = example_sensor_read;
+        let checksum_fn: unsafe extern "C" fn(*const u8, usize) -> u32 = example_sensor_checksum;
+
+        assert_eq!(read(1), 10);
+        assert_eq!(unsafe { checksum_fn([1, 2].as_ptr(), 2) }, 3);
+    }
+
+    #[test]
+    fn test_unsafe_extern_function_with_fake() {
+        example_sensor_checksum_fake::setup(|_, len| len as u32);
+
+        assert_eq!(checksum(&[1, 2, 3]), 3);
+    }
+}
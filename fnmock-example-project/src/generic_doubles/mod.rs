@@ -0,0 +1,92 @@
+pub mod codec {
+    use std::fmt::Display;
+    use std::str::FromStr;
+    use fnmock::derive::{fake_function, stub_function};
+
+    // The where clause is kept on the faked function
+    #[fake_function]
+    pub fn encode<T>(value: T) -> String
+    where
+        T: Display,
+    {
+        format!("<{}>", value)
+    }
+
+    #[fake_function]
+    pub async fn fetch_setting<T: FromStr>(key: String) -> Option<T> {
+        // Real implementation
+        match key.as_str() {
+            "retries" => "3".parse().ok(),
+            _ => None,
+        }
+    }
+
+    // Functions with only lifetime parameters are faked for all lifetimes
+    #[fake_function]
+    pub fn first_word<'a>(text: &'a str) -> &'a str {
+        text.split_whitespace().next().unwrap_or("")
+    }
+
+    // The bounds required for storing the return value (here `T: Clone + 'static`) are added automatically
+    #[stub_function]
+    pub fn default_of<T: Default>() -> T {
+        T::default()
+    }
+}
+
+use codec::{default_of, encode, fetch_setting, first_word};
+
+pub fn encode_pair(id: u32, name: &str) -> String {
+    format!("{}{}", encode(id), encode(name.to_string()))
+}
+
+pub async fn retries() -> u32 {
+    fetch_setting("retries".to_string()).await.unwrap_or(1)
+}
+
+pub fn command_of(line: &str) -> String {
+    first_word(line).to_uppercase()
+}
+
+pub fn empty_label() -> (String, u32) {
+    (default_of(), default_of())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::codec::{default_of_stub, encode_fake, fetch_setting_fake, first_word_fake};
+
+    #[test]
+    fn test_fake_is_used_for_the_configured_type() {
+        encode_fake::setup::<u32>(|id| format!("#{}", id));
+
+        assert_eq!(encode_pair(7, "alice"), "#7<alice>");
+        assert!(encode_fake::is_set::<u32>());
+        assert!(!encode_fake::is_set::<String>());
+    }
+
+    #[tokio::test]
+    async fn test_async_generic_fake() {
+        fetch_setting_fake::setup_async::<u32>(|_| Box::pin(async { Some(5) }));
+
+        assert_eq!(retries().await, 5);
+    }
+
+    #[test]
+    fn test_fake_with_lifetime_parameter() {
+        first_word_fake::setup(|line| line.split(',').next().unwrap());
+
+        assert_eq!(command_of("get,user 1"), "GET");
+    }
+
+    #[test]
+    fn test_stub_is_used_for_the_configured_type() {
+        default_of_stub::setup::<String>("unnamed".to_string());
+
+        assert_eq!(empty_label(), ("unnamed".to_string(), 0));
+
+        default_of_stub::clear();
+        assert_eq!(empty_label(), (String::new(), 0));
+    }
+}
@@ -0,0 +1,170 @@
+pub mod parser {
+    use std::str::FromStr;
+    use fnmock::derive::mock_function;
+
+    // The bounds required for recording (here `T: 'static`) are added automatically
+    #[mock_function]
+    pub fn parse<T: FromStr>(input: String) -> Option<T> {
+        input.parse().ok()
+    }
+
+    // The type parameter may also be returned directly
+    #[mock_function]
+    pub fn parse_strict<T: FromStr>(input: String) -> T {
+        match input.parse() {
+            Ok(value) => value,
+            Err(_) => panic!("invalid input {}", input),
+        }
+    }
+
+    pub trait Config {
+        fn load() -> Self;
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct DefaultConfig {
+        pub verbose: bool,
+    }
+
+    impl Config for DefaultConfig {
+        fn load() -> Self {
+            DefaultConfig { verbose: false }
+        }
+    }
+
+    // Default type parameters are kept on the function, but not on the generated proxy functions
+    #[allow(invalid_type_param_default)]
+    #[mock_function]
+    pub fn load<T: Config = DefaultConfig>() -> T {
+        T::load()
+    }
+
+    // Custom bounds replace the automatically added ones
+    #[mock_function(bounds = "T: Clone + std::fmt::Debug + PartialEq + 'static")]
+    pub fn describe<T: ToString>(value: T) -> String {
+        value.to_string()
+    }
+}
+
+use parser::{describe, load, parse, parse_strict, DefaultConfig};
+
+pub fn parse_port(input: String) -> u16 {
+    parse(input).unwrap_or(80)
+}
+
+pub fn parse_flag_and_port(flag: String, port: String) -> (bool, u16) {
+    (parse_strict(flag), parse_strict(port))
+}
+
+pub fn is_verbose() -> bool {
+    load::<DefaultConfig>().verbose
+}
+
+pub fn describe_all(values: Vec<u32>) -> Vec<String> {
+    values.into_iter().map(describe).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::parser::{describe_mock, load_mock, parse_mock, parse_strict_mock};
+
+    #[test]
+    fn test_mock_is_used_for_the_configured_type() {
+        parse_mock::setup::<u16>(|_| Some(8080));
+
+        assert_eq!(parse_port("not a port".to_string()), 8080);
+
+        parse_mock::assert_times::<u16>(1);
+        parse_mock::assert_with::<u16>("not a port".to_string());
+    }
+
+    #[test]
+    fn test_other_types_use_the_real_implementation() {
+        parse_mock::setup::<u16>(|_| Some(8080));
+
+        assert_eq!(parse::<i64>("-5".to_string()), Some(-5));
+
+        parse_mock::assert_times::<i64>(0);
+    }
+
+    #[test]
+    fn test_aliases_take_the_used_type_parameters() {
+        let params: parse_mock::Params = "8080".to_string();
+        let result: parse_mock::Return<u16> = Some(8080);
+
+        parse_mock::setup::<u16>(|_| Some(8080));
+
+        assert_eq!(parse::<u16>(params), result);
+    }
+
+    #[test]
+    fn test_returned_type_parameter_is_mocked_per_type() {
+        parse_strict_mock::setup::<bool>(|_| true);
+        parse_strict_mock::setup::<u16>(|_| 8080);
+
+        assert_eq!(parse_flag_and_port("yes".to_string(), "http".to_string()), (true, 8080));
+
+        parse_strict_mock::assert_with::<bool>("yes".to_string());
+        parse_strict_mock::assert_with::<u16>("http".to_string());
+        parse_strict_mock::assert_times::<u16>(1);
+    }
+
+    #[test]
+    fn test_clear_resets_all_types() {
+        parse_mock::setup::<u16>(|_| Some(8080));
+        parse_mock::setup::<i64>(|_| Some(1));
+
+        parse_mock::clear();
+
+        assert!(!parse_mock::is_set::<u16>());
+        assert!(!parse_mock::is_set::<i64>());
+    }
+
+    #[test]
+    fn test_restore_resets_all_types() {
+        parse_mock::setup::<u16>(|_| Some(8080));
+        let snapshot = parse_mock::snapshot();
+
+        parse_mock::setup::<u16>(|_| Some(1));
+        parse_mock::setup::<i64>(|_| Some(1));
+        parse_mock::restore(snapshot);
+
+        assert_eq!(parse_port("80".to_string()), 8080);
+        assert!(!parse_mock::is_set::<i64>());
+    }
+
+    #[test]
+    fn test_function_with_default_type_parameter() {
+        assert!(!is_verbose());
+
+        load_mock::setup::<DefaultConfig>(|_| DefaultConfig { verbose: true });
+
+        assert!(is_verbose());
+        load_mock::assert_times::<DefaultConfig>(1);
+    }
+
+    #[test]
+    fn test_generic_parameter_is_recorded() {
+        describe_mock::setup(|value: u32| format!("#{}", value));
+
+        let result = describe_all(vec![1, 2]);
+
+        assert_eq!(result, vec!["#1".to_string(), "#2".to_string()]);
+        describe_mock::assert_times::<u32>(2);
+        describe_mock::assert_with(2u32);
+    }
+
+    #[test]
+    fn test_hooks_are_registered_per_type() {
+        let seen = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        describe_mock::setup::<u32>(|value| format!("#{}", value));
+        let hook_seen = seen.clone();
+        describe_mock::on_call::<u32>(move |value| hook_seen.borrow_mut().push(*value));
+
+        describe_all(vec![1, 2]);
+        let _ = describe(3i64);
+
+        assert_eq!(*seen.borrow(), vec![1, 2]);
+    }
+}
@@ -24,9 +24,44 @@ pub mod db {
     }
 }
 
+pub mod infra {
+    use fnmock::derive::mock_function;
+
+    // Infrastructure types, which don't implement PartialEq
+    pub struct ConnectionPool;
+    pub struct RequestContext;
+
+    // Wildcards ignore every parameter following the naming convention
+    #[mock_function(ignore = [*_pool, ctx*])]
+    pub fn archive_order(db_pool: &ConnectionPool, cache_pool: &ConnectionPool, ctx: &RequestContext, order_id: u32) -> bool {
+        let _ = (db_pool, cache_pool, ctx);
+        order_id != 0
+    }
+
+    // Patterns don't have to match any parameter
+    #[mock_function(ignore = [*_pool, ctx*])]
+    pub fn order_total(order_id: u32) -> u64 {
+        order_id as u64 * 10
+    }
+}
+
+pub mod files {
+    use std::path::Path;
+    use fnmock::derive::mock_function;
+
+    // `impl Trait` params are ignored automatically, the signature stays as it is
+    #[mock_function]
+    pub fn load_file(path: impl AsRef<Path>, max_len: usize) -> Result<String, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Ok(content.chars().take(max_len).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::db::{save_user, save_user_mock, update_record, update_record_mock, delete_user, delete_user_mock};
+    use super::files::{load_file, load_file_mock};
+    use super::infra::{archive_order, archive_order_mock, order_total, order_total_mock, ConnectionPool, RequestContext};
 
     #[test]
     fn test_save_user_with_ignored_timestamp() {
@@ -84,6 +119,31 @@ mod tests {
         delete_user_mock::assert_with(123);
     }
 
+    // The Params and Return aliases name the types of the mock without re-deriving them
+    fn accept_user(params: save_user_mock::Params) -> save_user_mock::Return {
+        let (id, _name) = params;
+        if id == 0 { Err("invalid id".to_string()) } else { Ok(()) }
+    }
+
+    #[test]
+    fn test_params_and_return_aliases() {
+        save_user_mock::setup(accept_user);
+
+        let expected_calls: Vec<save_user_mock::Params> = vec![
+            (0, "Nobody".to_string()),
+            (7, "Carol".to_string()),
+        ];
+
+        for (id, name) in expected_calls.clone() {
+            let _ = save_user(id, name, 100);
+        }
+
+        for (id, name) in expected_calls {
+            save_user_mock::assert_with(id, name);
+        }
+        assert_eq!(save_user(0, "Nobody".to_string(), 100), Err("invalid id".to_string()));
+    }
+
     #[test]
     fn test_multiple_calls_with_different_ignored_values() {
         save_user_mock::setup(|_| Ok(()));
@@ -98,4 +158,42 @@ mod tests {
         // All three calls should match when checking with any timestamp (ignored)
         save_user_mock::assert_with(5, "Bob".to_string());
     }
+
+    #[test]
+    fn test_wildcard_patterns_ignore_matching_params() {
+        archive_order_mock::setup(|order_id| order_id == 7);
+        order_total_mock::setup(|order_id| order_id as u64);
+
+        assert!(archive_order(&ConnectionPool, &ConnectionPool, &RequestContext, 7));
+        assert_eq!(order_total(3), 3);
+
+        // Only the order id is recorded
+        archive_order_mock::assert_with(7);
+        order_total_mock::assert_with(3);
+    }
+
+    #[test]
+    fn test_impl_trait_params_are_ignored() {
+        load_file_mock::setup(|max_len| Ok("x".repeat(max_len)));
+
+        // Any type implementing AsRef<Path> can be passed
+        assert_eq!(load_file("config.toml", 3), Ok("xxx".to_string()));
+        assert_eq!(load_file(std::path::PathBuf::from("data.bin"), 1), Ok("x".to_string()));
+
+        load_file_mock::assert_times(2);
+        load_file_mock::assert_with(3);
+        load_file_mock::assert_with(1);
+    }
+
+    #[test]
+    fn test_partial_assert_skips_params_in_a_single_assertion() {
+        use fnmock::matchers::{any, eq};
+        update_record_mock::setup(|_| Ok(()));
+
+        let _ = update_record(42, "draft".to_string(), &[], 1000);
+
+        // Unlike `ignore`, `any()` only skips the value in this assertion
+        update_record_mock::assert_with_matchers(eq(42), any());
+        update_record_mock::assert_with_matchers(any(), eq("draft".to_string()));
+    }
 }
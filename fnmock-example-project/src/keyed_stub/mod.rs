@@ -0,0 +1,67 @@
+pub mod users {
+    use fnmock::derive::stub_function;
+
+    #[stub_function(keyed)]
+    pub fn get_user_name(id: u32) -> String {
+        // Real implementation
+        format!("user_{}", id)
+    }
+
+    #[stub_function(keyed)]
+    pub fn get_permission(id: u32, resource: String) -> bool {
+        // Real implementation
+        id == 0 && !resource.is_empty()
+    }
+}
+use users::{get_permission, get_user_name};
+
+pub fn greet_with_access(id: u32, resource: &str) -> String {
+    if get_permission(id, resource.to_string()) {
+        format!("Welcome {}", get_user_name(id))
+    } else {
+        format!("Access denied for {}", get_user_name(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::users::{get_permission_stub, get_user_name_stub};
+
+    #[test]
+    fn test_keyed_return_values() {
+        get_user_name_stub::setup_for(42, "alice".to_string());
+        get_user_name_stub::setup_for(7, "bob".to_string());
+        get_permission_stub::setup_for((42, "files".to_string()), true);
+        get_permission_stub::setup(false);
+
+        assert_eq!(greet_with_access(42, "files"), "Welcome alice");
+        assert_eq!(greet_with_access(42, "mail"), "Access denied for alice");
+        assert_eq!(greet_with_access(7, "files"), "Access denied for bob");
+    }
+
+    #[test]
+    fn test_setup_is_the_fallback() {
+        get_user_name_stub::setup_for(42, "alice".to_string());
+        get_user_name_stub::setup("unknown".to_string());
+
+        assert_eq!(get_user_name(1), "unknown");
+    }
+
+    #[test]
+    #[should_panic(expected = "get_user_name_stub stub not initialized for these parameters, set up for [42]")]
+    fn test_missing_key_without_fallback_panics() {
+        get_user_name_stub::setup_for(42, "alice".to_string());
+
+        get_user_name(1);
+    }
+
+    #[test]
+    fn test_clear_resets_keyed_return_values() {
+        get_user_name_stub::setup_for(42, "alice".to_string());
+
+        get_user_name_stub::clear();
+
+        assert_eq!(get_user_name(42), "user_42");
+    }
+}
@@ -0,0 +1,78 @@
+pub mod backend {
+    use fnmock::derive::{fake_function, mock_function};
+
+    #[mock_function]
+    pub async fn fetch_stock(sku: String) -> Result<u32, String> {
+        // Real implementation
+        Ok(sku.len() as u32)
+    }
+
+    #[fake_function]
+    pub fn lookup_price(sku: String) -> u32 {
+        // Real implementation
+        sku.len() as u32 * 100
+    }
+}
+use backend::{fetch_stock, lookup_price};
+use std::time::{Duration, Instant};
+
+/// Fetches the stock of a product, reporting a timeout if the backend is too slow.
+pub async fn stock_or_timeout(sku: &str, timeout: Duration) -> Result<u32, String> {
+    tokio::time::timeout(timeout, fetch_stock(sku.to_string()))
+        .await
+        .unwrap_or_else(|_| Err("timeout".to_string()))
+}
+
+/// Looks up the price and reports whether the lookup was slower than `budget`.
+pub fn price_within_budget(sku: &str, budget: Duration) -> (u32, bool) {
+    let start = Instant::now();
+    let price = lookup_price(sku.to_string());
+    (price, start.elapsed() <= budget)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::backend::{fetch_stock_mock, lookup_price_fake};
+
+    #[tokio::test]
+    async fn test_slow_backend_times_out() {
+        fetch_stock_mock::setup_with_delay(Duration::from_millis(200), |_| Ok(3));
+
+        let result = stock_or_timeout("book", Duration::from_millis(10)).await;
+
+        assert_eq!(result, Err("timeout".to_string()));
+        fetch_stock_mock::assert_times(1);
+    }
+
+    #[tokio::test]
+    async fn test_fast_backend_returns_stock() {
+        fetch_stock_mock::setup_with_delay(Duration::from_millis(10), |_| Ok(3));
+
+        let result = stock_or_timeout("book", Duration::from_secs(10)).await;
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_delays_follow_paused_time() {
+        // The delay and the timeout use the same paused clock, so the race doesn't depend on the real time
+        fetch_stock_mock::setup_with_delay(Duration::from_millis(10), |_| Ok(3));
+        assert_eq!(stock_or_timeout("book", Duration::from_secs(1)).await, Ok(3));
+
+        fetch_stock_mock::setup_with_delay(Duration::from_secs(60), |_| Ok(3));
+        assert_eq!(stock_or_timeout("book", Duration::from_secs(1)).await, Err("timeout".to_string()));
+        // Completes instantly, since the paused clock is advanced automatically
+        assert_eq!(stock_or_timeout("book", Duration::from_secs(120)).await, Ok(3));
+    }
+
+    #[test]
+    fn test_slow_price_lookup_exceeds_budget() {
+        lookup_price_fake::setup_with_delay(Duration::from_millis(50), |_| 500);
+
+        let (price, within_budget) = price_within_budget("book", Duration::from_millis(20));
+
+        assert_eq!(price, 500);
+        assert!(!within_budget);
+    }
+}
@@ -0,0 +1,7 @@
+//! Library target of the example project, used by the integration tests in `tests/`.
+//!
+//! The dev-dependency on fnmock enables the `test-doubles` feature,
+//! so the doubles of this library can be configured from the integration tests.
+
+pub mod warehouse;
+pub mod sandbox;
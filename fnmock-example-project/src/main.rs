@@ -6,9 +6,67 @@ mod async_fake;
 mod async_stub;
 mod async_mock;
 mod ignore_mock;
+mod generic_mock;
+mod embedded_fake;
+mod snapshot_restore;
+mod keyed_stub;
+mod traitify_repository;
+mod panic_supervisor;
+mod manifest_audit;
+mod parameterized_cases;
+mod call_hooks;
+mod watchdog;
+mod partial_mock;
+mod cfg_gated;
+mod failure_handler;
+mod event_stream;
+mod runtime_agnostic;
+mod callback_mock;
+mod caching_layer;
+mod background_task;
+mod scoped_setup;
+mod retry_loop;
+mod call_order;
+mod test_scope;
+mod shared_workers;
+mod debug_doubles;
+mod owned_params;
+mod module_visibility;
+mod custom_names;
+mod default_stub;
+mod checkout_spy;
+mod dummy_metrics;
+mod trait_mock;
+mod method_mock;
+mod ffi_shim;
+mod generic_doubles;
+mod named_params;
+mod argument_captor;
+mod task_local_mock;
+mod latency_simulation;
+mod result_shortcuts;
+mod no_debug_params;
+mod custom_comparators;
+mod call_counting;
+mod mocked_module;
+mod destructured_params;
+mod storage_backends;
+mod one_shot_stub;
+mod stub_factory;
+mod return_queue;
+mod when_dsl;
+mod calls_snapshot;
+mod arbitrary_returns;
+mod spy_timing;
+mod use_statements;
 
 fn main() {
     println!("=== fnmock Example Project ===");
+
+    // Staging builds (`--features sandbox`) replace the payment provider with a fake
+    #[cfg(feature = "sandbox")]
+    fnmock_example_project::sandbox::enable_sandbox();
+    let _ = fnmock_example_project::sandbox::checkout("alice", 100);
     
     // Call example functions to avoid unused warnings
     let _ = basic_mock::db::fetch_user(1);
@@ -17,15 +75,17 @@ fn main() {
     let _ = mock_and_fake::db::fetch_user(1);
     let _ = mock_and_fake::db::fetch_notes(1);
     let _ = mock_and_fake::handle_user(1);
+    let _ = mock_and_fake::register_users(&["alice"]);
     
     let _ = inline_mock::fetch_user(1);
     inline_mock::handle_user(1);
     
     let _ = basic_stub::config::get_config();
     let _ = basic_stub::process_config();
+    let _ = basic_stub::banner(200);
     
     // Async functions
-    let _ = tokio::runtime::Runtime::new().unwrap().block_on(async {
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
         let _ = async_fake::db::fetch_user(1).await;
         let _ = async_fake::handle_user(1).await;
         
@@ -34,9 +94,118 @@ fn main() {
         
         let _ = async_mock::db::fetch_user(1).await;
         async_mock::handle_user(1).await;
+        let _ = async_mock::fetch_user_or_cached(1, 1).await;
+
+        let _ = watchdog::read_sensor_with_timeout(1, std::time::Duration::from_secs(1)).await;
+        let _ = partial_mock::total_with_fee(100).await;
+
+        let _ = embedded_fake::toggle_led(1).await;
+
+        let _ = event_stream::average_temperature(1).await;
+        let _ = event_stream::collect_events(1).await;
+        let _ = runtime_agnostic::price_with_tax("book").await;
+        let _ = checkout_spy::checkout(100, 10, 1).await;
+        let _ = callback_mock::download_with_progress("file").await;
+        let _ = background_task::start_reporter(vec![("cpu".to_string(), 1)], std::time::Duration::ZERO).await;
+
+        let _ = traitify_repository::rename_user(&traitify_repository::RealUserRepository, 0, "admin").await;
+        let _ = trait_mock::charge_or_refund(&trait_mock::CardGateway, 1, 100, false).await;
+        let _ = method_mock::sync_profile(&method_mock::HttpClient::new("localhost").with_base_url("example.com".to_string()), 1).await;
+        let _ = generic_doubles::retries().await;
+
+        let _ = shared_workers::portfolio_value(&["ACME"]).await;
+        let _ = task_local_mock::convert(1.0, "USD").await;
+        let _ = latency_simulation::stock_or_timeout("book", std::time::Duration::from_secs(1)).await;
+        let _ = result_shortcuts::withdraw_within_limit(1, 100).await;
+        let _ = storage_backends::total_fees(&["EUR"]).await;
     });
     
     let _ = ignore_mock::db::save_user(1, "test".to_string(), 0);
     let _ = ignore_mock::db::update_record(1, "test".to_string(), &[1, 2], 0);
     let _ = ignore_mock::db::delete_user(1);
+    let _ = ignore_mock::files::load_file("Cargo.toml", 16);
+    let _ = ignore_mock::infra::archive_order(&ignore_mock::infra::ConnectionPool, &ignore_mock::infra::ConnectionPool, &ignore_mock::infra::RequestContext, 1);
+    let _ = ignore_mock::infra::order_total(1);
+
+    let _ = generic_mock::parse_port("8080".to_string());
+    let _ = generic_mock::describe_all(vec![1, 2]);
+    let _ = generic_mock::is_verbose();
+    let _ = generic_mock::parse_flag_and_port("true".to_string(), "8080".to_string());
+
+    let _ = snapshot_restore::describe_balance(1);
+
+    let _ = keyed_stub::greet_with_access(1, "files");
+
+    let _ = panic_supervisor::run_jobs(vec![1, 2]);
+
+    let _ = manifest_audit::repository::save_order(1, vec!["book".to_string()]);
+    let _ = manifest_audit::repository::format_order(1, &["book".to_string()]);
+    let _ = manifest_audit::repository::get_currency();
+    let _ = parameterized_cases::checkout(1, 100);
+
+    let _ = call_hooks::notify_all(vec![1, 2], "maintenance");
+
+    let _ = watchdog::ping_with_watchdog("10.0.0.1", std::time::Duration::from_secs(1));
+
+    let _ = failure_handler::send_welcome_mails(vec!["alice".to_string()]);
+    let _ = failure_handler::mailer::get_sender();
+
+    let _ = callback_mock::fetch_with_retry("example.com");
+    let _ = callback_mock::close_connection("db");
+
+    let _ = caching_layer::ProfileCache::default().get(1, "en");
+
+    let _ = partial_mock::convert(1.0, "EUR", "USD");
+
+    let _ = ffi_shim::read_all_channels();
+    let _ = ffi_shim::checksum(&[1, 2]);
+
+    let _ = generic_doubles::encode_pair(1, "alice");
+    let _ = generic_doubles::command_of("get user");
+    let _ = generic_doubles::empty_label();
+
+    let _ = named_params::notify_overdue("alice", 1);
+
+    let _ = argument_captor::record_orders(&[1]);
+    let _ = latency_simulation::price_within_budget("book", std::time::Duration::from_secs(1));
+    let _ = no_debug_params::count_rows("orders_db", "orders");
+    let _ = custom_comparators::save_orders("postgres://orders", &[1]);
+    let _ = custom_comparators::warm_up_products(10, &["book"]);
+    let _ = call_counting::checksum(&[1, 2, 3], 2);
+    let _ = destructured_params::double_size(destructured_params::geometry::Rect { width: 1, height: 2, label: "door".to_string() });
+    let _ = destructured_params::snap_to_grid(destructured_params::geometry::Rect { width: 1, height: 2, label: "door".to_string() }, 10);
+    let _ = destructured_params::describe(destructured_params::geometry::Rect { width: 1, height: 2, label: "door".to_string() });
+    let _ = mocked_module::weather_report(&mocked_module::HttpClient { timeout_secs: 1 }, "Berlin");
+
+    let _ = cfg_gated::config_file("fnmock");
+
+    let _ = scoped_setup::check_service("db");
+    let _ = retry_loop::fetch_with_retries("example.com", 3);
+    let _ = call_order::welcome_user(1);
+    let _ = test_scope::checkout("alice", 1);
+    let _ = shared_workers::upload_file("file", 1);
+    let _ = storage_backends::service_endpoints(&["auth"]);
+    let _ = one_shot_stub::collect_messages("orders");
+    let _ = stub_factory::place_orders(1);
+    let _ = return_queue::run_all(&["resize"]);
+    let _ = when_dsl::promote(42);
+    let _ = calls_snapshot::sync_users(&[42]);
+    let _ = arbitrary_returns::invoice_line("book", 2);
+    let _ = spy_timing::send_packets(&[("api", vec![1])]);
+    let _ = debug_doubles::pay("4242", 1, "EUR");
+    let _ = owned_params::send_message("localhost", "ping");
+    let _ = module_visibility::read_file(2);
+    let _ = module_visibility::disk::verified_block(0, 0);
+    let _ = custom_names::gross_price("book");
+    let _ = default_stub::product_page(1);
+    let _ = dummy_metrics::handle_request("/");
+    let _ = use_statements::greet_user(1);
+    let _ = use_statements::avatar_or_default(1);
+    let _ = use_statements::order_summary(1);
+    let _ = use_statements::offer(1);
+    let _ = use_statements::coupon_label("SALE");
+
+    for entry in manifest_audit::audit_repository() {
+        println!("{}", entry);
+    }
 }
@@ -0,0 +1,53 @@
+pub mod repository {
+    use fnmock::derive::{fake_function, mock_function, stub_function};
+
+    #[mock_function]
+    pub fn save_order(id: u32, items: Vec<String>) -> Result<(), String> {
+        // Real implementation
+        if items.is_empty() { Err(format!("order {} is empty", id)) } else { Ok(()) }
+    }
+
+    #[fake_function]
+    pub fn format_order(id: u32, items: &[String]) -> String {
+        // Real implementation
+        format!("#{}: {}", id, items.join(", "))
+    }
+
+    #[stub_function]
+    pub fn get_currency() -> String {
+        // Real implementation
+        "EUR".to_string()
+    }
+}
+
+/// Lists the doubled dependencies of the repository module, e.g. for a test coverage audit.
+pub fn audit_repository() -> Vec<String> {
+    fnmock::manifest::entries_in(module_path!())
+        .iter()
+        .map(|entry| entry.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fnmock::manifest::{find, DoubleKind};
+
+    #[test]
+    fn test_manifest_contains_doubled_functions() {
+        assert_eq!(audit_repository(), vec![
+            "fake\tfnmock_example_project::manifest_audit::repository::format_order\tfn format_order(id: u32, items: &[String]) -> String",
+            "stub\tfnmock_example_project::manifest_audit::repository::get_currency\tfn get_currency() -> String",
+            "mock\tfnmock_example_project::manifest_audit::repository::save_order\tfn save_order(id: u32, items: Vec<String>) -> Result<(), String>",
+        ]);
+    }
+
+    #[test]
+    fn test_find_doubled_function() {
+        let entry = find("fnmock_example_project::manifest_audit::repository::save_order").unwrap();
+
+        assert_eq!(entry.kind, DoubleKind::Mock);
+        assert_eq!(entry.name, "save_order");
+        assert!(find("fnmock_example_project::manifest_audit::audit_repository").is_none());
+    }
+}
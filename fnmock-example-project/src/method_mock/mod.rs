@@ -0,0 +1,81 @@
+use fnmock::derive::{mock_function, mock_impl};
+
+pub struct HttpClient {
+    base_url: String,
+}
+
+#[mock_impl]
+impl HttpClient {
+    // Associated functions are only mocked if annotated
+    #[mock_function(own = [base_url])]
+    pub fn new(base_url: &str) -> Self {
+        Self { base_url: base_url.to_string() }
+    }
+
+    pub fn get(&self, path: String) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("GET {}/{}", self.base_url, path))
+    }
+
+    pub async fn post(&self, path: String, body: String) -> Result<u16, String> {
+        // Real implementation
+        let _ = (path, body);
+        Ok(201)
+    }
+
+    pub fn with_base_url(&self, base_url: String) -> Self {
+        Self { base_url }
+    }
+}
+
+pub async fn sync_profile(client: &HttpClient, id: u32) -> Result<u16, String> {
+    let profile = client.get(format!("profiles/{}", id))?;
+    client.post("sync".to_string(), profile).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_methods_are_mocked_for_all_instances() {
+        HttpClient_get_mock::setup(|path| Ok(format!("mocked {}", path)));
+        HttpClient_post_mock::setup_returns(Ok(200));
+
+        let result = sync_profile(&HttpClient::new("localhost"), 1).await;
+
+        assert_eq!(result, Ok(200));
+        HttpClient_get_mock::assert_with("profiles/1".to_string());
+        HttpClient_post_mock::assert_with("sync".to_string(), "mocked profiles/1".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_unset_method_mocks_execute_the_original() {
+        HttpClient_post_mock::setup(|(path, _)| Err(format!("{} unavailable", path)));
+
+        let result = sync_profile(&HttpClient::new("localhost"), 1).await;
+
+        assert_eq!(result, Err("sync unavailable".to_string()));
+        HttpClient_post_mock::assert_with("sync".to_string(), "GET localhost/profiles/1".to_string());
+    }
+
+    #[test]
+    fn test_mocked_constructor() {
+        HttpClient_new_mock::setup(|_| HttpClient { base_url: "mocked".to_string() });
+
+        let client = HttpClient::new("localhost");
+
+        assert_eq!(client.get("users".to_string()), Ok("GET mocked/users".to_string()));
+        HttpClient_new_mock::assert_with("localhost".to_string());
+    }
+
+    #[test]
+    fn test_methods_returning_self() {
+        HttpClient_with_base_url_mock::setup(|_| HttpClient::new("mocked"));
+
+        let client = HttpClient::new("localhost").with_base_url("example.com".to_string());
+
+        assert_eq!(client.get("users".to_string()), Ok("GET mocked/users".to_string()));
+        HttpClient_with_base_url_mock::assert_times(1);
+    }
+}
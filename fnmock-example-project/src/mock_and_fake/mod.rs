@@ -11,6 +11,13 @@ pub mod db {
     pub fn fetch_notes(id: u32) -> Result<String, String> {
         Ok(format!("notes_{}", id))
     }
+
+    // Counts the calls of the fake, without the parameter requirements of mocks
+    #[fake_function(count_calls)]
+    pub fn save_user(name: &str) -> bool {
+        // Real implementation
+        !name.is_empty()
+    }
 }
 
 use db::fetch_user;
@@ -24,10 +31,16 @@ pub fn handle_user(id: u32) -> Result<(), String> {
     Ok(())
 }
 
+pub fn register_users(names: &[&str]) -> usize {
+    names.iter().filter(|name| db::save_user(name)).count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::db::{fetch_notes_mock, fetch_user_fake};
+    use super::db::{fetch_notes_mock, fetch_user_fake, save_user_fake};
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
     #[test]
     fn test_handle_invalid_user() {
@@ -48,4 +61,52 @@ mod tests {
 
         // No cleanup needed, since fakes are thread / test specific as well
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_fake_closure_captures_test_state() {
+        let saved = Rc::new(RefCell::new(Vec::new()));
+        let recorded = saved.clone();
+        save_user_fake::setup(move |name| {
+            recorded.borrow_mut().push(name.to_string());
+            name != "mallory"
+        });
+
+        assert_eq!(register_users(&["alice", "mallory", "bob"]), 2);
+        assert_eq!(*saved.borrow(), vec!["alice", "mallory", "bob"]);
+    }
+
+    #[test]
+    fn test_fake_closure_mutates_captured_state() {
+        // Only the first two users fit, the closure counts them without a Cell
+        let mut free_slots = 2;
+        save_user_fake::setup(move |_| {
+            if free_slots == 0 {
+                return false;
+            }
+            free_slots -= 1;
+            true
+        });
+
+        assert_eq!(register_users(&["alice", "bob", "carol"]), 2);
+    }
+
+    #[test]
+    fn test_fake_calls_the_faked_function_recursively() {
+        // Ids above 100 are aliases of the user 100 below
+        // Function pointers aren't borrowed while they run, so they may call the faked function again
+        fetch_user_fake::setup_fn(|id| if id > 100 { fetch_user(id - 100) } else { Ok(format!("fake_user_{}", id)) });
+
+        assert_eq!(fetch_user(242), Ok("fake_user_42".to_string()));
+    }
+
+    #[test]
+    fn test_fake_counts_calls() {
+        save_user_fake::setup(|name| name.len() > 3);
+
+        assert_eq!(register_users(&["alice", "bob"]), 1);
+        assert_eq!(save_user_fake::times_called(), 2);
+
+        save_user_fake::clear();
+        assert_eq!(save_user_fake::times_called(), 0);
+    }
+}
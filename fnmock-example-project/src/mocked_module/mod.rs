@@ -0,0 +1,74 @@
+use fnmock::derive::{mock_function, mock_module};
+
+pub struct HttpClient {
+    pub timeout_secs: u64,
+}
+
+// Every function of the wrapper module is mocked with the arguments of the module
+#[mock_module(ignore = [*_client], skip = [base_url])]
+pub mod weather {
+    use super::*;
+
+    pub fn temperature(http_client: &HttpClient, city: String) -> Result<f32, String> {
+        // Real implementation
+        Err(format!("{}/temperature/{} timed out after {}s", base_url(), city, http_client.timeout_secs))
+    }
+
+    pub fn humidity(http_client: &HttpClient, city: String) -> Result<u8, String> {
+        // Real implementation
+        Err(format!("{}/humidity/{} timed out after {}s", base_url(), city, http_client.timeout_secs))
+    }
+
+    // The function's own attribute replaces the arguments of the module
+    #[mock_function(named_params)]
+    pub fn forecast(city: String, days: u8) -> Result<Vec<f32>, String> {
+        // Real implementation
+        Err(format!("{}/forecast/{}?days={}", base_url(), city, days))
+    }
+
+    // Skipped, so it isn't mocked
+    fn base_url() -> &'static str {
+        "https://weather.example.com"
+    }
+}
+
+/// Describes the current weather and the trend of the next days.
+pub fn weather_report(client: &HttpClient, city: &str) -> Result<String, String> {
+    let temperature = weather::temperature(client, city.to_string())?;
+    let humidity = weather::humidity(client, city.to_string())?;
+    let forecast = weather::forecast(city.to_string(), 3)?;
+    let trend = if forecast.last().is_some_and(|last| *last > temperature) { "rising" } else { "falling" };
+    Ok(format!("{}: {:.1}°C, {}% humidity, {}", city, temperature, humidity, trend))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::weather::{forecast_mock, humidity_mock, temperature_mock};
+
+    #[test]
+    fn test_weather_report() {
+        temperature_mock::setup(|_| Ok(21.5));
+        humidity_mock::setup_ok(40);
+        forecast_mock::setup(|params| Ok(vec![22.0; params.days as usize]));
+
+        let report = weather_report(&HttpClient { timeout_secs: 5 }, "Berlin");
+
+        assert_eq!(report, Ok("Berlin: 21.5°C, 40% humidity, rising".to_string()));
+        // The client is ignored by the module's wildcard
+        temperature_mock::assert_with("Berlin".to_string());
+        humidity_mock::assert_with("Berlin".to_string());
+        forecast_mock::assert_with_days(3);
+    }
+
+    #[test]
+    fn test_unavailable_humidity() {
+        temperature_mock::setup(|_| Ok(21.5));
+        humidity_mock::setup_err("service down".to_string());
+
+        let report = weather_report(&HttpClient { timeout_secs: 5 }, "Berlin");
+
+        assert_eq!(report, Err("service down".to_string()));
+        forecast_mock::assert_times(0);
+    }
+}
@@ -0,0 +1,58 @@
+// The visibility of the generated modules can be chosen per double,
+// e.g. to keep test helpers next to the code they belong to
+pub mod disk {
+    use fnmock::derive::{fake_function, mock_function};
+
+    // The mock module is only visible in the parent module
+    #[mock_function(vis = "pub(super)")]
+    pub fn read_block(index: u64) -> Vec<u8> {
+        // Real implementation
+        vec![index as u8; 4]
+    }
+
+    // The fake module is only visible in this module (and its tests)
+    #[fake_function(vis = "pub(self)")]
+    pub fn checksum(data: &[u8]) -> u32 {
+        data.iter().map(|byte| *byte as u32).sum()
+    }
+
+    pub fn verified_block(index: u64, expected_checksum: u32) -> Result<Vec<u8>, String> {
+        let block = read_block(index);
+        if checksum(&block) == expected_checksum {
+            Ok(block)
+        } else {
+            Err(format!("corrupted block {}", index))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_private_fake_is_visible_in_child_modules() {
+            checksum_fake::setup(|_| 7);
+
+            assert_eq!(verified_block(1, 7), Ok(vec![1; 4]));
+            assert_eq!(verified_block(1, 4), Err("corrupted block 1".to_string()));
+        }
+    }
+}
+
+pub fn read_file(blocks: u64) -> Vec<u8> {
+    (0..blocks).flat_map(disk::read_block).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::disk::read_block_mock;
+
+    #[test]
+    fn test_mock_with_restricted_visibility() {
+        read_block_mock::setup(|index| vec![index as u8]);
+
+        assert_eq!(read_file(3), vec![0, 1, 2]);
+        read_block_mock::assert_times(3);
+    }
+}
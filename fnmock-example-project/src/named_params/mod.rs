@@ -0,0 +1,99 @@
+pub mod mailer {
+    use fnmock::derive::mock_function;
+
+    // The recorded parameters are stored in a `SendEmailParams` struct instead of a tuple
+    #[mock_function(named_params)]
+    pub fn send_email(recipient: String, subject: String, body: String, priority: u8, retries: u32) -> Result<(), String> {
+        // Real implementation
+        println!("Sending '{}' to {} (priority {}, {} retries): {}", subject, recipient, priority, retries, body);
+        Ok(())
+    }
+}
+
+use mailer::send_email;
+
+pub fn notify_overdue(customer: &str, days: u32) -> Result<(), String> {
+    let priority = if days > 30 { 1 } else { 3 };
+    send_email(
+        format!("{}@example.com", customer),
+        "Invoice overdue".to_string(),
+        format!("Your invoice is {} days overdue.", days),
+        priority,
+        2,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::mailer::send_email_mock;
+    use fnmock::matchers::{any, eq};
+    use fnmock::assert_with;
+
+    #[test]
+    fn test_params_are_named_in_setup() {
+        send_email_mock::setup(|params| {
+            if params.priority == 1 { Err("rate limited".to_string()) } else { Ok(()) }
+        });
+
+        assert_eq!(notify_overdue("alice", 45), Err("rate limited".to_string()));
+        assert_eq!(notify_overdue("bob", 5), Ok(()));
+    }
+
+    #[test]
+    fn test_single_fields_are_asserted() {
+        send_email_mock::setup(|_| Ok(()));
+
+        notify_overdue("alice", 45).unwrap();
+
+        send_email_mock::assert_with_recipient("alice@example.com".to_string());
+        send_email_mock::assert_with_priority(1);
+        assert_eq!(send_email_mock::last_call_params().unwrap().retries, 2);
+    }
+
+    #[test]
+    fn test_whole_call_is_asserted() {
+        send_email_mock::setup(|_| Ok(()));
+
+        notify_overdue("bob", 5).unwrap();
+
+        send_email_mock::assert_with(
+            "bob@example.com".to_string(),
+            "Invoice overdue".to_string(),
+            "Your invoice is 5 days overdue.".to_string(),
+            3,
+            2,
+        );
+        send_email_mock::assert_with_matchers(any(), eq("Invoice overdue".to_string()), any(), eq(3), any());
+    }
+
+    #[test]
+    fn test_only_interesting_arguments_are_asserted() {
+        send_email_mock::setup(|_| Ok(()));
+
+        notify_overdue("alice", 45).unwrap();
+
+        assert_with!(send_email_mock, "alice@example.com", _, _, 1, _);
+        assert_with!(send_email_mock, _, "Invoice overdue", _, _, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "matching { recipient: eq(\"carol@example.com\"), subject: any, body: any, priority: any, retries: any }")]
+    fn test_wildcard_assertion_describes_the_arguments() {
+        send_email_mock::setup(|_| Ok(()));
+
+        notify_overdue("bob", 5).unwrap();
+
+        assert_with!(send_email_mock, "carol@example.com", _, _, _, _);
+    }
+
+    #[test]
+    #[should_panic(expected = "{ priority: 1, .. }")]
+    fn test_field_assertion_describes_the_field() {
+        send_email_mock::setup(|_| Ok(()));
+
+        notify_overdue("bob", 5).unwrap();
+
+        send_email_mock::assert_with_priority(1);
+    }
+}
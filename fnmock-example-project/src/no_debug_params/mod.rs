@@ -0,0 +1,52 @@
+pub mod db {
+    use fnmock::derive::mock_function;
+
+    // Doesn't implement Debug, e.g. because it wraps a driver handle
+    #[derive(Clone, PartialEq)]
+    pub struct Connection {
+        pub name: String,
+    }
+
+    impl Connection {
+        pub fn new(name: &str) -> Self {
+            Self { name: name.to_string() }
+        }
+    }
+
+    #[mock_function(no_debug = [conn])]
+    pub fn query(conn: Connection, sql: String) -> Vec<String> {
+        // Real implementation
+        vec![format!("{}: {}", conn.name, sql)]
+    }
+}
+use db::{query, Connection};
+
+/// Counts the rows of the table in the database.
+pub fn count_rows(database: &str, table: &str) -> usize {
+    query(Connection::new(database), format!("SELECT * FROM {}", table)).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::db::query_mock;
+
+    #[test]
+    fn test_count_rows_queries_the_database() {
+        query_mock::setup(|(conn, _)| vec![conn.name.clone(); 3]);
+
+        assert_eq!(count_rows("orders_db", "orders"), 3);
+
+        query_mock::assert_with(Connection::new("orders_db"), "SELECT * FROM orders".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "[0] (<non-debug fnmock_example_project::no_debug_params::db::Connection>, \"SELECT * FROM orders\")")]
+    fn test_failure_message_formats_the_connection() {
+        query_mock::setup(|_| Vec::new());
+
+        count_rows("orders_db", "orders");
+
+        query_mock::assert_with(Connection::new("users_db"), "SELECT * FROM orders".to_string());
+    }
+}
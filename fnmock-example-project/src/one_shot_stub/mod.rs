@@ -0,0 +1,48 @@
+pub mod broker {
+    use fnmock::derive::stub_function;
+    use std::sync::mpsc::{channel, Receiver};
+
+    // Receivers can't be cloned, so the stub is set up with `setup_once`
+    #[stub_function]
+    pub fn subscribe(topic: String) -> Receiver<String> {
+        // Real implementation
+        println!("Subscribe to {}", topic);
+        let (_, receiver) = channel();
+        receiver
+    }
+}
+
+use broker::subscribe;
+
+/// Collects the messages of a topic until the publisher disconnects
+pub fn collect_messages(topic: &str) -> Vec<String> {
+    subscribe(topic.to_string()).iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::broker::subscribe_stub;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_collects_messages_of_the_receiver_set_up_once() {
+        let (sender, receiver) = channel();
+        sender.send("order created".to_string()).unwrap();
+        sender.send("order shipped".to_string()).unwrap();
+        drop(sender);
+        subscribe_stub::setup_once(receiver);
+
+        assert_eq!(collect_messages("orders"), vec!["order created".to_string(), "order shipped".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "subscribe_stub stub was set up with setup_once and already returned its value")]
+    fn test_second_subscription_fails() {
+        let (_, receiver) = channel();
+        subscribe_stub::setup_once(receiver);
+
+        collect_messages("orders");
+        collect_messages("orders");
+    }
+}
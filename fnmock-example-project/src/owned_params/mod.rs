@@ -0,0 +1,59 @@
+// The mock records owned copies of the reference params listed in `own`,
+// so the function doesn't have to take owned types just to be mocked
+pub mod network {
+    use fnmock::derive::mock_function;
+
+    #[mock_function(own = [host, payload])]
+    pub fn send_packet(host: &str, port: u16, payload: &[u8]) -> Result<usize, String> {
+        // Real implementation
+        println!("Sending {} bytes to {}:{}", payload.len(), host, port);
+        Ok(payload.len())
+    }
+}
+
+use network::send_packet;
+
+pub fn send_message(host: &str, message: &str) -> Result<usize, String> {
+    let mut sent = 0;
+    for chunk in message.as_bytes().chunks(4) {
+        sent += send_packet(host, 9000, chunk)?;
+    }
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::network::send_packet_mock;
+    use fnmock::matchers::{any, eq};
+
+    #[test]
+    fn test_reference_params_are_recorded_as_owned_copies() {
+        // The implementation receives the owned copies
+        send_packet_mock::setup(|(_, _, payload): (String, u16, Vec<u8>)| Ok(payload.len()));
+
+        assert_eq!(send_message("10.0.0.1", "hello"), Ok(5));
+
+        send_packet_mock::assert_times(2);
+        // Assertions take the params like the function does
+        send_packet_mock::assert_nth_call_with(0, "10.0.0.1", 9000, b"hell");
+        send_packet_mock::assert_last_call_with("10.0.0.1", 9000, b"o");
+        send_packet_mock::assert_with_matchers(eq("10.0.0.1".to_string()), any(), eq(vec![b'o']));
+        assert_eq!(send_packet_mock::calls()[0].2, b"hell".to_vec());
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected send_packet_mock mock to be called with")]
+    fn test_assert_with_compares_the_owned_copies() {
+        send_packet_mock::setup(|_| Ok(0));
+
+        let _ = send_message("10.0.0.1", "hi");
+
+        send_packet_mock::assert_with("10.0.0.2", 9000, b"hi");
+    }
+
+    #[test]
+    fn test_original_implementation_without_setup() {
+        assert_eq!(send_message("localhost", "abc"), Ok(3));
+    }
+}
@@ -0,0 +1,48 @@
+pub mod worker {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub fn process_job(job: u32) -> u32 {
+        // Real implementation
+        job * 2
+    }
+}
+use worker::process_job;
+
+/// Runs all jobs, isolating panicking jobs from the others
+pub fn run_jobs(jobs: Vec<u32>) -> Vec<Option<u32>> {
+    jobs.into_iter()
+        .map(|job| std::panic::catch_unwind(|| process_job(job)).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::worker::process_job_mock;
+
+    #[test]
+    fn test_supervisor_isolates_panicking_jobs() {
+        process_job_mock::setup(|job| {
+            if job == 13 {
+                panic!("unlucky job");
+            }
+            job
+        });
+
+        let results = run_jobs(vec![1, 13, 2, 13]);
+
+        assert_eq!(results, vec![Some(1), None, Some(2), None]);
+        process_job_mock::assert_times(4);
+        process_job_mock::assert_panicked_times(2);
+    }
+
+    #[test]
+    fn test_no_panics_recorded_for_successful_jobs() {
+        process_job_mock::setup(|job| job);
+
+        run_jobs(vec![1, 2]);
+
+        process_job_mock::assert_panicked_times(0);
+    }
+}
@@ -0,0 +1,73 @@
+pub mod pricing {
+    use fnmock::derive::{mock_function, stub_function};
+
+    #[stub_function]
+    pub fn get_discount(customer_id: u32) -> u32 {
+        // Real implementation
+        if customer_id == 0 { 50 } else { 0 }
+    }
+
+    #[mock_function]
+    pub fn log_price(customer_id: u32, price: u32) {
+        // Real implementation
+        println!("customer {} pays {}", customer_id, price);
+    }
+}
+use pricing::{get_discount, log_price};
+
+pub fn checkout(customer_id: u32, price: u32) -> u32 {
+    let price = price - price * get_discount(customer_id) / 100;
+    log_price(customer_id, price);
+    price
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::pricing::{get_discount_stub, log_price_mock};
+    use fnmock::derive::reset_doubles;
+    use test_case::test_case;
+
+    #[test_case(0, 100, 50 ; "admin discount")]
+    #[test_case(1, 100, 100 ; "no discount")]
+    #[reset_doubles]
+    fn test_checkout(customer_id: u32, price: u32, expected: u32) {
+        // Only the first case sets up the stub, the real implementation is used otherwise
+        if customer_id == 0 {
+            get_discount_stub::setup(50);
+        }
+        log_price_mock::setup(|_| {});
+
+        assert_eq!(checkout(customer_id, price), expected);
+        log_price_mock::assert_times(1);
+        log_price_mock::assert_with(customer_id, expected);
+    }
+
+    #[test]
+    fn test_cases_on_the_same_thread_are_isolated() {
+        for (discount, expected) in [(Some(10), 90), (None, 100)] {
+            let _guard = fnmock::registry::reset_doubles();
+
+            if let Some(discount) = discount {
+                get_discount_stub::setup(discount);
+            }
+            log_price_mock::setup(|_| {});
+
+            assert_eq!(checkout(1, 100), expected);
+            log_price_mock::assert_times(1);
+        }
+    }
+
+    #[test]
+    fn test_guard_clears_doubles_when_dropped() {
+        get_discount_stub::setup(10);
+
+        {
+            let _guard = fnmock::registry::reset_doubles();
+            assert!(!get_discount_stub::is_set());
+            get_discount_stub::setup(20);
+        }
+
+        assert!(!get_discount_stub::is_set());
+    }
+}
@@ -0,0 +1,82 @@
+pub mod rates {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub fn exchange_rate(from: String, to: String) -> f64 {
+        // Real implementation
+        if from == to { 1.0 } else { 1.1 }
+    }
+
+    #[mock_function]
+    pub async fn fee(amount: u32) -> u32 {
+        // Real implementation
+        amount / 100
+    }
+}
+use rates::{exchange_rate, fee};
+
+pub fn convert(amount: f64, from: &str, to: &str) -> f64 {
+    amount * exchange_rate(from.to_string(), to.to_string())
+}
+
+pub async fn total_with_fee(amount: u32) -> u32 {
+    amount + fee(amount).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::rates::{exchange_rate_mock, fee_mock};
+    use fnmock::matchers::{eq, gt, predicate};
+
+    #[test]
+    fn test_only_matching_calls_are_mocked() {
+        exchange_rate_mock::setup_when(
+            predicate("to JPY", |(_, to): &(String, String)| to == "JPY"),
+            |_| 160.0,
+        );
+        exchange_rate_mock::fallback_to_original();
+
+        assert_eq!(convert(2.0, "EUR", "JPY"), 320.0);
+        assert_eq!(convert(2.0, "EUR", "EUR"), 2.0);
+        assert_eq!(convert(10.0, "EUR", "USD"), 11.0);
+
+        exchange_rate_mock::assert_times(3);
+        exchange_rate_mock::assert_last_call_with("EUR".to_string(), "USD".to_string());
+    }
+
+    #[test]
+    fn test_cases_are_checked_in_order() {
+        exchange_rate_mock::setup_when(eq(("EUR".to_string(), "USD".to_string())), |_| 2.0);
+        exchange_rate_mock::setup(|_| 3.0);
+
+        assert_eq!(convert(1.0, "EUR", "USD"), 2.0);
+        assert_eq!(convert(1.0, "USD", "EUR"), 3.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "exchange_rate_mock mock not initialized for (\"USD\", \"EUR\"), set up for [eq((\"EUR\", \"USD\"))]")]
+    fn test_unmatched_call_panics_without_fallback() {
+        exchange_rate_mock::setup_when(eq(("EUR".to_string(), "USD".to_string())), |_| 2.0);
+
+        convert(1.0, "USD", "EUR");
+    }
+
+    #[test]
+    fn test_fallback_without_cases_records_calls() {
+        exchange_rate_mock::fallback_to_original();
+
+        assert_eq!(convert(10.0, "EUR", "USD"), 11.0);
+        exchange_rate_mock::assert_with("EUR".to_string(), "USD".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_async_fallback() {
+        fee_mock::setup_when(gt(1000), |_| 5);
+        fee_mock::fallback_to_original();
+
+        assert_eq!(total_with_fee(5000).await, 5005);
+        assert_eq!(total_with_fee(500).await, 505);
+        fee_mock::assert_times(2);
+    }
+}
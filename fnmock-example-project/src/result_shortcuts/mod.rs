@@ -0,0 +1,82 @@
+pub mod accounts {
+    use fnmock::derive::{mock_function, stub_function};
+
+    #[mock_function]
+    pub fn withdraw(account_id: u32, amount: u64) -> Result<u64, String> {
+        // Real implementation
+        Ok(amount * account_id as u64)
+    }
+
+    #[stub_function]
+    pub async fn fetch_limit() -> std::result::Result<u64, String> {
+        // Real implementation
+        Ok(1000)
+    }
+}
+use accounts::{fetch_limit, withdraw};
+
+/// Withdraws the amount if it is within the limit, returning the new balance.
+pub async fn withdraw_within_limit(account_id: u32, amount: u64) -> Result<u64, String> {
+    let limit = fetch_limit().await?;
+    if amount > limit {
+        return Err(format!("{} exceeds the limit of {}", amount, limit));
+    }
+    withdraw(account_id, amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::accounts::{fetch_limit_stub, withdraw_mock};
+
+    #[tokio::test]
+    async fn test_withdraw_within_limit() {
+        fetch_limit_stub::setup_ok(500);
+        withdraw_mock::setup_ok(150);
+
+        let result = withdraw_within_limit(1, 100).await;
+
+        assert_eq!(result, Ok(150));
+        withdraw_mock::assert_with(1, 100);
+    }
+
+    #[tokio::test]
+    async fn test_failing_withdrawal() {
+        fetch_limit_stub::setup_ok(500);
+        withdraw_mock::setup_err("insufficient funds".to_string());
+
+        let result = withdraw_within_limit(1, 100).await;
+
+        assert_eq!(result, Err("insufficient funds".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_amount_above_limit_is_rejected() {
+        fetch_limit_stub::setup_ok(500);
+        withdraw_mock::setup_panics("amounts above the limit must not be withdrawn");
+
+        let result = withdraw_within_limit(1, 600).await;
+
+        assert_eq!(result, Err("600 exceeds the limit of 500".to_string()));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "withdraw_mock mock was called with (1, 600): amounts above the limit must not be withdrawn")]
+    async fn test_setup_panics_names_the_call() {
+        fetch_limit_stub::setup_ok(1000);
+        withdraw_mock::setup_panics("amounts above the limit must not be withdrawn");
+
+        let _ = withdraw_within_limit(1, 600).await;
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_limit() {
+        fetch_limit_stub::setup_err("limit service down".to_string());
+        withdraw_mock::setup_ok(150);
+
+        let result = withdraw_within_limit(1, 100).await;
+
+        assert_eq!(result, Err("limit service down".to_string()));
+        withdraw_mock::assert_times(0);
+    }
+}
@@ -0,0 +1,73 @@
+pub mod http {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub fn fetch_page(url: String) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("<html>{}</html>", url))
+    }
+}
+use http::fetch_page;
+
+/// Fetches the page, retrying failed requests up to `max_attempts` times in total.
+pub fn fetch_with_retries(url: &str, max_attempts: u32) -> Result<String, String> {
+    let mut last_error = "no attempts".to_string();
+    for _ in 0..max_attempts {
+        match fetch_page(url.to_string()) {
+            Ok(page) => return Ok(page),
+            Err(error) => last_error = error,
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::http::fetch_page_mock;
+
+    #[test]
+    fn test_succeeds_after_failures() {
+        fetch_page_mock::setup_returns_sequence(vec![
+            Err("timeout".to_string()),
+            Err("timeout".to_string()),
+            Ok("page".to_string()),
+        ]);
+
+        assert_eq!(fetch_with_retries("example.com", 3), Ok("page".to_string()));
+        fetch_page_mock::assert_times(3);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        fetch_page_mock::setup_returns_sequence_repeat_last(vec![Err("offline".to_string())]);
+
+        assert_eq!(fetch_with_retries("example.com", 5), Err("offline".to_string()));
+        fetch_page_mock::assert_times(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "fetch_page_mock mock was called more often than its sequence has values")]
+    fn test_unexpected_retry_panics() {
+        fetch_page_mock::setup_returns_sequence(vec![Err("timeout".to_string())]);
+
+        let _ = fetch_with_retries("example.com", 2);
+    }
+
+    #[test]
+    fn test_retry_against_real_implementation() {
+        fetch_page_mock::setup_once(|_| Err("timeout".to_string()));
+
+        assert_eq!(fetch_with_retries("example.com", 3), Ok("<html>example.com</html>".to_string()));
+        fetch_page_mock::assert_times(2);
+    }
+
+    #[test]
+    fn test_limited_failures() {
+        fetch_page_mock::setup_times(2, |_| Err("timeout".to_string()));
+
+        assert_eq!(fetch_with_retries("example.com", 2), Err("timeout".to_string()));
+        assert_eq!(fetch_with_retries("example.com", 2), Ok("<html>example.com</html>".to_string()));
+        fetch_page_mock::assert_times(3);
+    }
+}
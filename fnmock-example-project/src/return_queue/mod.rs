@@ -0,0 +1,58 @@
+pub mod workers {
+    use fnmock::derive::mock_function;
+    use std::sync::mpsc::{channel, Receiver};
+
+    // Every job reports its result on its own channel
+    #[mock_function]
+    pub fn submit(job: String) -> Receiver<Result<u32, String>> {
+        // Real implementation
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || sender.send(Ok(job.len() as u32)));
+        receiver
+    }
+}
+
+use workers::submit;
+
+/// Submits all jobs and waits for their results
+pub fn run_all(jobs: &[&str]) -> Vec<Result<u32, String>> {
+    let receivers: Vec<_> = jobs.iter().map(|job| submit(job.to_string())).collect();
+    receivers.into_iter()
+        .map(|receiver| receiver.recv().unwrap_or_else(|_| Err("worker gone".to_string())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::workers::submit_mock;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_every_job_gets_its_own_channel() {
+        let (first_sender, first_receiver) = channel();
+        let (second_sender, second_receiver) = channel();
+        submit_mock::enqueue_return(move || first_receiver);
+        submit_mock::enqueue_return(move || second_receiver);
+        first_sender.send(Ok(1)).unwrap();
+        drop(second_sender);
+
+        assert_eq!(run_all(&["resize", "upload"]), vec![Ok(1), Err("worker gone".to_string())]);
+        submit_mock::assert_times(2);
+        submit_mock::assert_with("upload".to_string());
+    }
+
+    #[test]
+    fn test_falls_back_to_implementation_when_queue_is_empty() {
+        let (sender, receiver) = channel();
+        sender.send(Ok(7)).unwrap();
+        submit_mock::enqueue_return(move || receiver);
+        submit_mock::setup(|_| {
+            let (sender, receiver) = channel();
+            sender.send(Err("rejected".to_string())).unwrap();
+            receiver
+        });
+
+        assert_eq!(run_all(&["resize", "upload"]), vec![Ok(7), Err("rejected".to_string())]);
+    }
+}
@@ -0,0 +1,85 @@
+pub mod shop {
+    use fnmock::derive::{fake_function, mock_function, stub_function};
+
+    #[mock_function]
+    pub async fn fetch_price(item: String) -> Result<u32, String> {
+        // Real implementation
+        Ok(item.len() as u32 * 100)
+    }
+
+    #[stub_function]
+    pub async fn get_tax_rate() -> u32 {
+        // Real implementation
+        20
+    }
+
+    #[fake_function]
+    pub async fn apply_discount(price: u32) -> u32 {
+        // Real implementation
+        price
+    }
+}
+use shop::{apply_discount, fetch_price, get_tax_rate};
+
+pub async fn price_with_tax(item: &str) -> Result<u32, String> {
+    let price = apply_discount(fetch_price(item.to_string()).await?).await;
+    Ok(price + price * get_tax_rate().await / 100)
+}
+
+// None of the doubles depend on tokio, so the tests run on the executors of futures and async-std
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::shop::{apply_discount_fake, fetch_price_mock, get_tax_rate_stub};
+    use futures::executor::block_on;
+    use std::time::Duration;
+
+    #[test]
+    fn test_with_futures_executor() {
+        fetch_price_mock::setup(|_| Ok(100));
+        get_tax_rate_stub::setup(10);
+
+        assert_eq!(block_on(price_with_tax("book")), Ok(110));
+        fetch_price_mock::assert_with("book".to_string());
+    }
+
+    #[test]
+    fn test_yields_with_futures_executor() {
+        fetch_price_mock::setup_with_yields(3, |_| Ok(100));
+        get_tax_rate_stub::setup(0);
+
+        assert_eq!(block_on(price_with_tax("book")), Ok(100));
+        fetch_price_mock::assert_times(1);
+    }
+
+    #[async_std::test]
+    async fn test_with_async_std() {
+        fetch_price_mock::setup(|_| Err("out of stock".to_string()));
+
+        assert_eq!(price_with_tax("book").await, Err("out of stock".to_string()));
+    }
+
+    #[async_std::test]
+    async fn test_async_fake_with_async_std() {
+        fetch_price_mock::setup(|_| Ok(100));
+        get_tax_rate_stub::setup(0);
+        apply_discount_fake::setup_async(|price| Box::pin(async move {
+            async_std::task::yield_now().await;
+            price / 2
+        }));
+
+        assert_eq!(price_with_tax("book").await, Ok(50));
+    }
+
+    #[async_std::test]
+    async fn test_pending_mock_with_async_std() {
+        let release = fetch_price_mock::setup_pending(|_| Ok(100));
+        get_tax_rate_stub::setup(0);
+
+        let timeout = async_std::future::timeout(Duration::from_millis(10), price_with_tax("book")).await;
+        assert!(timeout.is_err());
+
+        release.release();
+        assert_eq!(price_with_tax("book").await, Ok(100));
+    }
+}
@@ -0,0 +1,24 @@
+pub mod payments {
+    use fnmock::derive::fake_function;
+
+    #[fake_function]
+    pub fn charge(customer: String, amount: u32) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("charged {} to {}", amount, customer))
+    }
+}
+
+use payments::charge;
+
+pub fn checkout(customer: &str, amount: u32) -> Result<String, String> {
+    charge(customer.to_string(), amount)
+}
+
+/// Replaces the payment provider with a sandbox for the current thread.
+///
+/// This isn't test code, so it only compiles if the `runtime-doubles` feature
+/// generates the doubles in normal builds.
+#[cfg(feature = "sandbox")]
+pub fn enable_sandbox() {
+    payments::charge_fake::setup(|_, amount| Ok(format!("sandbox-{}", amount)));
+}
@@ -0,0 +1,72 @@
+pub mod monitoring {
+    use fnmock::derive::{fake_function, mock_function, stub_function};
+
+    #[mock_function]
+    pub fn fetch_status(service: String) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("{} healthy", service))
+    }
+
+    #[fake_function]
+    pub fn measure_latency(service: &str) -> u64 {
+        service.len() as u64
+    }
+
+    #[stub_function]
+    pub fn get_region() -> String {
+        "eu-west".to_string()
+    }
+}
+
+use monitoring::{fetch_status, get_region, measure_latency};
+
+pub fn check_service(service: &str) -> String {
+    match fetch_status(service.to_string()) {
+        Ok(_) if measure_latency(service) > 100 => format!("{} slow in {}", service, get_region()),
+        Ok(_) => format!("{} up in {}", service, get_region()),
+        Err(_) => format!("{} down in {}", service, get_region()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::monitoring::{fetch_status_mock, get_region_stub, measure_latency_fake};
+    use std::panic::catch_unwind;
+
+    #[test]
+    fn test_guards_clear_doubles_on_drop() {
+        {
+            let _outage = fetch_status_mock::setup_scoped(|_| Err("offline".to_string()));
+            let _region = get_region_stub::setup_scoped("us-east".to_string());
+            assert_eq!(check_service("db"), "db down in us-east");
+            fetch_status_mock::assert_times(1);
+        }
+
+        // The doubles are cleared, so the original implementations run again
+        assert!(!fetch_status_mock::is_set());
+        assert!(!get_region_stub::is_set());
+        assert_eq!(check_service("db"), "db up in eu-west");
+    }
+
+    #[test]
+    fn test_phases_with_guards() {
+        let slow = measure_latency_fake::setup_scoped(|_| 500);
+        assert_eq!(check_service("db"), "db slow in eu-west");
+        drop(slow);
+
+        let _fast = measure_latency_fake::setup_scoped(|_| 5);
+        assert_eq!(check_service("db"), "db up in eu-west");
+    }
+
+    #[test]
+    fn test_guard_clears_on_panic() {
+        let result = catch_unwind(|| {
+            let _outage = fetch_status_mock::setup_scoped(|_| Err("offline".to_string()));
+            assert_eq!(check_service("db"), "db up in eu-west");
+        });
+
+        assert!(result.is_err());
+        assert!(!fetch_status_mock::is_set());
+    }
+}
@@ -0,0 +1,103 @@
+pub mod storage {
+    use fnmock::derive::mock_function;
+
+    #[mock_function(shared)]
+    pub fn upload_chunk(file: String, index: usize) -> Result<(), String> {
+        // Real implementation
+        println!("Upload chunk {} of {}", index, file);
+        Ok(())
+    }
+
+    #[mock_function(shared)]
+    pub async fn fetch_quote(symbol: String) -> Result<u32, String> {
+        // Real implementation
+        println!("Fetch quote of {}", symbol);
+        Ok(0)
+    }
+}
+
+use storage::{fetch_quote, upload_chunk};
+use std::thread;
+
+/// Uploads every chunk on its own worker thread
+pub fn upload_file(file: &str, chunks: usize) -> Result<(), String> {
+    let workers: Vec<_> = (0..chunks)
+        .map(|index| {
+            let file = file.to_string();
+            thread::spawn(move || upload_chunk(file, index))
+        })
+        .collect();
+
+    // Wait for all workers before reporting the first error
+    let results: Vec<Result<(), String>> = workers.into_iter()
+        .map(|worker| worker.join().unwrap())
+        .collect();
+    results.into_iter().collect()
+}
+
+/// Fetches the quotes of all symbols in tasks spawned on the runtime and sums them up
+pub async fn portfolio_value(symbols: &[&str]) -> Result<u32, String> {
+    let tasks: Vec<_> = symbols.iter()
+        .map(|symbol| tokio::spawn(fetch_quote(symbol.to_string())))
+        .collect();
+
+    let mut total = 0;
+    for task in tasks {
+        total += task.await.map_err(|e| e.to_string())??;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::storage::{fetch_quote_mock, upload_chunk_mock};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // Tests using the same shared mock mustn't run in parallel, so all scenarios run in one test
+    #[test]
+    fn test_workers_use_shared_mock() {
+        // Calls from all worker threads reach the mock set up here
+        upload_chunk_mock::setup(|(_, index)| if index == 2 { Err("timeout".to_string()) } else { Ok(()) });
+
+        assert_eq!(upload_file("video.mp4", 4), Err("timeout".to_string()));
+        upload_chunk_mock::assert_times(4);
+        upload_chunk_mock::assert_with("video.mp4".to_string(), 3);
+
+        let mut indices: Vec<usize> = upload_chunk_mock::calls().into_iter().map(|(_, index)| index).collect();
+        indices.sort();
+        assert_eq!(indices, vec![0, 1, 2, 3]);
+
+        // The implementation may capture state shared across threads
+        upload_chunk_mock::clear();
+        let uploaded = Arc::new(AtomicUsize::new(0));
+        let counter = uploaded.clone();
+        upload_chunk_mock::setup(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+
+        assert_eq!(upload_file("image.png", 3), Ok(()));
+        assert_eq!(uploaded.load(Ordering::SeqCst), 3);
+
+        upload_chunk_mock::clear();
+        assert!(!upload_chunk_mock::is_set());
+    }
+
+    // The test future may continue on another worker thread after every `.await`
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_async_shared_mock_survives_thread_switches() {
+        fetch_quote_mock::setup(|symbol| if symbol == "FAIL" { Err("unknown symbol".to_string()) } else { Ok(100) });
+
+        assert_eq!(portfolio_value(&["ACME", "INIT"]).await, Ok(200));
+        tokio::task::yield_now().await;
+        assert_eq!(portfolio_value(&["ACME", "FAIL"]).await, Err("unknown symbol".to_string()));
+
+        fetch_quote_mock::assert_times(4);
+        fetch_quote_mock::assert_with("FAIL".to_string());
+
+        fetch_quote_mock::clear();
+        assert!(!fetch_quote_mock::is_set());
+    }
+}
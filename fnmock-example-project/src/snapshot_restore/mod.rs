@@ -0,0 +1,137 @@
+pub mod service {
+    use fnmock::derive::{fake_function, mock_function, stub_function};
+
+    #[mock_function]
+    pub fn fetch_balance(account: u32) -> Result<u64, String> {
+        // Real implementation
+        Ok(account as u64 * 100)
+    }
+
+    #[fake_function]
+    pub fn convert(amount: u64, rate: u64) -> u64 {
+        amount * rate
+    }
+
+    #[stub_function]
+    pub fn get_currency() -> String {
+        "EUR".to_string()
+    }
+}
+use service::{convert, fetch_balance, get_currency};
+
+pub fn describe_balance(account: u32) -> String {
+    match fetch_balance(account) {
+        Ok(balance) => format!("{} {}", convert(balance, 2), get_currency()),
+        Err(error) => format!("error: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::service::{convert_fake, fetch_balance_mock, get_currency_stub};
+
+    /// Test helper temporarily letting every balance request fail
+    fn with_offline_backend<R>(test: impl FnOnce() -> R) -> R {
+        let snapshot = fetch_balance_mock::snapshot();
+        fetch_balance_mock::setup(|_| Err("offline".to_string()));
+
+        let result = test();
+
+        fetch_balance_mock::restore(snapshot);
+        result
+    }
+
+    /// Test helper temporarily reconfiguring several doubles
+    fn with_dollar_backend<R>(test: impl FnOnce() -> R) -> R {
+        let snapshot = fnmock::registry::snapshot_all();
+        fetch_balance_mock::setup(|_| Ok(1));
+        convert_fake::setup(|amount, _| amount);
+        get_currency_stub::setup("USD".to_string());
+
+        let result = test();
+
+        fnmock::registry::restore_all(snapshot);
+        result
+    }
+
+    #[test]
+    fn test_restore_puts_mock_back() {
+        fetch_balance_mock::setup(|account| Ok(account as u64));
+        convert_fake::setup(|amount, rate| amount + rate);
+        get_currency_stub::setup("CHF".to_string());
+
+        assert_eq!(describe_balance(5), "7 CHF");
+
+        let result = with_offline_backend(|| describe_balance(5));
+        assert_eq!(result, "error: offline");
+
+        // The setup and the call history are restored
+        assert_eq!(describe_balance(5), "7 CHF");
+        fetch_balance_mock::assert_times(2);
+    }
+
+    #[test]
+    fn test_restore_all_puts_all_doubles_back() {
+        fetch_balance_mock::setup(|account| Ok(account as u64));
+        convert_fake::setup(|amount, rate| amount * rate * 10);
+        get_currency_stub::setup("CHF".to_string());
+
+        let result = with_dollar_backend(|| describe_balance(5));
+        assert_eq!(result, "1 USD");
+
+        assert_eq!(describe_balance(5), "100 CHF");
+        fetch_balance_mock::assert_times(1);
+    }
+
+    #[test]
+    fn test_restore_all_clears_doubles_set_up_after_snapshot() {
+        let snapshot = fnmock::registry::snapshot_all();
+
+        get_currency_stub::setup("USD".to_string());
+        fetch_balance_mock::setup(|_| Ok(1));
+
+        fnmock::registry::restore_all(snapshot);
+
+        assert!(!get_currency_stub::is_set());
+        assert!(!fetch_balance_mock::is_set());
+        assert_eq!(describe_balance(1), "200 EUR");
+    }
+
+    #[test]
+    fn test_nested_helpers_restore_in_order() {
+        get_currency_stub::setup("CHF".to_string());
+
+        let result = with_dollar_backend(|| {
+            with_offline_backend(|| describe_balance(1))
+        });
+        assert_eq!(result, "error: offline");
+
+        assert!(!fetch_balance_mock::is_set());
+        assert!(!convert_fake::is_set());
+        assert_eq!(get_currency_stub::get_return_value(), "CHF");
+    }
+
+    #[test]
+    fn test_clear_all_resets_every_double() {
+        fetch_balance_mock::setup(|_| Ok(1));
+        convert_fake::setup(|amount, _| amount);
+        get_currency_stub::setup("USD".to_string());
+        assert_eq!(describe_balance(1), "1 USD");
+
+        fnmock::registry::clear_all();
+
+        assert!(!fetch_balance_mock::is_set());
+        assert!(!convert_fake::is_set());
+        assert!(!get_currency_stub::is_set());
+        fetch_balance_mock::assert_times(0);
+        assert_eq!(describe_balance(1), "200 EUR");
+    }
+
+    #[test]
+    #[should_panic(expected = "Snapshot of get_currency_stub can't be restored to fetch_balance_mock")]
+    fn test_restore_panics_for_snapshot_of_other_double() {
+        let snapshot = get_currency_stub::snapshot();
+        fetch_balance_mock::restore(snapshot);
+    }
+}
@@ -0,0 +1,74 @@
+pub mod network {
+    use fnmock::derive::{mock_function, spy_function};
+    use std::time::Duration;
+
+    #[mock_function]
+    pub fn resolve_host(name: String) -> Result<String, String> {
+        // Real implementation
+        std::thread::sleep(Duration::from_millis(2));
+        Ok(format!("10.0.0.{}", name.len()))
+    }
+
+    #[spy_function]
+    pub fn checksum(payload: Vec<u8>) -> u32 {
+        std::thread::sleep(Duration::from_millis(1));
+        payload.iter().map(|byte| *byte as u32).sum()
+    }
+}
+
+use network::{checksum, resolve_host};
+use std::collections::HashMap;
+
+/// Sends the packets to their hosts, resolving every host only once
+pub fn send_packets(packets: &[(&str, Vec<u8>)]) -> Vec<String> {
+    let mut resolved: HashMap<&str, String> = HashMap::new();
+    packets.iter()
+        .map(|(host, payload)| {
+            if !resolved.contains_key(host) {
+                let address = resolve_host(host.to_string()).unwrap_or_else(|_| "unreachable".to_string());
+                resolved.insert(host, address);
+            }
+            format!("{} <- {}", resolved[host], checksum(payload.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::network::{checksum_spy, resolve_host_mock};
+    use fnmock::matchers::eq;
+    use std::time::Duration;
+
+    #[test]
+    fn test_hosts_are_not_resolved_in_the_hot_loop() {
+        resolve_host_mock::spy();
+
+        let packets: Vec<(&str, Vec<u8>)> = (0..50).map(|idx| (if idx % 2 == 0 { "api" } else { "db" }, vec![idx])).collect();
+        send_packets(&packets);
+
+        let durations = resolve_host_mock::call_durations();
+        assert_eq!(durations.len(), 2);
+        assert!(durations.iter().all(|duration| *duration >= Duration::from_millis(2)));
+    }
+
+    #[test]
+    fn test_only_the_original_implementation_is_timed() {
+        resolve_host_mock::setup_when(eq("cache".to_string()), |_| Ok("127.0.0.1".to_string()));
+        resolve_host_mock::fallback_to_original();
+
+        assert_eq!(send_packets(&[("cache", vec![1]), ("api", vec![2])]), vec!["127.0.0.1 <- 1", "10.0.0.3 <- 2"]);
+
+        resolve_host_mock::assert_times(2);
+        assert_eq!(resolve_host_mock::call_durations().len(), 1);
+    }
+
+    #[test]
+    fn test_spy_times_every_call() {
+        send_packets(&[("api", vec![1, 2]), ("api", vec![3])]);
+
+        let durations = checksum_spy::call_durations();
+        assert_eq!(durations.len(), 2);
+        assert!(durations.iter().all(|duration| *duration >= Duration::from_millis(1)));
+    }
+}
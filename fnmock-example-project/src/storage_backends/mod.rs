@@ -0,0 +1,82 @@
+pub mod settings {
+    use fnmock::derive::stub_function;
+
+    // One stub shared by all threads, so worker threads read the value set up by the test
+    #[stub_function(storage = global)]
+    pub fn region() -> String {
+        // Real implementation
+        "eu-west-1".to_string()
+    }
+
+    // Every test gets its own stub by running in `exchange_fee_stub::scope()`
+    #[stub_function(storage = task_local)]
+    pub async fn exchange_fee(currency: String) -> u32 {
+        // Real implementation
+        println!("Fetch fee of {}", currency);
+        1
+    }
+}
+
+use settings::{exchange_fee, region};
+use std::thread;
+
+/// Builds the endpoints of all services on their own worker threads
+pub fn service_endpoints(services: &[&str]) -> Vec<String> {
+    let workers: Vec<_> = services.iter()
+        .map(|service| {
+            let service = service.to_string();
+            thread::spawn(move || format!("https://{}.{}.example.com", service, region()))
+        })
+        .collect();
+
+    workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+}
+
+/// Sums up the fees of all currencies
+pub async fn total_fees(currencies: &[&str]) -> u32 {
+    let mut total = 0;
+    for currency in currencies {
+        total += exchange_fee(currency.to_string()).await;
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::settings::{exchange_fee_stub, region_stub};
+
+    // Tests using the same global stub mustn't run in parallel, so all scenarios run in one test
+    #[test]
+    fn test_workers_use_global_stub() {
+        region_stub::setup("us-east-2".to_string());
+
+        assert_eq!(service_endpoints(&["auth", "billing"]), vec![
+            "https://auth.us-east-2.example.com".to_string(),
+            "https://billing.us-east-2.example.com".to_string(),
+        ]);
+
+        region_stub::clear();
+        assert_eq!(service_endpoints(&["auth"]), vec!["https://auth.eu-west-1.example.com".to_string()]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_task_local_stub_is_propagated_into_spawned_tasks() {
+        exchange_fee_stub::scope(async {
+            exchange_fee_stub::setup(5);
+
+            assert_eq!(total_fees(&["EUR", "GBP"]).await, 10);
+            let spawned = tokio::spawn(exchange_fee_stub::propagate(total_fees(&["USD"])));
+            assert_eq!(spawned.await.unwrap(), 5);
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn test_original_implementation_runs_outside_of_scope() {
+        assert_eq!(total_fees(&["EUR", "GBP"]).await, 2);
+
+        exchange_fee_stub::scope(async {
+            assert!(!exchange_fee_stub::is_set());
+        }).await;
+    }
+}
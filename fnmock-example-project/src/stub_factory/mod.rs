@@ -0,0 +1,50 @@
+pub mod ids {
+    use fnmock::derive::stub_function;
+
+    /// Identifies a request, ids must never be reused
+    #[derive(Debug, PartialEq)]
+    pub struct RequestId(pub u64);
+
+    // Every call returns a fresh id, so the stub is set up with a factory
+    #[stub_function]
+    pub fn next_request_id() -> RequestId {
+        // Real implementation
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0);
+        RequestId(nanos)
+    }
+}
+
+use ids::{next_request_id, RequestId};
+
+/// Places the orders, every order is sent with its own request id
+pub fn place_orders(count: usize) -> Vec<RequestId> {
+    (0..count).map(|_| next_request_id()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::ids::next_request_id_stub;
+
+    #[test]
+    fn test_every_order_gets_its_own_request_id() {
+        let mut next = 0;
+        next_request_id_stub::setup_factory(move || {
+            next += 1;
+            RequestId(next)
+        });
+
+        assert_eq!(place_orders(3), vec![RequestId(1), RequestId(2), RequestId(3)]);
+    }
+
+    #[test]
+    fn test_original_implementation_runs_after_clear() {
+        next_request_id_stub::setup_factory(|| RequestId(0));
+        next_request_id_stub::clear();
+
+        assert_ne!(place_orders(1), vec![RequestId(0)]);
+    }
+}
@@ -0,0 +1,64 @@
+pub mod rates {
+    use fnmock::derive::mock_function;
+
+    // Every test gets its own mock by running in `fetch_rate_mock::scope()`
+    #[mock_function(storage = task_local)]
+    pub async fn fetch_rate(currency: String) -> Result<f64, String> {
+        // Real implementation
+        println!("Fetch rate of {}", currency);
+        Ok(1.0)
+    }
+}
+
+use rates::fetch_rate;
+
+pub async fn convert(amount: f64, currency: &str) -> Result<f64, String> {
+    let rate = fetch_rate(currency.to_string()).await?;
+    Ok(amount * rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::rates::fetch_rate_mock;
+
+    // Both tests set up the same mock and may run in parallel, since each scope has its own mock
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_mock_follows_the_task_across_threads() {
+        fetch_rate_mock::scope(async {
+            fetch_rate_mock::setup(|currency| if currency == "USD" { Ok(2.0) } else { Err("unknown currency".to_string()) });
+
+            assert_eq!(convert(10.0, "USD").await, Ok(20.0));
+            tokio::task::yield_now().await;
+            assert_eq!(convert(10.0, "XYZ").await, Err("unknown currency".to_string()));
+
+            fetch_rate_mock::assert_times(2);
+            fetch_rate_mock::assert_with("XYZ".to_string());
+        }).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_mock_is_propagated_into_spawned_tasks() {
+        fetch_rate_mock::scope(async {
+            fetch_rate_mock::setup(|_| Ok(0.5));
+
+            let tasks: Vec<_> = ["EUR", "GBP"].into_iter()
+                .map(|currency| tokio::spawn(fetch_rate_mock::propagate(convert(4.0, currency))))
+                .collect();
+            for task in tasks {
+                assert_eq!(task.await.unwrap(), Ok(2.0));
+            }
+
+            fetch_rate_mock::assert_times(2);
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn test_original_implementation_runs_outside_of_scope() {
+        assert_eq!(convert(3.0, "USD").await, Ok(3.0));
+
+        fetch_rate_mock::scope(async {
+            assert!(!fetch_rate_mock::is_set());
+        }).await;
+    }
+}
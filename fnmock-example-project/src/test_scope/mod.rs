@@ -0,0 +1,136 @@
+pub mod billing {
+    use fnmock::derive::{mock_function, stub_function};
+
+    #[mock_function]
+    pub fn charge_card(customer: String, amount: u32) -> Result<(), String> {
+        // Real implementation
+        println!("Charge {} to {}", amount, customer);
+        Ok(())
+    }
+
+    #[stub_function]
+    pub fn get_discount() -> u32 {
+        0
+    }
+}
+
+use billing::{charge_card, get_discount};
+
+pub fn checkout(customer: &str, amount: u32) -> Result<u32, String> {
+    let total = amount - get_discount();
+    charge_card(customer.to_string(), total)?;
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::billing::{charge_card_mock, get_discount_stub};
+    use fnmock::registry::MockControl;
+    use test_case::test_case;
+
+    #[fnmock::test]
+    fn test_checkout_charges_discounted_amount() {
+        get_discount_stub::setup(10);
+        charge_card_mock::setup(|_| Ok(()));
+        charge_card_mock::expect_times(1);
+        charge_card_mock::expect_with("alice".to_string(), 90);
+
+        assert_eq!(checkout("alice", 100), Ok(90));
+        // No clear() needed, the scope verifies the expectations and clears the doubles
+    }
+
+    #[fnmock::test]
+    fn test_doubles_are_cleared_between_tests() {
+        // Whatever ran before on this thread, the test starts with cleared doubles
+        assert!(!charge_card_mock::is_set());
+        assert!(!get_discount_stub::is_set());
+    }
+
+    #[fnmock::test]
+    #[should_panic(expected = "Expected charge_card_mock mock to be called with (\"bob\", 100)")]
+    fn test_unmet_expectation_fails_test() {
+        charge_card_mock::setup(|_| Ok(()));
+        charge_card_mock::expect_with("bob".to_string(), 100);
+
+        checkout("alice", 100).unwrap();
+    }
+
+    #[test_case("alice", 100, 100)]
+    #[test_case("bob", 50, 50)]
+    #[fnmock::test]
+    fn test_checkout_cases(customer: &str, amount: u32, expected: u32) {
+        charge_card_mock::setup(|_| Ok(()));
+        charge_card_mock::expect_with(customer.to_string(), expected);
+
+        assert_eq!(checkout(customer, amount), Ok(expected));
+    }
+
+    #[tokio::test]
+    #[fnmock::test]
+    async fn test_async_checkout() {
+        charge_card_mock::setup(|_| Err("declined".to_string()));
+        charge_card_mock::expect_times(1);
+
+        assert_eq!(checkout("alice", 100), Err("declined".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected charge_card_mock mock to be called")]
+    fn test_verify_all_checks_expectations_without_scope() {
+        let _guard = fnmock::registry::reset_doubles();
+        charge_card_mock::setup(|_| Ok(()));
+        charge_card_mock::expect_times(2);
+
+        checkout("alice", 100).unwrap();
+
+        fnmock::verify_all();
+    }
+
+    #[fnmock::test]
+    fn test_handles_control_all_double_kinds() {
+        get_discount_stub::setup(10);
+        charge_card_mock::setup(|_| Ok(()));
+
+        checkout("alice", 100).unwrap();
+        checkout("bob", 50).unwrap();
+
+        let handles = fnmock::registry::doubles();
+        let charge_card = handles.iter().find(|double| double.name() == "charge_card_mock").unwrap();
+        let get_discount = handles.iter().find(|double| double.name() == "get_discount_stub").unwrap();
+        assert_eq!(charge_card.times_called(), Some(2));
+        assert_eq!(get_discount.times_called(), None);
+
+        for mut double in handles {
+            double.verify();
+            double.clear();
+        }
+        assert!(!charge_card_mock::is_set());
+        assert!(!get_discount_stub::is_set());
+    }
+}
+
+#[cfg(test)]
+mod glob_import_tests {
+    use super::*;
+    use super::billing::charge_card_mock;
+    use fnmock::derive::*;
+
+    #[stub_function]
+    fn currency() -> String {
+        "EUR".to_string()
+    }
+
+    // `fnmock::derive::*` doesn't import a `test` attribute, so this is the built-in one
+    #[test]
+    fn test_glob_import_keeps_plain_test_attribute() {
+        currency_stub::setup("USD".to_string());
+        charge_card_mock::setup(|_| Ok(()));
+
+        assert_eq!(currency(), "USD");
+        assert_eq!(checkout("alice", 100), Ok(100));
+
+        currency_stub::clear();
+        charge_card_mock::clear();
+    }
+}
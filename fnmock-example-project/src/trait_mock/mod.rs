@@ -0,0 +1,80 @@
+use fnmock::derive::mock_trait;
+
+#[mock_trait]
+#[allow(async_fn_in_trait)]
+pub trait PaymentGateway {
+    fn charge(&self, account: u32, amount: u64) -> Result<String, String>;
+
+    async fn refund(&self, transaction: String) -> Result<(), String>;
+
+    fn is_available(&self) -> bool;
+}
+
+pub struct CardGateway;
+
+impl PaymentGateway for CardGateway {
+    fn charge(&self, account: u32, amount: u64) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("tx_{}_{}", account, amount))
+    }
+
+    async fn refund(&self, _transaction: String) -> Result<(), String> {
+        // Real implementation
+        Ok(())
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+}
+
+pub async fn charge_or_refund(gateway: &impl PaymentGateway, account: u32, amount: u64, delivered: bool) -> Result<String, String> {
+    if !gateway.is_available() {
+        return Err("gateway unavailable".to_string());
+    }
+
+    let transaction = gateway.charge(account, amount)?;
+    if !delivered {
+        gateway.refund(transaction.clone()).await?;
+        return Ok(format!("refunded {}", transaction));
+    }
+
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::mock_payment_gateway::{charge_mock, is_available_mock, refund_mock};
+
+    #[tokio::test]
+    async fn test_charge_with_mock_gateway() {
+        is_available_mock::setup_returns(true);
+        charge_mock::setup(|(account, amount)| Ok(format!("mock_{}_{}", account, amount)));
+
+        let result = charge_or_refund(&MockPaymentGateway, 1, 100, true).await;
+
+        assert_eq!(result, Ok("mock_1_100".to_string()));
+        charge_mock::assert_times(1);
+        charge_mock::assert_with(1, 100);
+        refund_mock::assert_times(0);
+    }
+
+    #[tokio::test]
+    async fn test_refund_for_undelivered_order() {
+        is_available_mock::setup_returns(true);
+        charge_mock::setup_returns(Ok("tx".to_string()));
+        refund_mock::setup(|_| Ok(()));
+
+        let result = charge_or_refund(&MockPaymentGateway, 1, 100, false).await;
+
+        assert_eq!(result, Ok("refunded tx".to_string()));
+        refund_mock::assert_with("tx".to_string());
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "is_available_mock mock not initialized")]
+    async fn test_mock_gateway_panics_for_missing_setup() {
+        let _ = charge_or_refund(&MockPaymentGateway, 1, 100, true).await;
+    }
+}
@@ -0,0 +1,69 @@
+use fnmock::derive::traitify;
+
+#[traitify(UserRepository)]
+pub mod users {
+    use fnmock::derive::{fake_function, mock_function, stub_function};
+
+    #[mock_function(ignore = [timestamp])]
+    pub fn save_user(id: u32, name: String, timestamp: i64) -> Result<(), String> {
+        // Real implementation
+        let _ = (id, name, timestamp);
+        Ok(())
+    }
+
+    #[fake_function]
+    pub async fn fetch_user(id: u32) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("user_{}", id))
+    }
+
+    #[stub_function(keyed)]
+    pub fn is_admin(id: u32) -> bool {
+        id == 0
+    }
+}
+
+pub async fn rename_user(repository: &impl UserRepository, id: u32, name: &str) -> Result<String, String> {
+    if !repository.is_admin(id) {
+        return Err("only admins can be renamed".to_string());
+    }
+
+    let old_name = repository.fetch_user(id).await?;
+    repository.save_user(id, name.to_string(), 0)?;
+
+    Ok(format!("{} -> {}", old_name, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::users::{fetch_user_fake, is_admin_stub, save_user_mock};
+
+    #[tokio::test]
+    async fn test_with_test_repository() {
+        is_admin_stub::setup_for(1, true);
+        fetch_user_fake::setup_async(|id| Box::pin(async move { Ok(format!("admin_{}", id)) }));
+        save_user_mock::setup(|_| Ok(()));
+
+        let result = rename_user(&TestUserRepository, 1, "alice").await;
+
+        assert_eq!(result, Ok("admin_1 -> alice".to_string()));
+        save_user_mock::assert_with(1, "alice".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_real_repository_uses_doubles_if_set() {
+        is_admin_stub::setup(true);
+        save_user_mock::setup(|_| Err("read only".to_string()));
+
+        let result = rename_user(&RealUserRepository, 1, "alice").await;
+
+        assert_eq!(result, Err("read only".to_string()));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "is_admin_stub stub not initialized")]
+    async fn test_test_repository_panics_for_missing_setup() {
+        let _ = rename_user(&TestUserRepository, 1, "alice").await;
+    }
+}
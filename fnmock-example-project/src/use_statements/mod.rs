@@ -0,0 +1,180 @@
+pub mod service {
+    use fnmock::derive::{fake_function, mock_function, stub_function};
+
+    #[mock_function]
+    pub fn fetch_user(id: u32) -> Result<String, String> {
+        // Real implementation
+        Ok(format!("user_{}", id))
+    }
+
+    #[stub_function]
+    pub fn greeting() -> String {
+        // Real implementation
+        "Hello".to_string()
+    }
+
+    #[mock_function]
+    pub fn fetch_avatar(id: u32) -> Option<String> {
+        // Real implementation
+        Some(format!("avatar_{}.png", id))
+    }
+
+    #[mock_function]
+    pub fn fetch_order_total(order_id: u32) -> Result<u32, String> {
+        // Real implementation
+        Ok(order_id * 10)
+    }
+
+    #[stub_function]
+    pub fn shipping_fee() -> u32 {
+        // Real implementation
+        5
+    }
+
+    #[fake_function]
+    pub fn format_price(cents: u32) -> String {
+        // Real implementation
+        format!("{}.{:02} EUR", cents / 100, cents % 100)
+    }
+
+    #[mock_function]
+    pub fn fetch_stock(item: u32) -> u32 {
+        // Real implementation
+        item % 3
+    }
+
+    #[mock_function(name = "coupon_api")]
+    pub fn fetch_coupon(code: String) -> Option<u32> {
+        // Real implementation
+        Some(code.len() as u32)
+    }
+
+    #[stub_function(name = "currency_config")]
+    pub fn currency() -> String {
+        // Real implementation
+        "EUR".to_string()
+    }
+
+    pub mod pricing {
+        use fnmock::derive::mock_function;
+
+        #[mock_function]
+        pub fn base_price(item: u32) -> u32 {
+            // Real implementation
+            item * 100
+        }
+
+        #[mock_function]
+        pub fn discount(item: u32) -> u32 {
+            // Real implementation
+            item
+        }
+    }
+}
+
+use fnmock::derive::{use_doubles, use_function_mock, use_function_stub};
+
+// Imports `fetch_user_mock` and `greeting_stub` next to the functions in test builds
+#[use_function_mock]
+use service::fetch_user;
+#[use_function_stub]
+use service::greeting;
+// Renamed imports rename the double module as well: `fetch_avatar_mock` is imported as `avatar_mock`
+#[use_function_mock]
+use service::fetch_avatar as avatar;
+
+pub fn greet_user(id: u32) -> String {
+    match fetch_user(id) {
+        Ok(name) => format!("{} {}", greeting(), name),
+        Err(_) => format!("{} stranger", greeting()),
+    }
+}
+
+pub fn avatar_or_default(id: u32) -> String {
+    avatar(id).unwrap_or_else(|| "default.png".to_string())
+}
+
+// Every function is imported with the double module of its kind
+#[use_doubles(fetch_order_total = mock, shipping_fee = stub, format_price = fake)]
+use service::{fetch_order_total, shipping_fee, format_price};
+
+pub fn order_summary(order_id: u32) -> Result<String, String> {
+    let total = fetch_order_total(order_id)? + shipping_fee();
+    Ok(format_price(total))
+}
+
+// Every function of the nested groups gets its full path, e.g. `service::pricing::base_price_mock`
+#[use_function_mock]
+use service::{pricing::{base_price, discount}, fetch_stock};
+
+pub fn offer(item: u32) -> Option<u32> {
+    if fetch_stock(item) == 0 {
+        return None;
+    }
+    Some(base_price(item) - discount(item))
+}
+
+// Double modules generated with `name = "..."` are imported by that name: `coupon_api` and `currency_config`
+#[use_function_mock(fetch_coupon(name = "coupon_api"))]
+use service::fetch_coupon;
+#[use_doubles(currency = stub(name = "currency_config"))]
+use service::currency;
+
+pub fn coupon_label(code: &str) -> String {
+    match fetch_coupon(code.to_string()) {
+        Some(amount) => format!("-{} {}", amount, currency()),
+        None => "invalid coupon".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imported_doubles() {
+        fetch_user_mock::setup(|_| Ok("alice".to_string()));
+        greeting_stub::setup("Hi".to_string());
+
+        assert_eq!(greet_user(42), "Hi alice");
+        fetch_user_mock::assert_with(42);
+    }
+
+    #[test]
+    fn test_renamed_import() {
+        avatar_mock::setup(|_| None);
+
+        assert_eq!(avatar_or_default(7), "default.png");
+        avatar_mock::assert_with(7);
+    }
+
+    #[test]
+    fn test_mixed_double_kinds() {
+        fetch_order_total_mock::setup(|_| Ok(1000));
+        shipping_fee_stub::setup(250);
+        format_price_fake::setup(|cents| format!("${}", cents));
+
+        assert_eq!(order_summary(3), Ok("$1250".to_string()));
+        fetch_order_total_mock::assert_with(3);
+    }
+
+    #[test]
+    fn test_nested_groups() {
+        fetch_stock_mock::setup(|_| 4);
+        base_price_mock::setup(|_| 500);
+        discount_mock::setup(|_| 50);
+
+        assert_eq!(offer(9), Some(450));
+        base_price_mock::assert_with(9);
+        discount_mock::assert_with(9);
+    }
+
+    #[test]
+    fn test_custom_double_names() {
+        coupon_api::setup(|_| Some(15));
+        currency_config::setup("USD".to_string());
+
+        assert_eq!(coupon_label("SALE"), "-15 USD");
+        coupon_api::assert_with("SALE".to_string());
+    }
+}
@@ -0,0 +1,21 @@
+pub mod inventory {
+    use fnmock::derive::{mock_function, stub_function};
+
+    #[mock_function]
+    pub fn fetch_stock(item: String) -> Result<u32, String> {
+        // Real implementation
+        Ok(item.len() as u32 * 10)
+    }
+
+    #[stub_function]
+    pub fn get_reorder_threshold() -> u32 {
+        // Real implementation
+        25
+    }
+}
+
+use inventory::{fetch_stock, get_reorder_threshold};
+
+pub fn needs_reorder(item: &str) -> Result<bool, String> {
+    Ok(fetch_stock(item.to_string())? < get_reorder_threshold())
+}
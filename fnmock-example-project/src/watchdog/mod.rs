@@ -0,0 +1,97 @@
+pub mod device {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub fn ping(address: String) -> bool {
+        // Real implementation
+        !address.is_empty()
+    }
+
+    #[mock_function]
+    pub async fn read_sensor(id: u32) -> Result<f32, String> {
+        // Real implementation
+        Ok(id as f32 * 1.5)
+    }
+}
+use device::{ping, read_sensor};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Pings the device and reports a timeout if the ping took longer than `timeout`.
+pub fn ping_with_watchdog(address: &str, timeout: Duration) -> Result<bool, String> {
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let watchdog_timed_out = timed_out.clone();
+    let done = Arc::new(AtomicBool::new(false));
+    let watchdog_done = done.clone();
+
+    std::thread::spawn(move || {
+        std::thread::sleep(timeout);
+        if !watchdog_done.load(Ordering::SeqCst) {
+            watchdog_timed_out.store(true, Ordering::SeqCst);
+        }
+    });
+
+    let reachable = ping(address.to_string());
+    done.store(true, Ordering::SeqCst);
+
+    if timed_out.load(Ordering::SeqCst) {
+        Err("timeout".to_string())
+    } else {
+        Ok(reachable)
+    }
+}
+
+/// Reads the sensor, giving up after `timeout`.
+pub async fn read_sensor_with_timeout(id: u32, timeout: Duration) -> Result<f32, String> {
+    tokio::time::timeout(timeout, read_sensor(id))
+        .await
+        .unwrap_or_else(|_| Err("timeout".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::device::{ping_mock, read_sensor_mock};
+
+    #[test]
+    fn test_blocking_ping_times_out() {
+        let release_handle = ping_mock::setup_blocks(|_| true);
+        release_handle.release_after(Duration::from_millis(200));
+
+        let result = ping_with_watchdog("10.0.0.1", Duration::from_millis(10));
+
+        assert_eq!(result, Err("timeout".to_string()));
+        ping_mock::assert_times(1);
+    }
+
+    #[test]
+    fn test_released_ping_returns() {
+        let release_handle = ping_mock::setup_blocks(|_| true);
+        release_handle.release();
+
+        let result = ping_with_watchdog("10.0.0.1", Duration::from_secs(10));
+
+        assert_eq!(result, Ok(true));
+    }
+
+    #[tokio::test]
+    async fn test_pending_sensor_times_out() {
+        let _release_handle = read_sensor_mock::setup_pending(|_| Ok(1.0));
+
+        let result = read_sensor_with_timeout(1, Duration::from_millis(10)).await;
+
+        assert_eq!(result, Err("timeout".to_string()));
+        read_sensor_mock::assert_times(1);
+    }
+
+    #[tokio::test]
+    async fn test_released_sensor_returns() {
+        let release_handle = read_sensor_mock::setup_pending(|_| Ok(1.0));
+        release_handle.release_after(Duration::from_millis(10));
+
+        let result = read_sensor_with_timeout(1, Duration::from_secs(10)).await;
+
+        assert_eq!(result, Ok(1.0));
+    }
+}
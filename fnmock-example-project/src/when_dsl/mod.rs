@@ -0,0 +1,74 @@
+pub mod accounts {
+    use fnmock::derive::mock_function;
+
+    #[mock_function]
+    pub fn fetch_user(id: u32) -> Result<String, String> {
+        // Real implementation
+        Err(format!("database unavailable for user {}", id))
+    }
+
+    #[mock_function]
+    pub fn grant_role(user: String, role: String) -> bool {
+        // Real implementation
+        !user.is_empty() && !role.is_empty()
+    }
+}
+
+use accounts::{fetch_user, grant_role};
+
+/// Promotes the user to an admin and reports the outcome
+pub fn promote(id: u32) -> String {
+    match fetch_user(id) {
+        Ok(user) if grant_role(user.clone(), "admin".to_string()) => format!("{} is now an admin", user),
+        Ok(user) => format!("{} can't become an admin", user),
+        Err(e) => format!("promotion failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::accounts::{fetch_user_mock, grant_role_mock};
+    use fnmock::when;
+
+    #[test]
+    fn test_reads_like_a_specification() {
+        when!(fetch_user(42)).then_return(Ok("alice".to_string()));
+        when!(fetch_user(7)).then_return(Ok("bob".to_string()));
+        when!(fetch_user(_)).then_return(Err("not found".to_string()));
+        when!(grant_role("alice", _)).then_return(true);
+        when!(grant_role(_, "admin")).then_return(false);
+
+        assert_eq!(promote(42), "alice is now an admin");
+        assert_eq!(promote(7), "bob can't become an admin");
+        assert_eq!(promote(1), "promotion failed: not found");
+        grant_role_mock::assert_times(2);
+    }
+
+    #[test]
+    fn test_then_receives_the_params() {
+        when!(accounts::fetch_user(_)).then(|id| Ok(format!("user_{}", id)));
+        when!(grant_role(_, _)).then(|(user, _)| user.ends_with('3'));
+
+        assert_eq!(promote(3), "user_3 is now an admin");
+        assert_eq!(promote(4), "user_4 can't become an admin");
+    }
+
+    #[test]
+    #[should_panic(expected = "user is locked")]
+    fn test_then_panic() {
+        when!(fetch_user(13)).then_panic("user is locked");
+
+        promote(13);
+    }
+
+    #[test]
+    fn test_cases_come_before_setup() {
+        fetch_user_mock::setup(|_| Ok("carol".to_string()));
+        when!(fetch_user(42)).then_return(Err("banned".to_string()));
+        grant_role_mock::setup(|_| true);
+
+        assert_eq!(promote(42), "promotion failed: banned");
+        assert_eq!(promote(1), "carol is now an admin");
+    }
+}
@@ -0,0 +1,12 @@
+//! Integration tests of the sandbox build, run with `cargo test --features sandbox`
+//! (requires the `runtime-doubles` feature of fnmock)
+#![cfg(feature = "sandbox")]
+
+use fnmock_example_project::sandbox::{checkout, enable_sandbox};
+
+#[fnmock::test]
+fn test_sandbox_replaces_payment_provider() {
+    enable_sandbox();
+
+    assert_eq!(checkout("alice", 100), Ok("sandbox-100".to_string()));
+}
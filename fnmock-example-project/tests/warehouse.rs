@@ -0,0 +1,25 @@
+//! Integration tests configuring the doubles of the library (requires the `test-doubles` feature of fnmock)
+
+use fnmock_example_project::warehouse::inventory::{fetch_stock_mock, get_reorder_threshold_stub};
+use fnmock_example_project::warehouse::needs_reorder;
+
+#[fnmock::test]
+fn test_reorder_below_threshold() {
+    fetch_stock_mock::setup(|_| Ok(3));
+    get_reorder_threshold_stub::setup(5);
+
+    assert_eq!(needs_reorder("bolt"), Ok(true));
+    fetch_stock_mock::assert_with("bolt".to_string());
+}
+
+#[fnmock::test]
+fn test_error_is_passed_on() {
+    fetch_stock_mock::setup(|_| Err("warehouse offline".to_string()));
+
+    assert_eq!(needs_reorder("bolt"), Err("warehouse offline".to_string()));
+}
+
+#[fnmock::test]
+fn test_original_implementation_without_setup() {
+    assert_eq!(needs_reorder("bolt"), Ok(false));
+}
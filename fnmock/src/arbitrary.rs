@@ -0,0 +1,88 @@
+//! Return values drawn from proptest strategies (requires the `proptest` feature)
+//!
+//! Hand-written return values only cover the responses the test author thought of.
+//! With the `proptest` feature, mock and stub modules of functions with a nameable return type get
+//! `setup_arbitrary()` and `setup_strategy(strategy)`, which draw a new return value for every call,
+//! so property tests can stress the caller with diverse responses:
+//!
+//! ```ignore
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn test_price_is_never_negative(quantity in 0u32..100) {
+//!         get_discount_stub::setup_strategy(0u32..=100);
+//!         fetch_unit_price_mock::setup_arbitrary();
+//!
+//!         prop_assert!(total_price(quantity) >= 0.0);
+//!     }
+//! }
+//! ```
+//!
+//! The values are drawn by a test runner of their own, so they don't shrink with the inputs of the property test.
+//! The runner uses the proptest configuration (e.g. `PROPTEST_RNG_SEED` makes the values reproducible).
+
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+/// Returns a generator drawing a new value from the strategy for every call.
+///
+/// Used by the `setup_arbitrary` and `setup_strategy` proxy functions, which set up the generator
+/// as the implementation of a mock or the factory of a stub.
+/// The generator is `Send` if the strategy is, so it can be used by the stubs of every storage backend.
+///
+/// # Panics
+///
+/// The generator panics if the strategy rejects too many values, e.g. because of a strict `prop_filter`
+pub fn generator<S>(strategy: S) -> impl FnMut() -> S::Value
+where
+    S: Strategy,
+{
+    let mut runner = TestRunner::default();
+    move || {
+        strategy.new_tree(&mut runner)
+            .unwrap_or_else(|reason| panic!("Failed to draw a return value from the strategy: {}", reason))
+            .current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::function_stub::FunctionStub;
+    use proptest::arbitrary::any;
+    use proptest::strategy::Just;
+
+    #[test]
+    fn test_values_are_drawn_from_the_strategy() {
+        let mut next = generator(10u32..20);
+
+        for _ in 0..100 {
+            assert!((10..20).contains(&next()));
+        }
+    }
+
+    #[test]
+    fn test_generator_draws_diverse_values() {
+        let mut next = generator(any::<u64>());
+        let values: Vec<u64> = (0..10).map(|_| next()).collect();
+
+        assert!(values.iter().any(|value| *value != values[0]));
+    }
+
+    #[test]
+    fn test_generator_can_be_a_stub_factory() {
+        let mut stub: FunctionStub<String> = FunctionStub::new("get_name");
+        stub.setup_factory(generator(Just("alice".to_string())));
+
+        assert_eq!(stub.get_return_value(), "alice");
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to draw a return value from the strategy")]
+    fn test_generator_fails_for_rejecting_strategies() {
+        let mut next = generator(any::<u32>().prop_filter("never", |_| false));
+
+        next();
+    }
+}
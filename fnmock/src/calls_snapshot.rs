@@ -0,0 +1,49 @@
+//! Snapshots of the recorded calls of mocks, reviewed and approved with insta (requires the `insta` feature)
+//!
+//! Asserting every call of a complex interaction with `assert_with` gets unreadable quickly.
+//! `assert_calls_snapshot!` stores the formatted call history of a mock as an insta snapshot instead,
+//! so changes of the interaction show up as a snapshot diff, which is reviewed with `cargo insta review`.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! #[test]
+//! fn test_sync_users() {
+//!     fetch_user_mock::setup(|id| Ok(format!("user_{}", id)));
+//!
+//!     sync_users(&[42, 7]);
+//!
+//!     fnmock::assert_calls_snapshot!(fetch_user_mock);
+//! }
+//! ```
+//!
+//! The snapshot contains the output of the `formatted_calls()` proxy function:
+//!
+//! ```text
+//! fetch_user_mock (2 calls)
+//! [0] 42
+//! [1] 7
+//! ```
+
+/// Asserts the formatted call history of a mock against an insta snapshot.
+///
+/// Takes the path of the mock module and optionally the name of the snapshot,
+/// which defaults to the name of the test function like for `insta::assert_snapshot!`.
+/// The snapshot is compared with the `formatted_calls()` proxy function of the mock module,
+/// so it works with thread local and shared mocks.
+///
+/// # Examples
+///
+/// ```ignore
+/// fnmock::assert_calls_snapshot!(fetch_user_mock);
+/// fnmock::assert_calls_snapshot!(users::fetch_user_mock, "fetch_user_after_retry");
+/// ```
+#[macro_export]
+macro_rules! assert_calls_snapshot {
+    ($($mock:ident)::+ $(,)?) => {
+        $crate::insta::assert_snapshot!($($mock)::+::formatted_calls())
+    };
+    ($($mock:ident)::+, $name:expr $(,)?) => {
+        $crate::insta::assert_snapshot!($name, $($mock)::+::formatted_calls())
+    };
+}
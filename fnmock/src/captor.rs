@@ -0,0 +1,97 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Handle collecting the params of the calls of a mock, similar to an argument captor
+///
+/// A captor is returned by the `capture()` proxy function of a generated mock module.
+/// Every following call of the mock adds a copy of its params to the captor, so a test can run
+/// domain-specific checks on them instead of comparing whole calls with `assert_with`.
+/// Clones of a captor share the captured values, and the values stay available after the mock is cleared.
+///
+/// # Usage
+///
+/// ```ignore
+/// let captor = send_email_mock::capture();
+///
+/// notify_users(&["alice", "bob"]);
+///
+/// let recipients: Vec<String> = captor.values().into_iter().map(|(user, _)| user).collect();
+/// assert_eq!(recipients, vec!["alice", "bob"]);
+/// ```
+///
+/// Params can also be collected into any collection implementing `Extend`
+/// with the `capture_into(collector)` proxy function.
+///
+/// # Fields
+///
+/// - `values` - the captured params in the order of the calls
+#[derive(Clone)]
+pub struct Captor<T> {
+    values: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T> Default for Captor<T> {
+    fn default() -> Self {
+        Self {
+            values: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+}
+
+impl<T> Captor<T>
+where
+    T: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the collection the captured params are added to.
+    pub(crate) fn collector(&self) -> Rc<RefCell<Vec<T>>> {
+        self.values.clone()
+    }
+
+    /// Returns the captured params of all calls, in call order.
+    pub fn values(&self) -> Vec<T> {
+        self.values.borrow().clone()
+    }
+
+    /// Returns the params of the most recent captured call, or `None` if no call was captured.
+    pub fn value(&self) -> Option<T> {
+        self.values.borrow().last().cloned()
+    }
+
+    /// Returns the number of captured calls.
+    pub fn len(&self) -> usize {
+        self.values.borrow().len()
+    }
+
+    /// Returns `true` if no call was captured.
+    pub fn is_empty(&self) -> bool {
+        self.values.borrow().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_values() {
+        let captor: Captor<u32> = Captor::new();
+        captor.clone().collector().borrow_mut().push(1);
+        captor.collector().borrow_mut().push(2);
+
+        assert_eq!(captor.values(), vec![1, 2]);
+        assert_eq!(captor.value(), Some(2));
+        assert_eq!(captor.len(), 2);
+    }
+
+    #[test]
+    fn test_new_captor_is_empty() {
+        let captor: Captor<String> = Captor::new();
+
+        assert!(captor.is_empty());
+        assert_eq!(captor.value(), None);
+    }
+}
@@ -0,0 +1,207 @@
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Comparison of a recorded parameter, replacing its `PartialEq` implementation
+pub type Comparator<T> = fn(&T, &T) -> bool;
+
+/// Wrapper recording a parameter that is compared with a custom closure
+///
+/// Parameters listed in `compare` of `mock_function` are recorded in this wrapper, so parameters
+/// without `PartialEq` (or with fields that differ between runs) can still be checked by `assert_with`.
+/// The closure can't capture variables, since it is stored as a function pointer.
+///
+/// # Usage
+///
+/// ```ignore
+/// #[mock_function(compare(db = "|a, b| a.url() == b.url()"))]
+/// fn save(db: Database, id: u32) -> Result<(), String> { ... }
+///
+/// // Passes for every database with the same url
+/// save_mock::assert_with(Database::connect("postgres://localhost"), 1);
+/// ```
+///
+/// # Fields
+///
+/// - `value` - the recorded parameter
+/// - `compare` - the closure deciding if two recorded parameters are equal
+#[derive(Clone)]
+pub struct Compared<T> {
+    value: T,
+    compare: Comparator<T>,
+}
+
+impl<T> Compared<T> {
+    pub fn new(value: T, compare: Comparator<T>) -> Self {
+        Self { value, compare }
+    }
+
+    /// Returns the wrapped parameter.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> PartialEq for Compared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.compare)(&self.value, &other.value)
+    }
+}
+
+// Formatted like the parameter, so failure messages aren't cluttered by the wrapper
+impl<T: Debug> Debug for Compared<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T> Deref for Compared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Compared<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+/// Wrapper recording a parameter together with a comparable value derived from it
+///
+/// Parameters listed in `project` of `mock_function` are recorded in this wrapper. The assertions
+/// compare and format the projected value, while the mock implementation still receives the parameter.
+///
+/// # Usage
+///
+/// ```ignore
+/// #[mock_function(project(db = "db.name()"))]
+/// fn save(db: Database, id: u32) -> Result<(), String> { ... }
+///
+/// // Passes for every database with the same name
+/// save_mock::assert_with(Database::open("orders"), 1);
+/// ```
+///
+/// # Fields
+///
+/// - `value` - the recorded parameter
+/// - `projection` - the value derived from the parameter, which is compared and formatted
+pub struct Projected<T> {
+    value: T,
+    projection: Box<dyn Projection>,
+}
+
+impl<T> Projected<T> {
+    pub fn new<U>(value: T, project: impl FnOnce(&T) -> U) -> Self
+    where
+        U: PartialEq + Debug + Clone + Send + 'static,
+    {
+        let projection = Box::new(project(&value));
+        Self { value, projection }
+    }
+
+    /// Returns the wrapped parameter.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Clone> Clone for Projected<T> {
+    fn clone(&self) -> Self {
+        Self { value: self.value.clone(), projection: self.projection.clone_projection() }
+    }
+}
+
+impl<T> PartialEq for Projected<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.projection.eq_projection(&*other.projection)
+    }
+}
+
+// Formatted like the projection, since that is what the assertions compare
+impl<T> Debug for Projected<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.projection.fmt(f)
+    }
+}
+
+impl<T> Deref for Projected<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Projected<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+/// Type-erased projected value, so the type of the projection doesn't have to be named
+trait Projection: Debug + Send {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_projection(&self, other: &dyn Projection) -> bool;
+    fn clone_projection(&self) -> Box<dyn Projection>;
+}
+
+impl<U: PartialEq + Debug + Clone + Send + 'static> Projection for U {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_projection(&self, other: &dyn Projection) -> bool {
+        other.as_any().downcast_ref::<U>().is_some_and(|other| self == other)
+    }
+
+    fn clone_projection(&self) -> Box<dyn Projection> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug)]
+    struct Database {
+        url: String,
+        connection_id: u32,
+    }
+
+    fn database(url: &str, connection_id: u32) -> Compared<Database> {
+        Compared::new(Database { url: url.to_string(), connection_id }, |a, b| a.url == b.url)
+    }
+
+    #[test]
+    fn test_compares_with_the_closure() {
+        assert!(database("postgres://localhost", 1) == database("postgres://localhost", 2));
+        assert!(database("postgres://localhost", 1) != database("postgres://remote", 1));
+    }
+
+    #[test]
+    fn test_formats_and_derefs_the_wrapped_value() {
+        let db = database("postgres://localhost", 1);
+
+        assert_eq!(format!("{:?}", db), r#"Database { url: "postgres://localhost", connection_id: 1 }"#);
+        assert_eq!(db.connection_id, 1);
+        assert_eq!(db.into_inner().url, "postgres://localhost");
+    }
+
+    fn named(name: &str, connection_id: u32) -> Projected<Database> {
+        Projected::new(Database { url: format!("postgres://{}", name), connection_id }, |db| db.url.clone())
+    }
+
+    #[test]
+    fn test_compares_and_formats_the_projection() {
+        let db = named("orders", 1);
+
+        assert!(db == named("orders", 2));
+        assert!(db != named("users", 1));
+        assert_eq!(format!("{:?}", db.clone()), r#""postgres://orders""#);
+        assert_eq!(db.connection_id, 1);
+    }
+}
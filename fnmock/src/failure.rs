@@ -0,0 +1,255 @@
+use std::cell::{Cell, RefCell};
+use std::fmt::{Debug, Display};
+
+/// The kind of a verification failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// An assertion (`assert_times`, `assert_with`, ...) failed
+    Assertion,
+    /// A double was called without being set up for the call
+    MissingSetup,
+}
+
+/// Failure reported by a double to the failure handler
+///
+/// # Fields
+///
+/// - `double_name` - the name of the double, e.g. `fetch_user_mock`
+/// - `kind` - whether an assertion failed or a setup was missing
+/// - `message` - the description of the failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationFailure {
+    pub double_name: String,
+    pub kind: FailureKind,
+    pub message: String,
+}
+
+impl Display for VerificationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for VerificationFailure {}
+
+thread_local! {
+    static FAILURE_HANDLER: Cell<fn(VerificationFailure)> = const { Cell::new(panic_on_failure) };
+    static COLLECTED_FAILURES: RefCell<Vec<VerificationFailure>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The default failure handler, panicking with the message of the failure.
+pub fn panic_on_failure(failure: VerificationFailure) {
+    panic!("{}", failure.message)
+}
+
+/// Routes the failures of all doubles on the current thread to a custom handler instead of panicking.
+///
+/// Assertions continue after the handler returned, so failures can be collected and reported together
+/// or passed on to a custom test framework. Missing setups still panic after the handler returned,
+/// since the double has no value to return.
+///
+/// # Usage
+///
+/// ```ignore
+/// fn log_failure(failure: fnmock::failure::VerificationFailure) {
+///     eprintln!("[{}] {}", failure.double_name, failure);
+/// }
+///
+/// fnmock::set_failure_handler(log_failure);
+/// fetch_user_mock::assert_times(1); // logs instead of panicking
+/// fnmock::failure::reset_failure_handler();
+/// ```
+pub fn set_failure_handler(handler: fn(VerificationFailure)) {
+    FAILURE_HANDLER.with(|current| current.set(handler));
+}
+
+/// Restores the default failure handler, which panics.
+pub fn reset_failure_handler() {
+    set_failure_handler(panic_on_failure);
+}
+
+/// Runs `test` with a handler collecting the failures instead of panicking and returns them.
+///
+/// The previous failure handler is restored afterward, even if `test` panics.
+/// The failures collected before the panic are discarded, so they don't show up in later collections.
+pub fn collect_failures(test: impl FnOnce()) -> Vec<VerificationFailure> {
+    let guard = CollectionGuard {
+        previous_handler: FAILURE_HANDLER.with(|current| current.replace(collect_failure)),
+        collected_before: COLLECTED_FAILURES.with(|failures| failures.borrow().len()),
+    };
+
+    test();
+
+    COLLECTED_FAILURES.with(|failures| failures.borrow_mut().split_off(guard.collected_before))
+}
+
+/// Runs `assertion` without panicking and returns its first failure as an error.
+///
+/// Used by the `try_assert_*` proxy functions of the mocks, and usable with any other assertion,
+/// so helper functions can pass the failure on with `?` instead of aborting the test.
+///
+/// # Usage
+///
+/// ```ignore
+/// fn check_welcome_mail(user: &str) -> Result<(), fnmock::VerificationFailure> {
+///     send_mail_mock::try_assert_times(1)?;
+///     fnmock::failure::try_verify(|| send_mail_mock::assert_last_call_with(user.to_string()))
+/// }
+/// ```
+pub fn try_verify(assertion: impl FnOnce()) -> Result<(), VerificationFailure> {
+    match collect_failures(assertion).into_iter().next() {
+        Some(failure) => Err(failure),
+        None => Ok(()),
+    }
+}
+
+/// Restores the failure handler and discards the failures of the collection when dropped
+///
+/// # Fields
+///
+/// - `previous_handler` - the failure handler before the collection started
+/// - `collected_before` - the number of failures collected by enclosing collections
+struct CollectionGuard {
+    previous_handler: fn(VerificationFailure),
+    collected_before: usize,
+}
+
+impl Drop for CollectionGuard {
+    fn drop(&mut self) {
+        set_failure_handler(self.previous_handler);
+        COLLECTED_FAILURES.with(|failures| failures.borrow_mut().truncate(self.collected_before));
+    }
+}
+
+fn collect_failure(failure: VerificationFailure) {
+    COLLECTED_FAILURES.with(|failures| failures.borrow_mut().push(failure));
+}
+
+/// Reports a failed assertion to the failure handler of the current thread.
+pub(crate) fn report_assertion(double_name: &str, message: String) {
+    report(VerificationFailure {
+        double_name: double_name.to_string(),
+        kind: FailureKind::Assertion,
+        message,
+    });
+}
+
+/// Reports a missing setup to the failure handler of the current thread and panics afterward,
+/// since the double can't continue without a setup.
+pub(crate) fn report_missing_setup(double_name: &str, message: String) -> ! {
+    report(VerificationFailure {
+        double_name: double_name.to_string(),
+        kind: FailureKind::MissingSetup,
+        message: message.clone(),
+    });
+    panic!("{}", message)
+}
+
+/// Lists the recorded calls of a double with their indices, to be appended to a failure message.
+///
+/// ```text
+/// Recorded calls:
+///   [0] (1, "a")
+///   [1] (2, "b")
+/// ```
+pub(crate) fn describe_calls<Params: Debug>(calls: &[Params]) -> String {
+    if calls.is_empty() {
+        return "\nRecorded calls: none".to_string();
+    }
+
+    let mut description = "\nRecorded calls:".to_string();
+    for (idx, params) in calls.iter().enumerate() {
+        description.push_str(&format!("\n  [{}] {:?}", idx, params));
+    }
+    description
+}
+
+fn report(failure: VerificationFailure) {
+    let handler = FAILURE_HANDLER.with(|current| current.get());
+    handler(failure);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Expected double to be called")]
+    fn test_default_handler_panics() {
+        report_assertion("double", "Expected double to be called".to_string());
+    }
+
+    #[test]
+    fn test_collect_failures() {
+        let failures = collect_failures(|| {
+            report_assertion("first", "first failed".to_string());
+            report_assertion("second", "second failed".to_string());
+        });
+
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].double_name, "first");
+        assert_eq!(failures[1].to_string(), "second failed");
+        assert_eq!(failures[1].kind, FailureKind::Assertion);
+    }
+
+    #[test]
+    fn test_collect_failures_restores_previous_handler() {
+        fn ignore_failure(_: VerificationFailure) {}
+        set_failure_handler(ignore_failure);
+
+        let failures = collect_failures(|| report_assertion("double", "failed".to_string()));
+        report_assertion("double", "ignored".to_string());
+
+        assert_eq!(failures.len(), 1);
+        reset_failure_handler();
+    }
+
+    #[test]
+    fn test_collect_failures_discards_failures_when_panicking() {
+        let result = std::panic::catch_unwind(|| collect_failures(|| {
+            report_assertion("double", "failed".to_string());
+            panic!("test failed");
+        }));
+
+        assert!(result.is_err());
+        assert!(COLLECTED_FAILURES.with(|failures| failures.borrow().is_empty()));
+        assert_eq!(collect_failures(|| {}), Vec::new());
+    }
+
+    #[test]
+    fn test_try_verify_returns_first_failure() {
+        let result = try_verify(|| {
+            report_assertion("first", "first failed".to_string());
+            report_assertion("second", "second failed".to_string());
+        });
+
+        assert_eq!(result.unwrap_err().double_name, "first");
+        assert_eq!(try_verify(|| {}), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "double not initialized")]
+    fn test_missing_setup_panics_after_handler() {
+        fn ignore_failure(_: VerificationFailure) {}
+        set_failure_handler(ignore_failure);
+
+        report_missing_setup("double", "double not initialized".to_string());
+    }
+
+    #[test]
+    fn test_describe_calls_lists_calls_with_indices() {
+        assert_eq!(describe_calls(&[(1, "a"), (2, "b")]), "\nRecorded calls:\n  [0] (1, \"a\")\n  [1] (2, \"b\")");
+        assert_eq!(describe_calls::<u32>(&[]), "\nRecorded calls: none");
+    }
+
+    #[test]
+    fn test_missing_setup_is_reported() {
+        let failures = collect_failures(|| {
+            let result = std::panic::catch_unwind(|| report_missing_setup("double", "double not initialized".to_string()));
+            assert!(result.is_err());
+        });
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].kind, FailureKind::MissingSetup);
+    }
+}
@@ -1,11 +1,48 @@
+use std::cell::{Cell, RefCell, RefMut};
+use std::rc::Rc;
+use std::time::Duration;
+use crate::failure::report_missing_setup;
+use crate::registry::MockControl;
+
+/// Implementation of a fake, selected when the faked function is called
+///
+/// # Variants
+///
+/// - `Function` - a function pointer, copied out of the fake without any borrow checks
+/// - `Closure` - a closure capturing test state, shared between snapshots of the fake.
+///   It is borrowed mutably while it runs (see [`borrow_closure`])
+pub enum Implementation<Function, Closure>
+where
+    Function: Copy + 'static,
+    Closure: ?Sized + 'static,
+{
+    Function(Function),
+    Closure(Rc<RefCell<Closure>>),
+}
+
+/// Borrows a closure implementation mutably, so it can be called.
+///
+/// # Panics
+///
+/// If the closure is already running, i.e. it calls the faked function again.
+/// Recursive implementations have to be set up as function pointers.
+pub fn borrow_closure<'a, Closure: ?Sized>(closure: &'a RefCell<Closure>, name: &str) -> RefMut<'a, Closure> {
+    closure.try_borrow_mut().unwrap_or_else(|_| panic!(
+        "{} fake was called again from its own closure. Set up a function pointer with setup_fn for recursive fakes",
+        name
+    ))
+}
+
 /// Struct for faking a function with a custom implementation
 ///
 /// Fakes - in contrast to mocks - do not let you make assertions about if and how the function was called.
 ///
 /// # Generics
 ///
-/// - `Function: 'static + Copy` - the function type
-///   - Typically a function pointer like `fn(Args) -> Return`. Closures can be coerced to `fn` types if they do not capture any variables.
+/// - `Function: Copy + 'static` - the function pointer type like `fn(Args) -> Return`.
+///   Closures can be coerced to `fn` types if they do not capture any variables.
+/// - `Closure: ?Sized + 'static` - the closure type, typically a trait object like `dyn FnMut(Args) -> Return`,
+///   for implementations capturing test state (e.g. a `Vec` recording side effects)
 ///
 /// # Usage
 ///
@@ -15,7 +52,7 @@
 ///
 /// The function `calculate` is supposed to be faked for testing.
 ///
-/// ```
+/// ```ignore
 /// pub(crate) fn calculate(x: i32, y: i32) -> i32 {
 ///     x + y
 /// }
@@ -24,33 +61,42 @@
 /// Now we create a fake function - it should be the same signature,
 /// but with `_fake` at the end of the name and the body calls the fake implementation.
 ///
-/// ```
+/// ```ignore
 /// pub(crate) fn calculate_fake(x: i32, y: i32) -> i32 {
-///     calculate_fake::get_implementation()(x, y)
+///     calculate_fake::call(x, y)
 /// }
 /// ```
 ///
 /// Create a module named `calculate_fake` with the fake infrastructure:
 ///
-/// ```
+/// ```ignore
 /// pub(crate) mod calculate_fake {
-///     use fnmock::function_fake::FunctionFake;
+///     use fnmock::function_fake::{borrow_closure, FunctionFake, Implementation};
 ///     
 ///     type Function = fn(i32, i32) -> i32;
+///     type Closure = dyn FnMut(i32, i32) -> i32;
 ///     
+///     // The fake stores the implementation in a Cell and a RefCell itself, so it isn't wrapped in another one
 ///     thread_local! {
-///         static FAKE: std::cell::RefCell<FunctionFake<Function>> =
-///             std::cell::RefCell::new(FunctionFake::new("calculate"));
+///         static FAKE: FunctionFake<Function, Closure> = FunctionFake::new("calculate");
 ///     }
 ///
 ///     // Here we create proxy calls for the fake functions.
 ///     // This allows us to use `calculate_fake::` for all the important fake functionalities.
-///     pub(crate) fn setup(new_f: Function) {
-///         FAKE.with(|fake| { fake.borrow_mut().setup(new_f) })
+///     pub(crate) fn setup(new_f: impl FnMut(i32, i32) -> i32 + 'static) {
+///         FAKE.with(|fake| { fake.setup_closure(Rc::new(RefCell::new(new_f))) })
+///     }
+///
+///     pub(crate) fn setup_fn(new_f: Function) {
+///         FAKE.with(|fake| { fake.setup(new_f) })
 ///     }
 ///     
-///     pub(crate) fn get_implementation() -> Function {
-///         FAKE.with(|fake| { fake.borrow().get_implementation() })
+///     pub(crate) fn call(x: i32, y: i32) -> i32 {
+///         // The fake isn't borrowed while the implementation runs, so it may call the faked function again
+///         match FAKE.with(|fake| { fake.get_implementation() }) {
+///             Implementation::Function(implementation) => implementation(x, y),
+///             Implementation::Closure(implementation) => (*borrow_closure(&implementation, "calculate"))(x, y),
+///         }
 ///     }
 ///
 ///     // ...
@@ -61,43 +107,139 @@
 /// # Fields
 ///
 /// - `name` - the name of the function for display purposes when panicking
-/// - `implementation` - the fake function implementation or None
-pub struct FunctionFake<Function>
+/// - `function` - the fake function pointer or None
+///   - Stored in a `Cell`, so calling the fake is a load and a branch without any borrow checks
+/// - `closure` - the fake closure or None, only set if `function` is None.
+///   Stored in a `RefCell`, so it can be a `FnMut`. Snapshots share the closure, so state captured by it isn't restored
+/// - `delay` - how long the faked function sleeps before returning, if it is set up with a delay
+pub struct FunctionFake<Function, Closure>
 where
-    Function: 'static + Copy,
+    Function: Copy + 'static,
+    Closure: ?Sized + 'static,
 {
     name: String,
-    implementation: Option<Function>,
+    function: Cell<Option<Function>>,
+    closure: RefCell<Option<Rc<RefCell<Closure>>>>,
+    delay: Cell<Option<Duration>>,
 }
 
-impl<Function> FunctionFake<Function>
+// Implemented by hand, since deriving would require `Closure: Clone`.
+impl<Function, Closure> Clone for FunctionFake<Function, Closure>
 where
-    Function: 'static + Copy,
+    Function: Copy + 'static,
+    Closure: ?Sized + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            function: Cell::new(self.function.get()),
+            closure: RefCell::new(self.closure.borrow().clone()),
+            delay: Cell::new(self.delay()),
+        }
+    }
+}
+
+impl<Function, Closure> FunctionFake<Function, Closure>
+where
+    Function: Copy + 'static,
+    Closure: ?Sized + 'static,
 {
     pub fn new(function_name: &str) -> Self {
         Self {
             name: function_name.to_string(),
-            implementation: None,
+            function: Cell::new(None),
+            closure: RefCell::new(None),
+            delay: Cell::new(None),
         }
     }
 
     // --- Faking ---
 
-    pub fn setup(&mut self, new_f: Function) {
-        self.implementation = Some(new_f);
+    /// Sets up a function pointer as implementation, which is called without any borrow checks.
+    pub fn setup(&self, new_f: Function) {
+        self.function.set(Some(new_f));
+        *self.closure.borrow_mut() = None;
+        self.delay.set(None);
+    }
+
+    /// Sets up a closure capturing test-local state as implementation.
+    ///
+    /// The closure may mutate the captured state, since it is borrowed mutably while it runs.
+    /// Calling the faked function again from the closure panics (see [`borrow_closure`]).
+    pub fn setup_closure(&self, new_f: Rc<RefCell<Closure>>) {
+        self.function.set(None);
+        *self.closure.borrow_mut() = Some(new_f);
+        self.delay.set(None);
+    }
+
+    /// Sets up the implementation and how long the faked function sleeps before returning.
+    ///
+    /// The sleeping is done by the faked function, so async functions can await a timer instead of blocking.
+    pub fn setup_with_delay(&self, delay: Duration, new_f: Rc<RefCell<Closure>>) {
+        self.setup_closure(new_f);
+        self.delay.set(Some(delay));
+    }
+
+    pub fn clear(&self) {
+        self.function.set(None);
+        *self.closure.borrow_mut() = None;
+        self.delay.set(None);
     }
 
-    pub fn clear(&mut self) {
-        self.implementation = None;
+    /// Replaces the implementation and the delay with the ones of another fake, e.g. one restored from a snapshot.
+    pub fn restore(&self, state: Self) {
+        self.function.set(state.function.get());
+        *self.closure.borrow_mut() = state.closure.into_inner();
+        self.delay.set(state.delay.get());
     }
 
+    #[inline]
     pub fn is_set(&self) -> bool {
-        self.implementation.is_some()
+        self.function.get().is_some() || self.closure.borrow().is_some()
     }
 
-    pub fn get_implementation(&self) -> Function
+    #[inline]
+    pub fn try_get_implementation(&self) -> Option<Implementation<Function, Closure>> {
+        // Function pointers take the fast path, only closures are borrowed
+        if let Some(function) = self.function.get() {
+            return Some(Implementation::Function(function));
+        }
+        self.closure.borrow().clone().map(Implementation::Closure)
+    }
+
+    /// Gets how long the faked function sleeps before returning, if the fake is set up with a delay.
+    #[inline]
+    pub fn delay(&self) -> Option<Duration> {
+        self.delay.get()
+    }
+
+    #[inline]
+    pub fn get_implementation(&self) -> Implementation<Function, Closure>
     {
-        self.implementation.expect(format!("{} fake not initialized", self.name).as_str())
+        self.try_get_implementation()
+            .unwrap_or_else(|| report_missing_setup(&self.name, format!("{} fake not initialized", self.name)))
+    }
+}
+
+
+impl<Function, Closure> MockControl for FunctionFake<Function, Closure>
+where
+    Function: Copy + 'static,
+    Closure: ?Sized + 'static,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Fakes don't record their calls
+    fn times_called(&self) -> Option<u32> {
+        None
+    }
+
+    fn verify(&self) {}
+
+    fn clear(&mut self) {
+        FunctionFake::clear(self)
     }
 }
 
@@ -105,6 +247,10 @@ where
 mod tests {
     use super::*;
 
+    type AddFn = fn(i32, i32) -> i32;
+    type AddClosure = dyn FnMut(i32, i32) -> i32;
+    type AddFake = FunctionFake<AddFn, AddClosure>;
+
     // Helper fake functions for testing
     fn add_fake_implementation(a: i32, b: i32) -> i32 {
         a + b
@@ -122,99 +268,122 @@ mod tests {
         name.iter().sum()
     }
 
+    // Calls the implementation like the generated `call` proxy
+    fn call_add(implementation: Implementation<AddFn, AddClosure>, a: i32, b: i32) -> i32 {
+        match implementation {
+            Implementation::Function(implementation) => implementation(a, b),
+            Implementation::Closure(implementation) => (*borrow_closure(&implementation, "add"))(a, b),
+        }
+    }
+
+    // Wraps a closure like the generated `setup` proxy
+    fn closure(implementation: impl FnMut(i32, i32) -> i32 + 'static) -> Rc<RefCell<AddClosure>> {
+        Rc::new(RefCell::new(implementation))
+    }
+
     #[test]
     fn test_new_creates_fake_with_correct_name() {
-        let fake: FunctionFake<fn(i32, i32) -> i32> = FunctionFake::new("test_function");
+        let fake = AddFake::new("test_function");
         assert_eq!(fake.name, "test_function");
-        assert!(fake.implementation.is_none());
+        assert!(!fake.is_set());
     }
 
     #[test]
     fn test_fake_implementation_sets_function() {
-        let mut fake: FunctionFake<fn(i32, i32) -> i32> = FunctionFake::new("add");
+        let fake = AddFake::new("add");
         fake.setup(add_fake_implementation);
-        assert!(fake.implementation.is_some());
+        assert!(fake.is_set());
+        assert!(fake.function.get().is_some());
     }
 
     #[test]
     fn test_get_implementation_returns_function() {
-        let mut fake: FunctionFake<fn(i32, i32) -> i32> = FunctionFake::new("add");
+        let fake = AddFake::new("add");
         fake.setup(add_fake_implementation);
         
-        let implementation = fake.get_implementation();
-        let result = implementation(5, 3);
+        let result = call_add(fake.get_implementation(), 5, 3);
         assert_eq!(result, 8);
     }
 
     #[test]
     #[should_panic(expected = "add fake not initialized")]
     fn test_get_implementation_panics_when_not_initialized() {
-        let fake: FunctionFake<fn(i32, i32) -> i32> = FunctionFake::new("add");
+        let fake = AddFake::new("add");
         fake.get_implementation();
     }
 
     #[test]
     fn test_clear_fake_resets_implementation() {
-        let mut fake: FunctionFake<fn(i32, i32) -> i32> = FunctionFake::new("add");
+        let fake = AddFake::new("add");
         fake.setup(add_fake_implementation);
         
-        assert!(fake.implementation.is_some());
+        assert!(fake.is_set());
         
         fake.clear();
         
-        assert!(fake.implementation.is_none());
+        assert!(!fake.is_set());
+
+        fake.setup_closure(closure(|a, b| a - b));
+        fake.clear();
+
+        assert!(!fake.is_set());
     }
 
     #[test]
     fn test_fake_can_be_replaced() {
-        let mut fake: FunctionFake<fn(i32, i32) -> i32> = FunctionFake::new("math");
+        let fake = AddFake::new("math");
         fake.setup(add_fake_implementation);
         
-        let implementation1 = fake.get_implementation();
-        let result1 = implementation1(5, 3);
+        let result1 = call_add(fake.get_implementation(), 5, 3);
         assert_eq!(result1, 8);
         
         fake.setup(multiply_fake_implementation);
-        let implementation2 = fake.get_implementation();
-        let result2 = implementation2(5, 3);
+        let result2 = call_add(fake.get_implementation(), 5, 3);
         assert_eq!(result2, 15);
     }
 
+    #[test]
+    fn test_function_and_closure_replace_each_other() {
+        let fake = AddFake::new("math");
+        fake.setup(add_fake_implementation);
+        fake.setup_closure(closure(|a, b| a - b));
+
+        assert!(fake.function.get().is_none());
+        assert_eq!(call_add(fake.get_implementation(), 5, 3), 2);
+
+        fake.setup(multiply_fake_implementation);
+
+        assert!(fake.closure.borrow().is_none());
+        assert_eq!(call_add(fake.get_implementation(), 5, 3), 15);
+    }
+
     #[test]
     fn test_with_string_parameters() {
-        let mut fake: FunctionFake<fn(String, String) -> String> = FunctionFake::new("concat");
+        type ConcatFn = fn(String, String) -> String;
+
+        let fake: FunctionFake<ConcatFn, dyn FnMut(String, String) -> String> = FunctionFake::new("concat");
         fake.setup(string_concat_fake_implementation);
         
-        let implementation = fake.get_implementation();
+        let Some(Implementation::Function(implementation)) = fake.try_get_implementation() else { panic!("no function") };
         let result = implementation("Hello".to_string(), "World".to_string());
         assert_eq!(result, "HelloWorld");
     }
 
     #[test]
     fn test_with_reference_parameter() {
-        let mut fake: FunctionFake<fn(&[u32]) -> u32> = FunctionFake::new("sum");
+        type SumFn = fn(&[u32]) -> u32;
+        type SumClosure = dyn FnMut(&[u32]) -> u32;
+
+        let fake: FunctionFake<SumFn, SumClosure> = FunctionFake::new("sum");
         fake.setup(sum_fake_implementation);
 
         let vec = vec![1, 2, 3];
         
-        let implementation = fake.get_implementation();
+        let Some(Implementation::Function(implementation)) = fake.try_get_implementation() else { panic!("no function") };
         let result = implementation(vec.as_slice());
         assert_eq!(result, 6);
     }
 
-    #[test]
-    fn test_with_unit_return_type() {
-        fn void_fake(_x: i32) -> () {
-            // Do nothing
-        }
-        
-        let mut fake: FunctionFake<fn(i32) -> ()> = FunctionFake::new("void_fn");
-        fake.setup(void_fake);
-        
-        let implementation = fake.get_implementation();
-        implementation(42); // Should not panic
-    }
-
     #[test]
     fn test_with_result_return_type() {
         fn divide_fake(a: i32, b: i32) -> Result<i32, String> {
@@ -225,10 +394,13 @@ mod tests {
             }
         }
         
-        let mut fake: FunctionFake<fn(i32, i32) -> Result<i32, String>> = FunctionFake::new("divide");
+        type DivideFn = fn(i32, i32) -> Result<i32, String>;
+        type DivideClosure = dyn FnMut(i32, i32) -> Result<i32, String>;
+
+        let fake: FunctionFake<DivideFn, DivideClosure> = FunctionFake::new("divide");
         fake.setup(divide_fake);
         
-        let implementation = fake.get_implementation();
+        let Some(Implementation::Function(implementation)) = fake.try_get_implementation() else { panic!("no function") };
         
         let result1 = implementation(10, 2);
         assert_eq!(result1, Ok(5));
@@ -237,6 +409,22 @@ mod tests {
         assert_eq!(result2, Err("Division by zero".to_string()));
     }
 
+    #[test]
+    fn test_with_unit_return_type() {
+        fn void_fake(_x: i32) {
+            // Do nothing
+        }
+
+        type VoidFn = fn(i32);
+        type VoidClosure = dyn FnMut(i32);
+
+        let fake: FunctionFake<VoidFn, VoidClosure> = FunctionFake::new("void_fn");
+        fake.setup(void_fake);
+
+        let Some(Implementation::Function(implementation)) = fake.try_get_implementation() else { panic!("no function") };
+        implementation(42); // Should not panic
+    }
+
     #[test]
     fn test_with_option_return_type() {
         fn safe_divide_fake(a: i32, b: i32) -> Option<i32> {
@@ -246,34 +434,148 @@ mod tests {
                 Some(a / b)
             }
         }
-        
-        let mut fake: FunctionFake<fn(i32, i32) -> Option<i32>> = FunctionFake::new("safe_divide");
+
+        type SafeDivideFn = fn(i32, i32) -> Option<i32>;
+        type SafeDivideClosure = dyn FnMut(i32, i32) -> Option<i32>;
+
+        let fake: FunctionFake<SafeDivideFn, SafeDivideClosure> = FunctionFake::new("safe_divide");
         fake.setup(safe_divide_fake);
-        
-        let implementation = fake.get_implementation();
-        
-        let result1 = implementation(10, 2);
-        assert_eq!(result1, Some(5));
-        
-        let result2 = implementation(10, 0);
-        assert_eq!(result2, None);
+
+        let Some(Implementation::Function(implementation)) = fake.try_get_implementation() else { panic!("no function") };
+
+        assert_eq!(implementation(10, 2), Some(5));
+        assert_eq!(implementation(10, 0), None);
     }
 
     #[test]
     fn test_multiple_get_implementation_calls() {
-        let mut fake: FunctionFake<fn(i32, i32) -> i32> = FunctionFake::new("add");
+        let fake = AddFake::new("add");
         fake.setup(add_fake_implementation);
         
         let impl1 = fake.get_implementation();
         let impl2 = fake.get_implementation();
         
-        assert_eq!(impl1(5, 3), 8);
-        assert_eq!(impl2(10, 20), 30);
+        assert_eq!(call_add(impl1, 5, 3), 8);
+        assert_eq!(call_add(impl2, 10, 20), 30);
+    }
+
+    #[test]
+    fn test_with_capturing_closure() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let recorded_calls = calls.clone();
+
+        let fake = AddFake::new("add");
+        fake.setup_closure(closure(move |a, b| {
+            recorded_calls.borrow_mut().push((a, b));
+            a + b
+        }));
+
+        assert_eq!(call_add(fake.get_implementation(), 5, 3), 8);
+        assert_eq!(call_add(fake.get_implementation(), 1, 2), 3);
+        assert_eq!(*calls.borrow(), vec![(5, 3), (1, 2)]);
+    }
+
+    #[test]
+    fn test_with_stateful_closure() {
+        let mut total = 0;
+
+        let fake = AddFake::new("add");
+        fake.setup_closure(closure(move |a, b| {
+            total += a + b;
+            total
+        }));
+
+        assert_eq!(call_add(fake.get_implementation(), 5, 3), 8);
+        assert_eq!(call_add(fake.get_implementation(), 1, 2), 11);
+    }
+
+    type FactorialFake = FunctionFake<fn(u64) -> u64, dyn FnMut(u64) -> u64>;
+
+    thread_local! {
+        static FACTORIAL_FAKE: FactorialFake = FunctionFake::new("factorial");
+    }
+
+    fn factorial(n: u64) -> u64 {
+        match FACTORIAL_FAKE.with(|fake| fake.get_implementation()) {
+            Implementation::Function(implementation) => implementation(n),
+            Implementation::Closure(implementation) => (*borrow_closure(&implementation, "factorial"))(n),
+        }
+    }
+
+    #[test]
+    fn test_recursive_fake() {
+        // Function pointers aren't borrowed while they run
+        FACTORIAL_FAKE.with(|fake| fake.setup(|n| if n <= 1 { 1 } else { n * factorial(n - 1) }));
+        assert_eq!(factorial(5), 120);
+    }
+
+    #[test]
+    #[should_panic(expected = "factorial fake was called again from its own closure")]
+    fn test_recursive_closure_panics() {
+        FACTORIAL_FAKE.with(|fake| fake.setup_closure(Rc::new(RefCell::new(|n| {
+            if n <= 1 { 1 } else { n * factorial(n - 1) }
+        }))));
+        factorial(5);
+    }
+
+    #[test]
+    fn test_clone_shares_the_implementation() {
+        let fake = AddFake::new("add");
+        fake.setup_closure(closure(add_fake_implementation));
+
+        let copy = fake.clone();
+        fake.clear();
+
+        assert!(!fake.is_set());
+        assert_eq!(call_add(copy.get_implementation(), 5, 3), 8);
+    }
+
+    #[test]
+    fn test_setup_with_delay() {
+        let fake = AddFake::new("add");
+        fake.setup_with_delay(Duration::from_millis(20), closure(add_fake_implementation));
+
+        assert!(fake.is_set());
+        assert_eq!(fake.delay(), Some(Duration::from_millis(20)));
+        assert_eq!(fake.clone().delay(), Some(Duration::from_millis(20)));
+
+        fake.setup(multiply_fake_implementation);
+        assert_eq!(fake.delay(), None);
+
+        fake.setup_with_delay(Duration::from_millis(20), closure(add_fake_implementation));
+        fake.clear();
+        assert_eq!(fake.delay(), None);
+    }
+
+    #[test]
+    fn test_restore_replaces_implementation_and_delay() {
+        let fake = AddFake::new("add");
+        fake.setup_with_delay(Duration::from_millis(20), closure(add_fake_implementation));
+        let state = fake.clone();
+
+        fake.setup_closure(closure(multiply_fake_implementation));
+        fake.restore(state);
+
+        assert_eq!(fake.delay(), Some(Duration::from_millis(20)));
+        assert_eq!(call_add(fake.get_implementation(), 5, 3), 8);
     }
 
     #[test]
     fn test_function_name_preserved() {
-        let fake: FunctionFake<fn(i32) -> i32> = FunctionFake::new("my_custom_function");
+        type IncrementFake = FunctionFake<fn(i32) -> i32, dyn Fn(i32) -> i32>;
+
+        let fake = IncrementFake::new("my_custom_function");
         assert_eq!(fake.name, "my_custom_function");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_try_get_implementation() {
+        let fake = AddFake::new("add");
+        assert!(fake.try_get_implementation().is_none());
+
+        fake.setup(add_fake_implementation);
+
+        let implementation = fake.try_get_implementation().unwrap();
+        assert_eq!(call_add(implementation, 5, 3), 8);
+    }
+}
@@ -1,4 +1,34 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::fmt::Debug;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+use std::time::Duration;
+use crate::captor::Captor;
+use crate::failure::{describe_calls, report_assertion, report_missing_setup};
+use crate::hang::ReleaseHandle;
+use crate::matchers::Matcher;
+use crate::sequence::{next_call_index, Sequence};
+use crate::registry::MockControl;
+
+/// Hook invoked with the params before the mock implementation runs
+type BeforeCallHook<Params> = Rc<dyn Fn(&Params)>;
+
+/// Hook invoked with the params and the result after the mock implementation returned
+type AfterCallHook<Params, Result> = Rc<dyn Fn(&Params, &Result)>;
+
+/// Expectation checked by `verify`, running assertions on the mock
+type Expectation<Params, Result> = Rc<dyn Fn(&FunctionMock<Params, Result>)>;
+
+/// Mock implementation, shared between snapshots of the mock
+type Implementation<Params, Result> = Rc<RefCell<dyn FnMut(Params) -> Result>>;
+
+/// Implementation used for calls with params accepted by the matcher
+type Case<Params, Result> = (Rc<Matcher<Params>>, Implementation<Params, Result>);
+
+/// Return values queued by `enqueue_return`, each consumed by a single call.
+/// Shared between snapshots of the mock, since the closures can't be cloned
+type ReturnQueue<Result> = Rc<RefCell<VecDeque<Box<dyn FnOnce() -> Result>>>>;
 
 /// Struct containing the Data for mocking a Function
 ///
@@ -17,7 +47,7 @@ use std::fmt::Debug;
 ///
 /// The function send_email is supposed to be mocked.
 ///
-/// ```
+/// ```ignore
 /// pub(crate) fn send_email(user: String, body: String) -> Result<(), String> {
 ///     print!("Send email to {0}: {1}\n", user, body);
 ///     Ok(())
@@ -28,7 +58,7 @@ use std::fmt::Debug;
 /// but with _mock at the end of the name and the body replaced with `send_email_mock::call`.
 /// It is important, when passing the parameters, to put them in a tuple or the function will break.
 ///
-/// ```
+/// ```ignore
 /// pub(crate) fn send_email_mock(user: String, body: String) -> Result<(), String> {
 ///     send_email_mock::call((user, body))
 /// }
@@ -36,7 +66,7 @@ use std::fmt::Debug;
 ///
 /// But where does `send_email_mock::call` come from? Now we create a module named `send_email_mock`.
 ///
-/// ```
+/// ```ignore
 /// pub(crate) mod send_email_mock {
 ///     type Params = (String, String); // The params of the function in a tuple
 ///     type Return = Result<(), String>; // The return type
@@ -54,10 +84,13 @@ use std::fmt::Debug;
 ///
 ///     // Here we create proxy calls for the mock functions.
 ///     // This allows us to use `send_email_mock::` for all the important mock functionalities.
+///     // The call is prepared while the mock is borrowed and runs after releasing it,
+///     // so the implementation may use the other proxy functions.
 ///     pub(crate) fn call(params: Params) -> Return {
-///         MOCK.with(|mock| { mock.borrow_mut().call(params) })
+///         let call = MOCK.with(|mock| { mock.borrow_mut().prepare_call(params) });
+///         call.run(|message| MOCK.with(|mock| { mock.borrow_mut().record_panic(message) }))
 ///     }
-///     pub(crate) fn setup(new_f: fn(Params) -> Return) {
+///     pub(crate) fn setup(new_f: impl FnMut(Params) -> Return + 'static) {
 ///         MOCK.with(|mock| { mock.borrow_mut().setup(new_f) })
 ///     }
 ///     // ...
@@ -68,15 +101,78 @@ use std::fmt::Debug;
 /// # Fields
 ///
 /// - `name` - the name of the function for display purposes when asserting
-/// - `implementation` - the mock function with the params in a tuple or None
+/// - `implementation` - the mock function or closure with the params in a tuple or None.
+///   Snapshots share the implementation, so state captured by a closure isn't restored
 /// - `calls` - vector to hold all calls to the mock
+/// - `times_called` - the number of calls, which is also counted without history
+/// - `first_call_index` - the index of the first call in the calls of all mocks (see `fnmock::assert_call_order!`)
+/// - `history` - whether the params of the calls are recorded, or only counted (see `new_without_history`)
+/// - `panics` - the messages of all panics raised by the mock implementation
+/// - `call_durations` - the wall-clock durations of the calls executing the original implementation
+/// - `before_call_hooks` - hooks invoked with the params before the implementation runs
+/// - `after_call_hooks` - hooks invoked with the params and the result after the implementation returned
+/// - `yields` - how often async mocked functions yield to the executor before returning
+/// - `delay` - how long mocked functions sleep before returning, if they are set up with a delay
+/// - `release_handle` - the handle mocked functions wait for before returning, if they are set up to hang
+/// - `cases` - implementations for calls with specific params, checked in order before `implementation`
+/// - `queued_returns` - return values for the next calls, consumed in order before the cases and `implementation`
+/// - `fallback_to_original` - whether calls matching no case execute the original implementation
+/// - `remaining_calls` - how many more calls `implementation` handles, if it is limited
+/// - `sequences` - the sequences logging the calls of the mock
+/// - `expectations` - the expectations checked by `verify`
 pub struct FunctionMock<Params, Result>
 where
     Params: Clone + PartialEq + Debug + 'static
 {
     name: String,
-    implementation: Option<fn(Params) -> Result>,
-    calls: Vec<Params>
+    implementation: Option<Implementation<Params, Result>>,
+    calls: Vec<Params>,
+    times_called: u32,
+    first_call_index: Option<u64>,
+    history: bool,
+    panics: Vec<String>,
+    call_durations: Vec<Duration>,
+    before_call_hooks: Vec<BeforeCallHook<Params>>,
+    after_call_hooks: Vec<AfterCallHook<Params, Result>>,
+    yields: u32,
+    delay: Option<Duration>,
+    release_handle: Option<ReleaseHandle>,
+    cases: Vec<Case<Params, Result>>,
+    queued_returns: ReturnQueue<Result>,
+    fallback_to_original: bool,
+    remaining_calls: Option<u32>,
+    sequences: Vec<Sequence>,
+    expectations: Vec<Expectation<Params, Result>>,
+}
+
+// Implemented by hand, since deriving would require `Result: Clone`.
+impl<Params, Result> Clone for FunctionMock<Params, Result>
+where
+    Params: Clone + PartialEq + Debug + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            implementation: self.implementation.clone(),
+            calls: self.calls.clone(),
+            times_called: self.times_called,
+            first_call_index: self.first_call_index,
+            history: self.history,
+            panics: self.panics.clone(),
+            call_durations: self.call_durations.clone(),
+            before_call_hooks: self.before_call_hooks.clone(),
+            after_call_hooks: self.after_call_hooks.clone(),
+            yields: self.yields,
+            delay: self.delay,
+            release_handle: self.release_handle.clone(),
+            cases: self.cases.clone(),
+            queued_returns: self.queued_returns.clone(),
+            fallback_to_original: self.fallback_to_original,
+            remaining_calls: self.remaining_calls,
+            sequences: self.sequences.clone(),
+            expectations: self.expectations.clone(),
+        }
+    }
 }
 
 impl<Params, Result> FunctionMock<Params, Result>
@@ -88,43 +184,503 @@ where
             name: function_name.to_string(),
             implementation: None,
             calls: Vec::new(),
+            times_called: 0,
+            first_call_index: None,
+            history: true,
+            panics: Vec::new(),
+            call_durations: Vec::new(),
+            before_call_hooks: Vec::new(),
+            after_call_hooks: Vec::new(),
+            yields: 0,
+            delay: None,
+            release_handle: None,
+            cases: Vec::new(),
+            queued_returns: Rc::new(RefCell::new(VecDeque::new())),
+            fallback_to_original: false,
+            remaining_calls: None,
+            sequences: Vec::new(),
+            expectations: Vec::new(),
         }
     }
 
+    /// Creates a mock, which only counts its calls instead of recording their params.
+    ///
+    /// Calls don't clone the params, which keeps the overhead of hot functions (e.g. in property tests) predictable.
+    /// The call count assertions work as usual, while the assertions and accessors of the params panic.
+    pub fn new_without_history(function_name: &str) -> Self {
+        Self { history: false, ..Self::new(function_name) }
+    }
+
     // --- Mocking ---
 
-    pub fn setup(&mut self, new_f: fn(Params) -> Result) {
-        self.implementation = Some(new_f);
+    /// Sets up the implementation, which may be a function or a closure capturing test-local state.
+    pub fn setup(&mut self, new_f: impl FnMut(Params) -> Result + 'static) {
+        self.implementation = Some(Rc::new(RefCell::new(new_f)));
+        self.yields = 0;
+        self.delay = None;
+        self.release_handle = None;
+        self.remaining_calls = None;
+    }
+
+    /// Sets up an implementation returning a clone of `return_value` for every call.
+    pub fn setup_returns(&mut self, return_value: Result)
+    where
+        Result: Clone + 'static,
+    {
+        self.setup(move |_| return_value.clone());
+    }
+
+    /// Sets up an implementation returning the values in order, one per call.
+    ///
+    /// Calls after the last value panic, since the test didn't expect them.
+    pub fn setup_returns_sequence(&mut self, return_values: Vec<Result>)
+    where
+        Result: 'static,
+    {
+        let name = self.name.clone();
+        let mut return_values = VecDeque::from(return_values);
+        self.setup(move |_| return_values.pop_front()
+            .unwrap_or_else(|| report_missing_setup(&name, format!("{} mock was called more often than its sequence has values", name))));
+    }
+
+    /// Sets up an implementation returning the values in order, one per call, and the last value for all further calls.
+    pub fn setup_returns_sequence_repeat_last(&mut self, return_values: Vec<Result>)
+    where
+        Result: Clone + 'static,
+    {
+        let name = self.name.clone();
+        let mut return_values = VecDeque::from(return_values);
+        self.setup(move |_| match return_values.len() {
+            0 => report_missing_setup(&name, format!("{} mock has an empty sequence", name)),
+            1 => return_values[0].clone(),
+            _ => return_values.pop_front().unwrap(),
+        });
+    }
+
+    /// Sets up an implementation panicking with the message, naming the mock and the params of the call.
+    ///
+    /// Meant for functions the code under test must not call. The call is still recorded.
+    pub fn setup_panics(&mut self, message: impl Into<String>) {
+        let name = self.name.clone();
+        let message = message.into();
+        self.setup(move |params| panic!("{} mock was called with {:?}: {}", name, params, message));
+    }
+
+    /// Sets up the implementation for the next `times` calls only, later calls execute the original implementation.
+    ///
+    /// All calls are recorded, so e.g. a failing first call followed by a successful retry against the
+    /// real logic can be asserted.
+    pub fn setup_times(&mut self, times: u32, new_f: impl FnMut(Params) -> Result + 'static) {
+        self.setup(new_f);
+        self.remaining_calls = Some(times);
+        self.fallback_to_original = true;
+    }
+
+    /// Sets up the implementation and how often async mocked functions yield to the executor before returning.
+    ///
+    /// The yields happen after the call was recorded, so tests can cancel the calling future or let it
+    /// lose a `select!` race at a deterministic point.
+    pub fn setup_with_yields(&mut self, yields: u32, new_f: impl FnMut(Params) -> Result + 'static) {
+        self.implementation = Some(Rc::new(RefCell::new(new_f)));
+        self.yields = yields;
+        self.delay = None;
+        self.release_handle = None;
+        self.remaining_calls = None;
+    }
+
+    /// Sets up the implementation and how long mocked functions sleep before returning.
+    ///
+    /// The call is recorded and the implementation runs before the delay, so timeouts of the caller
+    /// can be exercised. Sync functions block the thread, async functions await a runtime independent timer.
+    pub fn setup_with_delay(&mut self, delay: Duration, new_f: impl FnMut(Params) -> Result + 'static) {
+        self.implementation = Some(Rc::new(RefCell::new(new_f)));
+        self.yields = 0;
+        self.delay = Some(delay);
+        self.release_handle = None;
+        self.remaining_calls = None;
+    }
+
+    /// Sets up the implementation and makes mocked functions hang until the returned handle is released.
+    ///
+    /// The call is recorded and the implementation runs before hanging, so tests can assert the call
+    /// even if the caller gave up on it (e.g. because of a timeout).
+    pub fn setup_hanging(&mut self, new_f: impl FnMut(Params) -> Result + 'static) -> ReleaseHandle {
+        let release_handle = ReleaseHandle::new();
+        self.implementation = Some(Rc::new(RefCell::new(new_f)));
+        self.yields = 0;
+        self.delay = None;
+        self.release_handle = Some(release_handle.clone());
+        self.remaining_calls = None;
+        release_handle
+    }
+
+    /// Sets up an implementation for calls with params accepted by the matcher.
+    ///
+    /// Cases are checked in the order they were set up, before the implementation of `setup`.
+    pub fn setup_when(&mut self, matcher: Matcher<Params>, new_f: impl FnMut(Params) -> Result + 'static) {
+        self.cases.push((Rc::new(matcher), Rc::new(RefCell::new(new_f))));
+    }
+
+    /// Queues a return value for a single call, produced by a closure consuming its captured state.
+    ///
+    /// Queued values are returned in order, one per call, before the cases and the implementation of `setup` are checked.
+    /// The closure runs once, so it may move non-cloneable values (e.g. a oneshot sender) into the result.
+    pub fn enqueue_return(&mut self, return_value: impl FnOnce() -> Result + 'static) {
+        self.queued_returns.borrow_mut().push_back(Box::new(return_value));
+    }
+
+    /// Makes calls matching no case (and no implementation set up with `setup`) execute the original implementation.
+    ///
+    /// The calls are still recorded, so the function can be mocked for specific params only.
+    pub fn fallback_to_original(&mut self) {
+        self.fallback_to_original = true;
+    }
+
+    /// Removes all implementations, so every call executes the original implementation and is recorded.
+    ///
+    /// The call history and hooks are kept.
+    pub fn spy(&mut self) {
+        self.implementation = None;
+        self.cases = Vec::new();
+        self.queued_returns = Rc::new(RefCell::new(VecDeque::new()));
+        self.remaining_calls = None;
+        self.yields = 0;
+        self.delay = None;
+        self.release_handle = None;
+        self.fallback_to_original = true;
     }
 
     pub fn clear(&mut self) {
         self.implementation = None;
         self.calls = Vec::new();
+        self.times_called = 0;
+        self.first_call_index = None;
+        self.panics = Vec::new();
+        self.call_durations = Vec::new();
+        self.before_call_hooks = Vec::new();
+        self.after_call_hooks = Vec::new();
+        self.yields = 0;
+        self.delay = None;
+        self.release_handle = None;
+        self.cases = Vec::new();
+        self.queued_returns = Rc::new(RefCell::new(VecDeque::new()));
+        self.fallback_to_original = false;
+        self.remaining_calls = None;
+        self.sequences = Vec::new();
+        self.expectations = Vec::new();
+    }
+
+    /// Resets the recorded calls and panics, keeping the setup in place.
+    ///
+    /// Lets multi-phase tests verify each phase independently without setting up the mock again.
+    pub fn clear_history(&mut self) {
+        self.calls = Vec::new();
+        self.times_called = 0;
+        self.first_call_index = None;
+        self.panics = Vec::new();
+        self.call_durations = Vec::new();
     }
 
     pub fn is_set(&self) -> bool {
-        self.implementation.is_some()
+        self.implementation.is_some() || !self.cases.is_empty() || !self.queued_returns.borrow().is_empty() || self.fallback_to_original
+    }
+
+    pub fn falls_back_to_original(&self) -> bool {
+        self.fallback_to_original
+    }
+
+    pub fn yields(&self) -> u32 {
+        self.yields
+    }
+
+    pub fn delay(&self) -> Option<Duration> {
+        self.delay
+    }
+
+    pub fn release_handle(&self) -> Option<ReleaseHandle> {
+        self.release_handle.clone()
+    }
+
+    // --- Hooks ---
+
+    /// Registers a hook invoked with the params of every call, before the implementation runs.
+    ///
+    /// The hook doesn't replace the implementation, so it can be used for logging, latching or
+    /// notifying a test that the call happened. Hooks run in registration order.
+    pub fn on_call(&mut self, hook: impl Fn(&Params) + 'static) {
+        self.before_call_hooks.push(Rc::new(hook));
+    }
+
+    /// Registers a hook invoked with the params and the result of every call, after the implementation returned.
+    ///
+    /// The hook isn't invoked if the implementation panicked. Hooks run in registration order.
+    pub fn after_call(&mut self, hook: impl Fn(&Params, &Result) + 'static) {
+        self.after_call_hooks.push(Rc::new(hook));
+    }
+
+    /// Adds a copy of the params of every following call to the collector.
+    ///
+    /// The collector is shared with the test, so it can be any collection implementing `Extend`
+    /// (e.g. a `Vec`, a `HashSet` or a domain-specific type). Like a hook, it is removed by `clear`.
+    pub fn capture_into<Collector>(&mut self, collector: Rc<RefCell<Collector>>)
+    where
+        Collector: Extend<Params> + 'static,
+    {
+        self.on_call(move |params| collector.borrow_mut().extend(Some(params.clone())));
+    }
+
+    /// Returns a [`Captor`] collecting the params of every following call.
+    pub fn capture(&mut self) -> Captor<Params> {
+        let captor = Captor::new();
+        self.capture_into(captor.collector());
+        captor
+    }
+
+    /// Joins the sequence, which logs every following call of the mock (see [`Sequence`]).
+    pub fn in_sequence(&mut self, sequence: &Sequence) {
+        self.sequences.push(sequence.clone());
     }
 
     // --- Execute ---
 
     pub fn call(&mut self, params: Params) -> Result {
-        let implementation = self.implementation.as_ref()
-            .expect(format!("{} mock not initialized", self.name).as_str());
+        let call = self.prepare_call(params);
+        call.run(|message| self.panics.push(message))
+    }
+
+    /// Returns the next queued return value, or calls the implementation of the first case accepting the params,
+    /// or the implementation of `setup`.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(result)` - the result of the implementation
+    /// - `None` - if no implementation handles the params and the mock falls back to the original implementation,
+    ///   the call is recorded nonetheless
+    pub fn try_call(&mut self, params: Params) -> Option<Result> {
+        let call = self.prepare_call(params);
+        call.try_run(|message| self.panics.push(message))
+    }
+
+    /// Records the call and selects the implementation handling it, without running the implementation.
+    ///
+    /// The generated proxy functions run the returned [`PreparedCall`] after releasing the mock,
+    /// so the implementation may inspect the mock or call the mocked function again.
+    pub fn prepare_call(&mut self, params: Params) -> PreparedCall<Params, Result> {
+        let queued_return = self.queued_returns.borrow_mut().pop_front();
+        let implementation = match queued_return {
+            Some(queued_return) => Some(PreparedImplementation::Queued(queued_return)),
+            None => {
+                let case_implementation = self.cases.iter()
+                    .find(|(matcher, _)| matcher.matches(&params))
+                    .map(|(_, implementation)| implementation.clone());
+                case_implementation.or_else(|| self.use_implementation()).map(PreparedImplementation::SetUp)
+            }
+        };
+
+        if implementation.is_none() && !self.fallback_to_original {
+            if self.cases.is_empty() {
+                report_missing_setup(&self.name, format!("{} mock not initialized", self.name));
+            }
+            let matchers: Vec<&Matcher<Params>> = self.cases.iter().map(|(matcher, _)| matcher.as_ref()).collect();
+            report_missing_setup(&self.name, format!("{} mock not initialized for {:?}, set up for {:?}", self.name, params, matchers));
+        }
+
+        self.prepare_call_with(params, implementation)
+    }
+
+    /// Records the call of a mock forwarding parameters, which runs the forwarding implementation instead of its own.
+    pub fn prepare_forwarded_call(&mut self, params: Params) -> PreparedCall<Params, Result> {
+        self.prepare_call_with(params, None)
+    }
+
+    fn prepare_call_with(&mut self, params: Params, implementation: Option<PreparedImplementation<Params, Result>>) -> PreparedCall<Params, Result> {
+        self.record_call(&params);
+
+        PreparedCall {
+            name: self.name.clone(),
+            params,
+            implementation,
+            before_call_hooks: self.before_call_hooks.clone(),
+            after_call_hooks: self.after_call_hooks.clone(),
+        }
+    }
+
+    /// Records the message of a panic raised by the implementation of a [`PreparedCall`].
+    pub fn record_panic(&mut self, message: String) {
+        self.panics.push(message);
+    }
+
+    /// Records the params of a call (or only counts it without history) and logs it in the joined sequences.
+    fn record_call(&mut self, params: &Params) {
+        self.times_called += 1;
+        self.first_call_index.get_or_insert_with(next_call_index);
+        if self.history {
+            self.calls.push(params.clone());
+        }
+        for sequence in &self.sequences {
+            sequence.record(&self.name);
+        }
+    }
+
+    /// Gets the implementation of `setup` for a call, counting down the remaining calls if they are limited.
+    fn use_implementation(&mut self) -> Option<Implementation<Params, Result>> {
+        match self.remaining_calls {
+            Some(0) => None,
+            Some(remaining_calls) => {
+                self.remaining_calls = Some(remaining_calls - 1);
+                self.implementation.clone()
+            }
+            None => self.implementation.clone(),
+        }
+    }
+
+    /// Records the call and runs `implementation` instead of the implementation set up with `setup`.
+    ///
+    /// Used by mocks forwarding parameters, which can't be recorded (e.g. callbacks), to their implementation.
+    /// The call runs the hooks and records panics like every other call.
+    pub fn call_with(&mut self, params: Params, implementation: impl FnOnce(Params) -> Result) -> Result {
+        let call = self.prepare_forwarded_call(params);
+        call.run_with(implementation, |message| self.panics.push(message))
+    }
+
+    // --- History ---
+
+    /// Returns how often the mock was called.
+    pub fn times(&self) -> u32 {
+        self.times_called
+    }
+
+    /// Returns the index of the first call among the calls of all mocks, or None if the mock wasn't called.
+    ///
+    /// The indices increase monotonically across all mocks and threads, so they order the first calls of different mocks.
+    pub fn first_call_index(&self) -> Option<u64> {
+        self.first_call_index
+    }
+
+    /// Records the wall-clock duration of a call executing the original implementation (see `fnmock::timing`).
+    pub fn record_duration(&mut self, duration: Duration) {
+        self.call_durations.push(duration);
+    }
+
+    /// Returns the wall-clock durations of the calls executing the original implementation, in call order.
+    ///
+    /// Calls handled by a mock implementation aren't timed.
+    pub fn call_durations(&self) -> Vec<Duration> {
+        self.call_durations.clone()
+    }
+
+    /// Returns the params of all calls, in call order.
+    pub fn calls(&self) -> Vec<Params> {
+        self.require_history("calls");
+        self.calls.clone()
+    }
+
+    /// Returns the params of the most recent call, or None if the mock wasn't called.
+    pub fn last_call_params(&self) -> Option<Params> {
+        self.require_history("last_call_params");
+        self.calls.last().cloned()
+    }
+
+    /// Returns the params of all calls formatted one call per line, e.g. for snapshot tests.
+    pub fn formatted_calls(&self) -> String {
+        self.require_history("formatted_calls");
+        format_calls(&self.name, &self.calls)
+    }
+
+    /// Returns the distinct params of all calls, in the order of their first call.
+    pub fn unique_calls(&self) -> Vec<Params> {
+        self.require_history("unique_calls");
+        let mut unique_calls: Vec<Params> = Vec::new();
+        for params in &self.calls {
+            if !unique_calls.contains(params) {
+                unique_calls.push(params.clone());
+            }
+        }
+        unique_calls
+    }
+
+    /// Panics if the mock only counts its calls, since `operation` needs the recorded params.
+    fn require_history(&self, operation: &str) {
+        if !self.history {
+            panic!("{} mock doesn't record the params of its calls (no_history), so {} can't be used", self.name, operation);
+        }
+    }
+
+    // --- Expectations ---
+
+    /// Expects the mock to be called exactly `expected_num_of_calls` times, checked by `verify`.
+    pub fn expect_times(&mut self, expected_num_of_calls: u32) {
+        self.expectations.push(Rc::new(move |mock: &Self| mock.assert_times(expected_num_of_calls)));
+    }
+
+    /// Expects the mock to be called with the params, checked by `verify`.
+    pub fn expect_with(&mut self, params: Params) {
+        self.require_history("expect_with");
+        self.expectations.push(Rc::new(move |mock: &Self| mock.assert_with(params.clone())));
+    }
+
+    /// Returns true if any expectation was registered since the last `clear`.
+    pub fn has_expectations(&self) -> bool {
+        !self.expectations.is_empty()
+    }
 
-        self.calls.push(params.clone());
-        implementation(params)
+    /// Checks all expectations, in the order they were registered.
+    pub fn verify(&self) {
+        for expectation in &self.expectations {
+            expectation(self);
+        }
     }
 
     // --- Assert ---
 
     pub fn assert_times(&self, expected_num_of_calls: u32) {
-        assert_eq!(self.calls.len(), expected_num_of_calls as usize,
-                   "Expected {} mock to be called {} times, received {}",
-                   self.name, self.calls.len(), expected_num_of_calls);
+        if self.times() != expected_num_of_calls {
+            report_assertion(&self.name, format!("Expected {} mock to be called {} times, received {}",
+                                                 self.name, expected_num_of_calls, self.times()));
+        }
+    }
+
+    pub fn assert_times_at_least(&self, min_num_of_calls: u32) {
+        if self.times() < min_num_of_calls {
+            report_assertion(&self.name, format!("Expected {} mock to be called at least {} times, received {}",
+                                                 self.name, min_num_of_calls, self.times()));
+        }
+    }
+
+    pub fn assert_times_at_most(&self, max_num_of_calls: u32) {
+        if self.times() > max_num_of_calls {
+            report_assertion(&self.name, format!("Expected {} mock to be called at most {} times, received {}",
+                                                 self.name, max_num_of_calls, self.times()));
+        }
+    }
+
+    /// Asserts that the number of calls is within `min_num_of_calls..=max_num_of_calls`.
+    pub fn assert_times_between(&self, min_num_of_calls: u32, max_num_of_calls: u32) {
+        if !(min_num_of_calls..=max_num_of_calls).contains(&self.times()) {
+            report_assertion(&self.name, format!("Expected {} mock to be called between {} and {} times, received {}",
+                                                 self.name, min_num_of_calls, max_num_of_calls, self.times()));
+        }
+    }
+
+    /// Asserts that the number of calls is accepted by the predicate, e.g. for counts that depend on test data.
+    ///
+    /// The description names the expected counts in the failure message, e.g. "an even number of".
+    pub fn assert_times_matching(&self, predicate: impl Fn(u32) -> bool, description: &str) {
+        if !predicate(self.times()) {
+            report_assertion(&self.name, format!("Expected {} mock to be called {} times, received {}",
+                                                 self.name, description, self.times()));
+        }
+    }
+
+    pub fn assert_panicked_times(&self, expected_num_of_panics: u32) {
+        if self.panics.len() != expected_num_of_panics as usize {
+            report_assertion(&self.name, format!("Expected {} mock to panic {} times, received {} {:?}",
+                                                 self.name, expected_num_of_panics, self.panics.len(), self.panics));
+        }
     }
 
     pub fn assert_with(&self, params: Params) {
+        self.require_history("assert_with");
         let mut was_called_with = false;
 
         for called_params in self.calls.iter() {
@@ -133,7 +689,217 @@ where
             }
         }
 
-        assert!(was_called_with, "Expected {} mock to be called with {:?}", self.name, params);
+        if !was_called_with {
+            report_assertion(&self.name, format!("Expected {} mock to be called with {:?}{}",
+                                                 self.name, params, describe_calls(&self.calls)));
+        }
+    }
+
+    pub fn assert_with_matching(&self, matcher: &Matcher<Params>) {
+        self.require_history("assert_with_matching");
+        let was_called_matching = self.calls.iter().any(|called_params| matcher.matches(called_params));
+
+        if !was_called_matching {
+            report_assertion(&self.name, format!("Expected {} mock to be called with params matching {:?}{}",
+                                                 self.name, matcher, describe_calls(&self.calls)));
+        }
+    }
+
+    /// Asserts that the mock was never called with the params, while it may have been called with others.
+    pub fn assert_never_called_with(&self, params: Params) {
+        self.require_history("assert_never_called_with");
+        let matching_calls: Vec<usize> = self.calls.iter()
+            .enumerate()
+            .filter(|(_, called_params)| **called_params == params)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if !matching_calls.is_empty() {
+            report_assertion(&self.name, format!("Expected {} mock to never be called with {:?}, received it in calls {:?}{}",
+                                                 self.name, params, matching_calls, describe_calls(&self.calls)));
+        }
+    }
+
+    /// Asserts that the mock was never called twice with the same params,
+    /// e.g. that a cache only fetches every key once.
+    pub fn assert_no_duplicate_calls(&self) {
+        self.require_history("assert_no_duplicate_calls");
+        let duplicates: Vec<String> = self.unique_calls()
+            .into_iter()
+            .map(|params| (self.calls.iter().filter(|called_params| **called_params == params).count(), params))
+            .filter(|(count, _)| *count > 1)
+            .map(|(count, params)| format!("{:?} ({} times)", params, count))
+            .collect();
+
+        if !duplicates.is_empty() {
+            report_assertion(&self.name, format!("Expected {} mock to be called at most once per params, received {}",
+                                                 self.name, duplicates.join(", ")));
+        }
+    }
+
+    pub fn assert_last_call_with(&self, params: Params) {
+        self.require_history("assert_last_call_with");
+        match self.calls.last() {
+            None => report_assertion(&self.name, format!(
+                "Expected last call of {} mock to be with {:?}, but it was never called", self.name, params)),
+            Some(last_params) if *last_params != params => report_assertion(&self.name, format!(
+                "Expected last call of {} mock to be with {:?}, received {:?}", self.name, params, last_params)),
+            Some(_) => {}
+        }
+    }
+
+    /// Asserts that the call at `call_index` (starting at 0) was made with the params.
+    pub fn assert_nth_call_with(&self, call_index: usize, params: Params) {
+        self.require_history("assert_nth_call_with");
+        match self.calls.get(call_index) {
+            None => report_assertion(&self.name, format!(
+                "Expected call {} of {} mock to be with {:?}, but it was only called {} times", call_index, self.name, params, self.calls.len())),
+            Some(nth_params) if *nth_params != params => report_assertion(&self.name, format!(
+                "Expected call {} of {} mock to be with {:?}, received {:?}", call_index, self.name, params, nth_params)),
+            Some(_) => {}
+        }
+    }
+}
+
+/// Implementation selected for a call by [`FunctionMock::prepare_call`]
+enum PreparedImplementation<Params, Result> {
+    /// A return value queued by `enqueue_return`
+    Queued(Box<dyn FnOnce() -> Result>),
+    /// The implementation of a case or of `setup`
+    SetUp(Implementation<Params, Result>),
+}
+
+/// Recorded call of a mock, whose implementation hasn't run yet
+///
+/// Created by [`FunctionMock::prepare_call`] while the mock is borrowed. The generated proxy functions
+/// release the mock before running the call, so the implementation and the hooks may inspect the mock
+/// (e.g. `fetch_user_mock::times_called()`) or call the mocked function again.
+/// Panics of the implementation are passed to `record_panic`, which records them in the mock.
+///
+/// # Fields
+///
+/// - `name` - the name of the mock for display purposes when failing
+/// - `params` - the params of the call
+/// - `implementation` - the implementation handling the call, or None if it executes the original implementation
+/// - `before_call_hooks` - the hooks of the mock invoked before the implementation runs
+/// - `after_call_hooks` - the hooks of the mock invoked after the implementation returned
+pub struct PreparedCall<Params, Result> {
+    name: String,
+    params: Params,
+    implementation: Option<PreparedImplementation<Params, Result>>,
+    before_call_hooks: Vec<BeforeCallHook<Params>>,
+    after_call_hooks: Vec<AfterCallHook<Params, Result>>,
+}
+
+impl<Params, Result> PreparedCall<Params, Result>
+where
+    Params: Clone + PartialEq + Debug + 'static,
+{
+    /// Runs the implementation, failing if the call has to execute the original implementation.
+    pub fn run(self, record_panic: impl FnOnce(String)) -> Result {
+        let name = self.name.clone();
+        self.try_run(record_panic)
+            .unwrap_or_else(|| report_missing_setup(&name, format!("{} mock has no implementation for the params of the last call", name)))
+    }
+
+    /// Runs the implementation, or returns None if the call executes the original implementation.
+    ///
+    /// The before call hooks run in both cases.
+    pub fn try_run(mut self, record_panic: impl FnOnce(String)) -> Option<Result> {
+        self.run_before_call_hooks();
+        let name = self.name.clone();
+        match self.implementation.take()? {
+            PreparedImplementation::Queued(queued_return) => Some(self.run_implementation(|_| queued_return(), record_panic)),
+            PreparedImplementation::SetUp(implementation) => Some(self.run_implementation(|params| {
+                // A closure calling the mocked function again is only re-entered for params handled by it
+                let mut implementation = implementation.try_borrow_mut()
+                    .unwrap_or_else(|_| panic!("{} mock implementation can't be re-entered by its own call, \
+                                                use setup_when or setup_times for the nested calls", name));
+                implementation(params)
+            }, record_panic)),
+        }
+    }
+
+    /// Runs `implementation` instead of the selected one, e.g. the forwarding implementation of the mock.
+    pub fn run_with(self, implementation: impl FnOnce(Params) -> Result, record_panic: impl FnOnce(String)) -> Result {
+        self.run_before_call_hooks();
+        self.run_implementation(implementation, record_panic)
+    }
+
+    fn run_before_call_hooks(&self) {
+        for hook in &self.before_call_hooks {
+            hook(&self.params);
+        }
+    }
+
+    fn run_implementation(self, implementation: impl FnOnce(Params) -> Result, record_panic: impl FnOnce(String)) -> Result {
+        // The implementation consumes the params, so the after call hooks get a copy
+        let hook_params = (!self.after_call_hooks.is_empty()).then(|| self.params.clone());
+
+        // Record panics of the implementation before passing them on to the caller
+        match catch_unwind(AssertUnwindSafe(|| implementation(self.params))) {
+            Ok(result) => {
+                if let Some(params) = hook_params {
+                    for hook in &self.after_call_hooks {
+                        hook(&params, &result);
+                    }
+                }
+                result
+            }
+            Err(payload) => {
+                record_panic(panic_message(payload.as_ref()));
+                resume_unwind(payload)
+            }
+        }
+    }
+}
+
+/// Formats the recorded calls of a mock, starting with the name and the number of calls:
+///
+/// ```text
+/// fetch_user_mock (2 calls)
+/// [0] 42
+/// [1] 7
+/// ```
+pub(crate) fn format_calls<Params: Debug>(name: &str, calls: &[Params]) -> String {
+    let plural = if calls.len() == 1 { "" } else { "s" };
+    let mut formatted = format!("{} ({} call{})", name, calls.len(), plural);
+    for (idx, params) in calls.iter().enumerate() {
+        formatted.push_str(&format!("\n[{}] {:?}", idx, params));
+    }
+    formatted
+}
+
+/// Extracts the message of a panic payload, which is a `&str` or `String` for all `panic!` invocations.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
+
+impl<Params, Result> MockControl for FunctionMock<Params, Result>
+where
+    Params: Clone + PartialEq + Debug + 'static,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn times_called(&self) -> Option<u32> {
+        Some(self.times())
+    }
+
+    fn verify(&self) {
+        FunctionMock::verify(self)
+    }
+
+    fn clear(&mut self) {
+        FunctionMock::clear(self)
     }
 }
 
@@ -228,55 +994,378 @@ mod tests {
     }
 
     #[test]
-    fn test_assert_times_passes_with_correct_count() {
-        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
-        mock.setup(add_mock_implementation);
-        
-        mock.call((1, 2));
-        mock.call((3, 4));
-        mock.call((5, 6));
-        
-        mock.assert_times(3);
+    fn test_setup_returns_constant() {
+        let mut mock: FunctionMock<(i32, i32), String> = FunctionMock::new("describe");
+        mock.setup_returns("constant".to_string());
+
+        assert_eq!(mock.call((1, 2)), "constant");
+        assert_eq!(mock.call((3, 4)), "constant");
+        mock.assert_times(2);
+        mock.assert_with((3, 4));
     }
 
     #[test]
-    #[should_panic(expected = "Expected add mock to be called 2 times, received 5")]
-    fn test_assert_times_fails_with_wrong_count() {
-        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
-        mock.setup(add_mock_implementation);
-        
-        mock.call((1, 2));
-        mock.call((3, 4));
-        
-        mock.assert_times(5);
+    fn test_setup_returns_sequence_in_order() {
+        let mut mock: FunctionMock<i32, Result<i32, String>> = FunctionMock::new("fetch");
+        mock.setup_returns_sequence(vec![Err("timeout".to_string()), Ok(1)]);
+
+        assert_eq!(mock.call(1), Err("timeout".to_string()));
+        assert_eq!(mock.call(1), Ok(1));
+        mock.assert_times(2);
     }
 
     #[test]
-    fn test_assert_times_with_zero_calls() {
-        let mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
-        mock.assert_times(0);
+    #[should_panic(expected = "fetch mock was called more often than its sequence has values")]
+    fn test_setup_returns_sequence_panics_when_exhausted() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.setup_returns_sequence(vec![1]);
+
+        mock.call(1);
+        mock.call(1);
     }
 
     #[test]
-    fn test_assert_with_passes_when_called_with_params() {
-        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
-        mock.setup(add_mock_implementation);
-        
-        mock.call((5, 3));
-        mock.call((10, 20));
-        
-        mock.assert_with((5, 3));
-        mock.assert_with((10, 20));
+    #[should_panic(expected = "delete mock was called with (1, \"admin\"): admins must never be deleted")]
+    fn test_setup_panics_names_mock_and_params() {
+        let mut mock: FunctionMock<(i32, String), ()> = FunctionMock::new("delete");
+        mock.setup_panics("admins must never be deleted");
+
+        mock.call((1, "admin".to_string()));
     }
 
     #[test]
-    #[should_panic(expected = "Expected add mock to be called with (7, 8)")]
-    fn test_assert_with_fails_when_not_called_with_params() {
-        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
-        mock.setup(add_mock_implementation);
-        
-        mock.call((5, 3));
-        mock.assert_with((7, 8));
+    fn test_setup_panics_records_the_call() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("delete");
+        mock.setup_panics("unexpected");
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| mock.call(1)));
+
+        assert!(result.is_err());
+        mock.assert_times(1);
+    }
+
+    #[test]
+    fn test_setup_returns_sequence_repeat_last() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.setup_returns_sequence_repeat_last(vec![1, 2]);
+
+        assert_eq!(mock.call(0), 1);
+        assert_eq!(mock.call(0), 2);
+        assert_eq!(mock.call(0), 2);
+    }
+
+    #[test]
+    fn test_setup_times_falls_back_to_original_afterward() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.setup_times(2, |id| -id);
+
+        assert!(mock.falls_back_to_original());
+        assert_eq!(mock.try_call(1), Some(-1));
+        assert_eq!(mock.try_call(2), Some(-2));
+        assert_eq!(mock.try_call(3), None);
+        mock.assert_times(3);
+    }
+
+    #[test]
+    fn test_enqueue_return_consumes_values_in_order_before_implementation() {
+        struct Sender {
+            id: u32,
+        }
+
+        let mut mock: FunctionMock<u32, Sender> = FunctionMock::new("connect");
+        let first = Sender { id: 1 };
+        let second = Sender { id: 2 };
+        mock.enqueue_return(move || first);
+        mock.enqueue_return(move || second);
+        mock.setup(|id| Sender { id });
+
+        assert!(mock.is_set());
+        assert_eq!(mock.call(7).id, 1);
+        assert_eq!(mock.call(7).id, 2);
+        assert_eq!(mock.call(7).id, 7);
+        mock.assert_times(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "connect mock not initialized")]
+    fn test_enqueue_return_without_implementation_fails_after_queue_is_empty() {
+        let mut mock: FunctionMock<u32, u32> = FunctionMock::new("connect");
+        mock.enqueue_return(|| 1);
+
+        assert_eq!(mock.call(7), 1);
+        mock.call(7);
+    }
+
+    #[test]
+    fn test_setup_removes_call_limit() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.setup_times(0, |id| -id);
+        mock.setup(|id| id);
+
+        assert_eq!(mock.try_call(1), Some(1));
+    }
+
+    #[test]
+    fn test_spy_records_calls_without_implementation() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.setup(|id| -id);
+        mock.spy();
+
+        assert!(mock.is_set());
+        assert_eq!(mock.try_call(1), None);
+        mock.assert_with(1);
+    }
+
+    #[test]
+    fn test_in_sequence_logs_calls_of_all_mocks() {
+        let sequence = Sequence::new();
+        let mut fetch: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        let mut send: FunctionMock<i32, i32> = FunctionMock::new("send");
+        fetch.setup(|id| id);
+        send.setup(|id| id);
+        fetch.in_sequence(&sequence);
+        send.in_sequence(&sequence);
+
+        fetch.call(1);
+        send.call(1);
+
+        sequence.assert_order(&["fetch", "send"]);
+    }
+
+    #[test]
+    fn test_verify_passes_for_met_expectations() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.setup(|id| id);
+        mock.expect_times(1);
+        mock.expect_with(7);
+
+        mock.call(7);
+
+        mock.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected fetch mock to be called with 7")]
+    fn test_verify_fails_for_unmet_expectations() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.expect_with(7);
+
+        mock.verify();
+    }
+
+    #[test]
+    fn test_clear_removes_expectations() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.expect_times(1);
+        assert!(mock.has_expectations());
+        mock.clear();
+
+        assert!(!mock.has_expectations());
+        mock.verify();
+    }
+
+    #[test]
+    fn test_setup_with_capturing_closure() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("next_id");
+        let offset = 100;
+        let mut counter = 0;
+        mock.setup(move |(a, b)| {
+            counter += 1;
+            a + b + offset + counter
+        });
+
+        assert_eq!(mock.call((1, 2)), 104);
+        assert_eq!(mock.call((1, 2)), 105);
+    }
+
+    #[test]
+    fn test_prepared_call_runs_without_borrowing_the_mock() {
+        thread_local! {
+            static MOCK: RefCell<FunctionMock<i32, u32>> = RefCell::new(FunctionMock::new("count"));
+        }
+        MOCK.with(|mock| mock.borrow_mut().setup(|_| MOCK.with(|mock| mock.borrow().times())));
+
+        let call = MOCK.with(|mock| mock.borrow_mut().prepare_call(7));
+        assert_eq!(call.run(|_| {}), 1);
+
+        let call = MOCK.with(|mock| mock.borrow_mut().prepare_call(7));
+        assert_eq!(call.run(|_| {}), 2);
+    }
+
+    #[test]
+    fn test_prepared_call_runs_hooks_without_borrowing_the_mock() {
+        thread_local! {
+            static MOCK: RefCell<FunctionMock<i32, u32>> = RefCell::new(FunctionMock::new("count"));
+            static SEEN: RefCell<Vec<(u32, u32)>> = const { RefCell::new(Vec::new()) };
+        }
+        MOCK.with(|mock| {
+            let mut mock = mock.borrow_mut();
+            mock.setup(|_| 0);
+            mock.on_call(|_| {
+                let times = MOCK.with(|mock| mock.borrow().times());
+                SEEN.with(|seen| seen.borrow_mut().push((times, 0)));
+            });
+            mock.after_call(|_, _| {
+                let times = MOCK.with(|mock| mock.borrow().times());
+                SEEN.with(|seen| seen.borrow_mut().last_mut().unwrap().1 = times);
+            });
+        });
+
+        let call = MOCK.with(|mock| mock.borrow_mut().prepare_call(7));
+        call.run(|_| {});
+        let call = MOCK.with(|mock| mock.borrow_mut().prepare_call(7));
+        call.run(|_| {});
+
+        SEEN.with(|seen| assert_eq!(*seen.borrow(), vec![(1, 1), (2, 2)]));
+    }
+
+    #[test]
+    fn test_prepared_call_passes_panics_to_record_panic() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("failing");
+        mock.setup(|_| panic!("failed"));
+
+        let call = mock.prepare_call(1);
+        let result = catch_unwind(AssertUnwindSafe(|| call.run(|message| mock.record_panic(message))));
+
+        assert!(result.is_err());
+        mock.assert_panicked_times(1);
+    }
+
+    #[test]
+    fn test_assert_times_passes_with_correct_count() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        
+        mock.call((1, 2));
+        mock.call((3, 4));
+        mock.call((5, 6));
+        
+        mock.assert_times(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add mock to be called 5 times, received 2")]
+    fn test_assert_times_fails_with_wrong_count() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        
+        mock.call((1, 2));
+        mock.call((3, 4));
+        
+        mock.assert_times(5);
+    }
+
+    #[test]
+    fn test_assert_times_with_zero_calls() {
+        let mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.assert_times(0);
+    }
+
+    #[test]
+    fn test_assert_times_ranges_pass_within_bounds() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 2));
+        mock.call((3, 4));
+
+        mock.assert_times_at_least(2);
+        mock.assert_times_at_most(2);
+        mock.assert_times_between(1, 3);
+        mock.assert_times_between(2, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add mock to be called at least 3 times, received 2")]
+    fn test_assert_times_at_least_fails_with_fewer_calls() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 2));
+        mock.call((3, 4));
+
+        mock.assert_times_at_least(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add mock to be called at most 1 times, received 2")]
+    fn test_assert_times_at_most_fails_with_more_calls() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 2));
+        mock.call((3, 4));
+
+        mock.assert_times_at_most(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add mock to be called between 3 and 5 times, received 2")]
+    fn test_assert_times_between_fails_outside_bounds() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 2));
+        mock.call((3, 4));
+
+        mock.assert_times_between(3, 5);
+    }
+
+    #[test]
+    fn test_assert_times_matching_passes_when_accepted() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 2));
+        mock.call((3, 4));
+
+        mock.assert_times_matching(|times| times % 2 == 0, "an even number of");
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add mock to be called an even number of times, received 3")]
+    fn test_assert_times_matching_fails_when_rejected() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        for _ in 0..3 {
+            mock.call((1, 2));
+        }
+
+        mock.assert_times_matching(|times| times % 2 == 0, "an even number of");
+    }
+
+    #[test]
+    fn test_assert_with_passes_when_called_with_params() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        
+        mock.call((5, 3));
+        mock.call((10, 20));
+        
+        mock.assert_with((5, 3));
+        mock.assert_with((10, 20));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add mock to be called with (7, 8)")]
+    fn test_assert_with_fails_when_not_called_with_params() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        
+        mock.call((5, 3));
+        mock.assert_with((7, 8));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add mock to be called with (7, 8)\nRecorded calls:\n  [0] (5, 3)\n  [1] (1, 2)")]
+    fn test_assert_with_failure_lists_recorded_calls() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((5, 3));
+        mock.call((1, 2));
+        mock.assert_with((7, 8));
     }
 
     #[test]
@@ -292,6 +1381,158 @@ mod tests {
         mock.assert_with((3, 3));
     }
 
+    #[test]
+    fn test_call_records_and_resumes_panics() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("checked");
+        mock.setup(|x| if x < 0 { panic!("negative input {}", x) } else { x });
+
+        let result = catch_unwind(AssertUnwindSafe(|| mock.call(-1)));
+        assert!(result.is_err());
+        mock.call(1);
+
+        mock.assert_times(2);
+        mock.assert_panicked_times(1);
+        assert_eq!(mock.panics, vec!["negative input -1".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected checked mock to panic 0 times, received 1 [\"boom\"]")]
+    fn test_assert_panicked_times_fails_with_wrong_count() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("checked");
+        mock.setup(|_| panic!("boom"));
+
+        let _ = catch_unwind(AssertUnwindSafe(|| mock.call(1)));
+        mock.assert_panicked_times(0);
+    }
+
+    #[test]
+    fn test_clear_resets_panics() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("checked");
+        mock.setup(|_| panic!("boom"));
+
+        let _ = catch_unwind(AssertUnwindSafe(|| mock.call(1)));
+        mock.clear();
+
+        mock.assert_panicked_times(0);
+    }
+
+    #[test]
+    fn test_assert_with_matching_passes_when_a_call_matches() {
+        use crate::matchers::{all_of, predicate};
+
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 2));
+        mock.call((50, 60));
+
+        mock.assert_with_matching(&all_of([
+            predicate("first > 10", |params: &(i32, i32)| params.0 > 10),
+            predicate("second < 100", |params: &(i32, i32)| params.1 < 100),
+        ]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add mock to be called with params matching not(any)")]
+    fn test_assert_with_matching_fails_when_no_call_matches() {
+        use crate::matchers::{any, not};
+
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 2));
+        mock.assert_with_matching(&not(any()));
+    }
+
+    #[test]
+    fn test_assert_never_called_with_passes_for_other_params() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 1));
+        mock.call((2, 2));
+
+        mock.assert_never_called_with((3, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add mock to never be called with (1, 1), received it in calls [0, 2]")]
+    fn test_assert_never_called_with_fails_listing_the_calls() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 1));
+        mock.call((2, 2));
+        mock.call((1, 1));
+
+        mock.assert_never_called_with((1, 1));
+    }
+
+    #[test]
+    fn test_assert_last_call_with_passes_for_last_call() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 1));
+        mock.call((2, 2));
+
+        mock.assert_last_call_with((2, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected last call of add mock to be with (1, 1), received (2, 2)")]
+    fn test_assert_last_call_with_fails_for_earlier_call() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 1));
+        mock.call((2, 2));
+
+        mock.assert_last_call_with((1, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected last call of add mock to be with (1, 1), but it was never called")]
+    fn test_assert_last_call_with_fails_without_calls() {
+        let mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.assert_last_call_with((1, 1));
+    }
+
+    #[test]
+    fn test_assert_nth_call_with_checks_each_call() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 1));
+        mock.call((2, 2));
+
+        mock.assert_nth_call_with(0, (1, 1));
+        mock.assert_nth_call_with(1, (2, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected call 0 of add mock to be with (2, 2), received (1, 1)")]
+    fn test_assert_nth_call_with_fails_for_other_params() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 1));
+        mock.call((2, 2));
+
+        mock.assert_nth_call_with(0, (2, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected call 2 of add mock to be with (1, 1), but it was only called 1 times")]
+    fn test_assert_nth_call_with_fails_for_missing_call() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((1, 1));
+
+        mock.assert_nth_call_with(2, (1, 1));
+    }
+
     #[test]
     fn test_with_string_parameters() {
         let mut mock: FunctionMock<(String, String), String> = FunctionMock::new("concat");
@@ -322,7 +1563,7 @@ mod tests {
 
     #[test]
     fn test_with_unit_return_type() {
-        fn void_mock(_params: i32) -> () {
+        fn void_mock(_params: i32) {
             // Do nothing
         }
         
@@ -367,4 +1608,355 @@ mod tests {
         
         assert_eq!(mock.calls, vec![1, 2, 3]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_clone_is_independent_of_original() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        mock.call((1, 2));
+
+        let copy = mock.clone();
+        mock.setup(multiply_mock_implementation);
+        mock.call((3, 4));
+
+        copy.assert_times(1);
+        copy.assert_with((1, 2));
+        assert_eq!((copy.implementation.unwrap().borrow_mut())((2, 3)), 5);
+    }
+
+    #[test]
+    fn test_on_call_runs_before_implementation() {
+        let events = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        let hook_events = events.clone();
+        mock.on_call(move |params| hook_events.borrow_mut().push(format!("before {:?}", params)));
+        let hook_events = events.clone();
+        mock.after_call(move |params, result| hook_events.borrow_mut().push(format!("after {:?} = {}", params, result)));
+
+        assert_eq!(mock.call((1, 2)), 3);
+        assert_eq!(*events.borrow(), vec!["before (1, 2)", "after (1, 2) = 3"]);
+    }
+
+    #[test]
+    fn test_capture_collects_params_of_following_calls() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        mock.call((1, 1));
+
+        let captor = mock.capture();
+        mock.call((1, 2));
+        mock.call((3, 4));
+
+        assert_eq!(captor.values(), vec![(1, 2), (3, 4)]);
+    }
+
+    #[test]
+    fn test_capture_into_extends_collector() {
+        let collector = Rc::new(std::cell::RefCell::new(std::collections::BTreeSet::new()));
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("identity");
+        mock.setup(|x| x);
+
+        mock.capture_into(collector.clone());
+        mock.call(3);
+        mock.call(1);
+        mock.call(3);
+
+        assert_eq!(collector.borrow().iter().copied().collect::<Vec<_>>(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_after_call_is_skipped_on_panic() {
+        let after_calls = Rc::new(std::cell::Cell::new(0));
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("failing");
+        mock.setup(|_| panic!("failed"));
+
+        let hook_after_calls = after_calls.clone();
+        mock.after_call(move |_, _| hook_after_calls.set(hook_after_calls.get() + 1));
+
+        let result = catch_unwind(AssertUnwindSafe(|| mock.call(1)));
+
+        assert!(result.is_err());
+        assert_eq!(after_calls.get(), 0);
+    }
+
+    #[test]
+    fn test_clear_removes_hooks() {
+        let before_calls = Rc::new(std::cell::Cell::new(0));
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("identity");
+
+        let hook_before_calls = before_calls.clone();
+        mock.on_call(move |_| hook_before_calls.set(hook_before_calls.get() + 1));
+        mock.clear();
+        mock.setup(|x| x);
+        mock.call(1);
+
+        assert_eq!(before_calls.get(), 0);
+    }
+
+    #[test]
+    fn test_setup_with_yields() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("identity");
+
+        mock.setup_with_yields(3, |x| x);
+        assert_eq!(mock.yields(), 3);
+        assert_eq!(mock.call(1), 1);
+
+        mock.setup(|x| x);
+        assert_eq!(mock.yields(), 0);
+
+        mock.setup_with_yields(3, |x| x);
+        mock.clear();
+        assert_eq!(mock.yields(), 0);
+    }
+
+    #[test]
+    fn test_setup_with_delay() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("identity");
+
+        mock.setup_with_delay(Duration::from_millis(20), |x| x);
+        assert_eq!(mock.delay(), Some(Duration::from_millis(20)));
+        assert_eq!(mock.call(1), 1);
+
+        mock.setup_with_yields(3, |x| x);
+        assert_eq!(mock.delay(), None);
+
+        mock.setup_with_delay(Duration::from_millis(20), |x| x);
+        mock.clear();
+        assert_eq!(mock.delay(), None);
+    }
+
+    #[test]
+    fn test_setup_hanging() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("identity");
+
+        let release_handle = mock.setup_hanging(|x| x);
+        assert_eq!(mock.call(1), 1);
+        assert!(!mock.release_handle().unwrap().is_released());
+
+        release_handle.release();
+        assert!(mock.release_handle().unwrap().is_released());
+
+        mock.setup(|x| x);
+        assert!(mock.release_handle().is_none());
+
+        mock.setup_hanging(|x| x);
+        mock.clear();
+        assert!(mock.release_handle().is_none());
+    }
+
+    #[test]
+    fn test_setup_when_routes_by_params() {
+        let mut mock: FunctionMock<i32, &str> = FunctionMock::new("classify");
+        mock.setup_when(crate::matchers::lt(0), |_| "negative");
+        mock.setup_when(crate::matchers::eq(0), |_| "zero");
+        mock.setup(|_| "positive");
+
+        assert_eq!(mock.call(-5), "negative");
+        assert_eq!(mock.call(0), "zero");
+        assert_eq!(mock.call(5), "positive");
+        mock.assert_times(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "classify mock not initialized for 5, set up for [lt(0)]")]
+    fn test_unmatched_params_panic_without_fallback() {
+        let mut mock: FunctionMock<i32, &str> = FunctionMock::new("classify");
+        mock.setup_when(crate::matchers::lt(0), |_| "negative");
+
+        mock.call(5);
+    }
+
+    #[test]
+    fn test_unmatched_params_fall_back_to_original() {
+        let mut mock: FunctionMock<i32, &str> = FunctionMock::new("classify");
+        mock.setup_when(crate::matchers::lt(0), |_| "negative");
+        mock.fallback_to_original();
+
+        assert!(mock.is_set());
+        assert_eq!(mock.try_call(-5), Some("negative"));
+        assert_eq!(mock.try_call(5), None);
+        mock.assert_times(2);
+        mock.assert_last_call_with(5);
+
+        mock.clear();
+        assert!(!mock.is_set());
+        assert!(!mock.falls_back_to_original());
+    }
+
+    #[test]
+    fn test_failed_assertions_are_reported_to_handler() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        mock.call((1, 2));
+
+        let failures = crate::failure::collect_failures(|| {
+            mock.assert_times(2);
+            mock.assert_with((3, 4));
+            mock.assert_last_call_with((1, 2));
+        });
+
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].double_name, "add");
+        assert_eq!(failures[0].kind, crate::failure::FailureKind::Assertion);
+        assert_eq!(failures[1].message, "Expected add mock to be called with (3, 4)\nRecorded calls:\n  [0] (1, 2)");
+    }
+
+    #[test]
+    fn test_missing_setup_is_reported_to_handler() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+
+        let failures = crate::failure::collect_failures(|| {
+            let result = catch_unwind(AssertUnwindSafe(|| mock.call((1, 2))));
+            assert!(result.is_err());
+        });
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].kind, crate::failure::FailureKind::MissingSetup);
+        assert_eq!(failures[0].message, "add mock not initialized");
+    }
+
+    #[test]
+    fn test_call_with_records_call_and_runs_given_implementation() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("retry");
+        let mut attempts = 0;
+
+        let result = mock.call_with(3, |max_attempts| {
+            attempts = max_attempts;
+            max_attempts * 2
+        });
+
+        assert_eq!(result, 6);
+        assert_eq!(attempts, 3);
+        mock.assert_times(1);
+        mock.assert_with(3);
+    }
+
+    #[test]
+    fn test_calls_in_call_order() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((2, 2));
+        mock.call((1, 1));
+        mock.call((2, 2));
+
+        assert_eq!(mock.calls(), vec![(2, 2), (1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_last_call_params() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        assert_eq!(mock.last_call_params(), None);
+
+        mock.call((2, 2));
+        mock.call((1, 3));
+
+        assert_eq!(mock.last_call_params(), Some((1, 3)));
+    }
+
+    #[test]
+    fn test_call_durations_are_cleared_with_the_history() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.spy();
+        assert_eq!(mock.try_call(1), None);
+        mock.record_duration(Duration::from_millis(3));
+
+        assert_eq!(mock.call_durations(), vec![Duration::from_millis(3)]);
+
+        mock.clear_history();
+        assert!(mock.call_durations().is_empty());
+    }
+
+    #[test]
+    fn test_formatted_calls_one_call_per_line() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        assert_eq!(mock.formatted_calls(), "add (0 calls)");
+
+        mock.call((2, 2));
+        assert_eq!(mock.formatted_calls(), "add (1 call)\n[0] (2, 2)");
+
+        mock.call((1, 3));
+        assert_eq!(mock.formatted_calls(), "add (2 calls)\n[0] (2, 2)\n[1] (1, 3)");
+    }
+
+    #[test]
+    fn test_unique_calls_in_order_of_first_call() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+
+        mock.call((2, 2));
+        mock.call((1, 1));
+        mock.call((2, 2));
+
+        assert_eq!(mock.unique_calls(), vec![(2, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn test_assert_no_duplicate_calls_passes_for_distinct_calls() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.setup(|id| id);
+
+        mock.call(1);
+        mock.call(2);
+
+        mock.assert_no_duplicate_calls();
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected fetch mock to be called at most once per params, received 1 (3 times), 2 (2 times)")]
+    fn test_assert_no_duplicate_calls_fails_for_duplicates() {
+        let mut mock: FunctionMock<i32, i32> = FunctionMock::new("fetch");
+        mock.setup(|id| id);
+
+        for id in [1, 2, 1, 3, 2, 1] {
+            mock.call(id);
+        }
+
+        mock.assert_no_duplicate_calls();
+    }
+
+    #[test]
+    fn test_clear_history_keeps_setup() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new("add");
+        mock.setup(add_mock_implementation);
+        mock.call((1, 2));
+
+        mock.clear_history();
+
+        mock.assert_times(0);
+        assert!(mock.is_set());
+        assert_eq!(mock.call((3, 4)), 7);
+        mock.assert_times(1);
+        mock.assert_last_call_with((3, 4));
+    }
+
+    #[test]
+    fn test_without_history_counts_calls() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new_without_history("add");
+        mock.setup(add_mock_implementation);
+
+        assert_eq!(mock.call((1, 2)), 3);
+        assert_eq!(mock.call((3, 4)), 7);
+
+        mock.assert_times(2);
+        mock.assert_times_between(1, 2);
+        assert!(mock.calls.is_empty());
+
+        mock.clear_history();
+        mock.assert_times(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "add mock doesn't record the params of its calls (no_history), so assert_with can't be used")]
+    fn test_without_history_rejects_param_assertions() {
+        let mut mock: FunctionMock<(i32, i32), i32> = FunctionMock::new_without_history("add");
+        mock.setup(add_mock_implementation);
+        mock.call((1, 2));
+
+        mock.assert_with((1, 2));
+    }
+}
@@ -0,0 +1,229 @@
+use std::fmt::Debug;
+use std::time::Duration;
+use crate::failure::{describe_calls, report_assertion};
+use crate::registry::MockControl;
+
+/// Struct recording the calls of a function, which always runs its real implementation
+///
+/// Spies fill the gap between mocks and plain functions: the behavior isn't replaced,
+/// but the parameters and return values of all calls are recorded for assertions.
+///
+/// # Generics
+///
+/// - `Params: Clone + PartialEq + Debug + 'static` - the parameters of the spied function as a tuple
+/// - `Return: Clone + PartialEq + Debug + 'static` - the return type of the spied function
+///
+/// # Usage
+///
+/// Normally you don't need to interact with the FunctionSpy directly.
+/// The usage is automated in the `fnmock-derive::spy_function` macro,
+/// and you interact with generated proxy functions.
+///
+/// The function `calculate` is supposed to be spied on.
+///
+/// ```ignore
+/// pub(crate) fn calculate(x: i32, y: i32) -> i32 {
+///     // Test builds run the original body and record the call
+///     let result = x + y;
+///     calculate_spy::record((x, y), result.clone());
+///     result
+/// }
+/// ```
+///
+/// Create a module named `calculate_spy` with the spy infrastructure:
+///
+/// ```ignore
+/// pub(crate) mod calculate_spy {
+///     use fnmock::function_spy::FunctionSpy;
+///
+///     thread_local! {
+///         static SPY: std::cell::RefCell<FunctionSpy<(i32, i32), i32>> =
+///             std::cell::RefCell::new(FunctionSpy::new("calculate"));
+///     }
+///
+///     pub(crate) fn record(params: (i32, i32), result: i32) {
+///         SPY.with(|spy| { spy.borrow_mut().record(params, result) })
+///     }
+///
+///     pub(crate) fn assert_with(x: i32, y: i32) {
+///         SPY.with(|spy| { spy.borrow().assert_with((x, y)) })
+///     }
+///
+///     // ...
+///     // the same for all other spy functions
+/// }
+/// ```
+///
+/// # Fields
+///
+/// - `name` - the name of the function for display purposes when asserting
+/// - `calls` - the params and the return value of all calls, in the order they happened
+/// - `call_durations` - the wall-clock durations of all calls, in the order they happened
+#[derive(Clone)]
+pub struct FunctionSpy<Params, Return>
+where
+    Params: Clone + PartialEq + Debug + 'static,
+    Return: Clone + PartialEq + Debug + 'static,
+{
+    name: String,
+    calls: Vec<(Params, Return)>,
+    call_durations: Vec<Duration>,
+}
+
+impl<Params, Return> FunctionSpy<Params, Return>
+where
+    Params: Clone + PartialEq + Debug + 'static,
+    Return: Clone + PartialEq + Debug + 'static,
+{
+    pub fn new(function_name: &str) -> Self {
+        Self {
+            name: function_name.to_string(),
+            calls: Vec::new(),
+            call_durations: Vec::new(),
+        }
+    }
+
+    // --- Recording ---
+
+    /// Records a call of the real implementation with its return value.
+    pub fn record(&mut self, params: Params, result: Return) {
+        self.calls.push((params, result));
+    }
+
+    /// Records the wall-clock duration of a call of the real implementation.
+    pub fn record_duration(&mut self, duration: Duration) {
+        self.call_durations.push(duration);
+    }
+
+    /// Removes the recorded calls.
+    pub fn clear(&mut self) {
+        self.calls = Vec::new();
+        self.call_durations = Vec::new();
+    }
+
+    // --- Inspect ---
+
+    pub fn times(&self) -> u32 {
+        self.calls.len() as u32
+    }
+
+    /// Returns the params of all calls, in the order they happened.
+    pub fn calls(&self) -> Vec<Params> {
+        self.calls.iter().map(|(params, _)| params.clone()).collect()
+    }
+
+    /// Returns the return values of all calls, in the order they happened.
+    pub fn results(&self) -> Vec<Return> {
+        self.calls.iter().map(|(_, result)| result.clone()).collect()
+    }
+
+    /// Returns the wall-clock durations of all calls, in the order they happened.
+    pub fn call_durations(&self) -> Vec<Duration> {
+        self.call_durations.clone()
+    }
+
+    /// Returns the params of the most recent call, or None if the function wasn't called.
+    pub fn last_call_params(&self) -> Option<Params> {
+        self.calls.last().map(|(params, _)| params.clone())
+    }
+
+    // --- Assert ---
+
+    pub fn assert_times(&self, expected_num_of_calls: u32) {
+        let times = self.times();
+        if times != expected_num_of_calls {
+            report_assertion(&self.name, format!("Expected {} spy to be called {} times, received {}",
+                                                 self.name, expected_num_of_calls, times));
+        }
+    }
+
+    pub fn assert_with(&self, params: Params) {
+        let calls = self.calls();
+        if !calls.contains(&params) {
+            report_assertion(&self.name, format!("Expected {} spy to be called with {:?}{}",
+                                                 self.name, params, describe_calls(&calls)));
+        }
+    }
+
+    pub fn assert_returned(&self, result: Return) {
+        let results = self.results();
+        if !results.contains(&result) {
+            report_assertion(&self.name, format!("Expected {} spy to return {:?}, returned {:?}",
+                                                 self.name, result, results));
+        }
+    }
+}
+
+
+impl<Params, Return> MockControl for FunctionSpy<Params, Return>
+where
+    Params: Clone + PartialEq + Debug + 'static,
+    Return: Clone + PartialEq + Debug + 'static,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn times_called(&self) -> Option<u32> {
+        Some(self.times())
+    }
+
+    // Spies don't have expectations
+    fn verify(&self) {}
+
+    fn clear(&mut self) {
+        FunctionSpy::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_stores_params_and_results() {
+        let mut spy: FunctionSpy<(i32, i32), i32> = FunctionSpy::new("add");
+        spy.record((1, 2), 3);
+        spy.record((5, 5), 10);
+
+        assert_eq!(spy.times(), 2);
+        assert_eq!(spy.calls(), vec![(1, 2), (5, 5)]);
+        assert_eq!(spy.results(), vec![3, 10]);
+        assert_eq!(spy.last_call_params(), Some((5, 5)));
+        spy.assert_times(2);
+        spy.assert_with((1, 2));
+        spy.assert_returned(10);
+    }
+
+    #[test]
+    fn test_clear_removes_calls() {
+        let mut spy: FunctionSpy<u32, String> = FunctionSpy::new("format_id");
+        spy.record(1, "1".to_string());
+        spy.record_duration(Duration::from_millis(2));
+        assert_eq!(spy.call_durations(), vec![Duration::from_millis(2)]);
+
+        spy.clear();
+
+        assert_eq!(spy.times(), 0);
+        assert!(spy.call_durations().is_empty());
+        assert_eq!(spy.last_call_params(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add spy to be called with (2, 2)\nRecorded calls:\n  [0] (1, 2)")]
+    fn test_assert_with_fails_for_other_params() {
+        let mut spy: FunctionSpy<(i32, i32), i32> = FunctionSpy::new("add");
+        spy.record((1, 2), 3);
+
+        spy.assert_with((2, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected add spy to return 4, returned [3]")]
+    fn test_assert_returned_fails_for_other_results() {
+        let mut spy: FunctionSpy<(i32, i32), i32> = FunctionSpy::new("add");
+        spy.record((1, 2), 3);
+
+        spy.assert_returned(4);
+    }
+}
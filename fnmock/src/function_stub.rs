@@ -1,3 +1,12 @@
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, PoisonError};
+use crate::failure::report_missing_setup;
+use crate::registry::MockControl;
+
+/// Producer of the return values, shared between snapshots of the stub.
+/// It's `Send`, so stubs can be stored in every storage backend (see `fnmock::storage`).
+type Factory<ReturnType> = Arc<Mutex<dyn FnMut() -> ReturnType + Send>>;
+
 /// Struct for stubbing a function with predetermined return values
 ///
 /// Stubs - in contrast to mocks and fakes - provide canned responses without behavior verification or custom logic.
@@ -5,8 +14,13 @@
 ///
 /// # Generics
 ///
-/// - `ReturnType: 'static + Clone` - the return type of the stubbed function
-///   - Must be cloneable since the stub may be called multiple times with the same return value
+/// - `ReturnType: 'static` - the return type of the stubbed function
+///   - Values returned by every call must be cloneable, since the stub may be called multiple times
+///   - Values set up with `setup_once` are moved out by the first call, so they don't have to be cloneable
+///   - `'static` references (`&'static str`, `&'static Config`, ...) are stored directly,
+///     since references are `Clone` even if the referenced type isn't
+/// - `Params: Clone + PartialEq + Debug + 'static` - the parameters of the stubbed function as a tuple
+///   - Only used by keyed stubs (`#[stub_function(keyed)]`) to look up return values by parameters, defaults to `()`
 ///
 /// # Usage
 ///
@@ -16,7 +30,7 @@
 ///
 /// The function `get_config` is supposed to be stubbed for testing.
 ///
-/// ```
+/// ```ignore
 /// pub(crate) fn get_config() -> String {
 ///     // Production code that reads from file or environment
 ///     std::fs::read_to_string("config.json").unwrap()
@@ -26,7 +40,7 @@
 /// Now we create a stub function - it should be the same signature,
 /// but with `_stub` at the end of the name and the body calls the stub return value.
 ///
-/// ```
+/// ```ignore
 /// pub(crate) fn get_config_stub() -> String {
 ///     get_config_stub::get_return_value()
 /// }
@@ -34,7 +48,7 @@
 ///
 /// Create a module named `get_config_stub` with the stub infrastructure:
 ///
-/// ```
+/// ```ignore
 /// pub(crate) mod get_config_stub {
 ///     use fnmock::function_stub::FunctionStub;
 ///     
@@ -62,42 +76,218 @@
 /// # Fields
 ///
 /// - `name` - the name of the function for display purposes when panicking
-/// - `return_value` - the stubbed return value or None
-pub struct FunctionStub<ReturnType>
+/// - `return_value` - the stubbed return value or None, used as fallback by keyed stubs
+/// - `keyed_return_values` - the return values for specific parameters
+/// - `factory` - produces a fresh return value for every call, used instead of `return_value`.
+///   Snapshots share the factory, so state captured by the closure isn't restored
+/// - `once_value` - the value set up with `setup_once`, moved out by the next call
+/// - `once_taken` - whether the value set up with `setup_once` was already returned
+/// - `clone_value` - clones the stored return values, captured by the setups requiring `ReturnType: Clone`
+/// - `default_value` - creates the value returned if no value is set up, used by `#[stub_function(default)]`
+pub struct FunctionStub<ReturnType, Params = ()>
 where
-    ReturnType: 'static + Clone,
+    ReturnType: 'static,
+    Params: Clone + PartialEq + Debug + 'static,
 {
     name: String,
     return_value: Option<ReturnType>,
+    keyed_return_values: Vec<(Params, ReturnType)>,
+    factory: Option<Factory<ReturnType>>,
+    once_value: Option<ReturnType>,
+    once_taken: bool,
+    clone_value: Option<fn(&ReturnType) -> ReturnType>,
+    default_value: Option<fn() -> ReturnType>,
+}
+
+// Implemented by hand, so stubs of non-cloneable return types can be snapshotted.
+// The value set up with `setup_once` can't be cloned, so it isn't part of the copy.
+impl<ReturnType, Params> Clone for FunctionStub<ReturnType, Params>
+where
+    ReturnType: 'static,
+    Params: Clone + PartialEq + Debug + 'static,
+{
+    fn clone(&self) -> Self {
+        // Return values are only stored by the setups capturing `clone_value`
+        let clone_value = |value: &ReturnType| (self.clone_value.expect("stored return values are cloneable"))(value);
+        Self {
+            name: self.name.clone(),
+            return_value: self.return_value.as_ref().map(clone_value),
+            keyed_return_values: self.keyed_return_values.iter()
+                .map(|(params, value)| (params.clone(), clone_value(value)))
+                .collect(),
+            factory: self.factory.clone(),
+            once_value: None,
+            once_taken: self.once_taken,
+            clone_value: self.clone_value,
+            default_value: self.default_value,
+        }
+    }
 }
 
-impl<ReturnType> FunctionStub<ReturnType>
+impl<ReturnType, Params> FunctionStub<ReturnType, Params>
 where
-    ReturnType: 'static + Clone,
+    ReturnType: 'static,
+    Params: Clone + PartialEq + Debug + 'static,
 {
     pub fn new(function_name: &str) -> Self {
         Self {
             name: function_name.to_string(),
             return_value: None,
+            keyed_return_values: Vec::new(),
+            factory: None,
+            once_value: None,
+            once_taken: false,
+            clone_value: None,
+            default_value: None,
+        }
+    }
+
+    /// Creates a stub returning `ReturnType::default()` instead of panicking if no value is set up.
+    pub fn with_default(function_name: &str) -> Self
+    where
+        ReturnType: Default,
+    {
+        Self {
+            default_value: Some(ReturnType::default),
+            ..Self::new(function_name)
         }
     }
 
     // --- Stubbing ---
 
-    pub fn setup(&mut self, new_r: ReturnType) {
-        self.return_value = Some(new_r.clone());
+    pub fn setup(&mut self, new_r: ReturnType)
+    where
+        ReturnType: Clone,
+    {
+        self.clone_value = Some(ReturnType::clone);
+        self.return_value = Some(new_r);
+        self.factory = None;
+    }
+
+    /// Sets up a factory producing a fresh return value for every call, e.g. a new id or connection handle.
+    ///
+    /// Replaces the value set up with `setup`, and the return type doesn't have to be cloneable.
+    /// The factory is `Send`, so the stub can be stored in every storage backend.
+    pub fn setup_factory(&mut self, factory: impl FnMut() -> ReturnType + Send + 'static) {
+        self.factory = Some(Arc::new(Mutex::new(factory)));
+        self.return_value = None;
+    }
+
+    pub fn setup_for(&mut self, params: Params, new_r: ReturnType)
+    where
+        ReturnType: Clone,
+    {
+        self.clone_value = Some(ReturnType::clone);
+        self.keyed_return_values.retain(|(key, _)| *key != params);
+        self.keyed_return_values.push((params, new_r));
+    }
+
+    /// Sets up a value, which is moved out by the next call instead of being cloned.
+    ///
+    /// Allows stubbing functions returning non-cloneable types like files or channel senders.
+    /// The value takes precedence over the values set up with `setup` and `setup_for`.
+    /// Calling the stub again panics, unless a new value is set up.
+    pub fn setup_once(&mut self, new_r: ReturnType) {
+        self.once_value = Some(new_r);
+        self.once_taken = false;
     }
 
     pub fn clear(&mut self) {
         self.return_value = None;
+        self.keyed_return_values = Vec::new();
+        self.factory = None;
+        self.once_value = None;
+        self.once_taken = false;
     }
 
     pub fn is_set(&self) -> bool {
-        self.return_value.is_some()
+        self.return_value.is_some() || self.factory.is_some() || !self.keyed_return_values.is_empty()
+            || self.once_value.is_some() || self.once_taken
     }
 
-    pub fn get_return_value(&self) -> ReturnType {
-        self.return_value.clone().expect(format!("{} stub not initialized", self.name).as_str())
+    pub fn get_return_value(&mut self) -> ReturnType {
+        if let Some(value) = self.take_once_value() {
+            return value;
+        }
+
+        self.fallback_value()
+            .unwrap_or_else(|| report_missing_setup(&self.name, format!("{} stub not initialized", self.name)))
+    }
+
+    /// Returns the value set up for the first parameters accepted by `matches`,
+    /// falling back to the value set up with `setup` (or the default value of stubs created with `with_default`).
+    ///
+    /// The parameters are matched with a function, so the stubbed function can compare
+    /// its parameters without cloning them into a tuple.
+    pub fn get_return_value_for(&mut self, matches: impl Fn(&Params) -> bool) -> ReturnType {
+        if let Some(value) = self.take_once_value() {
+            return value;
+        }
+        if let Some((_, value)) = self.keyed_return_values.iter().find(|(key, _)| matches(key)) {
+            return self.clone_stored(value);
+        }
+
+        self.fallback_value()
+            .unwrap_or_else(|| {
+                let keys: Vec<&Params> = self.keyed_return_values.iter().map(|(key, _)| key).collect();
+                report_missing_setup(&self.name, format!("{} stub not initialized for these parameters, set up for {:?}", self.name, keys))
+            })
+    }
+
+    /// Moves out the value set up with `setup_once`, or returns None if no such value is set up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value was already returned by a previous call and no other value (or default value) is set up
+    fn take_once_value(&mut self) -> Option<ReturnType> {
+        if let Some(value) = self.once_value.take() {
+            self.once_taken = true;
+            return Some(value);
+        }
+        if self.once_taken && self.return_value.is_none() && self.factory.is_none() && self.keyed_return_values.is_empty() && self.default_value.is_none() {
+            report_missing_setup(&self.name, format!("{} stub was set up with setup_once and already returned its value", self.name));
+        }
+        None
+    }
+
+    /// Produces the value of the factory or clones the value set up with `setup`,
+    /// falling back to the default value of stubs created with `with_default`.
+    fn fallback_value(&self) -> Option<ReturnType> {
+        if let Some(factory) = &self.factory {
+            let mut factory = factory.lock().unwrap_or_else(PoisonError::into_inner);
+            return Some(factory());
+        }
+
+        self.return_value.as_ref()
+            .map(|value| self.clone_stored(value))
+            .or_else(|| self.default_value.map(|default_value| default_value()))
+    }
+
+    fn clone_stored(&self, value: &ReturnType) -> ReturnType {
+        // Return values are only stored by the setups capturing `clone_value`
+        (self.clone_value.expect("stored return values are cloneable"))(value)
+    }
+}
+
+
+impl<ReturnType, Params> MockControl for FunctionStub<ReturnType, Params>
+where
+    ReturnType: 'static,
+    Params: Clone + PartialEq + Debug + 'static,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Stubs don't record their calls
+    fn times_called(&self) -> Option<u32> {
+        None
+    }
+
+    fn verify(&self) {}
+
+    fn clear(&mut self) {
+        FunctionStub::clear(self)
     }
 }
 
@@ -131,7 +321,7 @@ mod tests {
     #[test]
     #[should_panic(expected = "get_value stub not initialized")]
     fn test_get_return_value_panics_when_not_initialized() {
-        let stub: FunctionStub<i32> = FunctionStub::new("get_value");
+        let mut stub: FunctionStub<i32> = FunctionStub::new("get_value");
         stub.get_return_value();
     }
 
@@ -246,9 +436,172 @@ mod tests {
         assert_eq!(result.host, "localhost");
     }
 
+    #[test]
+    fn test_with_static_reference_to_non_clone_type() {
+        struct Limits {
+            max_users: u32,
+        }
+        static LIMITS: Limits = Limits { max_users: 10 };
+
+        let mut stub: FunctionStub<&'static Limits> = FunctionStub::new("get_limits");
+        stub.setup(&LIMITS);
+
+        assert!(std::ptr::eq(stub.get_return_value(), &LIMITS));
+        assert_eq!(stub.get_return_value().max_users, 10);
+    }
+
     #[test]
     fn test_function_name_preserved() {
         let stub: FunctionStub<i32> = FunctionStub::new("my_custom_function");
         assert_eq!(stub.name, "my_custom_function");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_setup_for_returns_value_for_matching_params() {
+        let mut stub: FunctionStub<String, u32> = FunctionStub::new("get_user");
+        stub.setup_for(42, "alice".to_string());
+        stub.setup_for(7, "bob".to_string());
+
+        assert!(stub.is_set());
+        assert_eq!(stub.get_return_value_for(|id| *id == 42), "alice");
+        assert_eq!(stub.get_return_value_for(|id| *id == 7), "bob");
+    }
+
+    #[test]
+    fn test_setup_for_replaces_value_for_same_params() {
+        let mut stub: FunctionStub<String, u32> = FunctionStub::new("get_user");
+        stub.setup_for(42, "alice".to_string());
+        stub.setup_for(42, "carol".to_string());
+
+        assert_eq!(stub.keyed_return_values.len(), 1);
+        assert_eq!(stub.get_return_value_for(|id| *id == 42), "carol");
+    }
+
+    #[test]
+    fn test_get_return_value_for_falls_back_to_setup() {
+        let mut stub: FunctionStub<String, u32> = FunctionStub::new("get_user");
+        stub.setup("default".to_string());
+        stub.setup_for(42, "alice".to_string());
+
+        assert_eq!(stub.get_return_value_for(|id| *id == 1), "default");
+    }
+
+    #[test]
+    #[should_panic(expected = "get_user stub not initialized for these parameters, set up for [42]")]
+    fn test_get_return_value_for_panics_without_match_and_fallback() {
+        let mut stub: FunctionStub<String, u32> = FunctionStub::new("get_user");
+        stub.setup_for(42, "alice".to_string());
+
+        stub.get_return_value_for(|id| *id == 1);
+    }
+
+    #[test]
+    fn test_with_default_returns_default_when_not_initialized() {
+        let mut stub: FunctionStub<String, u32> = FunctionStub::with_default("get_user");
+        assert!(!stub.is_set());
+        assert_eq!(stub.get_return_value(), "");
+
+        stub.setup_for(42, "alice".to_string());
+        assert_eq!(stub.get_return_value_for(|id| *id == 42), "alice");
+        assert_eq!(stub.get_return_value_for(|id| *id == 1), "");
+
+        stub.setup("unknown".to_string());
+        assert_eq!(stub.get_return_value(), "unknown");
+    }
+
+    #[test]
+    fn test_clear_resets_keyed_return_values() {
+        let mut stub: FunctionStub<String, u32> = FunctionStub::new("get_user");
+        stub.setup_for(42, "alice".to_string());
+
+        stub.clear();
+
+        assert!(!stub.is_set());
+        assert!(stub.keyed_return_values.is_empty());
+    }
+
+    #[test]
+    fn test_setup_once_moves_out_non_cloneable_value() {
+        struct Connection {
+            id: u32,
+        }
+
+        let mut stub: FunctionStub<Connection> = FunctionStub::new("connect");
+        stub.setup_once(Connection { id: 7 });
+
+        assert!(stub.is_set());
+        assert_eq!(stub.get_return_value().id, 7);
+        assert!(stub.is_set());
+    }
+
+    #[test]
+    #[should_panic(expected = "connect stub was set up with setup_once and already returned its value")]
+    fn test_setup_once_panics_on_second_call() {
+        struct Connection;
+
+        let mut stub: FunctionStub<Connection> = FunctionStub::new("connect");
+        stub.setup_once(Connection);
+
+        stub.get_return_value();
+        stub.get_return_value();
+    }
+
+    #[test]
+    fn test_setup_once_takes_precedence_and_falls_back_afterwards() {
+        let mut stub: FunctionStub<String, u32> = FunctionStub::new("get_user");
+        stub.setup("default".to_string());
+        stub.setup_for(42, "alice".to_string());
+        stub.setup_once("once".to_string());
+
+        assert_eq!(stub.get_return_value_for(|id| *id == 42), "once");
+        assert_eq!(stub.get_return_value_for(|id| *id == 42), "alice");
+        assert_eq!(stub.get_return_value(), "default");
+    }
+
+    #[test]
+    fn test_clone_skips_value_set_up_once() {
+        let mut stub: FunctionStub<String> = FunctionStub::new("get_value");
+        stub.setup("shared".to_string());
+        stub.setup_once("once".to_string());
+
+        let mut copy = stub.clone();
+
+        assert_eq!(copy.get_return_value(), "shared");
+        assert_eq!(stub.get_return_value(), "once");
+    }
+
+    #[test]
+    fn test_setup_factory_produces_value_per_call() {
+        struct Handle {
+            id: u32,
+        }
+
+        let mut stub: FunctionStub<Handle> = FunctionStub::new("connect");
+        let mut next_id = 0;
+        stub.setup_factory(move || {
+            next_id += 1;
+            Handle { id: next_id }
+        });
+
+        assert!(stub.is_set());
+        assert_eq!(stub.get_return_value().id, 1);
+        assert_eq!(stub.get_return_value().id, 2);
+    }
+
+    #[test]
+    fn test_setup_and_setup_factory_replace_each_other() {
+        let mut stub: FunctionStub<String, u32> = FunctionStub::new("get_user");
+        stub.setup("fixed".to_string());
+        stub.setup_factory(|| "produced".to_string());
+        stub.setup_for(42, "alice".to_string());
+
+        assert_eq!(stub.get_return_value_for(|id| *id == 1), "produced");
+        assert_eq!(stub.get_return_value_for(|id| *id == 42), "alice");
+
+        stub.setup("fixed".to_string());
+        assert_eq!(stub.get_return_value(), "fixed");
+
+        stub.clear();
+        assert!(!stub.is_set());
+    }
+}
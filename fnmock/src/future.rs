@@ -0,0 +1,264 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// A boxed future as returned by async double implementations
+///
+/// The future is neither `Send` nor bound to a specific runtime, so async doubles work with
+/// every executor that polls on the thread the double was set up on - tokio's current thread runtime,
+/// embassy's executor, `block_on` implementations, ...
+///
+/// # Usage
+///
+/// ```ignore
+/// read_register_fake::setup_async(|address| Box::pin(async move {
+///     yield_now().await;
+///     address * 2
+/// }));
+/// ```
+pub type LocalBoxFuture<Output> = Pin<Box<dyn Future<Output = Output>>>;
+
+/// Future returning `Pending` once before completing, created by [`yield_now`]
+///
+/// # Fields
+///
+/// - `yielded` - whether the future already returned `Pending`
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+
+        // Wake immediately, so the executor polls the future again after running other tasks
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// Yields control back to the executor once.
+///
+/// Used by async mocks set up with `setup_with_yields`, so the code under test can be cancelled
+/// or lose a `select!` race at a deterministic point. Works with every executor, since it only wakes itself.
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+/// Future completing after a deadline, created by [`sleep`]
+///
+/// # Variants
+///
+/// - `Tokio` - tokio's timer, used inside a tokio runtime with the `tokio` feature,
+///   so paused and auto-advanced time (`#[tokio::test(start_paused = true)]`) applies to it
+/// - `Thread` - completes at the `deadline`, woken by a timer thread sharing the `waker` of the last poll
+///   (`None` until the first poll), so it works with every executor
+pub enum Sleep {
+    #[cfg(feature = "tokio")]
+    Tokio(Pin<Box<tokio::time::Sleep>>),
+    Thread {
+        deadline: Instant,
+        waker: Option<Arc<Mutex<Waker>>>,
+    },
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let (deadline, waker) = match self.get_mut() {
+            #[cfg(feature = "tokio")]
+            Sleep::Tokio(sleep) => return sleep.as_mut().poll(cx),
+            Sleep::Thread { deadline, waker } => (*deadline, waker),
+        };
+        if Instant::now() >= deadline {
+            return Poll::Ready(());
+        }
+
+        match waker {
+            Some(waker) => waker.lock().unwrap_or_else(PoisonError::into_inner).clone_from(cx.waker()),
+            None => {
+                // The timer thread wakes the executor, so no runtime timer is needed
+                let shared_waker = Arc::new(Mutex::new(cx.waker().clone()));
+                let timer_waker = shared_waker.clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                    timer_waker.lock().unwrap_or_else(PoisonError::into_inner).wake_by_ref();
+                });
+                *waker = Some(shared_waker);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Completes after the given duration without blocking the executor.
+///
+/// With the `tokio` feature, the future uses `tokio::time::sleep` when it is created inside a tokio runtime,
+/// so tests with paused time stay deterministic. Otherwise a background thread wakes the future,
+/// which works with every executor.
+pub fn sleep(duration: Duration) -> Sleep {
+    #[cfg(feature = "tokio")]
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Sleep::Tokio(Box::pin(tokio::time::sleep(duration)));
+    }
+
+    Sleep::Thread { deadline: Instant::now() + duration, waker: None }
+}
+
+/// Gets the current time of the clock [`sleep`] uses.
+///
+/// Inside a tokio runtime with the `tokio` feature this is tokio's clock, which may be paused.
+fn now() -> Instant {
+    #[cfg(feature = "tokio")]
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return tokio::time::Instant::now().into_std();
+    }
+
+    Instant::now()
+}
+
+/// The longest pause between two checks of [`wait_until`]
+const MAX_BACKOFF: Duration = Duration::from_millis(32);
+
+/// Waits until `condition` returns true or the timeout expires, checking with exponential backoff.
+///
+/// The executor runs other tasks (e.g. background tasks calling a mock) between the checks.
+/// The timeout is measured with the clock of [`sleep`], so it follows paused tokio time with the `tokio` feature.
+///
+/// # Returns
+///
+/// Whether the condition was met before the timeout expired
+pub async fn wait_until(mut condition: impl FnMut() -> bool, timeout: Duration) -> bool {
+    let deadline = now() + timeout;
+    let mut backoff = Duration::from_millis(1);
+
+    loop {
+        if condition() {
+            return true;
+        }
+        let remaining = deadline.saturating_duration_since(now());
+        if remaining.is_zero() {
+            return false;
+        }
+
+        sleep(backoff.min(remaining)).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Wake;
+    use std::thread::Thread;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            std::thread::park();
+        }
+    }
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn test_yield_now_is_pending_once() {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = yield_now();
+
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+        assert!(Pin::new(&mut future).poll(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn test_sleep_completes_after_duration() {
+        let start = Instant::now();
+
+        block_on(sleep(Duration::from_millis(20)));
+
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_sleep_follows_paused_tokio_time() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+        let start = Instant::now();
+
+        runtime.block_on(async {
+            let tokio_start = tokio::time::Instant::now();
+            sleep(Duration::from_secs(60)).await;
+            assert!(tokio_start.elapsed() >= Duration::from_secs(60));
+        });
+
+        // The paused clock is advanced automatically, so the test doesn't wait a minute
+        assert!(start.elapsed() < Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_wait_until_returns_once_condition_is_met() {
+        let mut checks = 0;
+
+        let met = block_on(wait_until(|| {
+            checks += 1;
+            checks == 3
+        }, Duration::from_secs(5)));
+
+        assert!(met);
+        assert_eq!(checks, 3);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_wait_until_follows_paused_tokio_time() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+        let start = Instant::now();
+
+        let met = runtime.block_on(async {
+            let tokio_start = tokio::time::Instant::now();
+            let met = wait_until(|| false, Duration::from_secs(60)).await;
+            assert!(tokio_start.elapsed() >= Duration::from_secs(60));
+            met
+        });
+
+        assert!(!met);
+        // The timeout expires on the paused clock, so the test doesn't wait a minute
+        assert!(start.elapsed() < Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_wait_until_times_out() {
+        let start = Instant::now();
+
+        let met = block_on(wait_until(|| false, Duration::from_millis(20)));
+
+        assert!(!met);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
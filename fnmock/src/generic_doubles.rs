@@ -0,0 +1,226 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use crate::registry::MockControl;
+
+/// Struct containing one double per monomorphization of a generic function
+///
+/// A `thread_local!` static can't be generic, so a generic function can't store a single
+/// `FunctionMock<Params, Result>` like a non-generic function does. Instead the generated module
+/// stores a `GenericDoubles` and looks up the double belonging to the concrete type parameters of each call.
+///
+/// # Usage
+///
+/// Normally you don't need to interact with GenericDoubles.
+/// The usage is automated in the `fnmock-derive::mock_function` macro,
+/// and you interact with generated proxy functions.
+///
+/// The generic function `parse` is supposed to be mocked.
+///
+/// ```ignore
+/// pub(crate) fn parse<T: FromStr>(input: String) -> Option<T> {
+///     input.parse().ok()
+/// }
+/// ```
+///
+/// The generated module stores the doubles of all monomorphizations and every proxy function
+/// takes the type parameters of the original function to select the correct double.
+///
+/// ```ignore
+/// pub(crate) mod parse_mock {
+///     thread_local! {
+///         static MOCK: RefCell<GenericDoubles> = RefCell::new(GenericDoubles::new("parse_mock"));
+///     }
+///
+///     pub(crate) fn call<T: FromStr>(params: String) -> Option<T>
+///     where
+///         T: Clone + Debug + PartialEq + 'static
+///     {
+///         MOCK.with(|mock| {
+///             mock.borrow_mut()
+///                 .get_or_create::<(T,), FunctionMock<String, Option<T>>>(FunctionMock::new)
+///                 .call(params)
+///         })
+///     }
+///     // ...
+///     // the same for all other mock functions
+/// }
+/// ```
+///
+/// # Fields
+///
+/// - `name` - the name of the function, passed to every created double
+/// - `doubles` - the doubles keyed by the type parameters and the type of the double
+#[derive(Clone)]
+pub struct GenericDoubles {
+    name: String,
+    doubles: HashMap<(TypeId, TypeId), Box<dyn StoredDouble>>,
+}
+
+/// Type erased double that can be cloned, so `GenericDoubles` can be snapshotted
+trait StoredDouble: Any {
+    fn clone_box(&self) -> Box<dyn StoredDouble>;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn as_control(&self) -> &dyn MockControl;
+}
+
+impl<Double> StoredDouble for Double
+where
+    Double: Any + Clone + MockControl,
+{
+    fn clone_box(&self) -> Box<dyn StoredDouble> {
+        Box::new(self.clone())
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn as_control(&self) -> &dyn MockControl {
+        self
+    }
+}
+
+impl Clone for Box<dyn StoredDouble> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+impl GenericDoubles {
+    pub fn new(function_name: &str) -> Self {
+        Self {
+            name: function_name.to_string(),
+            doubles: HashMap::new(),
+        }
+    }
+
+    /// Returns the double for the type parameters `Key`, creating it with `create` on first use.
+    pub fn get_or_create<Key, Double>(&mut self, create: fn(&str) -> Double) -> &mut Double
+    where
+        Key: ?Sized + 'static,
+        Double: Clone + MockControl + 'static,
+    {
+        let name = &self.name;
+        let double = self.doubles
+            .entry((TypeId::of::<Key>(), TypeId::of::<Double>()))
+            .or_insert_with(|| Box::new(create(name)));
+
+        // Deref the box explicitly, since `Box<dyn StoredDouble>` implements `StoredDouble` itself
+        (**double)
+            .as_any_mut()
+            .downcast_mut::<Double>()
+            .expect("doubles are keyed by their own TypeId")
+    }
+
+    /// Drops the doubles of all monomorphizations.
+    pub fn clear(&mut self) {
+        self.doubles.clear();
+    }
+}
+
+impl MockControl for GenericDoubles {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the calls of all monomorphizations, or `None` if none of them records its calls.
+    fn times_called(&self) -> Option<u32> {
+        self.doubles.values()
+            .filter_map(|double| (**double).as_control().times_called())
+            .reduce(|total, times| total + times)
+    }
+
+    /// Checks the expectations of all monomorphizations.
+    fn verify(&self) {
+        for double in self.doubles.values() {
+            (**double).as_control().verify();
+        }
+    }
+
+    fn clear(&mut self) {
+        GenericDoubles::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::function_mock::FunctionMock;
+
+    #[test]
+    fn test_new_creates_empty_doubles() {
+        let doubles = GenericDoubles::new("parse");
+        assert_eq!(doubles.name, "parse");
+        assert!(doubles.doubles.is_empty());
+    }
+
+    #[test]
+    fn test_get_or_create_reuses_double_for_same_key() {
+        let mut doubles = GenericDoubles::new("parse");
+
+        doubles.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new)
+            .setup(|_| 1);
+        let result = doubles.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new)
+            .call("1".to_string());
+
+        assert_eq!(result, 1);
+        assert_eq!(doubles.doubles.len(), 1);
+    }
+
+    #[test]
+    fn test_get_or_create_separates_monomorphizations() {
+        let mut doubles = GenericDoubles::new("parse");
+
+        doubles.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new)
+            .setup(|_| 1);
+
+        assert!(doubles.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new).is_set());
+        assert!(!doubles.get_or_create::<(i64,), FunctionMock<String, i64>>(FunctionMock::new).is_set());
+        assert_eq!(doubles.doubles.len(), 2);
+    }
+
+    #[test]
+    fn test_get_or_create_separates_keys_with_same_double_type() {
+        let mut doubles = GenericDoubles::new("size_of");
+
+        doubles.get_or_create::<(u8,), FunctionMock<(), usize>>(FunctionMock::new)
+            .setup(|_| 1);
+
+        assert!(!doubles.get_or_create::<(u64,), FunctionMock<(), usize>>(FunctionMock::new).is_set());
+    }
+
+    #[test]
+    #[should_panic(expected = "parse mock not initialized")]
+    fn test_created_double_uses_function_name() {
+        let mut doubles = GenericDoubles::new("parse");
+        doubles.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new)
+            .call("1".to_string());
+    }
+
+    #[test]
+    fn test_clear_drops_all_doubles() {
+        let mut doubles = GenericDoubles::new("parse");
+        doubles.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new)
+            .setup(|_| 1);
+        doubles.get_or_create::<(i64,), FunctionMock<String, i64>>(FunctionMock::new)
+            .setup(|_| 2);
+
+        doubles.clear();
+
+        assert!(doubles.doubles.is_empty());
+        assert!(!doubles.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new).is_set());
+    }
+
+    #[test]
+    fn test_clone_copies_all_doubles() {
+        let mut doubles = GenericDoubles::new("parse");
+        doubles.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new)
+            .setup(|_| 1);
+
+        let mut copy = doubles.clone();
+        doubles.clear();
+
+        assert!(copy.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new).is_set());
+        assert!(!doubles.get_or_create::<(u32,), FunctionMock<String, u32>>(FunctionMock::new).is_set());
+    }
+}
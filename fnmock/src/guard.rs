@@ -0,0 +1,146 @@
+/// Guard clearing a double when it goes out of scope
+///
+/// Returned by the `setup_scoped` proxy functions of the generated modules. The double stays set up
+/// while the guard is alive and is cleared when it is dropped, even if the test panicked in between.
+/// This keeps the setup of one phase of a test from leaking into the next one.
+///
+/// # Usage
+///
+/// ```ignore
+/// {
+///     let _guard = fetch_user_mock::setup_scoped(|_| Err("offline".to_string()));
+///     assert_eq!(handle_user(42), "offline");
+/// }
+///
+/// // The mock is cleared again, so the original implementation runs
+/// assert_eq!(handle_user(42), "user_42");
+/// ```
+///
+/// # Fields
+///
+/// - `clear` - the `clear()` proxy function of the double
+#[must_use = "the double is cleared as soon as the guard is dropped"]
+pub struct MockGuard {
+    clear: fn(),
+}
+
+impl MockGuard {
+    /// Creates a guard calling `clear` when it is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `clear` - the `clear()` proxy function of the double
+    pub fn new(clear: fn()) -> Self {
+        Self { clear }
+    }
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        (self.clear)();
+    }
+}
+
+/// Guard verifying the expectations of a mock when it goes out of scope
+///
+/// Returned by the `verify_on_drop` proxy functions of the generated mock modules, so expectations
+/// registered with `expect_times` / `expect_with` fail the test even if it doesn't call `verify()` itself.
+/// The expectations aren't verified while the thread is already panicking, since the test failed anyway.
+///
+/// # Usage
+///
+/// ```ignore
+/// let _checkpoint = send_email_mock::verify_on_drop();
+/// send_email_mock::setup(|_| Ok(()));
+/// send_email_mock::expect_times(1);
+///
+/// welcome_user(42);
+/// // fails here if send_email wasn't called exactly once
+/// ```
+///
+/// # Fields
+///
+/// - `verify` - the `verify()` proxy function of the mock
+#[must_use = "the expectations are verified as soon as the checkpoint is dropped"]
+pub struct Checkpoint {
+    verify: fn(),
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint calling `verify` when it is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `verify` - the `verify()` proxy function of the mock
+    pub fn new(verify: fn()) -> Self {
+        Self { verify }
+    }
+}
+
+impl Drop for Checkpoint {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            (self.verify)();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::panic::catch_unwind;
+
+    thread_local! {
+        static CLEARED: Cell<u32> = const { Cell::new(0) };
+    }
+
+    fn clear() {
+        CLEARED.with(|cleared| cleared.set(cleared.get() + 1));
+    }
+
+    #[test]
+    fn test_clears_on_drop() {
+        let guard = MockGuard::new(clear);
+        assert_eq!(CLEARED.with(Cell::get), 0);
+
+        drop(guard);
+        assert_eq!(CLEARED.with(Cell::get), 1);
+    }
+
+    #[test]
+    fn test_clears_on_panic() {
+        let result = catch_unwind(|| {
+            let _guard = MockGuard::new(clear);
+            panic!("test failed");
+        });
+
+        assert!(result.is_err());
+        assert_eq!(CLEARED.with(Cell::get), 1);
+    }
+
+    thread_local! {
+        static VERIFIED: Cell<u32> = const { Cell::new(0) };
+    }
+
+    fn verify() {
+        VERIFIED.with(|verified| verified.set(verified.get() + 1));
+    }
+
+    #[test]
+    fn test_checkpoint_verifies_on_drop() {
+        drop(Checkpoint::new(verify));
+        assert_eq!(VERIFIED.with(Cell::get), 1);
+    }
+
+    #[test]
+    fn test_checkpoint_skips_verification_while_panicking() {
+        let result = catch_unwind(|| {
+            let _checkpoint = Checkpoint::new(verify);
+            panic!("test failed");
+        });
+
+        assert!(result.is_err());
+        assert_eq!(VERIFIED.with(Cell::get), 0);
+    }
+}
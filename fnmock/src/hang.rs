@@ -0,0 +1,166 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+/// Test-controlled release of a hanging double
+///
+/// Doubles set up with `setup_blocks` (sync functions) or `setup_pending` (async functions) don't return
+/// until the handle is released, so timeout and watchdog logic can be tested against a truly unresponsive
+/// dependency. The handle can be cloned and sent to other threads, e.g. to a watchdog releasing the call.
+///
+/// # Usage
+///
+/// ```ignore
+/// let release = fetch_user_mock::setup_blocks(|id| Ok(format!("user_{}", id)));
+///
+/// // Release the blocked call from another thread, so the suite doesn't hang
+/// release.release_after(Duration::from_millis(50));
+/// assert_eq!(fetch_user_with_watchdog(42), Err("timeout".to_string()));
+/// ```
+///
+/// # Fields
+///
+/// - `state` - the release state shared by all clones of the handle
+#[derive(Clone, Default)]
+pub struct ReleaseHandle {
+    state: Arc<ReleaseState>,
+}
+
+/// Shared state of a [`ReleaseHandle`]
+///
+/// # Fields
+///
+/// - `released` - whether the handle was released, together with the wakers of pending futures
+/// - `condvar` - notifies blocked threads about the release
+#[derive(Default)]
+struct ReleaseState {
+    released: Mutex<(bool, Vec<Waker>)>,
+    condvar: Condvar,
+}
+
+impl ReleaseHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Releases all blocked calls and pending futures, including the ones started afterward.
+    pub fn release(&self) {
+        let wakers = {
+            let mut released = self.lock();
+            released.0 = true;
+            std::mem::take(&mut released.1)
+        };
+
+        self.state.condvar.notify_all();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Releases the handle from a background thread after the given duration.
+    ///
+    /// Safety net for tests, so a hanging double can't hang the suite.
+    pub fn release_after(&self, duration: Duration) {
+        let handle = self.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            handle.release();
+        });
+    }
+
+    pub fn is_released(&self) -> bool {
+        self.lock().0
+    }
+
+    /// Blocks the current thread until the handle is released.
+    pub fn wait(&self) {
+        let mut released = self.lock();
+        while !released.0 {
+            released = self.state.condvar.wait(released).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Returns a future, which is pending until the handle is released.
+    pub fn wait_async(&self) -> Released {
+        Released { handle: self.clone() }
+    }
+
+    // A panicking test thread must not prevent the release of other threads
+    fn lock(&self) -> MutexGuard<'_, (bool, Vec<Waker>)> {
+        self.state.released.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Future pending until its [`ReleaseHandle`] is released, created by [`ReleaseHandle::wait_async`]
+///
+/// # Fields
+///
+/// - `handle` - the handle to wait for
+pub struct Released {
+    handle: ReleaseHandle,
+}
+
+impl Future for Released {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut released = self.handle.lock();
+        if released.0 {
+            return Poll::Ready(());
+        }
+
+        released.1.push(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Wake;
+
+    struct FlagWaker(Mutex<bool>);
+
+    impl Wake for FlagWaker {
+        fn wake(self: Arc<Self>) {
+            *self.0.lock().unwrap() = true;
+        }
+    }
+
+    #[test]
+    fn test_wait_returns_after_release() {
+        let handle = ReleaseHandle::new();
+        handle.release_after(Duration::from_millis(10));
+
+        handle.wait();
+
+        assert!(handle.is_released());
+    }
+
+    #[test]
+    fn test_wait_returns_immediately_if_released() {
+        let handle = ReleaseHandle::new();
+        handle.release();
+
+        handle.wait();
+    }
+
+    #[test]
+    fn test_wait_async_is_pending_until_release() {
+        let flag = Arc::new(FlagWaker(Mutex::new(false)));
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+        let handle = ReleaseHandle::new();
+        let mut released = handle.wait_async();
+
+        assert!(Pin::new(&mut released).poll(&mut cx).is_pending());
+        assert!(!*flag.0.lock().unwrap());
+
+        handle.release();
+
+        assert!(*flag.0.lock().unwrap());
+        assert!(Pin::new(&mut released).poll(&mut cx).is_ready());
+    }
+}
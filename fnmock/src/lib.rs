@@ -1,7 +1,64 @@
 pub mod function_mock;
 pub mod function_fake;
 pub mod function_stub;
+pub mod function_spy;
+pub mod generic_doubles;
+pub mod future;
+pub mod hang;
+pub mod timing;
+pub mod guard;
+pub mod snapshot;
+pub mod registry;
+pub mod matchers;
+pub mod sequence;
+pub mod when;
+pub mod captor;
+pub mod no_debug;
+pub mod compare;
+pub mod shared_mock;
+pub mod storage;
+pub mod failure;
+#[cfg(feature = "manifest")]
+pub mod manifest;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "task-local")]
+pub mod task_local;
+#[cfg(feature = "insta")]
+pub mod calls_snapshot;
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
 
+// Used by the generated manifest entries
+#[cfg(feature = "manifest")]
+#[doc(hidden)]
+pub use inventory;
+
+// Used by `assert_calls_snapshot!`, so the tests don't need their own insta dependency
+#[cfg(feature = "insta")]
+#[doc(hidden)]
+pub use insta;
+
+// Used by the generated `setup_arbitrary` proxy functions
+#[cfg(feature = "proptest")]
+#[doc(hidden)]
+pub use proptest;
+
+pub use failure::{set_failure_handler, VerificationFailure};
+pub use registry::verify_all;
+
+// Allows `#[fnmock::test]`
+pub use fnmock_derive::test;
+
+// Allows `fnmock::when!(...)` and `fnmock::assert_with!(...)`
+pub use fnmock_derive::{assert_with, when};
+
+// `test`, `when` and `assert_with` are only reachable from the crate root,
+// so `use fnmock::derive::*;` doesn't make a plain `#[test]` ambiguous
 pub mod derive {
-    pub use fnmock_derive::*;
-}
\ No newline at end of file
+    pub use fnmock_derive::{
+        mock_function, fake_function, stub_function, spy_function, dummy_function,
+        traitify, mock_trait, mock_impl, mock_module, reset_doubles,
+        use_function_mock, use_function_fake, use_function_stub, use_doubles,
+    };
+}
@@ -0,0 +1,176 @@
+use std::fmt::Display;
+use std::path::Path;
+
+/// The kind of double generated for a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoubleKind {
+    Mock,
+    Fake,
+    Stub,
+    Spy,
+    Dummy,
+}
+
+impl Display for DoubleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DoubleKind::Mock => "mock",
+            DoubleKind::Fake => "fake",
+            DoubleKind::Stub => "stub",
+            DoubleKind::Spy => "spy",
+            DoubleKind::Dummy => "dummy",
+        })
+    }
+}
+
+/// Metadata of a function annotated with `mock_function`, `fake_function`, `stub_function`, `spy_function` or `dummy_function`
+///
+/// One entry is emitted by the derive macros for every doubled function, if the `manifest`
+/// feature is enabled. The entries are collected at link time, so the manifest contains
+/// all doubled functions of the binary, including the ones of dependencies.
+///
+/// # Usage
+///
+/// ```ignore
+/// use fnmock::manifest;
+///
+/// // Audit which dependencies of the repository module can be doubled
+/// for entry in manifest::entries_in("my_crate::repository") {
+///     println!("{}", entry);
+/// }
+/// ```
+///
+/// # Fields
+///
+/// - `name` - the name of the function
+/// - `kind` - the kind of double generated for the function
+/// - `module_path` - the path of the module containing the function (from `module_path!()`)
+/// - `signature` - the signature of the function as written in the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub name: &'static str,
+    pub kind: DoubleKind,
+    pub module_path: &'static str,
+    pub signature: &'static str,
+}
+
+impl ManifestEntry {
+    /// The full path of the function, e.g. `my_crate::repository::fetch_user`.
+    pub fn path(&self) -> String {
+        format!("{}::{}", self.module_path, self.name)
+    }
+}
+
+impl Display for ManifestEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\t{}\t{}", self.kind, self.path(), self.signature)
+    }
+}
+
+inventory::collect!(ManifestEntry);
+
+/// Returns all doubled functions of the binary, sorted by their module path and name.
+pub fn entries() -> Vec<&'static ManifestEntry> {
+    let mut entries: Vec<&'static ManifestEntry> = inventory::iter::<ManifestEntry>.into_iter().collect();
+    entries.sort_by_key(|entry| (entry.module_path, entry.name));
+    entries
+}
+
+/// Returns the doubled functions of a module and its submodules, sorted by their module path and name.
+pub fn entries_in(module_path: &str) -> Vec<&'static ManifestEntry> {
+    entries()
+        .into_iter()
+        .filter(|entry| {
+            entry.module_path == module_path
+                || entry.module_path.strip_prefix(module_path).is_some_and(|rest| rest.starts_with("::"))
+        })
+        .collect()
+}
+
+/// Returns the entry of a doubled function by its full path, e.g. `my_crate::repository::fetch_user`.
+pub fn find(path: &str) -> Option<&'static ManifestEntry> {
+    entries().into_iter().find(|entry| entry.path() == path)
+}
+
+/// Renders the manifest with one tab separated line (`kind`, `path`, `signature`) per doubled function.
+pub fn render() -> String {
+    entries()
+        .iter()
+        .map(|entry| format!("{}\n", entry))
+        .collect()
+}
+
+/// Writes the rendered manifest to a file, e.g. from a test to make it available to external tooling.
+pub fn write_to(path: impl AsRef<Path>) -> std::io::Result<()> {
+    std::fs::write(path, render())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    inventory::submit! {
+        ManifestEntry {
+            name: "fetch_user",
+            kind: DoubleKind::Mock,
+            module_path: "fnmock::manifest::tests::repository",
+            signature: "fn fetch_user(id: u32) -> Result<String, String>",
+        }
+    }
+
+    inventory::submit! {
+        ManifestEntry {
+            name: "get_config",
+            kind: DoubleKind::Stub,
+            module_path: "fnmock::manifest::tests::repository::config",
+            signature: "fn get_config() -> String",
+        }
+    }
+
+    inventory::submit! {
+        ManifestEntry {
+            name: "add",
+            kind: DoubleKind::Fake,
+            module_path: "fnmock::manifest::tests::repository_utils",
+            signature: "fn add(a: i32, b: i32) -> i32",
+        }
+    }
+
+    #[test]
+    fn test_entries_are_sorted_by_module_path_and_name() {
+        let paths: Vec<String> = entries().iter().map(|entry| entry.path()).collect();
+
+        assert_eq!(paths, vec![
+            "fnmock::manifest::tests::repository::fetch_user",
+            "fnmock::manifest::tests::repository::config::get_config",
+            "fnmock::manifest::tests::repository_utils::add",
+        ]);
+    }
+
+    #[test]
+    fn test_entries_in_includes_submodules_only() {
+        let names: Vec<&str> = entries_in("fnmock::manifest::tests::repository")
+            .iter()
+            .map(|entry| entry.name)
+            .collect();
+
+        assert_eq!(names, vec!["fetch_user", "get_config"]);
+    }
+
+    #[test]
+    fn test_find_by_path() {
+        let entry = find("fnmock::manifest::tests::repository_utils::add").unwrap();
+
+        assert_eq!(entry.kind, DoubleKind::Fake);
+        assert_eq!(entry.signature, "fn add(a: i32, b: i32) -> i32");
+        assert!(find("fnmock::manifest::tests::unknown").is_none());
+    }
+
+    #[test]
+    fn test_render() {
+        let rendered = render();
+
+        assert!(rendered.contains("mock\tfnmock::manifest::tests::repository::fetch_user\tfn fetch_user(id: u32) -> Result<String, String>\n"));
+        assert_eq!(rendered.lines().count(), 3);
+    }
+}
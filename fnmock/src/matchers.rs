@@ -0,0 +1,246 @@
+use std::fmt::Debug;
+
+/// Condition on a value, used to check recorded parameters without exact equality
+///
+/// All matchers of a value type share the same type, so they can be combined freely,
+/// e.g. `all_of([gt(0), lt(100)])` or `not(eq(42))`.
+///
+/// # Usage
+///
+/// ```ignore
+/// use fnmock::matchers::{all_of, gt, lt, not, eq};
+///
+/// let in_range = all_of([gt(0), lt(100)]);
+/// assert!(in_range.matches(&42));
+/// assert!(!not(eq(42)).matches(&42));
+/// ```
+///
+/// # Fields
+///
+/// - `description` - the description of the condition for display purposes when asserting
+/// - `predicate` - the condition
+pub struct Matcher<T>
+where
+    T: ?Sized,
+{
+    description: String,
+    predicate: Box<dyn Fn(&T) -> bool>,
+}
+
+impl<T> Matcher<T>
+where
+    T: ?Sized,
+{
+    pub fn new(description: impl Into<String>, predicate: impl Fn(&T) -> bool + 'static) -> Self {
+        Self {
+            description: description.into(),
+            predicate: Box::new(predicate),
+        }
+    }
+
+    pub fn matches(&self, value: &T) -> bool {
+        (self.predicate)(value)
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl<T> Debug for Matcher<T>
+where
+    T: ?Sized,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.description)
+    }
+}
+
+// --- Matchers ---
+
+/// Matches every value.
+pub fn any<T>() -> Matcher<T>
+where
+    T: ?Sized,
+{
+    Matcher::new("any", |_| true)
+}
+
+/// Matches values equal to `expected`.
+pub fn eq<T>(expected: T) -> Matcher<T>
+where
+    T: PartialEq + Debug + 'static,
+{
+    Matcher::new(format!("eq({:?})", expected), move |value| *value == expected)
+}
+
+/// Matches values equal to `expected` of another comparable type, e.g. `String` values equal to a `&str`.
+///
+/// Used by the `when!` and `assert_with!` macros, so arguments can be written like in the call of the function.
+pub fn eq_to<T, V>(expected: V) -> Matcher<T>
+where
+    T: PartialEq<V>,
+    V: Debug + 'static,
+{
+    Matcher::new(format!("eq({:?})", expected), move |value: &T| *value == expected)
+}
+
+/// Matches values greater than `bound`.
+pub fn gt<T>(bound: T) -> Matcher<T>
+where
+    T: PartialOrd + Debug + 'static,
+{
+    Matcher::new(format!("gt({:?})", bound), move |value| *value > bound)
+}
+
+/// Matches values greater than or equal to `bound`.
+pub fn ge<T>(bound: T) -> Matcher<T>
+where
+    T: PartialOrd + Debug + 'static,
+{
+    Matcher::new(format!("ge({:?})", bound), move |value| *value >= bound)
+}
+
+/// Matches values less than `bound`.
+pub fn lt<T>(bound: T) -> Matcher<T>
+where
+    T: PartialOrd + Debug + 'static,
+{
+    Matcher::new(format!("lt({:?})", bound), move |value| *value < bound)
+}
+
+/// Matches values less than or equal to `bound`.
+pub fn le<T>(bound: T) -> Matcher<T>
+where
+    T: PartialOrd + Debug + 'static,
+{
+    Matcher::new(format!("le({:?})", bound), move |value| *value <= bound)
+}
+
+/// Matches values accepted by a custom predicate.
+pub fn predicate<T>(description: impl Into<String>, predicate: impl Fn(&T) -> bool + 'static) -> Matcher<T>
+where
+    T: ?Sized,
+{
+    Matcher::new(description, predicate)
+}
+
+// --- Combinators ---
+
+/// Matches values matched by all `matchers` (and).
+pub fn all_of<T>(matchers: impl IntoIterator<Item = Matcher<T>>) -> Matcher<T>
+where
+    T: ?Sized + 'static,
+{
+    let matchers: Vec<Matcher<T>> = matchers.into_iter().collect();
+    Matcher::new(
+        format!("all_of({:?})", matchers),
+        move |value| matchers.iter().all(|matcher| matcher.matches(value)),
+    )
+}
+
+/// Matches values matched by at least one of the `matchers` (or).
+pub fn any_of<T>(matchers: impl IntoIterator<Item = Matcher<T>>) -> Matcher<T>
+where
+    T: ?Sized + 'static,
+{
+    let matchers: Vec<Matcher<T>> = matchers.into_iter().collect();
+    Matcher::new(
+        format!("any_of({:?})", matchers),
+        move |value| matchers.iter().any(|matcher| matcher.matches(value)),
+    )
+}
+
+/// Matches values not matched by `matcher`.
+pub fn not<T>(matcher: Matcher<T>) -> Matcher<T>
+where
+    T: ?Sized + 'static,
+{
+    Matcher::new(
+        format!("not({:?})", matcher),
+        move |value| !matcher.matches(value),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_any_matches_everything() {
+        assert!(any::<i32>().matches(&0));
+        assert!(any::<str>().matches("anything"));
+    }
+
+    #[test]
+    fn test_comparison_matchers() {
+        assert!(eq(42).matches(&42));
+        assert!(!eq(42).matches(&41));
+        assert!(gt(0).matches(&1));
+        assert!(!gt(0).matches(&0));
+        assert!(ge(0).matches(&0));
+        assert!(lt(100).matches(&99));
+        assert!(!lt(100).matches(&100));
+        assert!(le(100).matches(&100));
+    }
+
+    #[test]
+    fn test_eq_to_compares_other_types() {
+        let bob: Matcher<String> = eq_to("bob");
+
+        assert!(bob.matches(&"bob".to_string()));
+        assert!(!bob.matches(&"alice".to_string()));
+        assert_eq!(bob.description(), "eq(\"bob\")");
+        assert!(eq_to::<u32, _>(42).matches(&42));
+    }
+
+    #[test]
+    fn test_predicate_uses_custom_condition() {
+        let even = predicate("even", |value: &i32| value % 2 == 0);
+
+        assert!(even.matches(&4));
+        assert!(!even.matches(&3));
+        assert_eq!(even.description(), "even");
+    }
+
+    #[test]
+    fn test_all_of_requires_every_matcher() {
+        let in_range = all_of([gt(0), lt(100)]);
+
+        assert!(in_range.matches(&42));
+        assert!(!in_range.matches(&0));
+        assert!(!in_range.matches(&100));
+    }
+
+    #[test]
+    fn test_any_of_requires_one_matcher() {
+        let edge = any_of([eq(0), eq(100)]);
+
+        assert!(edge.matches(&0));
+        assert!(edge.matches(&100));
+        assert!(!edge.matches(&50));
+    }
+
+    #[test]
+    fn test_not_inverts_matcher() {
+        assert!(!not(eq(42)).matches(&42));
+        assert!(not(eq(42)).matches(&41));
+    }
+
+    #[test]
+    fn test_combinators_nest() {
+        let matcher = all_of([gt(0), not(any_of([eq(13), eq(666)]))]);
+
+        assert!(matcher.matches(&42));
+        assert!(!matcher.matches(&13));
+        assert!(!matcher.matches(&-1));
+    }
+
+    #[test]
+    fn test_description_of_combinators() {
+        let matcher = all_of([gt(0), not(eq(42))]);
+
+        assert_eq!(matcher.description(), "all_of([gt(0), not(eq(42))])");
+        assert_eq!(format!("{:?}", matcher), "all_of([gt(0), not(eq(42))])");
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Wrapper recording a parameter that doesn't implement `Debug`
+///
+/// Parameters listed in `no_debug` of `mock_function` are recorded in this wrapper, so they don't
+/// have to be ignored. Equality-based assertions like `assert_with` keep working, while failure
+/// messages format the parameter as `<non-debug T>`, with `T` being the name of the type.
+///
+/// # Usage
+///
+/// ```ignore
+/// #[mock_function(no_debug = [conn])]
+/// fn query(conn: Connection, sql: String) -> Rows { ... }
+///
+/// query_mock::assert_with(Connection::new("db"), "SELECT 1".to_string());
+///
+/// // The recorded connection is wrapped
+/// let (conn, _) = query_mock::last_call_params().unwrap();
+/// assert_eq!(conn.name(), "db");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct NoDebug<T>(pub T);
+
+impl<T> NoDebug<T> {
+    /// Returns the wrapped parameter.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Debug for NoDebug<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<non-debug {}>", std::any::type_name::<T>())
+    }
+}
+
+impl<T> Deref for NoDebug<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for NoDebug<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for NoDebug<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq)]
+    struct Connection {
+        name: String,
+    }
+
+    #[test]
+    fn test_debug_names_the_type() {
+        let conn = NoDebug(Connection { name: "db".to_string() });
+
+        assert_eq!(format!("{:?}", (1, conn)), format!("(1, <non-debug {}>)", std::any::type_name::<Connection>()));
+    }
+
+    #[test]
+    fn test_compares_and_derefs_the_wrapped_value() {
+        let conn = NoDebug(Connection { name: "db".to_string() });
+
+        assert!(conn == NoDebug(Connection { name: "db".to_string() }));
+        assert_eq!(conn.name, "db");
+        assert_eq!(conn.into_inner().name, "db");
+    }
+}
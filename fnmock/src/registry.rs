@@ -0,0 +1,460 @@
+use std::cell::RefCell;
+use crate::snapshot::StateSnapshot;
+
+/// Uniform control over a double, regardless of its kind
+///
+/// Implemented by the stored doubles ([`FunctionMock`](crate::function_mock::FunctionMock),
+/// [`FunctionFake`](crate::function_fake::FunctionFake), [`FunctionStub`](crate::function_stub::FunctionStub),
+/// [`FunctionSpy`](crate::function_spy::FunctionSpy) and [`GenericDoubles`](crate::generic_doubles::GenericDoubles))
+/// and by the [`DoubleHandle`]s of the registry, so test helpers can treat all doubles the same.
+///
+/// # Usage
+///
+/// ```ignore
+/// for double in fnmock::registry::doubles() {
+///     println!("{} was called {:?} times", double.name(), double.times_called());
+/// }
+/// ```
+pub trait MockControl {
+    /// Returns the name of the double, e.g. `fetch_user_mock`.
+    fn name(&self) -> &str;
+
+    /// Returns how often the double was called, or `None` if the double doesn't record its calls (fakes and stubs).
+    fn times_called(&self) -> Option<u32>;
+
+    /// Checks the expectations of the double. Doubles without expectations always pass.
+    fn verify(&self);
+
+    /// Resets the double.
+    fn clear(&mut self);
+}
+
+/// Function calling its argument with the stored double, generated as `control` in every double module
+pub type ControlFn = fn(&mut dyn FnMut(&dyn MockControl));
+
+/// Handle of a double that was used on the current thread, created by [`register`]
+///
+/// The handle calls the proxy functions of the generated module, so it stays valid while the double is reconfigured.
+///
+/// # Fields
+///
+/// - `name` - the name of the generated module
+/// - `snapshot`, `restore` and `clear` - the proxy functions of the double
+/// - `control` - calls a function with the stored double
+#[derive(Clone, Copy)]
+pub struct DoubleHandle {
+    name: &'static str,
+    snapshot: fn() -> StateSnapshot,
+    restore: fn(StateSnapshot),
+    clear: fn(),
+    control: ControlFn,
+}
+
+impl DoubleHandle {
+    /// Calls the function with the stored double and returns its result.
+    fn with_double<T>(&self, f: impl FnOnce(&dyn MockControl) -> T) -> T {
+        let mut f = Some(f);
+        let mut result = None;
+        (self.control)(&mut |double| result = f.take().map(|f| f(double)));
+        result.expect("the control function of a double calls the function")
+    }
+}
+
+impl MockControl for DoubleHandle {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn times_called(&self) -> Option<u32> {
+        self.with_double(|double| double.times_called())
+    }
+
+    fn verify(&self) {
+        self.with_double(|double| double.verify())
+    }
+
+    fn clear(&mut self) {
+        (self.clear)()
+    }
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Vec<DoubleHandle>> = const { RefCell::new(Vec::new()) };
+    static VERIFICATIONS: RefCell<Vec<fn()>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Registers a double with the registry of the current thread.
+///
+/// The generated modules call this the first time their thread local storage is accessed,
+/// so the registry only contains the doubles used on the current thread.
+/// You don't need to call it yourself.
+///
+/// # Arguments
+///
+/// * `name` - the name of the generated module
+/// * `snapshot` - the `snapshot()` proxy function of the double
+/// * `restore` - the `restore(snapshot)` proxy function of the double
+/// * `clear` - the `clear()` proxy function of the double
+/// * `control` - a function calling its argument with the stored double
+pub fn register(
+    name: &'static str,
+    snapshot: fn() -> StateSnapshot,
+    restore: fn(StateSnapshot),
+    clear: fn(),
+    control: ControlFn,
+) {
+    REGISTRY.with(|registry| {
+        registry.borrow_mut().push(DoubleHandle { name, snapshot, restore, clear, control })
+    });
+}
+
+/// Copies the handles out of the registry, so they can register other doubles while being called.
+fn registered_doubles() -> Vec<DoubleHandle> {
+    REGISTRY.with(|registry| registry.borrow().clone())
+}
+
+/// Returns the handles of every double used on the current thread, in registration order.
+pub fn doubles() -> Vec<DoubleHandle> {
+    registered_doubles()
+}
+
+/// Saved state of all doubles used on the current thread, created by [`snapshot_all`]
+///
+/// # Fields
+///
+/// - `snapshots` - the snapshots of the registered doubles, in registration order
+pub struct RegistrySnapshot {
+    snapshots: Vec<StateSnapshot>,
+}
+
+/// Takes a snapshot of every double used on the current thread.
+///
+/// # Usage
+///
+/// Useful for test helpers that reconfigure several doubles and have to put everything back afterward.
+///
+/// ```ignore
+/// fn with_failing_backend(test: impl FnOnce()) {
+///     let snapshot = fnmock::registry::snapshot_all();
+///
+///     fetch_user_mock::setup(|_| Err("offline".to_string()));
+///     get_config_stub::setup("fallback".to_string());
+///     test();
+///
+///     fnmock::registry::restore_all(snapshot);
+/// }
+/// ```
+pub fn snapshot_all() -> RegistrySnapshot {
+    RegistrySnapshot {
+        snapshots: registered_doubles()
+            .iter()
+            .map(|double| (double.snapshot)())
+            .collect(),
+    }
+}
+
+/// Restores every double used on the current thread to the state saved in the snapshot.
+///
+/// Doubles that were used for the first time after the snapshot was taken are cleared,
+/// since they were uninitialized when the snapshot was taken.
+pub fn restore_all(snapshot: RegistrySnapshot) {
+    let mut snapshots = snapshot.snapshots.into_iter();
+
+    for double in registered_doubles() {
+        match snapshots.next() {
+            Some(snapshot) => (double.restore)(snapshot),
+            None => (double.clear)(),
+        }
+    }
+}
+
+/// Clears every double used on the current thread.
+///
+/// Pending verifications are discarded as well, since clearing a mock removes its expectations.
+pub fn clear_all() {
+    VERIFICATIONS.with(|verifications| verifications.borrow_mut().clear());
+    for double in registered_doubles() {
+        (double.clear)();
+    }
+}
+
+/// Registers a pending verification with the registry of the current thread.
+///
+/// The generated mock modules call this when the first expectation of a mock is registered
+/// with `expect_times` / `expect_with`, so [`verify_all`] checks it at the end of the test.
+/// You don't need to call it yourself.
+///
+/// # Arguments
+///
+/// * `verify` - the `verify()` proxy function of the mock
+pub fn register_verification(verify: fn()) {
+    VERIFICATIONS.with(|verifications| verifications.borrow_mut().push(verify));
+}
+
+/// Verifies the expectations of every mock on the current thread that has pending verifications.
+///
+/// The pending verifications are removed, so calling it again only verifies expectations registered since.
+pub fn verify_all() {
+    let verifications = VERIFICATIONS.with(|verifications| verifications.take());
+    for verify in verifications {
+        verify();
+    }
+}
+
+/// Guard clearing every double used on the current thread when it is created and when it is dropped,
+/// created by [`reset_doubles`]
+pub struct ResetGuard {
+    // Prevents construction without clearing the doubles
+    _private: (),
+}
+
+impl Drop for ResetGuard {
+    fn drop(&mut self) {
+        clear_all();
+    }
+}
+
+/// Clears every double used on the current thread and returns a guard clearing them again when dropped.
+///
+/// # Usage
+///
+/// Parameterized tests (e.g. `test_case` or `rstest` cases) reuse the same test body for every case.
+/// Holding the guard for the duration of the body makes sure no case sees the doubles of another one,
+/// even if the cases run on the same thread. The `#[reset_doubles]` attribute does this for a whole test.
+///
+/// ```ignore
+/// #[test_case(1, "alice")]
+/// #[test_case(2, "bob")]
+/// fn test_greet(id: u32, name: &str) {
+///     let _guard = fnmock::registry::reset_doubles();
+///
+///     get_user_name_stub::setup(name.to_string());
+///     assert_eq!(greet(id), format!("Hello {}", name));
+/// }
+/// ```
+#[must_use = "the doubles are cleared again when the guard is dropped"]
+pub fn reset_doubles() -> ResetGuard {
+    clear_all();
+    ResetGuard { _private: () }
+}
+
+/// Guard clearing every double used on the current thread when it is created and
+/// verifying the pending expectations before clearing them again when it is dropped, created by [`test_scope`]
+pub struct TestScope {
+    // Prevents construction without clearing the doubles
+    _private: (),
+}
+
+impl Drop for TestScope {
+    fn drop(&mut self) {
+        // Clears the doubles even if the verification fails
+        let _reset = ResetGuard { _private: () };
+
+        // The test failed anyway, so unmet expectations would only hide the original panic
+        if !std::thread::panicking() {
+            verify_all();
+        }
+    }
+}
+
+/// Clears every double used on the current thread and returns a guard verifying
+/// the pending expectations and clearing the doubles again when dropped.
+///
+/// This is what the `#[fnmock::test]` attribute inserts at the start of a test.
+///
+/// # Usage
+///
+/// ```ignore
+/// #[test]
+/// fn test_welcome_user() {
+///     let _scope = fnmock::registry::test_scope();
+///
+///     send_email_mock::setup(|_| Ok(()));
+///     send_email_mock::expect_times(1);
+///
+///     welcome_user(42);
+///     // fails here if send_email wasn't called exactly once
+/// }
+/// ```
+#[must_use = "the expectations are verified and the doubles cleared when the scope is dropped"]
+pub fn test_scope() -> TestScope {
+    clear_all();
+    TestScope { _private: () }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::function_stub::FunctionStub;
+
+    thread_local! {
+        static FIRST: RefCell<FunctionStub<i32>> = {
+            register("first", first_snapshot, first_restore, first_clear, first_control);
+            RefCell::new(FunctionStub::new("first"))
+        };
+        static SECOND: RefCell<FunctionStub<i32>> = {
+            register("second", second_snapshot, second_restore, second_clear, second_control);
+            RefCell::new(FunctionStub::new("second"))
+        };
+    }
+
+    fn first_snapshot() -> StateSnapshot {
+        FIRST.with(|stub| StateSnapshot::new("first", stub.borrow().clone()))
+    }
+
+    fn first_restore(snapshot: StateSnapshot) {
+        FIRST.with(|stub| *stub.borrow_mut() = snapshot.into_state("first"))
+    }
+
+    fn first_clear() {
+        FIRST.with(|stub| stub.borrow_mut().clear())
+    }
+
+    fn first_control(f: &mut dyn FnMut(&dyn MockControl)) {
+        FIRST.with(|stub| f(&*stub.borrow()))
+    }
+
+    fn second_snapshot() -> StateSnapshot {
+        SECOND.with(|stub| StateSnapshot::new("second", stub.borrow().clone()))
+    }
+
+    fn second_restore(snapshot: StateSnapshot) {
+        SECOND.with(|stub| *stub.borrow_mut() = snapshot.into_state("second"))
+    }
+
+    fn second_clear() {
+        SECOND.with(|stub| stub.borrow_mut().clear())
+    }
+
+    fn second_control(f: &mut dyn FnMut(&dyn MockControl)) {
+        SECOND.with(|stub| f(&*stub.borrow()))
+    }
+
+    #[test]
+    fn test_register_adds_double_on_first_use() {
+        assert_eq!(registered_doubles().len(), 0);
+
+        FIRST.with(|stub| stub.borrow_mut().setup(1));
+        FIRST.with(|stub| stub.borrow_mut().setup(2));
+
+        assert_eq!(registered_doubles().len(), 1);
+    }
+
+    #[test]
+    fn test_handles_control_registered_doubles() {
+        FIRST.with(|stub| stub.borrow_mut().setup(1));
+
+        let mut handles = doubles();
+        assert_eq!(handles.len(), 1);
+        assert_eq!(handles[0].name(), "first");
+        assert_eq!(handles[0].times_called(), None);
+        handles[0].verify();
+
+        handles[0].clear();
+        assert!(!FIRST.with(|stub| stub.borrow().is_set()));
+    }
+
+    #[test]
+    fn test_restore_all_restores_registered_doubles() {
+        FIRST.with(|stub| stub.borrow_mut().setup(1));
+        let snapshot = snapshot_all();
+
+        FIRST.with(|stub| stub.borrow_mut().setup(2));
+        restore_all(snapshot);
+
+        assert_eq!(FIRST.with(|stub| stub.borrow_mut().get_return_value()), 1);
+    }
+
+    #[test]
+    fn test_restore_all_clears_doubles_registered_after_snapshot() {
+        FIRST.with(|stub| stub.borrow_mut().setup(1));
+        let snapshot = snapshot_all();
+
+        SECOND.with(|stub| stub.borrow_mut().setup(2));
+        restore_all(snapshot);
+
+        assert_eq!(FIRST.with(|stub| stub.borrow_mut().get_return_value()), 1);
+        assert!(!SECOND.with(|stub| stub.borrow().is_set()));
+    }
+
+    #[test]
+    fn test_clear_all_clears_registered_doubles() {
+        FIRST.with(|stub| stub.borrow_mut().setup(1));
+        SECOND.with(|stub| stub.borrow_mut().setup(2));
+
+        clear_all();
+
+        assert!(!FIRST.with(|stub| stub.borrow().is_set()));
+        assert!(!SECOND.with(|stub| stub.borrow().is_set()));
+    }
+
+    #[test]
+    fn test_reset_doubles_clears_on_creation_and_drop() {
+        FIRST.with(|stub| stub.borrow_mut().setup(1));
+
+        {
+            let _guard = reset_doubles();
+            assert!(!FIRST.with(|stub| stub.borrow().is_set()));
+
+            FIRST.with(|stub| stub.borrow_mut().setup(2));
+        }
+
+        assert!(!FIRST.with(|stub| stub.borrow().is_set()));
+    }
+
+    thread_local! {
+        static VERIFIED: std::cell::Cell<u32> = const { std::cell::Cell::new(0) };
+    }
+
+    fn verify() {
+        VERIFIED.with(|verified| verified.set(verified.get() + 1));
+    }
+
+    #[test]
+    fn test_verify_all_runs_pending_verifications_once() {
+        register_verification(verify);
+        verify_all();
+        verify_all();
+
+        assert_eq!(VERIFIED.with(|verified| verified.get()), 1);
+    }
+
+    #[test]
+    fn test_clear_all_discards_pending_verifications() {
+        register_verification(verify);
+        clear_all();
+        verify_all();
+
+        assert_eq!(VERIFIED.with(|verified| verified.get()), 0);
+    }
+
+    #[test]
+    fn test_test_scope_verifies_and_clears_on_drop() {
+        FIRST.with(|stub| stub.borrow_mut().setup(1));
+
+        {
+            let _scope = test_scope();
+            assert!(!FIRST.with(|stub| stub.borrow().is_set()));
+
+            FIRST.with(|stub| stub.borrow_mut().setup(2));
+            register_verification(verify);
+        }
+
+        assert_eq!(VERIFIED.with(|verified| verified.get()), 1);
+        assert!(!FIRST.with(|stub| stub.borrow().is_set()));
+    }
+
+    #[test]
+    fn test_test_scope_skips_verification_on_panic() {
+        let result = std::panic::catch_unwind(|| {
+            let _scope = test_scope();
+            FIRST.with(|stub| stub.borrow_mut().setup(2));
+            register_verification(verify);
+            panic!("test failed");
+        });
+
+        assert!(result.is_err());
+        assert_eq!(VERIFIED.with(|verified| verified.get()), 0);
+        assert!(!FIRST.with(|stub| stub.borrow().is_set()));
+    }
+}
@@ -0,0 +1,208 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::failure::report_assertion;
+
+/// Index of the next call of any mock, shared by all mocks on all threads
+static NEXT_CALL_INDEX: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the index of a new call, which is greater than the indices of all previous calls of all mocks.
+pub(crate) fn next_call_index() -> u64 {
+    NEXT_CALL_INDEX.fetch_add(1, Ordering::SeqCst)
+}
+
+/// Asserts that the mocks were first called in the given order, used by [`assert_call_order!`](crate::assert_call_order).
+///
+/// # Arguments
+///
+/// * `mocks` - the names of the mocks with the index of their first call (see `first_call_index`), in the expected order
+pub fn assert_first_call_order(mocks: &[(&str, Option<u64>)]) {
+    let names: Vec<&str> = mocks.iter().map(|(name, _)| *name).collect();
+    let not_called: Vec<&str> = mocks.iter()
+        .filter(|(_, index)| index.is_none())
+        .map(|(name, _)| *name)
+        .collect();
+    if !not_called.is_empty() {
+        report_assertion(not_called[0], format!(
+            "Expected {:?} to be called in this order, but {:?} weren't called", names, not_called));
+    }
+
+    let mut received = mocks.to_vec();
+    received.sort_by_key(|(_, index)| *index);
+    let received: Vec<&str> = received.iter().map(|(name, _)| *name).collect();
+    if received != names {
+        report_assertion(names[0], format!(
+            "Expected the first calls in order {:?}, received {:?}", names, received));
+    }
+}
+
+/// Asserts that the mocks were first called in the given order.
+///
+/// Unlike a [`Sequence`], the mocks don't have to join anything before the calls: every mock remembers the index
+/// of its first call, which increases monotonically across all mocks and threads. Only the first call of each mock
+/// is compared, so later calls (e.g. retries) don't affect the assertion.
+///
+/// # Usage
+///
+/// ```ignore
+/// welcome_user(42);
+///
+/// fnmock::assert_call_order!([fetch_user_mock, send_email_mock]);
+/// fnmock::assert_call_order!([db::fetch_user_mock, mail::send_email_mock]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if a mock wasn't called, or the first calls happened in a different order
+#[macro_export]
+macro_rules! assert_call_order {
+    ([$($($mock:ident)::+),+ $(,)?]) => {
+        $crate::sequence::assert_first_call_order(&[
+            $((stringify!($($mock)::+), $($mock)::+::first_call_index())),+
+        ])
+    };
+}
+
+/// Shared call log of several mocks, used to verify the order of calls across mocks
+///
+/// Mocks join a sequence with the `in_sequence(&sequence)` proxy function of their generated module.
+/// Every call of a joined mock is logged with the name of the mock, so the order of the calls of
+/// different mocks can be asserted. Clones of a sequence share the same log.
+///
+/// # Usage
+///
+/// ```ignore
+/// let sequence = fnmock::sequence::Sequence::new();
+/// fetch_user_mock::in_sequence(&sequence);
+/// send_email_mock::in_sequence(&sequence);
+///
+/// welcome_user(42);
+///
+/// sequence.assert_called_before("fetch_user_mock", "send_email_mock");
+/// sequence.assert_order(&["fetch_user_mock", "send_email_mock"]);
+/// ```
+///
+/// # Fields
+///
+/// - `calls` - the names of the mocks in the order of their calls
+#[derive(Clone, Default)]
+pub struct Sequence {
+    calls: Rc<RefCell<Vec<String>>>,
+}
+
+impl Sequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Logs a call of the mock named `double_name`.
+    pub(crate) fn record(&self, double_name: &str) {
+        self.calls.borrow_mut().push(double_name.to_string());
+    }
+
+    /// Returns the names of the mocks in the order of their calls.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.borrow().clone()
+    }
+
+    /// Asserts that the first call of `first` happened before the first call of `second`.
+    pub fn assert_called_before(&self, first: &str, second: &str) {
+        let calls = self.calls.borrow();
+        let first_position = calls.iter().position(|name| name == first);
+        let second_position = calls.iter().position(|name| name == second);
+
+        match (first_position, second_position) {
+            (Some(first_position), Some(second_position)) if first_position < second_position => {}
+            (Some(_), Some(_)) => report_assertion(second, format!(
+                "Expected {} to be called before {}, received {:?}", first, second, calls)),
+            _ => report_assertion(second, format!(
+                "Expected {} to be called before {}, but not both were called, received {:?}", first, second, calls)),
+        }
+    }
+
+    /// Asserts that the joined mocks were called exactly in the expected order.
+    pub fn assert_order(&self, expected: &[&str]) {
+        let calls = self.calls.borrow();
+        if !calls.iter().map(String::as_str).eq(expected.iter().copied()) {
+            report_assertion("sequence", format!("Expected calls in order {:?}, received {:?}", expected, calls));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_calls() {
+        let sequence = Sequence::new();
+        sequence.clone().record("fetch_user_mock");
+        sequence.record("send_email_mock");
+
+        assert_eq!(sequence.calls(), vec!["fetch_user_mock", "send_email_mock"]);
+    }
+
+    #[test]
+    fn test_assert_called_before_passes_in_order() {
+        let sequence = Sequence::new();
+        sequence.record("fetch_user_mock");
+        sequence.record("send_email_mock");
+        sequence.record("fetch_user_mock");
+
+        sequence.assert_called_before("fetch_user_mock", "send_email_mock");
+        sequence.assert_order(&["fetch_user_mock", "send_email_mock", "fetch_user_mock"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected fetch_user_mock to be called before send_email_mock, received [\"send_email_mock\", \"fetch_user_mock\"]")]
+    fn test_assert_called_before_fails_out_of_order() {
+        let sequence = Sequence::new();
+        sequence.record("send_email_mock");
+        sequence.record("fetch_user_mock");
+
+        sequence.assert_called_before("fetch_user_mock", "send_email_mock");
+    }
+
+    #[test]
+    #[should_panic(expected = "but not both were called")]
+    fn test_assert_called_before_fails_without_calls() {
+        let sequence = Sequence::new();
+        sequence.record("fetch_user_mock");
+
+        sequence.assert_called_before("fetch_user_mock", "send_email_mock");
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected calls in order [\"send_email_mock\"], received [\"fetch_user_mock\"]")]
+    fn test_assert_order_fails_for_other_calls() {
+        let sequence = Sequence::new();
+        sequence.record("fetch_user_mock");
+
+        sequence.assert_order(&["send_email_mock"]);
+    }
+
+    #[test]
+    fn test_call_indices_increase() {
+        let first = next_call_index();
+        let second = next_call_index();
+
+        assert!(first < second);
+    }
+
+    #[test]
+    fn test_assert_first_call_order_passes_in_order() {
+        assert_first_call_order(&[("fetch_user_mock", Some(3)), ("send_email_mock", Some(8))]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected the first calls in order [\"fetch_user_mock\", \"send_email_mock\"], received [\"send_email_mock\", \"fetch_user_mock\"]")]
+    fn test_assert_first_call_order_fails_out_of_order() {
+        assert_first_call_order(&[("fetch_user_mock", Some(8)), ("send_email_mock", Some(3))]);
+    }
+
+    #[test]
+    #[should_panic(expected = "but [\"send_email_mock\"] weren't called")]
+    fn test_assert_first_call_order_fails_without_call() {
+        assert_first_call_order(&[("fetch_user_mock", Some(3)), ("send_email_mock", None)]);
+    }
+}
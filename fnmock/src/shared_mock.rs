@@ -0,0 +1,360 @@
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, ThreadId};
+use crate::failure::{describe_calls, report_assertion, report_missing_setup};
+use crate::sequence::next_call_index;
+use crate::function_mock::format_calls;
+
+/// Mock implementation, which can be called from any thread
+type SharedImplementation<Params, Result> = Arc<Mutex<dyn FnMut(Params) -> Result + Send>>;
+
+/// State of a shared mock, protected by the mutex of the [`SharedFunctionMock`]
+///
+/// # Fields
+///
+/// - `implementation` - the mock function or closure with the params in a tuple or None
+/// - `calls` - vector to hold the calls to the mock from all threads, in the order they happened
+/// - `first_call_index` - the index of the first call in the calls of all mocks (see `fnmock::assert_call_order!`)
+/// - `running_on` - the thread running the implementation, to detect calls re-entering it
+struct SharedMockState<Params, Result> {
+    implementation: Option<SharedImplementation<Params, Result>>,
+    calls: Vec<Params>,
+    first_call_index: Option<u64>,
+    running_on: Option<ThreadId>,
+}
+
+impl<Params, Result> SharedMockState<Params, Result> {
+    /// Removes the implementation and the recorded calls.
+    fn reset(&mut self) {
+        self.implementation = None;
+        self.calls = Vec::new();
+        self.first_call_index = None;
+        self.running_on = None;
+    }
+
+    /// Whether a call panicked while holding the lock of the implementation.
+    fn is_poisoned(&self) -> bool {
+        self.implementation.as_ref().is_some_and(|implementation| implementation.is_poisoned())
+    }
+}
+
+/// Struct containing the Data for mocking a Function across threads
+///
+/// Used by `#[mock_function(shared)]` instead of the thread local [`FunctionMock`](crate::function_mock::FunctionMock).
+/// The mock lives in a static, so code under test spawning worker threads calls the mock set up by the test,
+/// and the recorded calls can be asserted from any thread.
+///
+/// Since the mock is shared by all threads, tests using the same shared mock mustn't run in parallel.
+/// The implementation runs without holding the lock of the mock, so it may inspect the mock
+/// and calls from other threads are recorded meanwhile. Calls of the implementation are serialized,
+/// so an implementation calling the mocked function again on the same thread panics instead of deadlocking.
+///
+/// If the implementation panics (or a panic poisons the lock of the mock), the mock is reset the next time
+/// it is used, so the failing test can't wedge the tests running after it.
+///
+/// # Generics
+///
+/// - `Params: Clone + PartialEq + Debug + Send + 'static` - the parameters of the mocked function as a tuple
+/// - `Result` - the result of the function
+///
+/// # Usage
+///
+/// Normally you don't need to interact with the SharedFunctionMock.
+/// The generated module stores it in a static and provides proxy functions for it:
+///
+/// ```ignore
+/// pub(crate) mod send_email_mock {
+///     static MOCK: SharedFunctionMock<(String, String), Result<(), String>> = SharedFunctionMock::new("send_email");
+///
+///     pub(crate) fn setup(new_f: impl FnMut((String, String)) -> Result<(), String> + Send + 'static) {
+///         MOCK.setup(new_f)
+///     }
+///     // ...
+///     // the same for all other mock functions
+/// }
+/// ```
+///
+/// # Fields
+///
+/// - `name` - the name of the function for display purposes when asserting
+/// - `state` - the implementation and the recorded calls
+pub struct SharedFunctionMock<Params, Result> {
+    name: &'static str,
+    state: Mutex<SharedMockState<Params, Result>>,
+}
+
+impl<Params, Result> SharedFunctionMock<Params, Result>
+where
+    Params: Clone + PartialEq + Debug + Send + 'static,
+{
+    pub const fn new(function_name: &'static str) -> Self {
+        Self {
+            name: function_name,
+            state: Mutex::new(SharedMockState {
+                implementation: None,
+                calls: Vec::new(),
+                first_call_index: None,
+                running_on: None,
+            }),
+        }
+    }
+
+    /// Locks the state, resetting it if a panic poisoned the lock of the state or of the implementation.
+    ///
+    /// The state of a panicked call can't be trusted, and a failed test on one thread mustn't break the other tests.
+    fn state(&self) -> MutexGuard<'_, SharedMockState<Params, Result>> {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| {
+            self.state.clear_poison();
+            let mut state = poisoned.into_inner();
+            state.reset();
+            state
+        });
+        if state.is_poisoned() {
+            state.reset();
+        }
+        state
+    }
+
+    // --- Mocking ---
+
+    /// Sets up the implementation, which may be called from any thread.
+    pub fn setup(&self, new_f: impl FnMut(Params) -> Result + Send + 'static) {
+        self.state().implementation = Some(Arc::new(Mutex::new(new_f)));
+    }
+
+    /// Removes the implementation and the recorded calls.
+    pub fn clear(&self) {
+        self.state().reset();
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.state().implementation.is_some()
+    }
+
+    /// Records the call and calls the implementation.
+    ///
+    /// The lock of the mock is released before the implementation runs,
+    /// so calls from other threads aren't blocked by a slow implementation.
+    ///
+    /// # Panics
+    ///
+    /// If the implementation calls the mocked function again on the same thread,
+    /// since waiting for the running implementation would deadlock.
+    pub fn call(&self, params: Params) -> Result {
+        let current_thread = thread::current().id();
+        let implementation = {
+            let mut state = self.state();
+            if state.running_on == Some(current_thread) {
+                drop(state);
+                panic!("{} mock implementation can't be re-entered by its own call on the same thread", self.name);
+            }
+            // Calls failing because of the missing setup aren't recorded, like for thread local mocks
+            let Some(implementation) = state.implementation.clone() else {
+                drop(state);
+                report_missing_setup(self.name, format!("{} mock not initialized", self.name));
+            };
+            state.calls.push(params.clone());
+            state.first_call_index.get_or_insert_with(next_call_index);
+            implementation
+        };
+
+        let mut implementation = implementation.lock().unwrap_or_else(PoisonError::into_inner);
+        self.state().running_on = Some(current_thread);
+        let _running = RunningImplementation { mock: self };
+        implementation(params)
+    }
+
+    // --- Inspect ---
+
+    /// Returns the number of calls from all threads.
+    pub fn times(&self) -> u32 {
+        self.state().calls.len() as u32
+    }
+
+    /// Returns the params of all calls from all threads, in the order they happened.
+    pub fn calls(&self) -> Vec<Params> {
+        self.state().calls.clone()
+    }
+
+    /// Returns the params of all calls from any thread formatted one call per line, e.g. for snapshot tests.
+    pub fn formatted_calls(&self) -> String {
+        format_calls(self.name, &self.state().calls)
+    }
+
+    /// Returns the index of the first call among the calls of all mocks, or None if the mock wasn't called.
+    pub fn first_call_index(&self) -> Option<u64> {
+        self.state().first_call_index
+    }
+
+    /// Returns the params of the most recent call from any thread, or None if the mock wasn't called.
+    pub fn last_call_params(&self) -> Option<Params> {
+        self.state().calls.last().cloned()
+    }
+
+    // --- Assert ---
+
+    pub fn assert_times(&self, expected_num_of_calls: u32) {
+        let times = self.times();
+        if times != expected_num_of_calls {
+            report_assertion(self.name, format!("Expected {} mock to be called {} times, received {}",
+                                                self.name, expected_num_of_calls, times));
+        }
+    }
+
+    pub fn assert_with(&self, params: Params) {
+        let calls = self.calls();
+        if !calls.contains(&params) {
+            report_assertion(self.name, format!("Expected {} mock to be called with {:?}{}",
+                                                self.name, params, describe_calls(&calls)));
+        }
+    }
+}
+
+/// Marks the implementation of a shared mock as finished when dropped, even if it panicked
+struct RunningImplementation<'a, Params, Result>
+where
+    Params: Clone + PartialEq + Debug + Send + 'static,
+{
+    mock: &'a SharedFunctionMock<Params, Result>,
+}
+
+impl<Params, Result> Drop for RunningImplementation<'_, Params, Result>
+where
+    Params: Clone + PartialEq + Debug + Send + 'static,
+{
+    fn drop(&mut self) {
+        self.mock.state().running_on = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_calls_from_other_threads_use_the_implementation() {
+        let mock: Arc<SharedFunctionMock<u32, u32>> = Arc::new(SharedFunctionMock::new("double"));
+        mock.setup(|value| value * 2);
+
+        let handles: Vec<_> = (0..4)
+            .map(|value| {
+                let mock = mock.clone();
+                thread::spawn(move || mock.call(value))
+            })
+            .collect();
+        let mut results: Vec<u32> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        results.sort();
+
+        assert_eq!(results, vec![0, 2, 4, 6]);
+        mock.assert_times(4);
+        mock.assert_with(3);
+    }
+
+    #[test]
+    fn test_implementation_can_inspect_the_mock() {
+        static MOCK: SharedFunctionMock<u32, u32> = SharedFunctionMock::new("count");
+        MOCK.setup(|_| MOCK.times());
+
+        assert_eq!(MOCK.call(7), 1);
+        assert_eq!(MOCK.call(7), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "factorial mock implementation can't be re-entered by its own call on the same thread")]
+    fn test_reentrant_call_panics_instead_of_deadlocking() {
+        static MOCK: SharedFunctionMock<u32, u32> = SharedFunctionMock::new("factorial");
+        MOCK.setup(|value| if value == 0 { 1 } else { value * MOCK.call(value - 1) });
+
+        MOCK.call(3);
+    }
+
+    #[test]
+    fn test_reentrant_call_resets_the_mock() {
+        let mock: Arc<SharedFunctionMock<u32, u32>> = Arc::new(SharedFunctionMock::new("factorial"));
+        let inner_mock = mock.clone();
+        mock.setup(move |value| if value == 0 { 1 } else { value * inner_mock.call(value - 1) });
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| mock.call(3)));
+
+        assert!(result.is_err());
+        assert!(!mock.is_set());
+        mock.setup(|value| value * 2);
+        assert_eq!(mock.call(2), 4);
+    }
+
+    #[test]
+    fn test_clear_removes_implementation_and_calls() {
+        let mock: SharedFunctionMock<u32, u32> = SharedFunctionMock::new("double");
+        mock.setup(|value| value * 2);
+        mock.call(1);
+
+        mock.clear();
+
+        assert!(!mock.is_set());
+        assert_eq!(mock.calls(), Vec::<u32>::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected double mock to be called 2 times, received 1")]
+    fn test_assert_times_fails_with_wrong_count() {
+        let mock: SharedFunctionMock<u32, u32> = SharedFunctionMock::new("double");
+        mock.setup(|value| value * 2);
+        mock.call(1);
+
+        mock.assert_times(2);
+    }
+
+    #[test]
+    fn test_panicking_implementation_resets_the_mock() {
+        let mock: SharedFunctionMock<u32, u32> = SharedFunctionMock::new("double");
+        mock.setup(|value| if value == 0 { panic!("zero") } else { value * 2 });
+        mock.call(1);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| mock.call(0)));
+
+        assert!(result.is_err());
+        assert!(!mock.is_set());
+        mock.assert_times(0);
+
+        mock.setup(|value| value * 3);
+        assert_eq!(mock.call(2), 6);
+        mock.assert_times(1);
+    }
+
+    #[test]
+    fn test_poisoned_state_is_reset() {
+        let mock: Arc<SharedFunctionMock<u32, u32>> = Arc::new(SharedFunctionMock::new("double"));
+        mock.setup(|value| value * 2);
+        mock.call(1);
+
+        let poisoning_mock = mock.clone();
+        let result = thread::spawn(move || {
+            let _state = poisoning_mock.state();
+            panic!("failed while holding the lock");
+        }).join();
+
+        assert!(result.is_err());
+        assert!(!mock.is_set());
+        mock.assert_times(0);
+        assert!(!mock.state.is_poisoned());
+    }
+
+    #[test]
+    fn test_call_without_setup_isnt_recorded() {
+        let mock: SharedFunctionMock<u32, u32> = SharedFunctionMock::new("double");
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| mock.call(1)));
+
+        assert!(result.is_err());
+        mock.assert_times(0);
+        assert_eq!(mock.first_call_index(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "double mock not initialized")]
+    fn test_call_without_setup_fails() {
+        let mock: SharedFunctionMock<u32, u32> = SharedFunctionMock::new("double");
+        mock.call(1);
+    }
+}
@@ -0,0 +1,90 @@
+use std::any::Any;
+
+/// Saved state of a double, created by the generated `snapshot()` proxy function
+///
+/// The state is type erased, so snapshots of all doubles share a type and can be stored together
+/// (see `fnmock::registry::snapshot_all`). A snapshot can only be restored to the double it was taken from.
+///
+/// # Usage
+///
+/// Normally you don't need to interact with the StateSnapshot directly.
+/// The generated modules create and consume it in their `snapshot()` and `restore(snapshot)` proxy functions.
+///
+/// ```ignore
+/// fn with_offline_user<R>(test: impl FnOnce() -> R) -> R {
+///     let snapshot = fetch_user_mock::snapshot();
+///     fetch_user_mock::setup(|_| Err("offline".to_string()));
+///
+///     let result = test();
+///
+///     fetch_user_mock::restore(snapshot);
+///     result
+/// }
+/// ```
+///
+/// # Fields
+///
+/// - `name` - the name of the double the snapshot was taken from, for display purposes when panicking
+/// - `state` - a copy of the stored double
+pub struct StateSnapshot {
+    name: String,
+    state: Box<dyn Any>,
+}
+
+impl StateSnapshot {
+    pub fn new<State>(double_name: &str, state: State) -> Self
+    where
+        State: 'static,
+    {
+        Self {
+            name: double_name.to_string(),
+            state: Box::new(state),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the saved state, panicking if the snapshot was taken from a different double.
+    pub fn into_state<State>(self, double_name: &str) -> State
+    where
+        State: 'static,
+    {
+        match self.state.downcast::<State>() {
+            Ok(state) => *state,
+            Err(_) => panic!("Snapshot of {} can't be restored to {}", self.name, double_name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::function_stub::FunctionStub;
+
+    #[test]
+    fn test_new_stores_name() {
+        let snapshot = StateSnapshot::new("get_value", FunctionStub::<i32>::new("get_value"));
+        assert_eq!(snapshot.name(), "get_value");
+    }
+
+    #[test]
+    fn test_into_state_returns_saved_state() {
+        let mut stub: FunctionStub<i32> = FunctionStub::new("get_value");
+        stub.setup(42);
+
+        let snapshot = StateSnapshot::new("get_value", stub.clone());
+        stub.clear();
+
+        let mut restored: FunctionStub<i32> = snapshot.into_state("get_value");
+        assert_eq!(restored.get_return_value(), 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "Snapshot of get_value can't be restored to get_name")]
+    fn test_into_state_panics_for_other_double() {
+        let snapshot = StateSnapshot::new("get_value", FunctionStub::<i32>::new("get_value"));
+        let _: FunctionStub<String> = snapshot.into_state("get_name");
+    }
+}
@@ -0,0 +1,210 @@
+use std::cell::RefCell;
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::thread::LocalKey;
+
+/// Policy deciding where the generated module stores its double and from where it can be reached
+///
+/// The doubles themselves (`FunctionStub`, `SharedFunctionMock`, ...) don't know where they are stored.
+/// The generated module declares a static slot of the backend selected with `storage = ...`
+/// and accesses the double only through the backend, so the proxy functions are the same for every backend.
+///
+/// - [`ThreadLocal`] (default) - every thread has its own double, so parallel tests don't interfere
+/// - [`Global`] - one double shared by all threads, e.g. for code under test spawning worker threads
+/// - [`TaskLocal`] - every tokio task running inside its own `scope()` has its own double
+///   (requires the `task-local` feature)
+///
+/// # Usage
+///
+/// Normally you don't need to interact with the backends.
+/// The generated module declares the slot and provides proxy functions for it:
+///
+/// ```ignore
+/// pub(crate) mod get_config_stub {
+///     static STUB: LazyLock<Mutex<FunctionStub<String>>> =
+///         LazyLock::new(|| Mutex::new(FunctionStub::new("get_config_stub")));
+///
+///     pub(crate) fn setup(return_value: String) {
+///         <fnmock::storage::Global as fnmock::storage::Storage>::with(&STUB, "get_config_stub", |stub| {
+///             stub.setup(return_value)
+///         })
+///     }
+///     // ...
+///     // the same for all other stub functions
+/// }
+/// ```
+pub trait Storage {
+    /// The type of the static holding a double of type `T`
+    type Slot<T: 'static>: 'static;
+
+    /// Runs `f` with the double stored in the slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the double can't be reached from here (see [`Storage::try_with`])
+    fn with<T: 'static, R>(slot: &'static Self::Slot<T>, name: &str, f: impl FnOnce(&mut T) -> R) -> R;
+
+    /// Runs `f` with the double stored in the slot, or returns `None` if the double can't be reached from here,
+    /// e.g. a task local double outside of a `scope()`.
+    fn try_with<T: 'static, R>(slot: &'static Self::Slot<T>, f: impl FnOnce(&mut T) -> R) -> Option<R>;
+}
+
+/// Stores the double in a `thread_local!`, so every thread (and therefore every test) has its own double
+pub struct ThreadLocal;
+
+impl Storage for ThreadLocal {
+    type Slot<T: 'static> = LocalKey<RefCell<T>>;
+
+    fn with<T: 'static, R>(slot: &'static Self::Slot<T>, _name: &str, f: impl FnOnce(&mut T) -> R) -> R {
+        slot.with(|double| f(&mut double.borrow_mut()))
+    }
+
+    fn try_with<T: 'static, R>(slot: &'static Self::Slot<T>, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        slot.try_with(|double| f(&mut double.borrow_mut())).ok()
+    }
+}
+
+/// Stores the double in a static behind a mutex, so all threads share the same double
+///
+/// Since the double is shared, tests using the same global double mustn't run in parallel.
+/// Global doubles aren't registered with `fnmock::registry`, which only tracks the doubles of the current thread.
+pub struct Global;
+
+impl Storage for Global {
+    type Slot<T: 'static> = LazyLock<Mutex<T>>;
+
+    /// Locks the double, ignoring poisoning, since a failed assertion on one thread mustn't break the other tests.
+    fn with<T: 'static, R>(slot: &'static Self::Slot<T>, _name: &str, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut slot.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn try_with<T: 'static, R>(slot: &'static Self::Slot<T>, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        Some(f(&mut slot.lock().unwrap_or_else(PoisonError::into_inner)))
+    }
+}
+
+/// Stores the double in a tokio task local, so every task running inside its own `scope()` has its own double
+///
+/// Unlike thread local doubles, the double belongs to the task, so it still applies when a multi-threaded
+/// runtime resumes the task on another thread. `propagate()` passes the double on to spawned tasks.
+#[cfg(feature = "task-local")]
+pub struct TaskLocal;
+
+#[cfg(feature = "task-local")]
+impl TaskLocal {
+    /// Runs the future with a new double, which is only visible to the future.
+    pub fn scope<T: 'static, F: std::future::Future>(
+        slot: &'static <Self as Storage>::Slot<T>,
+        double: T,
+        future: F,
+    ) -> impl std::future::Future<Output = F::Output> {
+        slot.scope(std::sync::Arc::new(Mutex::new(double)), future)
+    }
+
+    /// Runs the future with the double of the current task, e.g. to pass it on to a spawned task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a `scope()`
+    pub fn propagate<T: 'static, F: std::future::Future>(
+        slot: &'static <Self as Storage>::Slot<T>,
+        name: &str,
+        future: F,
+    ) -> impl std::future::Future<Output = F::Output> {
+        let double = slot.try_with(std::sync::Arc::clone).unwrap_or_else(|_| outside_of_scope(name));
+        slot.scope(double, future)
+    }
+}
+
+#[cfg(feature = "task-local")]
+impl Storage for TaskLocal {
+    type Slot<T: 'static> = tokio::task::LocalKey<std::sync::Arc<Mutex<T>>>;
+
+    fn with<T: 'static, R>(slot: &'static Self::Slot<T>, name: &str, f: impl FnOnce(&mut T) -> R) -> R {
+        Self::try_with(slot, f).unwrap_or_else(|| outside_of_scope(name))
+    }
+
+    fn try_with<T: 'static, R>(slot: &'static Self::Slot<T>, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        slot.try_with(|double| f(&mut double.lock().unwrap_or_else(PoisonError::into_inner))).ok()
+    }
+}
+
+#[cfg(feature = "task-local")]
+fn outside_of_scope(name: &str) -> ! {
+    panic!("{} is stored task local and can only be used inside `{}::scope()` or `{}::propagate()`", name, name, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    thread_local! {
+        static THREAD_LOCAL: RefCell<u32> = const { RefCell::new(0) };
+    }
+
+    static GLOBAL: LazyLock<Mutex<u32>> = LazyLock::new(|| Mutex::new(0));
+
+    #[test]
+    fn test_thread_local_double_is_not_shared_with_other_threads() {
+        ThreadLocal::with(&THREAD_LOCAL, "counter", |counter| *counter = 5);
+
+        let other_thread = thread::spawn(|| ThreadLocal::with(&THREAD_LOCAL, "counter", |counter| *counter))
+            .join()
+            .unwrap();
+
+        assert_eq!(other_thread, 0);
+        assert_eq!(ThreadLocal::try_with(&THREAD_LOCAL, |counter| *counter), Some(5));
+    }
+
+    #[test]
+    fn test_global_double_is_shared_with_other_threads() {
+        Global::with(&GLOBAL, "counter", |counter| *counter = 7);
+
+        let other_thread = thread::spawn(|| Global::with(&GLOBAL, "counter", |counter| *counter))
+            .join()
+            .unwrap();
+
+        assert_eq!(other_thread, 7);
+    }
+
+    #[cfg(feature = "task-local")]
+    mod task_local {
+        use super::*;
+
+        tokio::task_local! {
+            static TASK_LOCAL: std::sync::Arc<Mutex<u32>>;
+        }
+
+        fn block_on<F: std::future::Future>(future: F) -> F::Output {
+            tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(future)
+        }
+
+        #[test]
+        fn test_scopes_get_their_own_double() {
+            block_on(TaskLocal::scope(&TASK_LOCAL, 1, async {
+                TaskLocal::with(&TASK_LOCAL, "counter", |counter| *counter += 1);
+
+                let inner = TaskLocal::scope(&TASK_LOCAL, 10, async {
+                    TaskLocal::with(&TASK_LOCAL, "counter", |counter| *counter)
+                }).await;
+                let propagated = TaskLocal::propagate(&TASK_LOCAL, "counter", async {
+                    TaskLocal::with(&TASK_LOCAL, "counter", |counter| *counter)
+                }).await;
+
+                assert_eq!(inner, 10);
+                assert_eq!(propagated, 2);
+            }));
+        }
+
+        #[test]
+        fn test_try_with_is_none_outside_of_scope() {
+            assert_eq!(TaskLocal::try_with(&TASK_LOCAL, |counter| *counter), None);
+        }
+
+        #[test]
+        #[should_panic(expected = "counter is stored task local")]
+        fn test_with_fails_outside_of_scope() {
+            TaskLocal::with(&TASK_LOCAL, "counter", |counter| *counter);
+        }
+    }
+}
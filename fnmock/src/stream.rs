@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pub use futures_core::Stream;
+
+/// A boxed stream as returned by fakes of functions returning `impl Stream<Item = Item>`
+///
+/// Like [`LocalBoxFuture`](crate::future::LocalBoxFuture), the stream is not `Send`,
+/// so it works with every executor polling on the thread the fake was set up on.
+///
+/// # Usage
+///
+/// ```ignore
+/// read_events_fake::setup(|device| Box::pin(stub_stream(vec![device, device + 1])));
+/// ```
+pub type LocalBoxStream<Item> = Pin<Box<dyn Stream<Item = Item>>>;
+
+/// A boxed `Send` stream as returned by fakes of functions returning `impl Stream<Item = Item> + Send`
+pub type BoxStream<Item> = Pin<Box<dyn Stream<Item = Item> + Send>>;
+
+/// Stream yielding a fixed list of items, created by [`stub_stream`] and [`stub_stream_with_error`]
+///
+/// Stubs of functions returning `impl Stream<Item = Item>` return a `StubStream<Item>`,
+/// so the same stream can be returned by every call.
+///
+/// # Fields
+///
+/// - `items` - the items, which were not yielded yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct StubStream<Item> {
+    items: VecDeque<Item>,
+}
+
+impl<Item> Stream for StubStream<Item> {
+    type Item = Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Item>> {
+        Poll::Ready(self.items.pop_front())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.items.len(), Some(self.items.len()))
+    }
+}
+
+// The items are never pinned
+impl<Item> Unpin for StubStream<Item> {}
+
+/// Creates a stream yielding the items in order and ending afterward.
+///
+/// # Usage
+///
+/// ```ignore
+/// read_events_stub::setup(stub_stream(vec![1, 2, 3]));
+/// ```
+pub fn stub_stream<Item>(items: Vec<Item>) -> StubStream<Item> {
+    StubStream { items: items.into() }
+}
+
+/// Creates a stream yielding the items as `Ok`, followed by the terminal `error` as `Err`.
+///
+/// # Usage
+///
+/// ```ignore
+/// read_events_stub::setup(stub_stream_with_error(vec![1, 2], "connection lost".to_string()));
+/// ```
+pub fn stub_stream_with_error<T, E>(items: Vec<T>, error: E) -> StubStream<Result<T, E>> {
+    let mut items: VecDeque<Result<T, E>> = items.into_iter().map(Ok).collect();
+    items.push_back(Err(error));
+    StubStream { items }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Waker;
+
+    fn collect<S: Stream + Unpin>(mut stream: S) -> Vec<S::Item> {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut items = Vec::new();
+        while let Poll::Ready(Some(item)) = Pin::new(&mut stream).poll_next(&mut cx) {
+            items.push(item);
+        }
+        items
+    }
+
+    #[test]
+    fn test_stub_stream_yields_items_in_order() {
+        let stream = stub_stream(vec![1, 2, 3]);
+
+        assert_eq!(stream.size_hint(), (3, Some(3)));
+        assert_eq!(collect(stream), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_stub_stream_with_error_ends_with_error() {
+        let stream = stub_stream_with_error(vec![1, 2], "closed");
+
+        assert_eq!(collect(stream), vec![Ok(1), Ok(2), Err("closed")]);
+    }
+
+    #[test]
+    fn test_stub_stream_clones_are_independent() {
+        let stream = stub_stream(vec!["a", "b"]);
+        let clone = stream.clone();
+
+        assert_eq!(collect(stream), vec!["a", "b"]);
+        assert_eq!(collect(clone), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_boxed_stub_stream() {
+        let stream: LocalBoxStream<u8> = Box::pin(stub_stream(vec![7]));
+
+        assert_eq!(collect(stream), vec![7]);
+    }
+}
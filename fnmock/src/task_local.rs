@@ -0,0 +1,140 @@
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::Arc;
+use crate::shared_mock::SharedFunctionMock;
+
+// Used by the generated mock modules to declare the task local storage
+#[doc(hidden)]
+pub use tokio::task_local;
+
+/// Key of a mock stored in a tokio task local, declared by the generated mock module
+///
+/// Used by `#[mock_function(storage = task_local)]`. Unlike thread local mocks, the mock belongs
+/// to the task, so it still applies when a multi-threaded runtime resumes the task on another thread.
+/// Unlike shared mocks, every task running inside its own `scope()` gets its own mock,
+/// so tests using the same mock can run in parallel.
+///
+/// # Usage
+///
+/// Normally you don't need to interact with the key.
+/// The generated module declares it and provides proxy functions for it:
+///
+/// ```ignore
+/// pub(crate) mod fetch_price_mock {
+///     fnmock::task_local::task_local! {
+///         static MOCK: Arc<SharedFunctionMock<String, Result<u32, String>>>;
+///     }
+///
+///     pub(crate) fn scope<F: Future>(future: F) -> impl Future<Output = F::Output> {
+///         fnmock::task_local::scope(&MOCK, "fetch_price_mock", future)
+///     }
+///
+///     pub(crate) fn setup(new_f: impl FnMut(String) -> Result<u32, String> + Send + 'static) {
+///         fnmock::task_local::current(&MOCK, "fetch_price_mock").setup(new_f)
+///     }
+///     // ...
+///     // the same for all other mock functions
+/// }
+/// ```
+pub type TaskLocalMockKey<Params, Result> = tokio::task::LocalKey<Arc<SharedFunctionMock<Params, Result>>>;
+
+/// Runs the future with a new mock, which is only visible to the future.
+pub fn scope<Params, Result, F>(
+    key: &'static TaskLocalMockKey<Params, Result>,
+    name: &'static str,
+    future: F,
+) -> impl Future<Output = F::Output>
+where
+    Params: Clone + PartialEq + Debug + Send + 'static,
+    F: Future,
+{
+    key.scope(Arc::new(SharedFunctionMock::new(name)), future)
+}
+
+/// Runs the future with the mock of the current task, e.g. to pass it on to a spawned task.
+///
+/// # Panics
+///
+/// Panics if called outside of a `scope()`
+pub fn propagate<Params, Result, F>(
+    key: &'static TaskLocalMockKey<Params, Result>,
+    name: &'static str,
+    future: F,
+) -> impl Future<Output = F::Output>
+where
+    Params: Clone + PartialEq + Debug + Send + 'static,
+    F: Future,
+{
+    key.scope(current(key, name), future)
+}
+
+/// Returns the mock of the current task.
+///
+/// # Panics
+///
+/// Panics if called outside of a `scope()`
+pub fn current<Params, Result>(
+    key: &'static TaskLocalMockKey<Params, Result>,
+    name: &'static str,
+) -> Arc<SharedFunctionMock<Params, Result>> {
+    try_current(key).unwrap_or_else(|| {
+        panic!("{} is stored task local and can only be used inside `{}::scope()` or `{}::propagate()`", name, name, name)
+    })
+}
+
+/// Returns the mock of the current task, or `None` outside of a `scope()`.
+pub fn try_current<Params, Result>(
+    key: &'static TaskLocalMockKey<Params, Result>,
+) -> Option<Arc<SharedFunctionMock<Params, Result>>> {
+    key.try_with(Arc::clone).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    task_local! {
+        static MOCK: Arc<SharedFunctionMock<u32, u32>>;
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(future)
+    }
+
+    #[test]
+    fn test_scopes_get_their_own_mock() {
+        block_on(scope(&MOCK, "double", async {
+            current(&MOCK, "double").setup(|value| value * 2);
+            assert_eq!(current(&MOCK, "double").call(2), 4);
+
+            scope(&MOCK, "double", async {
+                assert!(!current(&MOCK, "double").is_set());
+            }).await;
+
+            current(&MOCK, "double").assert_times(1);
+        }));
+    }
+
+    #[test]
+    fn test_propagate_shares_the_mock() {
+        block_on(scope(&MOCK, "double", async {
+            current(&MOCK, "double").setup(|value| value * 2);
+
+            let result = propagate(&MOCK, "double", async { current(&MOCK, "double").call(3) }).await;
+
+            assert_eq!(result, 6);
+            current(&MOCK, "double").assert_with(3);
+        }));
+    }
+
+    #[test]
+    fn test_try_current_is_none_outside_of_scope() {
+        assert!(try_current(&MOCK).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "double is stored task local")]
+    fn test_current_fails_outside_of_scope() {
+        current(&MOCK, "double");
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+/// Measures the wall-clock duration of a real invocation of a doubled function
+///
+/// Mocks falling back to the original implementation (`spy()` / `fallback_to_original()`) run the original
+/// statements inline, so `return` and `?` keep working. The generated function starts a timer before them,
+/// which records the elapsed time when it's dropped, i.e. when the original implementation returns or panics.
+/// The durations are available with the `call_durations()` proxy function of the mock module.
+///
+/// # Usage
+///
+/// ```ignore
+/// pub(crate) fn load_config(path: String) -> Config {
+///     let _fnmock_call_timer = if load_config_mock::is_set() {
+///         // ... returns the result of the mock implementation if it handles the call
+///         Some(fnmock::timing::CallTimer::start(load_config_mock::record_duration))
+///     } else {
+///         None
+///     };
+///
+///     // original implementation
+/// }
+/// ```
+///
+/// # Fields
+///
+/// - `started` - when the real invocation started
+/// - `record` - records the duration in the double
+pub struct CallTimer {
+    started: Instant,
+    record: fn(Duration),
+}
+
+impl CallTimer {
+    pub fn start(record: fn(Duration)) -> Self {
+        Self {
+            started: Instant::now(),
+            record,
+        }
+    }
+}
+
+impl Drop for CallTimer {
+    fn drop(&mut self) {
+        (self.record)(self.started.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static DURATIONS: RefCell<Vec<Duration>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(duration: Duration) {
+        DURATIONS.with(|durations| durations.borrow_mut().push(duration));
+    }
+
+    #[test]
+    fn test_records_elapsed_time_when_dropped() {
+        let timer = CallTimer::start(record);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(DURATIONS.with(|durations| durations.borrow().is_empty()));
+
+        drop(timer);
+
+        let durations = DURATIONS.with(|durations| durations.borrow().clone());
+        assert_eq!(durations.len(), 1);
+        assert!(durations[0] >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_records_when_the_invocation_panics() {
+        let result = std::panic::catch_unwind(|| {
+            let _timer = CallTimer::start(record);
+            panic!("original implementation failed");
+        });
+
+        assert!(result.is_err());
+        assert_eq!(DURATIONS.with(|durations| durations.borrow().len()), 1);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::matchers::Matcher;
+
+/// Sets up the case of a mock, connecting the matcher of a `When` with the mock implementation
+type SetupCase<Params, Result> = Box<dyn FnOnce(Box<dyn FnMut(Params) -> Result>)>;
+
+/// Pending case of a mock, created by the `fnmock::when!` macro and completed by one of the `then` functions
+///
+/// The `when!` macro turns a call of a mocked function into a case of its mock,
+/// so tests read like a specification of the expected behavior.
+/// Arguments are compared with `eq_to`, and `_` accepts every value of its parameter.
+/// The case is set up with `setup_when` of the generated mock module,
+/// so it is checked before the implementation set up with `setup()` (see Partial Mocking).
+///
+/// # Usage
+///
+/// ```ignore
+/// use fnmock::when;
+///
+/// when!(fetch_user(42)).then_return(Ok("alice".to_string()));
+/// when!(fetch_user(_)).then_return(Err("not found".to_string()));
+/// when!(send_email(_, "Hello")).then(|(user, _)| Ok(println!("sent to {}", user)));
+///
+/// assert_eq!(greet(42), "Hello alice");
+/// ```
+///
+/// The macro expands to the `when()` proxy function of the generated mock module,
+/// which takes one matcher per parameter:
+///
+/// ```ignore
+/// fetch_user_mock::when(fnmock::matchers::eq_to(42)).then_return(Ok("alice".to_string()));
+/// ```
+///
+/// # Fields
+///
+/// - `setup_case` - sets up the case with the matcher of the call on the mock
+#[must_use = "the case is only set up by one of the `then` functions"]
+pub struct When<Params, Result> {
+    setup_case: SetupCase<Params, Result>,
+}
+
+impl<Params, Result> When<Params, Result>
+where
+    Params: 'static,
+    Result: 'static,
+{
+    pub fn new(setup_case: impl FnOnce(Box<dyn FnMut(Params) -> Result>) + 'static) -> Self {
+        Self {
+            setup_case: Box::new(setup_case),
+        }
+    }
+
+    /// Returns a clone of the value for every matching call.
+    pub fn then_return(self, return_value: Result)
+    where
+        Result: Clone,
+    {
+        self.then(move |_| return_value.clone())
+    }
+
+    /// Runs the implementation for every matching call.
+    pub fn then(self, new_f: impl FnMut(Params) -> Result + 'static) {
+        (self.setup_case)(Box::new(new_f))
+    }
+
+    /// Panics with the message for every matching call.
+    pub fn then_panic(self, message: impl Into<String>) {
+        let message = message.into();
+        self.then(move |_| panic!("{}", message))
+    }
+}
+
+/// Creates a `When` setting up the case on the mock, as done by the generated `when()` proxy functions.
+pub fn when_matching<Params, Result>(
+    matcher: Matcher<Params>,
+    setup_when: impl FnOnce(Matcher<Params>, Box<dyn FnMut(Params) -> Result>) + 'static,
+) -> When<Params, Result>
+where
+    Params: 'static,
+    Result: 'static,
+{
+    When::new(move |new_f| setup_when(matcher, new_f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::function_mock::FunctionMock;
+    use crate::matchers::{any, eq};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn when_on(mock: &Rc<RefCell<FunctionMock<u32, String>>>, matcher: Matcher<u32>) -> When<u32, String> {
+        let mock = mock.clone();
+        when_matching(matcher, move |matcher, new_f| mock.borrow_mut().setup_when(matcher, new_f))
+    }
+
+    #[test]
+    fn test_then_return_applies_to_matching_calls() {
+        let mock = Rc::new(RefCell::new(FunctionMock::new("fetch_user_mock")));
+
+        when_on(&mock, eq(42)).then_return("alice".to_string());
+        when_on(&mock, any()).then_return("unknown".to_string());
+
+        assert_eq!(mock.borrow_mut().call(42), "alice");
+        assert_eq!(mock.borrow_mut().call(42), "alice");
+        assert_eq!(mock.borrow_mut().call(7), "unknown");
+    }
+
+    #[test]
+    fn test_then_receives_params() {
+        let mock = Rc::new(RefCell::new(FunctionMock::new("fetch_user_mock")));
+
+        when_on(&mock, any()).then(|id| format!("user_{}", id));
+
+        assert_eq!(mock.borrow_mut().call(7), "user_7");
+    }
+
+    #[test]
+    #[should_panic(expected = "user is blocked")]
+    fn test_then_panic() {
+        let mock = Rc::new(RefCell::new(FunctionMock::new("fetch_user_mock")));
+
+        when_on(&mock, eq(13)).then_panic("user is blocked");
+
+        mock.borrow_mut().call(13);
+    }
+
+    #[test]
+    fn test_case_is_not_set_up_without_then() {
+        let mock = Rc::new(RefCell::new(FunctionMock::new("fetch_user_mock")));
+
+        let _ = when_on(&mock, eq(42));
+
+        assert!(!mock.borrow().is_set());
+    }
+}